@@ -0,0 +1,77 @@
+//! Compares the ways of running a program on the same workloads: the match-per-step
+//! interpreter, the threaded interpreter over fused operations, and its variant that
+//! indexes the tape directly
+
+use brainfuck_interpreter::{
+    io::{NullOutput, SliceInput},
+    nested_input, programs, BrainfuckProgram,
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// The workloads, with the input they read
+fn workloads() -> [(&'static str, &'static str, Vec<u8>); 4] {
+    [
+        (
+            programs::MANDELBROT.name,
+            programs::MANDELBROT.source,
+            Vec::new(),
+        ),
+        (programs::HANOI.name, programs::HANOI.source, Vec::new()),
+        (
+            "tight-loop",
+            include_str!("programs/tight-loop.b"),
+            Vec::new(),
+        ),
+        (
+            "dbfi-sierpinski",
+            programs::DBFI.source,
+            nested_input(programs::SIERPINSKI.source, b""),
+        ),
+    ]
+}
+
+fn compile(source: &str) -> BrainfuckProgram<Vec<u8>> {
+    BrainfuckProgram::compile(source, vec![0u8; 30_000]).unwrap()
+}
+
+fn backends(c: &mut Criterion) {
+    for (name, source, input) in workloads().iter() {
+        let mut group = c.benchmark_group(format!("backends/{}", name));
+        group.sample_size(10);
+        group.bench_function("step", |b| {
+            b.iter(|| {
+                let mut program = compile(source);
+                let mut input = SliceInput::new(input);
+                while program.try_step(&mut NullOutput, &mut input).unwrap() {}
+            })
+        });
+        group.bench_function("interpreter", |b| {
+            b.iter(|| {
+                let mut program = compile(source);
+                program
+                    .try_run(&mut NullOutput, &mut SliceInput::new(input))
+                    .unwrap();
+            })
+        });
+        group.bench_function("threaded", |b| {
+            b.iter(|| {
+                let mut program = compile(source);
+                program
+                    .try_run_threaded(&mut NullOutput, &mut SliceInput::new(input))
+                    .unwrap();
+            })
+        });
+        group.bench_function("fast", |b| {
+            b.iter(|| {
+                let mut program = compile(source);
+                program
+                    .try_run_fast(&mut NullOutput, &mut SliceInput::new(input))
+                    .unwrap();
+            })
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, backends);
+criterion_main!(benches);
@@ -0,0 +1,47 @@
+use brainfuck_interpreter::BrainfuckProgram;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// Generate a program of roughly `len` bytes, consisting of many loops nested
+/// up to 64 levels deep, similar to large machine-generated programs
+fn generate(len: usize) -> String {
+    let mut program = String::with_capacity(len);
+    let mut depth = 0;
+    let mut state = 0x2545_f491u32;
+    while program.len() < len {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        match state % 8 {
+            0 if depth < 64 => {
+                program.push('[');
+                depth += 1;
+            }
+            1 if depth > 0 => {
+                program.push(']');
+                depth -= 1;
+            }
+            2 => program.push('>'),
+            3 => program.push('<'),
+            4 | 5 => program.push('+'),
+            6 => program.push('-'),
+            _ => program.push('.'),
+        }
+    }
+    program.extend(std::iter::repeat_n(']', depth));
+    program
+}
+
+fn compile(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compile");
+    for len in [10_000, 100_000, 500_000] {
+        let source = generate(len);
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(len), &source, |b, source| {
+            b.iter(|| BrainfuckProgram::compile(source, [0u8; 0]).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, compile);
+criterion_main!(benches);
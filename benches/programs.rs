@@ -0,0 +1,114 @@
+use brainfuck_interpreter::{
+    io::{NullOutput, SliceInput},
+    nested_input, programs, BrainfuckProgram, CompileOptions, OptimizationLevel, Tape,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// A program to benchmark, with the input it reads
+struct Workload {
+    name: &'static str,
+    source: &'static str,
+    input: Vec<u8>,
+}
+
+/// The programs to benchmark, from `benches/programs` and the `programs` module
+///
+/// Mandelbrot and the self-interpreter running sierpinski execute hundreds of millions
+/// of instructions, so they take fewer samples.
+fn workloads() -> Vec<Workload> {
+    vec![
+        Workload {
+            name: programs::HELLO_WORLD.name,
+            source: programs::HELLO_WORLD.source,
+            input: Vec::new(),
+        },
+        Workload {
+            name: programs::MANDELBROT.name,
+            source: programs::MANDELBROT.source,
+            input: Vec::new(),
+        },
+        Workload {
+            name: programs::HANOI.name,
+            source: programs::HANOI.source,
+            input: Vec::new(),
+        },
+        Workload {
+            name: "tight-loop",
+            source: include_str!("programs/tight-loop.b"),
+            input: Vec::new(),
+        },
+        Workload {
+            name: "dbfi-sierpinski",
+            source: programs::DBFI.source,
+            input: nested_input(programs::SIERPINSKI.source, b""),
+        },
+    ]
+}
+
+const LEVELS: [OptimizationLevel; 4] = [
+    OptimizationLevel::None,
+    OptimizationLevel::Basic,
+    OptimizationLevel::Aggressive,
+    OptimizationLevel::Peeling,
+];
+
+/// Run `program` to completion with `input`, discarding its output and reading zeroes
+/// after the end of `input`
+fn run_null<T: Tape<Data = u8>>(program: &mut BrainfuckProgram<T>, input: &[u8]) {
+    program.run(&mut NullOutput, &mut SliceInput::new(input));
+}
+
+fn compile<T: Tape<Data = u8>>(
+    source: &str,
+    tape: T,
+    optimization: OptimizationLevel,
+) -> BrainfuckProgram<T> {
+    let options = CompileOptions {
+        optimization,
+        ..CompileOptions::default()
+    };
+    BrainfuckProgram::compile_with_options(source, tape, &options).unwrap()
+}
+
+fn programs(c: &mut Criterion) {
+    for Workload {
+        name,
+        source,
+        input,
+    } in workloads()
+    {
+        let input = &input[..];
+        let mut group = c.benchmark_group(name);
+        if name == "dbfi-sierpinski" || name == programs::MANDELBROT.name {
+            group.sample_size(10);
+        }
+        for level in LEVELS {
+            let level_name = format!("{:?}", level);
+            group.bench_function(BenchmarkId::new("vec", &level_name), |b| {
+                b.iter(|| run_null(&mut compile(source, vec![0u8; 30_000], level), input))
+            });
+            group.bench_function(BenchmarkId::new("array", &level_name), |b| {
+                b.iter(|| run_null(&mut compile(source, [0u8; 30_000], level), input))
+            });
+            group.bench_function(BenchmarkId::new("growing_vec", &level_name), |b| {
+                b.iter(|| run_null(&mut compile(source, Vec::new(), level), input))
+            });
+        }
+        group.bench_function("threaded", |b| {
+            b.iter(|| {
+                let mut program = compile(source, vec![0u8; 30_000], OptimizationLevel::None);
+                program.run_threaded(&mut NullOutput, &mut SliceInput::new(input));
+            })
+        });
+        group.bench_function("fast", |b| {
+            b.iter(|| {
+                let mut program = compile(source, vec![0u8; 30_000], OptimizationLevel::None);
+                program.run_fast(&mut NullOutput, &mut SliceInput::new(input));
+            })
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, programs);
+criterion_main!(benches);
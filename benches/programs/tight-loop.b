@@ -0,0 +1,3 @@
+Three nested loops that count cells down from 255 without any I/O
+so that the interpreter loop itself dominates the run time
+++++[>-[>-[-]<-]<-]
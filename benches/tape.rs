@@ -0,0 +1,29 @@
+use brainfuck_interpreter::{
+    io::{NullOutput, SliceInput},
+    BrainfuckProgram, Tape,
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// Move back and forth over the first 257 cells 255 times, incrementing
+/// all of them on the way, so that every instruction accesses the tape
+fn sweep() -> String {
+    format!("-[>{}{}-]", "+>".repeat(255), "<".repeat(256))
+}
+
+fn run<T: Tape<Data = u8>>(source: &str, tape: T) -> T {
+    let mut program = BrainfuckProgram::compile(source, tape).unwrap();
+    program.run(&mut NullOutput, &mut SliceInput::new(&[]));
+    program.tape
+}
+
+fn tape(c: &mut Criterion) {
+    let source = sweep();
+    let mut group = c.benchmark_group("tape");
+    group.bench_function("vec", |b| b.iter(|| run(&source, vec![0u8; 300])));
+    group.bench_function("array", |b| b.iter(|| run(&source, [0u8; 300])));
+    group.bench_function("growing_vec", |b| b.iter(|| run(&source, Vec::<u8>::new())));
+    group.finish();
+}
+
+criterion_group!(benches, tape);
+criterion_main!(benches);
@@ -0,0 +1,37 @@
+use brainfuck_interpreter::{io::InteractiveInput, programs, BrainfuckProgram, VisualizeConfig};
+
+fn main() {
+    // Run the program in the file given as the first argument, or Hello World
+    let source = match std::env::args().nth(1) {
+        Some(path) => std::fs::read_to_string(path).expect("Failed to read program"),
+        None => programs::HELLO_WORLD.source.to_string(),
+    };
+
+    let mut program = match BrainfuckProgram::compile_with_source_map(&source, vec![0u8; 1]) {
+        Ok(program) => program,
+        Err(error) => {
+            eprintln!("{}", error);
+            return;
+        }
+    };
+
+    let config = VisualizeConfig {
+        interval: 1 << 8,
+        ..VisualizeConfig::default()
+    };
+
+    let mut output = Vec::new();
+    // Reads zero once stdin has run out
+    let mut input = InteractiveInput::new(std::io::stdin());
+    program
+        .run_visualized(
+            &source,
+            &config,
+            std::io::stdout(),
+            &mut |d| output.push(d),
+            &mut input,
+        )
+        .expect("Failed to write to the terminal");
+
+    println!("\n{}", String::from_utf8_lossy(&output));
+}
@@ -0,0 +1,161 @@
+//! Procedural macros for `brainfuck-interpreter`
+//!
+//! These are re-exported by `brainfuck-interpreter` when its `macros` feature is enabled,
+//! and should be used through that crate.
+
+#![deny(missing_docs)]
+#![deny(warnings)]
+#![forbid(unsafe_code)]
+
+use proc_macro::{Delimiter, Group, Literal, Span, TokenStream, TokenTree};
+
+/// Decode the value of a string literal, given its source representation
+fn string_value(literal: &str) -> Option<String> {
+    if let Some(raw) = literal.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let inner = &raw[hashes..raw.len() - hashes];
+        return Some(inner.strip_prefix('"')?.strip_suffix('"')?.to_string());
+    }
+
+    let inner = literal.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => value.push('\n'),
+            'r' => value.push('\r'),
+            't' => value.push('\t'),
+            '0' => value.push('\0'),
+            '\\' => value.push('\\'),
+            '\'' => value.push('\''),
+            '"' => value.push('"'),
+            'x' => {
+                let digits: String = chars.by_ref().take(2).collect();
+                value.push(u8::from_str_radix(&digits, 16).ok()? as char);
+            }
+            'u' => {
+                let digits: String = chars
+                    .by_ref()
+                    .skip(1)
+                    .take_while(|c| *c != '}')
+                    .filter(|c| *c != '_')
+                    .collect();
+                value.push(char::from_u32(u32::from_str_radix(&digits, 16).ok()?)?);
+            }
+            '\n' => {
+                let rest = chars.as_str().trim_start();
+                chars = rest.chars();
+            }
+            _ => return None,
+        }
+    }
+    Some(value)
+}
+
+fn compile_error(message: &str, span: Span) -> TokenStream {
+    let mut literal = Literal::string(message);
+    literal.set_span(span);
+    let tokens: TokenStream = "::core::compile_error!".parse().unwrap();
+    let mut tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut arguments = Group::new(Delimiter::Parenthesis, TokenTree::Literal(literal).into());
+    arguments.set_span(span);
+    tokens.push(TokenTree::Group(arguments));
+    tokens
+        .into_iter()
+        .map(|mut token| {
+            token.set_span(span);
+            token
+        })
+        .collect()
+}
+
+/// Compile a Brainfuck program at build time
+///
+/// Takes a single string literal containing the program, and expands to a
+/// `&'static [BrainfuckInstruction]` with all jump targets resolved. All non-valid
+/// characters are ignored. If the brackets in the program do not match, compilation
+/// fails with an error pointing at the unmatched bracket.
+///
+/// ```ignore
+/// use brainfuck_interpreter::{brainfuck, BrainfuckInstruction};
+///
+/// static ADD: &[BrainfuckInstruction] = brainfuck!("[->+<]");
+/// ```
+#[proc_macro]
+pub fn brainfuck(input: TokenStream) -> TokenStream {
+    let mut tokens = input.into_iter();
+    let literal = match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Literal(literal)), None) => literal,
+        (Some(TokenTree::Group(group)), None) if group.delimiter() == Delimiter::None => {
+            return brainfuck(group.stream())
+        }
+        (Some(token), _) => return compile_error("Expected a single string literal", token.span()),
+        (None, _) => return compile_error("Expected a string literal", Span::call_site()),
+    };
+
+    let source = match string_value(&literal.to_string()) {
+        Some(source) => source,
+        None => return compile_error("Expected a string literal", literal.span()),
+    };
+
+    let mut instructions = Vec::new();
+    let mut open: Vec<(usize, usize, usize)> = Vec::new();
+    for (line, text) in source.lines().enumerate() {
+        for (character, c) in text.chars().enumerate() {
+            let instruction = match c {
+                '>' => "IncrementDataPointer".to_string(),
+                '<' => "DecrementDataPointer".to_string(),
+                '+' => "IncreaseData".to_string(),
+                '-' => "DecreaseData".to_string(),
+                '.' => "Output".to_string(),
+                ',' => "Input".to_string(),
+                '[' => {
+                    open.push((instructions.len(), line, character + 1));
+                    // The target is filled in once the matching `]` is found
+                    String::new()
+                }
+                ']' => match open.pop() {
+                    Some((opener, _, _)) => {
+                        instructions[opener] = format!("JumpForward({})", instructions.len());
+                        format!("JumpBackwards({})", opener)
+                    }
+                    None => {
+                        return compile_error(
+                            &format!(
+                                "Missing opening brace at line {}, character {}",
+                                line,
+                                character + 1
+                            ),
+                            literal.span(),
+                        )
+                    }
+                },
+                _ => continue,
+            };
+            instructions.push(instruction);
+        }
+    }
+
+    if let Some((_, line, character)) = open.pop() {
+        return compile_error(
+            &format!(
+                "Missing closing brace at line {}, character {}",
+                line, character
+            ),
+            literal.span(),
+        );
+    }
+
+    let mut expansion = String::from("&[");
+    for instruction in instructions {
+        expansion.push_str("::brainfuck_interpreter::BrainfuckInstruction::");
+        expansion.push_str(&instruction);
+        expansion.push(',');
+    }
+    expansion.push(']');
+    expansion.parse().unwrap()
+}
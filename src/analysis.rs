@@ -0,0 +1,523 @@
+//! Statistics about programs, gathered without running them
+
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+use core::fmt::Display;
+
+use crate::{
+    BrainfuckInstruction, BrainfuckLexer, BrainfuckProgram, Error, SourcePos, VALID_CHARS,
+};
+
+/// Static statistics about a program, as returned by [`analyze`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgramAnalysis {
+    /// The amount of every instruction, in the order of [`VALID_CHARS`]
+    pub counts: [usize; 8],
+    /// The deepest nesting of loops
+    pub max_depth: usize,
+    /// The amount of cells, starting at the first one, that the data pointer can be
+    /// seen to reach without running the program
+    ///
+    /// If [`pointer_bounded`](Self::pointer_bounded) is `false`, the program may use
+    /// more cells than this.
+    pub min_tape_cells: usize,
+    /// Whether every loop leaves the data pointer where it was when the loop started,
+    /// so that the program never uses more than [`min_tape_cells`](Self::min_tape_cells)
+    /// cells
+    pub pointer_bounded: bool,
+    /// Whether the data pointer can be seen to move to the left of the first cell
+    pub moves_left_of_start: bool,
+    /// The positions of the `[` and `]` of every loop, ordered by the position of the `[`
+    pub bracket_pairs: Vec<(SourcePos, SourcePos)>,
+    /// For every instruction, whether the current cell is known to be zero whenever
+    /// the instruction is reached
+    ///
+    /// Cells are known to be zero at the start of the program, and the current cell
+    /// is known to be zero after a loop.
+    pub known_zero: Vec<bool>,
+    /// The positions of the `[` and `]` of every loop that is never entered, because
+    /// the current cell is known to be zero when it is reached
+    pub dead_loops: Vec<(SourcePos, SourcePos)>,
+    /// The positions of the `[` and `]` of every loop that is known to run exactly
+    /// once when it is reached, such as `+[-]`
+    pub single_iteration_loops: Vec<(SourcePos, SourcePos)>,
+}
+
+impl ProgramAnalysis {
+    /// The total amount of instructions
+    pub fn instructions(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// The amount of times `instruction` occurs in the program
+    pub fn count(&self, instruction: BrainfuckInstruction) -> usize {
+        VALID_CHARS
+            .iter()
+            .position(|c| *c == instruction.as_char())
+            .map_or(0, |index| self.counts[index])
+    }
+}
+
+impl Display for ProgramAnalysis {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "instructions: {}", self.instructions())?;
+        for (c, count) in VALID_CHARS.iter().zip(self.counts) {
+            writeln!(f, "  {}: {}", c, count)?;
+        }
+        writeln!(f, "loops: {}", self.bracket_pairs.len())?;
+        writeln!(f, "  never entered: {}", self.dead_loops.len())?;
+        writeln!(f, "  run once: {}", self.single_iteration_loops.len())?;
+        writeln!(f, "max loop depth: {}", self.max_depth)?;
+        write!(f, "tape cells: ")?;
+        if self.pointer_bounded {
+            write!(f, "{}", self.min_tape_cells)?;
+        } else {
+            write!(f, "at least {}", self.min_tape_cells)?;
+        }
+        if self.moves_left_of_start {
+            write!(f, ", moves left of the first cell")?;
+        }
+        Ok(())
+    }
+}
+
+/// Gather statistics about the Brainfuck program `source`, without running it
+///
+/// ```
+/// # use brainfuck_interpreter::{analyze, BrainfuckInstruction};
+/// let analysis = analyze("++[>+>+<<-]>.").unwrap();
+/// assert_eq!(analysis.count(BrainfuckInstruction::IncreaseData), 4);
+/// assert_eq!(analysis.max_depth, 1);
+/// assert_eq!(analysis.min_tape_cells, 3);
+/// assert!(analysis.pointer_bounded);
+///
+/// let analysis = analyze("[,]+[-]>[-]").unwrap();
+/// assert_eq!(analysis.dead_loops.len(), 2);
+/// assert_eq!(analysis.single_iteration_loops.len(), 1);
+/// assert!(analysis.known_zero[6]);
+/// ```
+pub fn analyze(source: &str) -> Result<ProgramAnalysis, Error<'_>> {
+    let spans = BrainfuckProgram::<Vec<u8>>::compile_spans(source, &BrainfuckLexer)?;
+
+    let mut analysis = ProgramAnalysis {
+        counts: [0; 8],
+        max_depth: 0,
+        min_tape_cells: 1,
+        pointer_bounded: true,
+        moves_left_of_start: false,
+        bracket_pairs: Vec::new(),
+        known_zero: Vec::new(),
+        dead_loops: Vec::new(),
+        single_iteration_loops: Vec::new(),
+    };
+
+    // The offset of the data pointer from the first cell, while it is known, and the
+    // offset at the start of every open loop
+    let mut offset = Some(0isize);
+    let mut loops: Vec<Option<isize>> = Vec::new();
+
+    for span in &spans {
+        let instruction = span.get_instruction();
+        if let Some(index) = VALID_CHARS.iter().position(|c| *c == instruction.as_char()) {
+            analysis.counts[index] += 1;
+        }
+
+        match instruction {
+            BrainfuckInstruction::IncrementDataPointer => offset = offset.map(|o| o + 1),
+            BrainfuckInstruction::DecrementDataPointer => offset = offset.map(|o| o - 1),
+            BrainfuckInstruction::JumpForward(end) => {
+                analysis
+                    .bracket_pairs
+                    .push((span.get_position(), spans[end].get_position()));
+                loops.push(offset);
+                analysis.max_depth = analysis.max_depth.max(loops.len());
+            }
+            // After an unbalanced loop, the data pointer could be anywhere
+            BrainfuckInstruction::JumpBackwards(_) if loops.pop().flatten() != offset => {
+                analysis.pointer_bounded = false;
+                offset = None;
+            }
+            _ => {}
+        }
+
+        if let Some(offset) = offset {
+            if offset < 0 {
+                analysis.moves_left_of_start = true;
+            } else {
+                analysis.min_tape_cells = analysis.min_tape_cells.max(offset as usize + 1);
+            }
+        }
+    }
+
+    let instructions: Vec<_> = spans.iter().map(|span| span.get_instruction()).collect();
+    let zero = known_zero(&instructions, &KnownCells::zero());
+    for (span, iterations) in spans.iter().zip(&zero.iterations) {
+        let loops = match iterations {
+            Iterations::Never => &mut analysis.dead_loops,
+            Iterations::Once => &mut analysis.single_iteration_loops,
+            Iterations::Unknown => continue,
+        };
+        if let BrainfuckInstruction::JumpForward(end) = span.get_instruction() {
+            loops.push((span.get_position(), spans[end].get_position()));
+        }
+    }
+    analysis.known_zero = zero.zero;
+
+    Ok(analysis)
+}
+
+/// The values of the cells around the data pointer that are known without running a
+/// program
+///
+/// Values are only tracked while they are between 0 and 255, so that they are the
+/// same for every type of cell, including saturating and trapping cells.
+#[derive(Clone)]
+pub(crate) struct KnownCells {
+    /// The offset of the data pointer from where tracking started
+    offset: isize,
+    /// The cells whose value differs from `rest`, by their offset
+    cells: BTreeMap<isize, Option<u8>>,
+    /// The value of all other cells
+    rest: Option<u8>,
+}
+
+impl KnownCells {
+    /// All cells are known to be zero
+    pub(crate) fn zero() -> Self {
+        Self {
+            offset: 0,
+            cells: BTreeMap::new(),
+            rest: Some(0),
+        }
+    }
+
+    /// Nothing is known about any cell
+    pub(crate) fn unknown() -> Self {
+        Self {
+            rest: None,
+            ..Self::zero()
+        }
+    }
+
+    /// Only the current cell is known to be zero, as it is after a loop
+    pub(crate) fn after_loop() -> Self {
+        let mut known = Self::unknown();
+        known.cells.insert(0, Some(0));
+        known
+    }
+
+    /// The value of the current cell, if it is known
+    pub(crate) fn current(&self) -> Option<u8> {
+        self.cells.get(&self.offset).copied().unwrap_or(self.rest)
+    }
+
+    fn update(&mut self, change: fn(u8) -> Option<u8>) {
+        let value = self.current().and_then(change);
+        self.cells.insert(self.offset, value);
+    }
+
+    /// Update the known cells for running `instruction`. Loops and procedures run code
+    /// that is not known here, so nothing is known after them
+    pub(crate) fn apply(&mut self, instruction: BrainfuckInstruction) {
+        match instruction {
+            BrainfuckInstruction::IncrementDataPointer => self.offset += 1,
+            BrainfuckInstruction::DecrementDataPointer => self.offset -= 1,
+            BrainfuckInstruction::IncreaseData => self.update(|value| value.checked_add(1)),
+            BrainfuckInstruction::DecreaseData => self.update(|value| value.checked_sub(1)),
+            BrainfuckInstruction::Input => self.update(|_| None),
+            BrainfuckInstruction::Output => {}
+            _ => *self = Self::unknown(),
+        }
+    }
+}
+
+/// How often a loop runs every time that it is reached
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Iterations {
+    Never,
+    Once,
+    Unknown,
+}
+
+/// The results of [`known_zero`]
+pub(crate) struct KnownZero {
+    /// Whether the current cell is known to be zero before every instruction
+    pub(crate) zero: Vec<bool>,
+    /// How often the loop that every `[` and `]` belongs to runs, and `Unknown` for
+    /// other instructions
+    pub(crate) iterations: Vec<Iterations>,
+}
+
+/// How many times [`known_zero`] may walk over every instruction, before it stops
+/// trying whether loops run once. The body of a loop that does not turn out to run
+/// once is walked over a second time
+const WALKS: usize = 4;
+
+/// A loop or procedure whose body is being walked over by [`known_zero`]
+enum Frame {
+    /// The first iteration of a loop, starting with the cells that are known at its `[`
+    Trial { open: usize },
+    /// Any iteration of a loop, starting with cells that are not known
+    Body { open: usize },
+    /// The body of a procedure, which does not run where it is defined, and may be
+    /// called from anywhere. Afterwards, the cells that were known at its `(` are known
+    Procedure { known: KnownCells },
+}
+
+/// Find out which cells are known to be zero before every instruction, and which
+/// loops never run or run exactly once
+///
+/// The cells are known as `start` before the first instruction. The jump targets in
+/// `instructions` are not used, so that this can be used while optimizing.
+pub(crate) fn known_zero(instructions: &[BrainfuckInstruction], start: &KnownCells) -> KnownZero {
+    let len = instructions.len();
+    let mut matching = vec![len; len];
+    let mut open = Vec::new();
+    for (index, instruction) in instructions.iter().enumerate() {
+        match instruction {
+            BrainfuckInstruction::JumpForward(_) | BrainfuckInstruction::DefineProcedure(_) => {
+                open.push(index)
+            }
+            BrainfuckInstruction::JumpBackwards(_) | BrainfuckInstruction::EndProcedure => {
+                if let Some(opener) = open.pop() {
+                    matching[opener] = index;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut result = KnownZero {
+        zero: vec![false; len],
+        iterations: vec![Iterations::Unknown; len],
+    };
+    let mut known = start.clone();
+    let mut frames: Vec<(Frame, usize)> = Vec::new();
+    let mut index = 0;
+    let mut trying = true;
+    let mut work = 0;
+
+    while index < len {
+        work += 1;
+        if trying && work > WALKS * len {
+            // Start over without trying loops, so that every instruction is only
+            // walked over once
+            trying = false;
+            result.zero.fill(false);
+            result.iterations.fill(Iterations::Unknown);
+            known = start.clone();
+            frames.clear();
+            index = 0;
+            continue;
+        }
+
+        // Reached the `]` or `)` of the innermost frame
+        if let Some((_, close)) = frames.last() {
+            if *close == index {
+                let (frame, close) = frames.pop().unwrap();
+                match frame {
+                    Frame::Trial { open } if known.current() == Some(0) => {
+                        result.zero[close] = true;
+                        result.iterations[open] = Iterations::Once;
+                        result.iterations[close] = Iterations::Once;
+                    }
+                    Frame::Trial { open } => {
+                        // Walk over the body again, for the iterations after the first
+                        frames.push((Frame::Body { open }, close));
+                        known = KnownCells::unknown();
+                        index = open + 1;
+                        continue;
+                    }
+                    Frame::Body { open } => {
+                        result.zero[close] = known.current() == Some(0);
+                        result.iterations[open] = Iterations::Unknown;
+                        result.iterations[close] = Iterations::Unknown;
+                        known = KnownCells::after_loop();
+                    }
+                    Frame::Procedure { known: before } => {
+                        result.zero[close] = false;
+                        known = before;
+                    }
+                }
+                index = close + 1;
+                continue;
+            }
+        }
+
+        let instruction = instructions[index];
+        result.zero[index] = known.current() == Some(0);
+        let close = matching[index];
+        match instruction {
+            BrainfuckInstruction::JumpForward(_) if close < len => match known.current() {
+                Some(0) => {
+                    for index in index + 1..=close {
+                        result.zero[index] = false;
+                        result.iterations[index] = Iterations::Unknown;
+                    }
+                    result.iterations[index] = Iterations::Never;
+                    result.iterations[close] = Iterations::Never;
+                    index = close + 1;
+                    continue;
+                }
+                Some(_) if trying => {
+                    frames.push((Frame::Trial { open: index }, close));
+                }
+                _ => {
+                    frames.push((Frame::Body { open: index }, close));
+                    known = KnownCells::unknown();
+                }
+            },
+            BrainfuckInstruction::DefineProcedure(_) if close < len => {
+                let before = core::mem::replace(&mut known, KnownCells::unknown());
+                frames.push((Frame::Procedure { known: before }, close));
+            }
+            _ => known.apply(instruction),
+        }
+        index += 1;
+    }
+
+    result
+}
+
+/// How a loop moves the data pointer, as stored in [`BrainfuckProgram::loops`]
+///
+/// Offsets are relative to the cell that the data pointer is at when an iteration of
+/// the loop starts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LoopInfo {
+    /// The index of the `[` of the loop
+    pub start: usize,
+    /// The index of the `]` of the loop
+    pub end: usize,
+    /// How far one iteration moves the data pointer, or `None` if that depends on the
+    /// cells, because of an inner loop that does not have a fixed movement, or a
+    /// procedure call
+    pub delta: Option<isize>,
+    /// The smallest and largest offset of the data pointer during one iteration, if
+    /// [`delta`](Self::delta) is known
+    pub offsets: Option<(isize, isize)>,
+    /// Whether the body of the loop only consists of `+`, `-`, `>` and `<`
+    pub simple: bool,
+}
+
+impl LoopInfo {
+    /// Whether every iteration leaves the data pointer where it started
+    pub fn is_balanced(&self) -> bool {
+        self.delta == Some(0)
+    }
+}
+
+/// Determine how every loop in `instructions` moves the data pointer, ordered by the
+/// index of their `[`
+///
+/// ```
+/// # use brainfuck_interpreter::{analyze_loops, BrainfuckProgram};
+/// let program = BrainfuckProgram::compile("[->++>+<<][>]", vec![0u8]).unwrap();
+/// let loops = analyze_loops(program.instructions());
+/// assert!(loops[0].is_balanced());
+/// assert_eq!(loops[0].offsets, Some((0, 2)));
+/// assert!(loops[0].simple);
+/// assert_eq!(loops[1].delta, Some(1));
+/// ```
+pub fn analyze_loops(instructions: &[BrainfuckInstruction]) -> Vec<LoopInfo> {
+    /// A loop whose `]` has not been reached yet, or a procedure whose `)` has not
+    /// been reached yet, which has no index in the loops
+    struct Open {
+        index: Option<usize>,
+        offset: Option<isize>,
+        min: isize,
+        max: isize,
+        simple: bool,
+    }
+
+    let mut loops = Vec::new();
+    let mut open: Vec<Open> = Vec::new();
+
+    for (index, instruction) in instructions.iter().enumerate() {
+        match instruction {
+            BrainfuckInstruction::JumpForward(_) | BrainfuckInstruction::DefineProcedure(_) => {
+                if let Some(parent) = open.last_mut() {
+                    parent.simple = false;
+                }
+                let loop_index = match instruction {
+                    BrainfuckInstruction::JumpForward(end) => {
+                        loops.push(LoopInfo {
+                            start: index,
+                            end: *end,
+                            delta: None,
+                            offsets: None,
+                            simple: true,
+                        });
+                        Some(loops.len() - 1)
+                    }
+                    // The body of a procedure does not run where it is defined
+                    _ => None,
+                };
+                open.push(Open {
+                    index: loop_index,
+                    offset: Some(0),
+                    min: 0,
+                    max: 0,
+                    simple: true,
+                });
+                continue;
+            }
+            BrainfuckInstruction::JumpBackwards(_) | BrainfuckInstruction::EndProcedure => {
+                let closed = match open.pop() {
+                    Some(closed) => closed,
+                    None => continue,
+                };
+                let info = match closed.index {
+                    Some(loop_index) => &mut loops[loop_index],
+                    None => continue,
+                };
+                info.delta = closed.offset;
+                info.offsets = closed.offset.map(|_| (closed.min, closed.max));
+                info.simple = closed.simple;
+
+                if let Some(parent) = open.last_mut() {
+                    // Only a balanced inner loop leaves the data pointer at a known offset
+                    match (parent.offset, info.delta, info.offsets) {
+                        (Some(offset), Some(0), Some((min, max))) => {
+                            parent.min = parent.min.min(offset + min);
+                            parent.max = parent.max.max(offset + max);
+                        }
+                        _ => parent.offset = None,
+                    }
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        let current = match open.last_mut() {
+            Some(current) => current,
+            None => continue,
+        };
+        match instruction {
+            BrainfuckInstruction::IncrementDataPointer
+            | BrainfuckInstruction::DecrementDataPointer => {
+                let step = match instruction {
+                    BrainfuckInstruction::IncrementDataPointer => 1,
+                    _ => -1,
+                };
+                if let Some(offset) = &mut current.offset {
+                    *offset += step;
+                    current.min = current.min.min(*offset);
+                    current.max = current.max.max(*offset);
+                }
+            }
+            BrainfuckInstruction::IncreaseData | BrainfuckInstruction::DecreaseData => {}
+            BrainfuckInstruction::CallProcedure
+            | BrainfuckInstruction::MoveUp
+            | BrainfuckInstruction::MoveDown
+            | BrainfuckInstruction::PreviousTape
+            | BrainfuckInstruction::NextTape => {
+                current.simple = false;
+                current.offset = None;
+            }
+            _ => current.simple = false,
+        }
+    }
+
+    loops
+}
@@ -0,0 +1,281 @@
+//! Recording the evolution of the tape as an animated GIF
+//!
+//! Every frame shows the first cells of the tape as a grid, with the value of every
+//! cell mapped to a color on a heatmap, from black for zero through red and yellow to
+//! white for 255.
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use crate::{
+    io::{Input, Output},
+    result::{Counted, RunStart},
+    BrainfuckProgram, RunOutcome, RunResult, Tape,
+};
+
+/// The largest code in the LZW compression of GIF images
+const MAX_CODE: u16 = 4095;
+
+/// Configuration of a [`TapeRecorder`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AnimationConfig {
+    /// The amount of instructions executed between two frames
+    pub interval: usize,
+    /// The amount of cells in every row of the grid
+    pub width: usize,
+    /// The amount of rows of the grid
+    pub rows: usize,
+    /// The width and height of every cell, in pixels
+    pub scale: usize,
+    /// The time between two frames, in hundredths of a second
+    pub delay: u16,
+    /// The maximum amount of frames. Once it is reached, no more frames are recorded
+    pub max_frames: usize,
+}
+
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        Self {
+            interval: 1 << 12,
+            width: 32,
+            rows: 8,
+            scale: 8,
+            delay: 4,
+            max_frames: 1000,
+        }
+    }
+}
+
+/// Records snapshots of the tape of a program, and writes them as an animated GIF
+///
+/// ```
+/// # use brainfuck_interpreter::{AnimationConfig, BrainfuckProgram, TapeRecorder};
+/// let mut program = BrainfuckProgram::compile("+[>++[>+<-]<+]", vec![0u8; 64]).unwrap();
+/// let config = AnimationConfig { interval: 16, width: 8, rows: 8, ..AnimationConfig::default() };
+/// let mut recorder = TapeRecorder::new(config);
+/// program.run_recorded(&mut recorder, &mut |_| {}, &mut || 0);
+///
+/// let mut gif = Vec::new();
+/// recorder.write_gif(&mut gif).unwrap();
+/// assert!(gif.starts_with(b"GIF89a"));
+/// assert!(recorder.frames().len() > 1);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TapeRecorder {
+    config: AnimationConfig,
+    frames: Vec<Vec<u8>>,
+}
+
+impl TapeRecorder {
+    /// Create a recorder without any frames
+    pub fn new(config: AnimationConfig) -> Self {
+        Self {
+            config,
+            frames: Vec::new(),
+        }
+    }
+
+    /// The configuration of this recorder
+    pub fn config(&self) -> &AnimationConfig {
+        &self.config
+    }
+
+    /// The recorded frames, as the values of the cells in the grid, row by row
+    pub fn frames(&self) -> &[Vec<u8>] {
+        &self.frames
+    }
+
+    /// Add a frame showing the current state of `tape`, unless
+    /// [`AnimationConfig::max_frames`] frames have been recorded already
+    ///
+    /// Cells that are not on the tape are shown as zero.
+    pub fn snapshot<T>(&mut self, tape: &mut T)
+    where
+        T: Tape<Data = u8>,
+    {
+        if self.frames.len() >= self.config.max_frames {
+            return;
+        }
+        let cells = self.config.width * self.config.rows;
+        let len = tape.len_hint().unwrap_or(cells).min(cells);
+        let mut frame: Vec<u8> = (0..len)
+            .map(|index| tape.get_data_at(index).copied().unwrap_or(0))
+            .collect();
+        frame.resize(cells, 0);
+        self.frames.push(frame);
+    }
+
+    /// Write the recorded frames as an animated GIF that loops forever
+    pub fn write_gif<W>(&self, mut writer: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let scale = self.config.scale.max(1);
+        let width = self.config.width * scale;
+        let height = self.config.rows * scale;
+        if width == 0 || height == 0 || width > 0xffff || height > 0xffff {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The size of the animation must be between 1 and 65535 pixels",
+            ));
+        }
+
+        writer.write_all(b"GIF89a")?;
+        writer.write_all(&(width as u16).to_le_bytes())?;
+        writer.write_all(&(height as u16).to_le_bytes())?;
+        // A global color table of 256 colors
+        writer.write_all(&[0xf7, 0, 0])?;
+        for value in 0..=255 {
+            writer.write_all(&heat(value))?;
+        }
+        // Loop forever
+        writer.write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+
+        for frame in &self.frames {
+            writer.write_all(&[0x21, 0xf9, 0x04, 0x00])?;
+            writer.write_all(&self.config.delay.to_le_bytes())?;
+            writer.write_all(&[0x00, 0x00])?;
+
+            writer.write_all(&[0x2c, 0, 0, 0, 0])?;
+            writer.write_all(&(width as u16).to_le_bytes())?;
+            writer.write_all(&(height as u16).to_le_bytes())?;
+            writer.write_all(&[0x00])?;
+
+            let pixels = (0..height).flat_map(|y| {
+                (0..width).map(move |x| frame[(y / scale) * self.config.width + x / scale])
+            });
+            let data = lzw_compress(pixels);
+            writer.write_all(&[8])?;
+            for block in data.chunks(255) {
+                writer.write_all(&[block.len() as u8])?;
+                writer.write_all(block)?;
+            }
+            writer.write_all(&[0])?;
+        }
+        writer.write_all(&[0x3b])
+    }
+}
+
+/// The heatmap color of `value`
+fn heat(value: u8) -> [u8; 3] {
+    let value = value as u16 * 3;
+    let channel = |offset: u16| value.saturating_sub(offset).min(255) as u8;
+    [channel(0), channel(255), channel(510)]
+}
+
+/// Packs codes of varying size into bytes, least significant bit first
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u32) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Compress 8-bit `pixels` with the variant of LZW that GIF uses
+fn lzw_compress(pixels: impl Iterator<Item = u8>) -> Vec<u8> {
+    const CLEAR: u16 = 256;
+    const END: u16 = 257;
+
+    let mut output = BitWriter {
+        bytes: Vec::new(),
+        buffer: 0,
+        bits: 0,
+    };
+    let mut codes: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = END + 1;
+    let mut size = 9;
+
+    // The decoder adds a code after every code that it reads, so the code size grows
+    // right after a code is written once `next` no longer fits
+    let emit = |output: &mut BitWriter, code: u16, next: u16, size: &mut u32| {
+        output.write(code, *size);
+        if next >= 1 << *size && *size < 12 {
+            *size += 1;
+        }
+    };
+
+    output.write(CLEAR, size);
+    let mut pixels = pixels;
+    let mut prefix = match pixels.next() {
+        Some(pixel) => pixel as u16,
+        None => {
+            output.write(END, size);
+            return output.finish();
+        }
+    };
+
+    for pixel in pixels {
+        if let Some(code) = codes.get(&(prefix, pixel)) {
+            prefix = *code;
+            continue;
+        }
+        emit(&mut output, prefix, next, &mut size);
+        if next <= MAX_CODE {
+            codes.insert((prefix, pixel), next);
+            next += 1;
+        } else {
+            output.write(CLEAR, size);
+            codes.clear();
+            next = END + 1;
+            size = 9;
+        }
+        prefix = pixel as u16;
+    }
+    emit(&mut output, prefix, next, &mut size);
+    output.write(END, size);
+    output.finish()
+}
+
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape<Data = u8>,
+{
+    /// Run the Brainfuck program to completion, adding a frame to `recorder` at the
+    /// start, every [`AnimationConfig::interval`] instructions, and at the end
+    pub fn run_recorded<FnOut, FnIn>(
+        &mut self,
+        recorder: &mut TapeRecorder,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> RunResult<u8>
+    where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        let start = RunStart::new(self);
+        let (mut output, mut input) = (Counted::new(output), Counted::new(input));
+        let interval = recorder.config.interval.max(1);
+        recorder.snapshot(&mut self.tape);
+        let mut steps = 0usize;
+        while self.step(&mut output, &mut input) {
+            steps += 1;
+            if steps.is_multiple_of(interval) {
+                recorder.snapshot(&mut self.tape);
+            }
+        }
+        if !steps.is_multiple_of(interval) {
+            recorder.snapshot(&mut self.tape);
+        }
+        start.finish(self, RunOutcome::Halted, &output, &input)
+    }
+}
@@ -0,0 +1,278 @@
+//! A tree representation of Brainfuck programs
+//!
+//! In contrast to the flat instruction list of a [`BrainfuckProgram`], loops are
+//! represented by a [`Node::Loop`] that contains its body, so there are no jump
+//! targets to keep up to date while transforming a program.
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::{Display, Write};
+
+use crate::{BrainfuckInstruction, BrainfuckProgram, Tape};
+
+/// An instruction that is not part of a loop construct
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Op {
+    /// `>` command, to increment the data pointer
+    IncrementDataPointer,
+    /// `<` command, to decrement the data pointer
+    DecrementDataPointer,
+    /// `+` command, to increment the data at the data pointer
+    IncreaseData,
+    /// `-` command, to decrement the data at the data pointer
+    DecreaseData,
+    /// `.` command, to output a byte
+    Output,
+    /// `,` command, to input a byte
+    Input,
+    /// `:` command, to call a procedure, only available in [`Dialect::PBrain`](crate::Dialect::PBrain)
+    CallProcedure,
+    /// `%` command, to call a host function, only available in [`Dialect::Hosted`](crate::Dialect::Hosted)
+    HostCall,
+    /// `^` command, to move the data pointer one row up, only available in [`Dialect::TwoD`](crate::Dialect::TwoD)
+    MoveUp,
+    /// `v` command, to move the data pointer one row down, only available in [`Dialect::TwoD`](crate::Dialect::TwoD)
+    MoveDown,
+    /// `{` command, to switch to the previous tape, only available in [`Dialect::MultiTape`](crate::Dialect::MultiTape)
+    PreviousTape,
+    /// `}` command, to switch to the next tape, only available in [`Dialect::MultiTape`](crate::Dialect::MultiTape)
+    NextTape,
+    /// `@` command, to push the current cell onto the stack, only available in [`Dialect::Stack`](crate::Dialect::Stack)
+    Push,
+    /// `$` command, to pop the stack into the current cell, only available in [`Dialect::Stack`](crate::Dialect::Stack)
+    Pop,
+}
+
+impl Op {
+    /// The character that represents this operation in source code
+    pub fn as_char(&self) -> char {
+        self.to_instruction().as_char()
+    }
+
+    /// The instruction that performs this operation
+    pub fn to_instruction(&self) -> BrainfuckInstruction {
+        match self {
+            Op::IncrementDataPointer => BrainfuckInstruction::IncrementDataPointer,
+            Op::DecrementDataPointer => BrainfuckInstruction::DecrementDataPointer,
+            Op::IncreaseData => BrainfuckInstruction::IncreaseData,
+            Op::DecreaseData => BrainfuckInstruction::DecreaseData,
+            Op::Output => BrainfuckInstruction::Output,
+            Op::Input => BrainfuckInstruction::Input,
+            Op::CallProcedure => BrainfuckInstruction::CallProcedure,
+            Op::HostCall => BrainfuckInstruction::HostCall,
+            Op::MoveUp => BrainfuckInstruction::MoveUp,
+            Op::MoveDown => BrainfuckInstruction::MoveDown,
+            Op::PreviousTape => BrainfuckInstruction::PreviousTape,
+            Op::NextTape => BrainfuckInstruction::NextTape,
+            Op::Push => BrainfuckInstruction::Push,
+            Op::Pop => BrainfuckInstruction::Pop,
+        }
+    }
+
+    /// The operation performed by `instruction`, or `None` if it is a jump or
+    /// delimits a procedure
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{ast::Op, BrainfuckInstruction};
+    /// let op = Op::from_instruction(BrainfuckInstruction::Output).unwrap();
+    /// assert_eq!(op, Op::Output);
+    /// assert_eq!(op.as_char(), '.');
+    /// assert_eq!(op.to_instruction(), BrainfuckInstruction::Output);
+    /// assert_eq!(Op::from_instruction(BrainfuckInstruction::JumpForward(3)), None);
+    /// ```
+    pub fn from_instruction(instruction: BrainfuckInstruction) -> Option<Self> {
+        Some(match instruction {
+            BrainfuckInstruction::IncrementDataPointer => Op::IncrementDataPointer,
+            BrainfuckInstruction::DecrementDataPointer => Op::DecrementDataPointer,
+            BrainfuckInstruction::IncreaseData => Op::IncreaseData,
+            BrainfuckInstruction::DecreaseData => Op::DecreaseData,
+            BrainfuckInstruction::Output => Op::Output,
+            BrainfuckInstruction::Input => Op::Input,
+            BrainfuckInstruction::CallProcedure => Op::CallProcedure,
+            BrainfuckInstruction::HostCall => Op::HostCall,
+            BrainfuckInstruction::MoveUp => Op::MoveUp,
+            BrainfuckInstruction::MoveDown => Op::MoveDown,
+            BrainfuckInstruction::PreviousTape => Op::PreviousTape,
+            BrainfuckInstruction::NextTape => Op::NextTape,
+            BrainfuckInstruction::Push => Op::Push,
+            BrainfuckInstruction::Pop => Op::Pop,
+            BrainfuckInstruction::JumpForward(_)
+            | BrainfuckInstruction::JumpBackwards(_)
+            | BrainfuckInstruction::DefineProcedure(_)
+            | BrainfuckInstruction::EndProcedure => return None,
+        })
+    }
+}
+
+/// A node in the tree representation of a Brainfuck program
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Node {
+    /// A single operation
+    Op(Op),
+    /// A loop, that executes its body while the data at the data pointer is non-zero
+    Loop(Vec<Node>),
+    /// A procedure definition, only available in [`Dialect::PBrain`](crate::Dialect::PBrain)
+    Procedure(Vec<Node>),
+}
+
+impl Node {
+    /// The amount of instructions that this node consists of, including the
+    /// brackets of loops and procedures
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::ast::{Node, Op};
+    /// let node = Node::Loop(vec![Node::Op(Op::DecreaseData), Node::Loop(vec![])]);
+    /// assert_eq!(node.instruction_count(), 5);
+    /// assert_eq!(node.to_string(), "[-[]]");
+    /// ```
+    pub fn instruction_count(&self) -> usize {
+        match self {
+            Node::Op(_) => 1,
+            Node::Loop(body) | Node::Procedure(body) => {
+                2 + body.iter().map(Node::instruction_count).sum::<usize>()
+            }
+        }
+    }
+}
+
+impl Display for Node {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Node::Op(op) => f.write_char(op.as_char()),
+            Node::Loop(body) => {
+                f.write_char('[')?;
+                for node in body {
+                    write!(f, "{}", node)?;
+                }
+                f.write_char(']')
+            }
+            Node::Procedure(body) => {
+                f.write_char('(')?;
+                for node in body {
+                    write!(f, "{}", node)?;
+                }
+                f.write_char(')')
+            }
+        }
+    }
+}
+
+/// Convert `nodes` back into Brainfuck source code, without any formatting
+///
+/// ```
+/// # use brainfuck_interpreter::{ast, BrainfuckProgram};
+/// let program = BrainfuckProgram::compile("+ [ -> + < ] comment", vec![0u8]).unwrap();
+/// assert_eq!(ast::to_source(&program.to_ast()), "+[->+<]");
+/// ```
+pub fn to_source(nodes: &[Node]) -> String {
+    let mut source = String::with_capacity(nodes.iter().map(Node::instruction_count).sum());
+    for node in nodes {
+        // Writing to a `String` cannot fail
+        write!(source, "{}", node).ok();
+    }
+    source
+}
+
+fn push_instructions(nodes: &[Node], instructions: &mut Vec<BrainfuckInstruction>) {
+    for node in nodes {
+        match node {
+            Node::Op(op) => instructions.push(op.to_instruction()),
+            Node::Loop(body) => {
+                let start = instructions.len();
+                instructions.push(BrainfuckInstruction::JumpForward(0));
+                push_instructions(body, instructions);
+                let end = instructions.len();
+                instructions.push(BrainfuckInstruction::JumpBackwards(start));
+                instructions[start] = BrainfuckInstruction::JumpForward(end);
+            }
+            Node::Procedure(body) => {
+                let start = instructions.len();
+                instructions.push(BrainfuckInstruction::DefineProcedure(0));
+                push_instructions(body, instructions);
+                let end = instructions.len();
+                instructions.push(BrainfuckInstruction::EndProcedure);
+                instructions[start] = BrainfuckInstruction::DefineProcedure(end);
+            }
+        }
+    }
+}
+
+/// Convert `nodes` into a flat list of instructions, with jump targets
+///
+/// ```
+/// # use brainfuck_interpreter::{ast::{self, Node, Op}, BrainfuckInstruction::*};
+/// let nodes = [Node::Op(Op::Input), Node::Loop(vec![Node::Op(Op::Output)])];
+/// assert_eq!(
+///     ast::to_instructions(&nodes),
+///     [Input, JumpForward(3), Output, JumpBackwards(1)]
+/// );
+/// ```
+pub fn to_instructions(nodes: &[Node]) -> Vec<BrainfuckInstruction> {
+    let mut instructions = Vec::with_capacity(nodes.iter().map(Node::instruction_count).sum());
+    push_instructions(nodes, &mut instructions);
+    instructions
+}
+
+#[allow(deprecated)]
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Convert the instructions of this program into a tree
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{ast::{Node, Op}, BrainfuckProgram};
+    /// let program = BrainfuckProgram::compile("+[-]", vec![0u8]).unwrap();
+    /// assert_eq!(
+    ///     program.to_ast(),
+    ///     [Node::Op(Op::IncreaseData), Node::Loop(vec![Node::Op(Op::DecreaseData)])]
+    /// );
+    /// ```
+    pub fn to_ast(&self) -> Vec<Node> {
+        let mut stack: Vec<Vec<Node>> = alloc::vec![Vec::new()];
+        for instruction in &self.instructions {
+            match instruction {
+                BrainfuckInstruction::JumpForward(_) | BrainfuckInstruction::DefineProcedure(_) => {
+                    stack.push(Vec::new())
+                }
+                BrainfuckInstruction::JumpBackwards(_) | BrainfuckInstruction::EndProcedure => {
+                    let body = stack
+                        .pop()
+                        .expect("Compiled programs have matching brackets");
+                    let node = match instruction {
+                        BrainfuckInstruction::JumpBackwards(_) => Node::Loop(body),
+                        _ => Node::Procedure(body),
+                    };
+                    stack
+                        .last_mut()
+                        .expect("Compiled programs have matching brackets")
+                        .push(node);
+                }
+                instruction => {
+                    if let (Some(op), Some(nodes)) =
+                        (Op::from_instruction(*instruction), stack.last_mut())
+                    {
+                        nodes.push(Node::Op(op));
+                    }
+                }
+            }
+        }
+        stack.pop().unwrap_or_default()
+    }
+
+    /// Create a program from a tree of instructions, running on `tape`
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{ast::{Node, Op}, BrainfuckProgram};
+    /// let echo = Node::Loop(vec![Node::Op(Op::Output), Node::Op(Op::Input)]);
+    /// let nodes = [Node::Op(Op::Input), echo];
+    /// let mut program = BrainfuckProgram::from_ast(&nodes, vec![0u8]);
+    ///
+    /// let mut input = b"hi".iter().copied();
+    /// let mut output = Vec::new();
+    /// program.run(&mut |value| output.push(value), &mut || input.next().unwrap_or(0));
+    /// assert_eq!(output, b"hi");
+    /// ```
+    pub fn from_ast(nodes: &[Node], tape: T) -> Self {
+        Self::from_instructions(to_instructions(nodes), tape)
+    }
+}
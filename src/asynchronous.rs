@@ -0,0 +1,135 @@
+//! Asynchronous execution of Brainfuck programs
+
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{
+    result::{Counted, RunStart},
+    BrainfuckInstruction, BrainfuckProgram, RunOutcome, RunResult, RuntimeError, Tape,
+};
+
+/// A future that returns `Pending` exactly once, so that the executor
+/// gets a chance to run other tasks
+#[derive(Default)]
+struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            Poll::Ready(())
+        } else {
+            self.yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Run the Brainfuck program to completion, or until it fails, awaiting `output`
+    /// for every `.` and `input` for every `,`
+    ///
+    /// Every `yield_every` instructions, control is handed back to the executor
+    /// so that long-running programs do not starve other tasks. A `yield_every`
+    /// of `0` disables yielding. Like [`try_run`](Self::try_run), this returns a
+    /// [`RunResult`], or an error instead of panicking. Input is only awaited once a `,`
+    /// is about to execute.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::BrainfuckProgram;
+    /// # use std::{future::Future, pin::pin, task::{Context, Poll, Waker}};
+    /// // An executor that polls a single future until it is ready
+    /// fn block_on<F: Future>(future: F) -> F::Output {
+    ///     let mut future = pin!(future);
+    ///     let mut cx = Context::from_waker(Waker::noop());
+    ///     loop {
+    ///         if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+    ///             return output;
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut program = BrainfuckProgram::compile(",[.,]", vec![0u8]).unwrap();
+    /// let mut input = b"echo".iter().copied();
+    /// let mut output = Vec::new();
+    /// block_on(program.run_async(
+    ///     &mut |data| {
+    ///         output.push(data);
+    ///         async {}
+    ///     },
+    ///     &mut || std::future::ready(input.next().unwrap_or(0)),
+    ///     2,
+    /// ))
+    /// .unwrap();
+    /// assert_eq!(output, b"echo");
+    ///
+    /// // The input is not read by a `,` that the program stops at
+    /// let mut program = BrainfuckProgram::compile("+<,", [0u8; 1]).unwrap();
+    /// let mut reads = 0;
+    /// let mut input = || {
+    ///     reads += 1;
+    ///     async { 0 }
+    /// };
+    /// assert!(block_on(program.run_async(&mut |_| async {}, &mut input, 0)).is_err());
+    /// assert_eq!(reads, 0);
+    /// ```
+    pub async fn run_async<FnOut, FutOut, FnIn, FutIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+        yield_every: usize,
+    ) -> Result<RunResult<T::Data>, RuntimeError>
+    where
+        FnOut: FnMut(T::Data) -> FutOut,
+        FutOut: Future<Output = ()>,
+        FnIn: FnMut() -> FutIn,
+        FutIn: Future<Output = T::Data>,
+    {
+        let start = RunStart::new(self);
+        let (mut output, mut input) = (Counted::new(output), Counted::new(input));
+        let mut since_yield = 0;
+        loop {
+            // Input is only read once the `,` is sure to execute, so that no input is
+            // lost if the program stops at it
+            let mut read = None;
+            if let Some(BrainfuckInstruction::Input) =
+                self.instructions.get(self.instruction_pointer)
+            {
+                if self.tape.get_data_at_mut(self.data_pointer).is_some() {
+                    read = Some((input.count_one())().await);
+                }
+            }
+
+            let mut written = None;
+            let running = self.try_step(&mut |data| written = Some(data), &mut || {
+                read.take()
+                    .expect("Input was not read before executing `,`")
+            })?;
+
+            if let Some(data) = written {
+                (output.count_one())(data).await;
+            }
+
+            if !running {
+                return Ok(start.finish(self, RunOutcome::Halted, &output, &input));
+            }
+
+            since_yield += 1;
+            if yield_every != 0 && since_yield >= yield_every {
+                since_yield = 0;
+                YieldNow::default().await;
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Choosing the size of the tape automatically
+
+use alloc::vec::Vec;
+
+use crate::{analyze, BrainfuckProgram, Error, Tape, TapeData};
+
+/// A tape that grows whenever a cell past its end is accessed, and remembers how many
+/// cells have been accessed
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GrowingTape<D> {
+    cells: Vec<D>,
+    high_water_mark: usize,
+}
+
+impl<D> GrowingTape<D>
+where
+    D: TapeData,
+{
+    /// Create an empty tape
+    pub fn new() -> Self {
+        Self {
+            cells: Vec::new(),
+            high_water_mark: 0,
+        }
+    }
+
+    /// Create a tape that starts out with `len` cells
+    pub fn with_len(len: usize) -> Self {
+        Self {
+            cells: alloc::vec![D::zero(); len],
+            high_water_mark: 0,
+        }
+    }
+
+    /// The amount of cells, starting at the first one, up to and including the last
+    /// cell that has been accessed
+    pub fn high_water_mark(&self) -> usize {
+        self.high_water_mark
+    }
+
+    /// The cells of this tape
+    pub fn cells(&self) -> &[D] {
+        &self.cells
+    }
+
+    /// Consume this tape, returning its cells
+    pub fn into_inner(self) -> Vec<D> {
+        self.cells
+    }
+
+    fn access(&mut self, index: usize) {
+        if self.cells.len() <= index {
+            self.cells.resize(index + 1, D::zero());
+        }
+        self.high_water_mark = self.high_water_mark.max(index + 1);
+    }
+}
+
+impl<D> Tape for GrowingTape<D>
+where
+    D: TapeData,
+{
+    type Data = D;
+
+    fn get_data_at(&mut self, index: usize) -> Option<&D> {
+        self.access(index);
+        self.cells.get(index)
+    }
+
+    fn get_data_at_mut(&mut self, index: usize) -> Option<&mut D> {
+        self.access(index);
+        self.cells.get_mut(index)
+    }
+
+    fn reset(&mut self) {
+        self.cells.iter_mut().for_each(|data| *data = D::zero());
+        self.high_water_mark = 0;
+    }
+
+    fn len_hint(&self) -> Option<usize> {
+        Some(self.cells.len())
+    }
+}
+
+/// The tape chosen by [`BrainfuckProgram::compile_auto`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AutoTape {
+    /// The program was shown to never use more than these cells
+    Fixed(Vec<u8>),
+    /// The amount of cells that the program uses could not be determined, so the tape
+    /// grows as needed
+    Growing(GrowingTape<u8>),
+}
+
+impl AutoTape {
+    /// The amount of cells that have been accessed, if the tape is growing
+    pub fn high_water_mark(&self) -> Option<usize> {
+        match self {
+            AutoTape::Fixed(_) => None,
+            AutoTape::Growing(tape) => Some(tape.high_water_mark()),
+        }
+    }
+
+    /// The cells of this tape
+    pub fn cells(&self) -> &[u8] {
+        match self {
+            AutoTape::Fixed(cells) => cells,
+            AutoTape::Growing(tape) => tape.cells(),
+        }
+    }
+}
+
+impl Tape for AutoTape {
+    type Data = u8;
+
+    fn get_data_at(&mut self, index: usize) -> Option<&u8> {
+        match self {
+            AutoTape::Fixed(cells) => cells.get(index),
+            AutoTape::Growing(tape) => tape.get_data_at(index),
+        }
+    }
+
+    fn get_data_at_mut(&mut self, index: usize) -> Option<&mut u8> {
+        match self {
+            AutoTape::Fixed(cells) => cells.get_mut(index),
+            AutoTape::Growing(tape) => tape.get_data_at_mut(index),
+        }
+    }
+
+    fn reset(&mut self) {
+        match self {
+            AutoTape::Fixed(cells) => cells.iter_mut().for_each(|cell| *cell = 0),
+            AutoTape::Growing(tape) => tape.reset(),
+        }
+    }
+
+    fn len_hint(&self) -> Option<usize> {
+        Some(self.cells().len())
+    }
+
+    fn as_mut_slice(&mut self) -> Option<&mut [u8]> {
+        match self {
+            AutoTape::Fixed(cells) => Some(cells),
+            // Growing tapes keep track of the cells that are accessed
+            AutoTape::Growing(_) => None,
+        }
+    }
+}
+
+impl BrainfuckProgram<AutoTape> {
+    /// Compile a Brainfuck program, given by `input`, with a tape of byte cells that is
+    /// sized using [`analyze`]. All non-valid characters are ignored
+    ///
+    /// If the analysis shows how many cells the program uses, the tape has exactly that
+    /// many cells. Otherwise, the tape starts out with the cells that the analysis found,
+    /// and grows as needed. Its [`high_water_mark`](AutoTape::high_water_mark) shows how
+    /// many cells the program used.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{AutoTape, BrainfuckProgram};
+    /// let mut program = BrainfuckProgram::compile_auto("++[>+++<-]>.").unwrap();
+    /// assert_eq!(program.tape, AutoTape::Fixed(vec![0; 2]));
+    ///
+    /// let mut program = BrainfuckProgram::compile_auto("+[>+]").unwrap();
+    /// assert!(matches!(program.tape, AutoTape::Growing(_)));
+    /// ```
+    pub fn compile_auto(input: &str) -> Result<Self, Error<'_>> {
+        Self::compile(input, auto_tape(input)?)
+    }
+
+    /// Compile a Brainfuck program like [`compile_auto`](Self::compile_auto), retaining
+    /// the position in `input` of every instruction like
+    /// [`compile_with_source_map`](Self::compile_with_source_map)
+    pub fn compile_auto_with_source_map(input: &str) -> Result<Self, Error<'_>> {
+        Self::compile_with_source_map(input, auto_tape(input)?)
+    }
+}
+
+/// A tape for `input` that is sized using [`analyze`]
+fn auto_tape(input: &str) -> Result<AutoTape, Error<'_>> {
+    let analysis = analyze(input)?;
+    Ok(if analysis.pointer_bounded {
+        AutoTape::Fixed(alloc::vec![0; analysis.min_tape_cells])
+    } else {
+        AutoTape::Growing(GrowingTape::with_len(analysis.min_tape_cells))
+    })
+}
@@ -0,0 +1,39 @@
+//! Native execution on platforms without a machine code emitter
+
+use crate::{
+    io::{Input, Output},
+    BrainfuckProgram, RunResult, RuntimeError, Tape,
+};
+
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape<Data = u8> + AsMut<[u8]>,
+{
+    /// Run the Brainfuck program to completion
+    ///
+    /// Machine code generation is not supported on this platform, so this is
+    /// equivalent to [`run`](Self::run).
+    pub fn run_native<FnOut, FnIn>(&mut self, output: &mut FnOut, input: &mut FnIn) -> RunResult<u8>
+    where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        self.run(output, input)
+    }
+
+    /// Run the Brainfuck program to completion
+    ///
+    /// Machine code generation is not supported on this platform, so this is
+    /// equivalent to [`try_run`](Self::try_run).
+    pub fn try_run_native<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<RunResult<u8>, RuntimeError>
+    where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        self.try_run(output, input)
+    }
+}
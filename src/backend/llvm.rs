@@ -0,0 +1,313 @@
+//! Emit a compiled program as textual LLVM IR
+
+use alloc::string::String;
+use core::fmt::Write;
+
+use crate::{BrainfuckInstruction, BrainfuckProgram, EofBehavior, Tape};
+
+/// Options for [`BrainfuckProgram::to_llvm_ir`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LlvmOptions {
+    /// The width of every cell, in bits. Cells wrap around on overflow
+    pub cell_bits: u32,
+    /// The amount of cells on the tape
+    pub tape_size: usize,
+    /// Whether moving the data pointer off the tape exits the program with status 1.
+    /// Without bounds checks, doing so is undefined behaviour
+    pub bounds_checks: bool,
+    /// What `,` does at the end of input
+    pub eof: EofBehavior,
+}
+
+impl Default for LlvmOptions {
+    /// Byte-sized cells, a tape of 30000 cells, bounds checks, and storing zero at the
+    /// end of input
+    fn default() -> Self {
+        Self {
+            cell_bits: 8,
+            tape_size: 30_000,
+            bounds_checks: true,
+            eof: EofBehavior::Zero,
+        }
+    }
+}
+
+/// Writes the body of `main`, with a fresh name for every value and block
+struct Emitter<'a> {
+    options: &'a LlvmOptions,
+    ir: String,
+    next: usize,
+}
+
+impl<'a> Emitter<'a> {
+    /// A name that has not been used yet
+    fn fresh(&mut self) -> String {
+        self.next += 1;
+        alloc::format!("%t{}", self.next)
+    }
+
+    /// The type of a cell
+    fn cell(&self) -> String {
+        alloc::format!("i{}", self.options.cell_bits)
+    }
+
+    /// `amount`, wrapped around to fit in a cell and written as a signed constant
+    fn wrapping(&self, amount: i128) -> i128 {
+        if self.options.cell_bits >= 64 {
+            return amount;
+        }
+        let modulus = 1i128 << self.options.cell_bits;
+        let amount = amount.rem_euclid(modulus);
+        if amount >= modulus / 2 {
+            amount - modulus
+        } else {
+            amount
+        }
+    }
+
+    /// Emit a pointer to the cell at the data pointer, and return its name
+    fn cell_pointer(&mut self) -> String {
+        let (pointer, address) = (self.fresh(), self.fresh());
+        // Writing to a `String` cannot fail
+        writeln!(self.ir, "  {} = load i64, ptr %dp", pointer).ok();
+        writeln!(
+            self.ir,
+            "  {} = getelementptr inbounds [{} x {}], ptr @tape, i64 0, i64 {}",
+            address,
+            self.options.tape_size,
+            self.cell(),
+            pointer
+        )
+        .ok();
+        address
+    }
+
+    /// Emit a load of the cell at the data pointer, and return the address and value
+    fn load_cell(&mut self) -> (String, String) {
+        let address = self.cell_pointer();
+        let value = self.fresh();
+        let cell = self.cell();
+        writeln!(self.ir, "  {} = load {}, ptr {}", value, cell, address).ok();
+        (address, value)
+    }
+
+    /// Convert `value` from `from` bits to `to` bits, and return the converted value
+    fn convert(&mut self, value: String, from: u32, to: u32) -> String {
+        let operation = match from.cmp(&to) {
+            core::cmp::Ordering::Less => "zext",
+            core::cmp::Ordering::Greater => "trunc",
+            core::cmp::Ordering::Equal => return value,
+        };
+        let converted = self.fresh();
+        writeln!(
+            self.ir,
+            "  {} = {} i{} {} to i{}",
+            converted, operation, from, value, to
+        )
+        .ok();
+        converted
+    }
+
+    /// Emit moving the data pointer by `amount`
+    fn move_pointer(&mut self, amount: i64) {
+        let (pointer, moved) = (self.fresh(), self.fresh());
+        writeln!(self.ir, "  {} = load i64, ptr %dp", pointer).ok();
+        writeln!(self.ir, "  {} = add i64 {}, {}", moved, pointer, amount).ok();
+        if self.options.bounds_checks {
+            // A pointer below zero wraps around, and is larger than the tape as well
+            let (on_tape, label) = (self.fresh(), self.fresh());
+            writeln!(
+                self.ir,
+                "  {} = icmp ult i64 {}, {}",
+                on_tape, moved, self.options.tape_size
+            )
+            .ok();
+            writeln!(
+                self.ir,
+                "  br i1 {}, label {}, label %out_of_bounds",
+                on_tape, label
+            )
+            .ok();
+            writeln!(self.ir, "{}:", &label[1..]).ok();
+        }
+        writeln!(self.ir, "  store i64 {}, ptr %dp", moved).ok();
+    }
+
+    /// Emit adding `amount` to the cell at the data pointer
+    fn change_cell(&mut self, amount: i128) {
+        let (address, value) = self.load_cell();
+        let changed = self.fresh();
+        let cell = self.cell();
+        let amount = self.wrapping(amount);
+        writeln!(
+            self.ir,
+            "  {} = add {} {}, {}",
+            changed, cell, value, amount
+        )
+        .ok();
+        writeln!(self.ir, "  store {} {}, ptr {}", cell, changed, address).ok();
+    }
+
+    /// Emit writing the cell at the data pointer
+    fn output(&mut self) {
+        let (_, value) = self.load_cell();
+        let character = self.convert(value, self.options.cell_bits, 32);
+        writeln!(self.ir, "  call i32 @putchar(i32 {})", character).ok();
+    }
+
+    /// Emit reading into the cell at the data pointer
+    fn input(&mut self) {
+        let (read, end_of_file) = (self.fresh(), self.fresh());
+        writeln!(self.ir, "  {} = call i32 @getchar()", read).ok();
+        writeln!(self.ir, "  {} = icmp slt i32 {}, 0", end_of_file, read).ok();
+        let value = self.convert(read, 32, self.options.cell_bits);
+
+        let (address, current) = match self.options.eof {
+            EofBehavior::Zero => (self.cell_pointer(), String::from("0")),
+            EofBehavior::Unchanged => self.load_cell(),
+        };
+        let (stored, cell) = (self.fresh(), self.cell());
+        writeln!(
+            self.ir,
+            "  {} = select i1 {}, {} {}, {} {}",
+            stored, end_of_file, cell, current, cell, value
+        )
+        .ok();
+        writeln!(self.ir, "  store {} {}, ptr {}", cell, stored, address).ok();
+    }
+
+    /// Emit `instructions`, merging runs of the same instruction
+    fn emit(&mut self, instructions: &[BrainfuckInstruction]) {
+        let mut index = 0;
+        while let Some(instruction) = instructions.get(index) {
+            let run = instructions[index..]
+                .iter()
+                .take_while(|next| {
+                    core::mem::discriminant(*next) == core::mem::discriminant(instruction)
+                })
+                .count();
+
+            match instruction {
+                BrainfuckInstruction::IncrementDataPointer => {
+                    self.move_pointer(run as i64);
+                    index += run;
+                    continue;
+                }
+                BrainfuckInstruction::DecrementDataPointer => {
+                    self.move_pointer(-(run as i64));
+                    index += run;
+                    continue;
+                }
+                BrainfuckInstruction::IncreaseData => {
+                    self.change_cell(run as i128);
+                    index += run;
+                    continue;
+                }
+                BrainfuckInstruction::DecreaseData => {
+                    self.change_cell(-(run as i128));
+                    index += run;
+                    continue;
+                }
+                BrainfuckInstruction::Output => self.output(),
+                BrainfuckInstruction::Input => self.input(),
+                BrainfuckInstruction::JumpForward(_) => {
+                    // The loop is entered while the cell is non-zero, and left at its `]`
+                    writeln!(self.ir, "  br label %loop{}", index).ok();
+                    writeln!(self.ir, "loop{}:", index).ok();
+                    let (_, value) = self.load_cell();
+                    let zero = self.fresh();
+                    let cell = self.cell();
+                    writeln!(self.ir, "  {} = icmp eq {} {}, 0", zero, cell, value).ok();
+                    writeln!(
+                        self.ir,
+                        "  br i1 {}, label %end{}, label %body{}",
+                        zero, index, index
+                    )
+                    .ok();
+                    writeln!(self.ir, "body{}:", index).ok();
+                }
+                BrainfuckInstruction::JumpBackwards(start) => {
+                    writeln!(self.ir, "  br label %loop{}", start).ok();
+                    writeln!(self.ir, "end{}:", start).ok();
+                }
+                BrainfuckInstruction::DefineProcedure(_)
+                | BrainfuckInstruction::EndProcedure
+                | BrainfuckInstruction::CallProcedure
+                | BrainfuckInstruction::HostCall
+                | BrainfuckInstruction::MoveUp
+                | BrainfuckInstruction::MoveDown
+                | BrainfuckInstruction::PreviousTape
+                | BrainfuckInstruction::NextTape
+                | BrainfuckInstruction::Push
+                | BrainfuckInstruction::Pop => {
+                    writeln!(self.ir, "  call void @llvm.trap()").ok();
+                }
+            }
+            index += 1;
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Translate this program into a module of textual LLVM IR
+    ///
+    /// The module defines `main`, which runs the program and returns 0, and can be
+    /// compiled with `clang program.ll` or run with `lli program.ll`. It uses opaque
+    /// pointers, so it needs LLVM 15 or later. Output is written with `putchar`, and
+    /// input is read with `getchar`.
+    ///
+    /// The cells, the tape and the end of input are described by `options`. The tape and state of `self` are
+    /// not used. Extensions of Brainfuck, like procedures
+    /// ([`Dialect::PBrain`](crate::Dialect::PBrain)) or host calls
+    /// ([`Dialect::Hosted`](crate::Dialect::Hosted)), are not supported: reaching any of
+    /// their instructions traps.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckProgram, LlvmOptions};
+    /// let program = BrainfuckProgram::compile("+[>+<-]>.", vec![0u8; 2]).unwrap();
+    /// let options = LlvmOptions {
+    ///     cell_bits: 16,
+    ///     ..LlvmOptions::default()
+    /// };
+    ///
+    /// let ir = program.to_llvm_ir(&options);
+    /// assert!(ir.contains("@tape = internal global [30000 x i16] zeroinitializer"));
+    /// assert!(ir.contains("define i32 @main() {"));
+    /// ```
+    pub fn to_llvm_ir(&self, options: &LlvmOptions) -> String {
+        let mut emitter = Emitter {
+            options,
+            ir: String::new(),
+            next: 0,
+        };
+        emitter.emit(&self.instructions);
+
+        let mut module = String::new();
+        let cell = emitter.cell();
+        writeln!(
+            module,
+            "@tape = internal global [{} x {}] zeroinitializer\n",
+            options.tape_size, cell
+        )
+        .ok();
+        module.push_str(
+            "declare i32 @putchar(i32)\n\
+             declare i32 @getchar()\n\
+             declare void @exit(i32) noreturn\n\
+             declare void @llvm.trap() noreturn\n",
+        );
+        module.push_str("\ndefine i32 @main() {\nentry:\n");
+        module.push_str("  %dp = alloca i64\n  store i64 0, ptr %dp\n");
+        module.push_str(&emitter.ir);
+        module.push_str("  ret i32 0\n");
+        if options.bounds_checks {
+            module.push_str("out_of_bounds:\n  call void @exit(i32 1)\n  unreachable\n");
+        }
+        module.push_str("}\n");
+        module
+    }
+}
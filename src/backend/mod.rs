@@ -0,0 +1,19 @@
+//! Backends that translate a compiled program into another representation
+
+#[cfg(feature = "llvm-ir")]
+mod llvm;
+
+mod wasm;
+
+#[cfg(all(feature = "native-jit", target_arch = "x86_64", unix))]
+#[allow(unsafe_code)]
+mod x86_64;
+
+#[cfg(all(feature = "native-jit", not(all(target_arch = "x86_64", unix))))]
+mod fallback;
+
+#[cfg(feature = "llvm-ir")]
+pub use llvm::LlvmOptions;
+
+#[cfg(all(feature = "native-jit", target_arch = "x86_64", unix))]
+pub(crate) use x86_64::NativeLoop;
@@ -0,0 +1,245 @@
+//! Emit a compiled program as a standalone WebAssembly module
+
+use alloc::vec::Vec;
+
+use crate::{BrainfuckInstruction, BrainfuckProgram, Tape};
+
+/// The amount of 64 KiB pages of linear memory used as the tape
+const TAPE_PAGES: u32 = 1;
+
+fn write_unsigned(buffer: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            buffer.push(byte);
+            return;
+        }
+        buffer.push(byte | 0x80);
+    }
+}
+
+fn write_signed(buffer: &mut Vec<u8>, mut value: i32) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            buffer.push(byte);
+            return;
+        }
+        buffer.push(byte | 0x80);
+    }
+}
+
+fn write_name(buffer: &mut Vec<u8>, name: &str) {
+    write_unsigned(buffer, name.len() as u32);
+    buffer.extend_from_slice(name.as_bytes());
+}
+
+fn write_section(module: &mut Vec<u8>, id: u8, contents: &[u8]) {
+    module.push(id);
+    write_unsigned(module, contents.len() as u32);
+    module.extend_from_slice(contents);
+}
+
+/// Opcodes and immediates used by the emitter
+mod op {
+    pub const UNREACHABLE: u8 = 0x00;
+    pub const BLOCK: u8 = 0x02;
+    pub const LOOP: u8 = 0x03;
+    pub const END: u8 = 0x0B;
+    pub const BR_IF: u8 = 0x0D;
+    pub const CALL: u8 = 0x10;
+    pub const LOCAL_GET: u8 = 0x20;
+    pub const LOCAL_SET: u8 = 0x21;
+    pub const I32_LOAD8_U: u8 = 0x2D;
+    pub const I32_STORE8: u8 = 0x3A;
+    pub const I32_CONST: u8 = 0x41;
+    pub const I32_EQZ: u8 = 0x45;
+    pub const I32_ADD: u8 = 0x6A;
+    pub const EMPTY_BLOCK_TYPE: u8 = 0x40;
+    pub const I32: u8 = 0x7F;
+    pub const FUNC_TYPE: u8 = 0x60;
+}
+
+const OUTPUT_FUNCTION: u8 = 0;
+const INPUT_FUNCTION: u8 = 1;
+const POINTER_LOCAL: u8 = 0;
+
+fn load_cell(code: &mut Vec<u8>) {
+    code.extend_from_slice(&[op::LOCAL_GET, POINTER_LOCAL, op::I32_LOAD8_U, 0, 0]);
+}
+
+fn emit_body(instructions: &[BrainfuckInstruction]) -> Vec<u8> {
+    let mut code = Vec::new();
+    let mut index = 0;
+    while let Some(instruction) = instructions.get(index) {
+        let run = instructions[index..]
+            .iter()
+            .take_while(|next| {
+                core::mem::discriminant(*next) == core::mem::discriminant(instruction)
+            })
+            .count();
+
+        match instruction {
+            BrainfuckInstruction::IncrementDataPointer
+            | BrainfuckInstruction::DecrementDataPointer => {
+                let amount = match instruction {
+                    BrainfuckInstruction::IncrementDataPointer => run as i32,
+                    _ => -(run as i32),
+                };
+                code.extend_from_slice(&[op::LOCAL_GET, POINTER_LOCAL, op::I32_CONST]);
+                write_signed(&mut code, amount);
+                code.extend_from_slice(&[op::I32_ADD, op::LOCAL_SET, POINTER_LOCAL]);
+                index += run;
+                continue;
+            }
+            BrainfuckInstruction::IncreaseData | BrainfuckInstruction::DecreaseData => {
+                let amount = match instruction {
+                    BrainfuckInstruction::IncreaseData => run as i32,
+                    _ => -(run as i32),
+                };
+                code.extend_from_slice(&[op::LOCAL_GET, POINTER_LOCAL]);
+                load_cell(&mut code);
+                code.push(op::I32_CONST);
+                write_signed(&mut code, amount);
+                code.extend_from_slice(&[op::I32_ADD, op::I32_STORE8, 0, 0]);
+                index += run;
+                continue;
+            }
+            BrainfuckInstruction::Output => {
+                load_cell(&mut code);
+                code.extend_from_slice(&[op::CALL, OUTPUT_FUNCTION]);
+            }
+            BrainfuckInstruction::Input => {
+                code.extend_from_slice(&[
+                    op::LOCAL_GET,
+                    POINTER_LOCAL,
+                    op::CALL,
+                    INPUT_FUNCTION,
+                    op::I32_STORE8,
+                    0,
+                    0,
+                ]);
+            }
+            BrainfuckInstruction::JumpForward(_) => {
+                // block
+                //   br_if 0 (cell == 0)
+                //   loop
+                code.extend_from_slice(&[op::BLOCK, op::EMPTY_BLOCK_TYPE]);
+                load_cell(&mut code);
+                code.extend_from_slice(&[
+                    op::I32_EQZ,
+                    op::BR_IF,
+                    0,
+                    op::LOOP,
+                    op::EMPTY_BLOCK_TYPE,
+                ]);
+            }
+            BrainfuckInstruction::JumpBackwards(_) => {
+                //   br_if 0 (cell != 0)
+                //   end
+                // end
+                load_cell(&mut code);
+                code.extend_from_slice(&[op::BR_IF, 0, op::END, op::END]);
+            }
+            BrainfuckInstruction::DefineProcedure(_)
+            | BrainfuckInstruction::EndProcedure
+            | BrainfuckInstruction::CallProcedure
+            | BrainfuckInstruction::HostCall
+            | BrainfuckInstruction::MoveUp
+            | BrainfuckInstruction::MoveDown
+            | BrainfuckInstruction::PreviousTape
+            | BrainfuckInstruction::NextTape
+            | BrainfuckInstruction::Push
+            | BrainfuckInstruction::Pop => code.push(op::UNREACHABLE),
+        }
+        index += 1;
+    }
+    code.push(op::END);
+    code
+}
+
+#[allow(deprecated)]
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Translate this program into a standalone WebAssembly module
+    ///
+    /// The module imports two functions from the `env` module: `output`, of type
+    /// `(i32) -> ()`, which is called for every `.`, and `input`, of type `() -> i32`,
+    /// which is called for every `,`. It exports a function `run`, of type `() -> ()`,
+    /// which runs the program, and `memory`, its tape.
+    ///
+    /// The tape consists of 65536 byte-sized, wrapping cells. Moving the data pointer
+    /// outside of the tape traps. The tape and state of `self` are not used.
+    ///
+    /// Extensions of Brainfuck, like procedures ([`Dialect::PBrain`](crate::Dialect::PBrain))
+    /// or host calls ([`Dialect::Hosted`](crate::Dialect::Hosted)), are not supported:
+    /// reaching any of their instructions traps.
+    pub fn to_wasm_module(&self) -> Vec<u8> {
+        let mut module = Vec::new();
+        module.extend_from_slice(b"\0asm");
+        module.extend_from_slice(&1u32.to_le_bytes());
+
+        // Types: 0 = (i32) -> (), 1 = () -> i32, 2 = () -> ()
+        let types = [
+            op::FUNC_TYPE,
+            1,
+            op::I32,
+            0,
+            op::FUNC_TYPE,
+            0,
+            1,
+            op::I32,
+            op::FUNC_TYPE,
+            0,
+            0,
+        ];
+        let mut section = Vec::new();
+        write_unsigned(&mut section, 3);
+        section.extend_from_slice(&types);
+        write_section(&mut module, 1, &section);
+
+        let mut section = Vec::new();
+        write_unsigned(&mut section, 2);
+        for (name, ty) in [("output", 0), ("input", 1)].iter() {
+            write_name(&mut section, "env");
+            write_name(&mut section, name);
+            section.extend_from_slice(&[0x00, *ty]);
+        }
+        write_section(&mut module, 2, &section);
+
+        // Function 2 (after the imports) has type 2
+        write_section(&mut module, 3, &[1, 2]);
+
+        let mut section = Vec::new();
+        write_unsigned(&mut section, 1);
+        section.push(0x00);
+        write_unsigned(&mut section, TAPE_PAGES);
+        write_section(&mut module, 5, &section);
+
+        let mut section = Vec::new();
+        write_unsigned(&mut section, 2);
+        write_name(&mut section, "memory");
+        section.extend_from_slice(&[0x02, 0]);
+        write_name(&mut section, "run");
+        section.extend_from_slice(&[0x00, 2]);
+        write_section(&mut module, 7, &section);
+
+        // A single local: the data pointer
+        let mut function = Vec::new();
+        function.extend_from_slice(&[1, 1, op::I32]);
+        function.extend(emit_body(&self.instructions));
+
+        let mut section = Vec::new();
+        write_unsigned(&mut section, 1);
+        write_unsigned(&mut section, function.len() as u32);
+        section.extend(function);
+        write_section(&mut module, 10, &section);
+
+        module
+    }
+}
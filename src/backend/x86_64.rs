@@ -0,0 +1,495 @@
+//! An x86-64 machine code emitter, for running programs natively
+//!
+//! The generated function keeps the state it needs in callee-saved registers:
+//!
+//! * `r12`: a pointer to the [`Context`]
+//! * `r13`: the base address of the tape
+//! * `r14`: the length of the tape
+//! * `rbx`: the data pointer
+//!
+//! Every data pointer move is bounds-checked. If a move would leave the tape, the
+//! function returns the index of the instruction that performed it, so that the
+//! interpreter can take over from there.
+
+use core::ffi::c_void;
+
+use alloc::vec::Vec;
+
+use crate::{
+    io::{Input, Output},
+    result::{Counted, RunStart},
+    BrainfuckInstruction, BrainfuckProgram, RunOutcome, RunResult, RuntimeError, Tape,
+};
+
+/// The state shared between Rust and the generated code
+#[repr(C)]
+struct Context {
+    tape: *mut u8,
+    len: usize,
+    data_pointer: usize,
+    output: extern "sysv64" fn(*mut c_void, u8),
+    input: extern "sysv64" fn(*mut c_void) -> u8,
+    callbacks: *mut c_void,
+}
+
+const CONTEXT_DATA_POINTER: u8 = 16;
+const CONTEXT_OUTPUT: u8 = 24;
+const CONTEXT_INPUT: u8 = 32;
+const CONTEXT_CALLBACKS: u8 = 40;
+
+/// The value returned by the generated code if the program ran to completion
+const FINISHED: u64 = u64::MAX;
+
+struct Callbacks<'a, FnOut, FnIn> {
+    output: &'a mut FnOut,
+    input: &'a mut FnIn,
+}
+
+extern "sysv64" fn output_trampoline<FnOut, FnIn>(callbacks: *mut c_void, data: u8)
+where
+    FnOut: Output<u8>,
+{
+    // SAFETY: `callbacks` is the pointer to `Callbacks` that was placed in the context
+    let callbacks = unsafe { &mut *(callbacks as *mut Callbacks<FnOut, FnIn>) };
+    callbacks.output.output(data)
+}
+
+extern "sysv64" fn input_trampoline<FnOut, FnIn>(callbacks: *mut c_void) -> u8
+where
+    FnIn: Input<u8>,
+{
+    // SAFETY: `callbacks` is the pointer to `Callbacks` that was placed in the context
+    let callbacks = unsafe { &mut *(callbacks as *mut Callbacks<FnOut, FnIn>) };
+    callbacks.input.input()
+}
+
+/// Emits the instructions for the cell operand `byte [r13 + rbx]`, with
+/// `reg` in the `reg` field of the ModRM byte
+fn cell_operand(code: &mut Vec<u8>, reg: u8) {
+    code.extend_from_slice(&[0x44 | (reg << 3), 0x1D, 0x00]);
+}
+
+fn patch_rel32(code: &mut [u8], at: usize, target: usize) {
+    let rel = target as i64 - (at as i64 + 4);
+    code[at..at + 4].copy_from_slice(&(rel as i32).to_le_bytes());
+}
+
+fn emit(instructions: &[BrainfuckInstruction]) -> Vec<u8> {
+    let mut code = Vec::new();
+
+    // push rbx; push r12; push r13; push r14; push r15
+    code.extend_from_slice(&[0x53, 0x41, 0x54, 0x41, 0x55, 0x41, 0x56, 0x41, 0x57]);
+    // mov r12, rdi
+    code.extend_from_slice(&[0x49, 0x89, 0xFC]);
+    // mov r13, [r12]; mov r14, [r12 + 8]; mov rbx, [r12 + 16]
+    code.extend_from_slice(&[0x4D, 0x8B, 0x2C, 0x24]);
+    code.extend_from_slice(&[0x4D, 0x8B, 0x74, 0x24, 0x08]);
+    code.extend_from_slice(&[0x49, 0x8B, 0x5C, 0x24, CONTEXT_DATA_POINTER]);
+
+    let mut open_loops = Vec::new();
+    let mut bailouts = Vec::new();
+
+    let mut index = 0;
+    while let Some(instruction) = instructions.get(index) {
+        let run = instructions[index..]
+            .iter()
+            .take(i32::MAX as usize)
+            .take_while(|next| {
+                core::mem::discriminant(*next) == core::mem::discriminant(instruction)
+            })
+            .count();
+
+        match instruction {
+            BrainfuckInstruction::IncrementDataPointer
+            | BrainfuckInstruction::DecrementDataPointer => {
+                let amount = match instruction {
+                    BrainfuckInstruction::IncrementDataPointer => run as i32,
+                    _ => -(run as i32),
+                };
+                // lea rax, [rbx + amount]
+                code.extend_from_slice(&[0x48, 0x8D, 0x83]);
+                code.extend_from_slice(&amount.to_le_bytes());
+                // cmp rax, r14; jae bailout
+                code.extend_from_slice(&[0x4C, 0x39, 0xF0, 0x0F, 0x83, 0, 0, 0, 0]);
+                bailouts.push((code.len() - 4, index));
+                // mov rbx, rax
+                code.extend_from_slice(&[0x48, 0x89, 0xC3]);
+                index += run;
+                continue;
+            }
+            BrainfuckInstruction::IncreaseData | BrainfuckInstruction::DecreaseData => {
+                let amount = match instruction {
+                    BrainfuckInstruction::IncreaseData => run as u8,
+                    _ => (run as u8).wrapping_neg(),
+                };
+                // add byte [cell], amount
+                code.extend_from_slice(&[0x41, 0x80]);
+                cell_operand(&mut code, 0);
+                code.push(amount);
+                index += run;
+                continue;
+            }
+            BrainfuckInstruction::Output => {
+                // mov rdi, [r12 + callbacks]
+                code.extend_from_slice(&[0x49, 0x8B, 0x7C, 0x24, CONTEXT_CALLBACKS]);
+                // movzx esi, byte [cell]
+                code.extend_from_slice(&[0x41, 0x0F, 0xB6]);
+                cell_operand(&mut code, 6);
+                // call [r12 + output]
+                code.extend_from_slice(&[0x41, 0xFF, 0x54, 0x24, CONTEXT_OUTPUT]);
+            }
+            BrainfuckInstruction::Input => {
+                // mov rdi, [r12 + callbacks]
+                code.extend_from_slice(&[0x49, 0x8B, 0x7C, 0x24, CONTEXT_CALLBACKS]);
+                // call [r12 + input]
+                code.extend_from_slice(&[0x41, 0xFF, 0x54, 0x24, CONTEXT_INPUT]);
+                // mov byte [cell], al
+                code.extend_from_slice(&[0x41, 0x88]);
+                cell_operand(&mut code, 0);
+            }
+            BrainfuckInstruction::JumpForward(_) => {
+                // cmp byte [cell], 0; je <after matching ]>
+                code.extend_from_slice(&[0x41, 0x80]);
+                cell_operand(&mut code, 7);
+                code.extend_from_slice(&[0x00, 0x0F, 0x84, 0, 0, 0, 0]);
+                open_loops.push(code.len());
+            }
+            BrainfuckInstruction::JumpBackwards(_) => {
+                let body = open_loops
+                    .pop()
+                    .expect("Compiled programs have matching brackets");
+                // cmp byte [cell], 0; jne <after matching [>
+                code.extend_from_slice(&[0x41, 0x80]);
+                cell_operand(&mut code, 7);
+                code.extend_from_slice(&[0x00, 0x0F, 0x85, 0, 0, 0, 0]);
+                let end = code.len();
+                patch_rel32(&mut code, end - 4, body);
+                patch_rel32(&mut code, body - 4, end);
+            }
+            BrainfuckInstruction::DefineProcedure(_)
+            | BrainfuckInstruction::EndProcedure
+            | BrainfuckInstruction::CallProcedure
+            | BrainfuckInstruction::HostCall
+            | BrainfuckInstruction::MoveUp
+            | BrainfuckInstruction::MoveDown
+            | BrainfuckInstruction::PreviousTape
+            | BrainfuckInstruction::NextTape
+            | BrainfuckInstruction::Push
+            | BrainfuckInstruction::Pop => {
+                unreachable!("Programs with extensions of Brainfuck are not run natively")
+            }
+        }
+        index += 1;
+    }
+
+    // mov rax, FINISHED
+    code.extend_from_slice(&[0x48, 0xC7, 0xC0, 0xFF, 0xFF, 0xFF, 0xFF]);
+    let exit = code.len();
+    // mov [r12 + data_pointer], rbx
+    code.extend_from_slice(&[0x49, 0x89, 0x5C, 0x24, CONTEXT_DATA_POINTER]);
+    // pop r15; pop r14; pop r13; pop r12; pop rbx; ret
+    code.extend_from_slice(&[0x41, 0x5F, 0x41, 0x5E, 0x41, 0x5D, 0x41, 0x5C, 0x5B, 0xC3]);
+
+    for (jump, index) in bailouts {
+        let stub = code.len();
+        patch_rel32(&mut code, jump, stub);
+        // mov eax, index; jmp exit
+        code.push(0xB8);
+        code.extend_from_slice(&(index as u32).to_le_bytes());
+        code.extend_from_slice(&[0xE9, 0, 0, 0, 0]);
+        let end = code.len();
+        patch_rel32(&mut code, end - 4, exit);
+    }
+
+    code
+}
+
+mod sys {
+    use core::ffi::c_void;
+
+    pub const PROT_READ: i32 = 1;
+    pub const PROT_WRITE: i32 = 2;
+    pub const PROT_EXEC: i32 = 4;
+    pub const MAP_PRIVATE: i32 = 0x02;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub const MAP_ANONYMOUS: i32 = 0x20;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub const MAP_ANONYMOUS: i32 = 0x1000;
+    pub const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+
+    extern "C" {
+        pub fn mmap(
+            addr: *mut c_void,
+            len: usize,
+            prot: i32,
+            flags: i32,
+            fd: i32,
+            offset: i64,
+        ) -> *mut c_void;
+        pub fn mprotect(addr: *mut c_void, len: usize, prot: i32) -> i32;
+        pub fn munmap(addr: *mut c_void, len: usize) -> i32;
+    }
+}
+
+/// A region of executable memory containing generated code
+struct NativeCode {
+    memory: *mut c_void,
+    len: usize,
+}
+
+impl NativeCode {
+    fn new(code: &[u8]) -> Option<Self> {
+        let len = code.len();
+        // SAFETY: an anonymous private mapping does not alias any existing memory
+        let memory = unsafe {
+            sys::mmap(
+                core::ptr::null_mut(),
+                len,
+                sys::PROT_READ | sys::PROT_WRITE,
+                sys::MAP_PRIVATE | sys::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        if memory == sys::MAP_FAILED {
+            return None;
+        }
+        let native = Self { memory, len };
+
+        // SAFETY: the mapping is writable and at least `len` bytes long
+        unsafe {
+            core::ptr::copy_nonoverlapping(code.as_ptr(), memory as *mut u8, len);
+            if sys::mprotect(memory, len, sys::PROT_READ | sys::PROT_EXEC) != 0 {
+                return None;
+            }
+        }
+        Some(native)
+    }
+
+    /// Run the generated code
+    ///
+    /// # Safety
+    /// The generated code must have been produced by [`emit`], and `context` must point
+    /// to a tape of at least `context.len` bytes.
+    unsafe fn call(&self, context: &mut Context) -> u64 {
+        let function: extern "sysv64" fn(*mut Context) -> u64 = core::mem::transmute(self.memory);
+        function(context)
+    }
+}
+
+impl Drop for NativeCode {
+    fn drop(&mut self) {
+        // SAFETY: `memory` was mapped by `mmap` with length `len`
+        unsafe {
+            sys::munmap(self.memory, self.len);
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape<Data = u8> + AsMut<[u8]>,
+{
+    /// Run the Brainfuck program to completion, translating it to x86-64 machine code first
+    ///
+    /// Native execution always starts at the first instruction, so if the program has
+    /// already been stepped, if it uses extensions of Brainfuck, like procedures, or if
+    /// generating code fails, this is equivalent to [`run`](Self::run). When the data
+    /// pointer leaves the current bounds of the tape, execution continues in the
+    /// interpreter, so growing tapes work as usual.
+    ///
+    /// Instructions executed natively are not counted in `execution_count`. `output`
+    /// and `input` must not panic, as unwinding through generated code is not possible:
+    /// a panic aborts the process.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`try_run_native`](Self::try_run_native) returns an error.
+    pub fn run_native<FnOut, FnIn>(&mut self, output: &mut FnOut, input: &mut FnIn) -> RunResult<u8>
+    where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        match self.try_run_native(output, input) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", self.explain(&error)),
+        }
+    }
+
+    /// Run the Brainfuck program to completion like [`run_native`](Self::run_native),
+    /// returning an error instead of panicking once the interpreter has taken over
+    pub fn try_run_native<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<RunResult<u8>, RuntimeError>
+    where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        let needs_interpreter = self.instructions.iter().any(|instruction| {
+            matches!(
+                instruction,
+                BrainfuckInstruction::DefineProcedure(_)
+                    | BrainfuckInstruction::EndProcedure
+                    | BrainfuckInstruction::CallProcedure
+                    | BrainfuckInstruction::HostCall
+                    | BrainfuckInstruction::MoveUp
+                    | BrainfuckInstruction::MoveDown
+                    | BrainfuckInstruction::PreviousTape
+                    | BrainfuckInstruction::NextTape
+                    | BrainfuckInstruction::Push
+                    | BrainfuckInstruction::Pop
+            )
+        });
+        if self.instruction_pointer != 0 || needs_interpreter {
+            return self.try_run(output, input);
+        }
+
+        let start = RunStart::new(self);
+        let native = match NativeCode::new(&emit(&self.instructions)) {
+            Some(native) => native,
+            None => return self.try_run(output, input),
+        };
+
+        let (mut output, mut input) = (Counted::new(output), Counted::new(input));
+        let tape = self.tape.as_mut();
+        let stopped_at = run(
+            &native,
+            tape,
+            &mut self.data_pointer,
+            &mut output,
+            &mut input,
+        );
+        if stopped_at == FINISHED {
+            self.instruction_pointer = self.instructions.len();
+        } else {
+            self.instruction_pointer = stopped_at as usize;
+            self.execute(None, false, &mut output, &mut input)?;
+        }
+        Ok(start.finish(self, RunOutcome::Halted, &output, &input))
+    }
+}
+
+#[allow(deprecated)]
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape<Data = u8>,
+{
+    /// Run `code`, which must have been translated from the loop whose `[` is at the
+    /// instruction pointer, on the contiguous cells of the tape, and continue after
+    /// the loop
+    ///
+    /// If a move would leave the tape, the instruction pointer is left at the move
+    /// instead. Returns `false` without running anything if the tape does not store its
+    /// cells contiguously.
+    pub(crate) fn run_native_loop<FnOut, FnIn>(
+        &mut self,
+        code: &NativeLoop,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> bool
+    where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        let start = self.instruction_pointer;
+        let tape = match self.tape.as_mut_slice() {
+            Some(tape) => tape,
+            None => return false,
+        };
+        let stopped_at = run(&code.native, tape, &mut self.data_pointer, output, input);
+        self.instruction_pointer = if stopped_at == FINISHED {
+            start + code.len
+        } else {
+            start + stopped_at as usize
+        };
+        true
+    }
+}
+
+/// A single loop, translated to machine code
+pub(crate) struct NativeLoop {
+    native: NativeCode,
+    /// The amount of instructions in the loop, including its brackets
+    len: usize,
+}
+
+impl NativeLoop {
+    /// Translate the loop whose `[` is at `start` in `instructions`, or return `None` if
+    /// it uses extensions of Brainfuck, like procedures, or generating code fails
+    pub(crate) fn new(instructions: &[BrainfuckInstruction], start: usize) -> Option<Self> {
+        let end = match instructions.get(start)? {
+            BrainfuckInstruction::JumpForward(end) => *end,
+            _ => return None,
+        };
+        let body = &instructions[start..=end];
+        let needs_interpreter = body.iter().any(|instruction| {
+            matches!(
+                instruction,
+                BrainfuckInstruction::DefineProcedure(_)
+                    | BrainfuckInstruction::EndProcedure
+                    | BrainfuckInstruction::CallProcedure
+                    | BrainfuckInstruction::HostCall
+                    | BrainfuckInstruction::MoveUp
+                    | BrainfuckInstruction::MoveDown
+                    | BrainfuckInstruction::PreviousTape
+                    | BrainfuckInstruction::NextTape
+                    | BrainfuckInstruction::Push
+                    | BrainfuckInstruction::Pop
+            )
+        });
+        if needs_interpreter {
+            return None;
+        }
+        // The emitter matches brackets itself, and reports moves off the tape by their
+        // index in `body`
+        Some(Self {
+            native: NativeCode::new(&emit(body))?,
+            len: body.len(),
+        })
+    }
+}
+
+impl core::fmt::Debug for NativeLoop {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("NativeLoop")
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+/// Run `native` on `tape`, and return the index of the instruction that would have
+/// moved the data pointer off the tape, or [`FINISHED`]
+fn run<FnOut, FnIn>(
+    native: &NativeCode,
+    tape: &mut [u8],
+    data_pointer: &mut usize,
+    output: &mut FnOut,
+    input: &mut FnIn,
+) -> u64
+where
+    FnOut: Output<u8>,
+    FnIn: Input<u8>,
+{
+    let mut callbacks = Callbacks { output, input };
+    let mut context = Context {
+        tape: tape.as_mut_ptr(),
+        len: tape.len(),
+        data_pointer: *data_pointer,
+        output: output_trampoline::<FnOut, FnIn>,
+        input: input_trampoline::<FnOut, FnIn>,
+        callbacks: &mut callbacks as *mut Callbacks<FnOut, FnIn> as *mut c_void,
+    };
+
+    let data_pointer_in_bounds = context.data_pointer < context.len;
+    let stopped_at = if data_pointer_in_bounds {
+        // SAFETY: the code was generated by `emit`, and `context` describes `tape`
+        unsafe { native.call(&mut context) }
+    } else {
+        0
+    };
+    *data_pointer = context.data_pointer;
+    stopped_at
+}
@@ -0,0 +1,183 @@
+//! Running many programs in parallel
+
+use core::fmt::Display;
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::{
+    io::{SliceInput, VecOutput},
+    BoundedVecTape, BrainfuckProgram, CompileError, RunConfig, RunOutcome, RuntimeError,
+};
+
+/// Configuration of [`run_batch`]
+#[derive(Clone, Debug)]
+pub struct BatchConfig {
+    /// The amount of threads to run programs on. If this is zero, the available
+    /// parallelism is used
+    pub parallelism: usize,
+    /// The limits on every run of a program
+    pub run: RunConfig,
+    /// The maximum amount of cells of the tape of every program
+    pub tape_limit: usize,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            parallelism: 0,
+            run: RunConfig {
+                max_instructions: Some(1 << 20),
+                ..RunConfig::default()
+            },
+            tape_limit: 1 << 16,
+        }
+    }
+}
+
+/// Why a program in a batch failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BatchError {
+    /// The program could not be compiled
+    Compile(CompileError),
+    /// The program failed while running
+    Runtime(RuntimeError),
+}
+
+impl Display for BatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BatchError::Compile(error) => error.fmt(f),
+            BatchError::Runtime(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+/// The result of running a single program with a single input in [`run_batch`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchResult {
+    /// The index of the program
+    pub program: usize,
+    /// The index of the input
+    pub input: usize,
+    /// Why the run ended, or why it failed
+    pub outcome: Result<RunOutcome, BatchError>,
+    /// The output of the program, up to the point where the run ended
+    pub output: Vec<u8>,
+    /// The amount of instructions that were executed
+    pub instructions: usize,
+}
+
+/// Run every program in `programs` with every input in `inputs`, on
+/// [`BatchConfig::parallelism`] threads
+///
+/// Every program is compiled once, and runs on a [`BoundedVecTape`] that is reset for
+/// every input, within the limits of `config`. Input reads zero once it has run out. The
+/// results are ordered by program, and then by input.
+///
+/// ```
+/// # use brainfuck_interpreter::{run_batch, BatchConfig, RunOutcome};
+/// let programs = [",.", ",+.", "+[]"];
+/// let inputs = [b"a", b"b"];
+/// let results = run_batch(&programs, &inputs, &BatchConfig::default());
+///
+/// assert_eq!(results.len(), 6);
+/// assert_eq!(results[3].output, b"c");
+/// assert_eq!(results[4].outcome, Ok(RunOutcome::InstructionLimit));
+/// ```
+pub fn run_batch<P, I>(programs: &[P], inputs: &[I], config: &BatchConfig) -> Vec<BatchResult>
+where
+    P: AsRef<str> + Sync,
+    I: AsRef<[u8]> + Sync,
+{
+    let threads = match config.parallelism {
+        0 => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        parallelism => parallelism,
+    }
+    .min(programs.len());
+
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(programs.len() * inputs.len()));
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(program) = programs.get(index) else {
+                    break;
+                };
+                let program_results = run_program(index, program.as_ref(), inputs, config);
+                results
+                    .lock()
+                    .unwrap_or_else(|error| error.into_inner())
+                    .extend(program_results);
+            });
+        }
+    });
+
+    let mut results = results
+        .into_inner()
+        .unwrap_or_else(|error| error.into_inner());
+    results.sort_by_key(|result| (result.program, result.input));
+    results
+}
+
+/// Run the program at `index`, with source code `source`, with every input
+fn run_program<I>(
+    index: usize,
+    source: &str,
+    inputs: &[I],
+    config: &BatchConfig,
+) -> Vec<BatchResult>
+where
+    I: AsRef<[u8]>,
+{
+    let tape = BoundedVecTape::with_limit(config.tape_limit);
+    let mut program = match BrainfuckProgram::compile(source, tape) {
+        Ok(program) => program,
+        Err(error) => {
+            let error = BatchError::Compile(error.into());
+            return (0..inputs.len())
+                .map(|input| BatchResult {
+                    program: index,
+                    input,
+                    outcome: Err(error.clone()),
+                    output: Vec::new(),
+                    instructions: 0,
+                })
+                .collect();
+        }
+    };
+
+    inputs
+        .iter()
+        .enumerate()
+        .map(|(input_index, input)| {
+            program.reset();
+            program.execution_count = 0;
+            let mut output = VecOutput::new();
+            let outcome = program
+                .try_run_with_config(
+                    &config.run,
+                    &mut output,
+                    &mut SliceInput::new(input.as_ref()),
+                )
+                .map(RunOutcome::from)
+                .map_err(BatchError::Runtime);
+            BatchResult {
+                program: index,
+                input: input_index,
+                outcome,
+                output: output.into_inner(),
+                instructions: program.execution_count,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,15 @@
+//! A Debug Adapter Protocol server for Brainfuck programs, talking over standard input
+//! and output
+
+use std::process::exit;
+
+use brainfuck_interpreter::debugger::dap::DapServer;
+
+fn main() {
+    let stdin = std::io::stdin();
+    let mut server = DapServer::new(stdin.lock(), std::io::stdout());
+    if let Err(error) = server.run() {
+        eprintln!("{}", error);
+        exit(1);
+    }
+}
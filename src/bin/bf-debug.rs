@@ -0,0 +1,42 @@
+//! A GDB-style debugger for Brainfuck programs
+//!
+//! Usage: `bf-debug PROGRAM [INPUT]`, where `INPUT` is a file that the program reads
+//! its input from.
+
+use std::{fs, process::exit};
+
+use brainfuck_interpreter::debugger::cli::Cli;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let (program, input) = match (args.next(), args.next(), args.next()) {
+        (Some(program), input, None) => (program, input),
+        _ => {
+            eprintln!("Usage: bf-debug PROGRAM [INPUT]");
+            exit(2);
+        }
+    };
+
+    let source = fs::read_to_string(&program).unwrap_or_else(|error| {
+        eprintln!("Could not read {}: {}", program, error);
+        exit(1);
+    });
+    let input = match input {
+        Some(input) => fs::read(&input).unwrap_or_else(|error| {
+            eprintln!("Could not read {}: {}", input, error);
+            exit(1);
+        }),
+        None => Vec::new(),
+    };
+
+    let mut cli = Cli::new(&source, input).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        exit(1);
+    });
+
+    let stdin = std::io::stdin();
+    if let Err(error) = cli.run_repl(stdin.lock(), std::io::stdout()) {
+        eprintln!("{}", error);
+        exit(1);
+    }
+}
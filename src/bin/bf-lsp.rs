@@ -0,0 +1,15 @@
+//! A Language Server Protocol server for Brainfuck, talking over standard input and
+//! output
+
+use std::process::exit;
+
+use brainfuck_interpreter::lsp::LanguageServer;
+
+fn main() {
+    let stdin = std::io::stdin();
+    let mut server = LanguageServer::new(stdin.lock(), std::io::stdout());
+    if let Err(error) = server.run() {
+        eprintln!("{}", error);
+        exit(1);
+    }
+}
@@ -0,0 +1,52 @@
+//! Run a Brainfuck program on standard input and output
+//!
+//! Usage: `bf-run PROGRAM [ARGUMENT]...`. Every argument is read by the program before
+//! its input, followed by a newline. Once the program finishes, the value of its first
+//! cell is used as the exit code.
+
+use std::{fs, io::Write, process::exit};
+
+use brainfuck_interpreter::{
+    io::{ChainedInput, FlushPolicy, InteractiveInput, InteractiveOutput},
+    BrainfuckProgram,
+};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let program = args.next().unwrap_or_else(|| {
+        eprintln!("Usage: bf-run PROGRAM [ARGUMENT]...");
+        exit(2);
+    });
+
+    let source = fs::read_to_string(&program).unwrap_or_else(|error| {
+        eprintln!("Could not read {}: {}", program, error);
+        exit(1);
+    });
+    let mut program =
+        BrainfuckProgram::compile_auto_with_source_map(&source).unwrap_or_else(|error| {
+            eprintln!("{}", error.diagnostic().with_context(1, 1));
+            exit(1);
+        });
+
+    let arguments = args.flat_map(|arg| arg.into_bytes().into_iter().chain(Some(b'\n')));
+    let stdin = std::io::stdin();
+    let mut input = ChainedInput::new(arguments, InteractiveInput::new(stdin.lock()));
+    let stdout = std::io::stdout();
+    let mut output = InteractiveOutput::new(stdout.lock(), FlushPolicy::Newline);
+
+    let result = program.try_run(&mut output, &mut input);
+    let written = match output.take_error() {
+        Some(error) => Err(error),
+        // Output that was not followed by a newline may not have been flushed yet
+        None => output.into_inner().flush(),
+    };
+    let result = result.unwrap_or_else(|error| {
+        eprintln!("{}", program.explain(&error));
+        exit(1);
+    });
+    if let Err(error) = written {
+        eprintln!("Could not write the output: {}", error);
+        exit(1);
+    }
+    exit(result.exit_code().unwrap_or(0));
+}
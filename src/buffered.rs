@@ -0,0 +1,151 @@
+//! Buffered execution of Brainfuck programs
+//!
+//! Instead of calling back into the host for every `.` and `,`, a [`BufferedProgram`]
+//! collects output into a buffer and reads input from a queue, pausing whenever
+//! the queue runs dry. This is useful for hosts that cannot block, such as a browser.
+
+use alloc::{collections::VecDeque, vec::Vec};
+
+use crate::{BrainfuckInstruction, BrainfuckProgram, RuntimeError, Tape};
+
+/// The state of a [`BufferedProgram`] after running it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BufferedStatus {
+    /// The program can continue running
+    Running,
+    /// The program is at a `,`, but there is no input available
+    WaitingForInput,
+    /// The program has finished
+    Halted,
+    /// The program stopped because of an error
+    ///
+    /// The instruction pointer and data pointer are left at the cause of the error, so
+    /// running the program again fails with the same error.
+    Failed(RuntimeError),
+}
+
+/// A Brainfuck program with buffered input and output
+#[derive(Clone, Debug)]
+pub struct BufferedProgram<T>
+where
+    T: Tape,
+{
+    program: BrainfuckProgram<T>,
+    input: VecDeque<T::Data>,
+    output: Vec<T::Data>,
+}
+
+#[allow(deprecated)]
+impl<T> BufferedProgram<T>
+where
+    T: Tape,
+{
+    /// Create a new buffered program, with empty input and output buffers
+    pub fn new(program: BrainfuckProgram<T>) -> Self {
+        Self {
+            program,
+            input: VecDeque::new(),
+            output: Vec::new(),
+        }
+    }
+
+    /// Get the program that is being executed
+    pub fn program(&self) -> &BrainfuckProgram<T> {
+        &self.program
+    }
+
+    /// Get the program that is being executed, mutably
+    pub fn program_mut(&mut self) -> &mut BrainfuckProgram<T> {
+        &mut self.program
+    }
+
+    /// Consume this buffered program, returning the program it wraps
+    pub fn into_program(self) -> BrainfuckProgram<T> {
+        self.program
+    }
+
+    /// Queue `data` as input for the program
+    pub fn write_input(&mut self, data: &[T::Data]) {
+        self.input.extend(data.iter().cloned());
+    }
+
+    /// Take all output the program has produced since the last call to this function
+    pub fn read_output(&mut self) -> Vec<T::Data> {
+        core::mem::take(&mut self.output)
+    }
+
+    /// The amount of input that has been queued, but not yet read by the program
+    pub fn pending_input(&self) -> usize {
+        self.input.len()
+    }
+
+    /// Perform at most `steps` steps of the program
+    ///
+    /// Execution pauses early if the program finishes, if it fails, or if it wants to
+    /// read input while none is available.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckProgram, BufferedProgram, BufferedStatus};
+    /// let program = BrainfuckProgram::compile(",.<", vec![0u8]).unwrap();
+    /// let mut buffered = BufferedProgram::new(program);
+    /// assert_eq!(buffered.step(10), BufferedStatus::WaitingForInput);
+    /// buffered.write_input(b"a");
+    /// assert!(matches!(buffered.step(10), BufferedStatus::Failed(_)));
+    /// assert_eq!(buffered.read_output(), b"a");
+    /// ```
+    pub fn step(&mut self, steps: usize) -> BufferedStatus {
+        for _ in 0..steps {
+            let status = self.step_once();
+            if status != BufferedStatus::Running {
+                return status;
+            }
+        }
+        BufferedStatus::Running
+    }
+
+    /// Run the program until it finishes, fails, or runs out of input
+    pub fn run(&mut self) -> BufferedStatus {
+        loop {
+            let status = self.step_once();
+            if status != BufferedStatus::Running {
+                return status;
+            }
+        }
+    }
+
+    /// Reset the program, and clear both buffers
+    pub fn reset(&mut self) {
+        self.program.reset();
+        self.input.clear();
+        self.output.clear();
+    }
+
+    fn step_once(&mut self) -> BufferedStatus {
+        let instruction = self
+            .program
+            .instructions
+            .get(self.program.instruction_pointer);
+
+        if let Some(BrainfuckInstruction::Input) = instruction {
+            if self.input.is_empty() {
+                return BufferedStatus::WaitingForInput;
+            }
+        }
+
+        let output = &mut self.output;
+        let input = &mut self.input;
+        let result = self
+            .program
+            .try_step(&mut |data| output.push(data), &mut || {
+                input
+                    .pop_front()
+                    .expect("Input was checked to be available")
+            });
+
+        match result {
+            Ok(true) => BufferedStatus::Running,
+            Ok(false) => BufferedStatus::Halted,
+            Err(error) => BufferedStatus::Failed(error),
+        }
+    }
+}
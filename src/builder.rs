@@ -0,0 +1,283 @@
+//! Programmatic construction of Brainfuck programs
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::Display;
+
+use crate::{
+    ast::{self, Node, Op},
+    BrainfuckInstruction, BrainfuckProgram, SourcePos, Tape, ValidationError,
+};
+
+/// A builder for Brainfuck programs
+///
+/// All methods return the builder, so calls can be chained:
+///
+/// ```
+/// use brainfuck_interpreter::ProgramBuilder;
+///
+/// let mut builder = ProgramBuilder::new();
+/// builder.add(8).loop_(|body| {
+///     body.move_right(1).add(8).move_left(1).sub(1);
+/// });
+/// builder.move_right(1).add(1).output();
+///
+/// assert_eq!(builder.to_source(), "++++++++[>++++++++<-]>+.");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProgramBuilder {
+    nodes: Vec<Node>,
+}
+
+impl ProgramBuilder {
+    /// Create an empty builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn repeat(&mut self, op: Op, count: usize) -> &mut Self {
+        self.nodes.extend(core::iter::repeat_n(Node::Op(op), count));
+        self
+    }
+
+    /// Increase the current cell `count` times (`+`)
+    pub fn add(&mut self, count: usize) -> &mut Self {
+        self.repeat(Op::IncreaseData, count)
+    }
+
+    /// Decrease the current cell `count` times (`-`)
+    pub fn sub(&mut self, count: usize) -> &mut Self {
+        self.repeat(Op::DecreaseData, count)
+    }
+
+    /// Move the data pointer `count` cells to the right (`>`)
+    pub fn move_right(&mut self, count: usize) -> &mut Self {
+        self.repeat(Op::IncrementDataPointer, count)
+    }
+
+    /// Move the data pointer `count` cells to the left (`<`)
+    pub fn move_left(&mut self, count: usize) -> &mut Self {
+        self.repeat(Op::DecrementDataPointer, count)
+    }
+
+    /// Output the current cell (`.`)
+    pub fn output(&mut self) -> &mut Self {
+        self.repeat(Op::Output, 1)
+    }
+
+    /// Read input into the current cell (`,`)
+    pub fn input(&mut self) -> &mut Self {
+        self.repeat(Op::Input, 1)
+    }
+
+    /// Add a loop, whose body is built by `body`
+    pub fn loop_<F>(&mut self, body: F) -> &mut Self
+    where
+        F: FnOnce(&mut ProgramBuilder),
+    {
+        let mut builder = ProgramBuilder::new();
+        body(&mut builder);
+        self.nodes.push(Node::Loop(builder.nodes));
+        self
+    }
+
+    /// Set the current cell to zero (`[-]`)
+    pub fn clear(&mut self) -> &mut Self {
+        self.loop_(|body| {
+            body.sub(1);
+        })
+    }
+
+    /// Append a node
+    pub fn node(&mut self, node: Node) -> &mut Self {
+        self.nodes.push(node);
+        self
+    }
+
+    /// The nodes that have been built so far
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Consume this builder, returning the nodes that were built
+    pub fn into_ast(self) -> Vec<Node> {
+        self.nodes
+    }
+
+    /// The source code of the program that has been built so far
+    pub fn to_source(&self) -> String {
+        ast::to_source(&self.nodes)
+    }
+
+    /// Create a program from the instructions that have been built, running on `tape`
+    pub fn build<T>(&self, tape: T) -> BrainfuckProgram<T>
+    where
+        T: Tape,
+    {
+        BrainfuckProgram::from_ast(&self.nodes, tape)
+    }
+}
+
+/// A builder for a [`BrainfuckProgram`] in a specific state, as returned by
+/// [`BrainfuckProgram::builder`]
+///
+/// Unlike setting the fields of a program directly, [`build`](Self::build) checks
+/// that the result is a valid program.
+///
+/// ```
+/// use brainfuck_interpreter::{BrainfuckInstruction::*, BrainfuckProgram};
+///
+/// let program = BrainfuckProgram::builder(vec![0u8; 4])
+///     .instructions(vec![IncrementDataPointer, IncreaseData])
+///     .data_pointer(2)
+///     .build()
+///     .unwrap();
+/// assert_eq!(program.data_pointer(), 2);
+///
+/// let invalid = BrainfuckProgram::builder(vec![0u8]).instructions(vec![JumpForward(7)]);
+/// assert!(invalid.build().is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct BrainfuckProgramBuilder<T> {
+    tape: T,
+    instructions: Vec<BrainfuckInstruction>,
+    source_map: Option<Vec<SourcePos>>,
+    instruction_pointer: usize,
+    data_pointer: usize,
+}
+
+#[allow(deprecated)]
+impl<T> BrainfuckProgramBuilder<T>
+where
+    T: Tape,
+{
+    /// Use `instructions` as the instructions of the program. Jump targets are
+    /// absolute, see [`BrainfuckInstruction`]
+    pub fn instructions(mut self, instructions: Vec<BrainfuckInstruction>) -> Self {
+        self.instructions = instructions;
+        self
+    }
+
+    /// Use `source_map` as the position in the source code of every instruction
+    pub fn source_map(mut self, source_map: Vec<SourcePos>) -> Self {
+        self.source_map = Some(source_map);
+        self
+    }
+
+    /// Start executing at the instruction at `instruction_pointer`
+    pub fn instruction_pointer(mut self, instruction_pointer: usize) -> Self {
+        self.instruction_pointer = instruction_pointer;
+        self
+    }
+
+    /// Start at the cell at `data_pointer`
+    pub fn data_pointer(mut self, data_pointer: usize) -> Self {
+        self.data_pointer = data_pointer;
+        self
+    }
+
+    /// Create the program, if its state is valid
+    ///
+    /// The instructions must pass [`BrainfuckProgram::validate`], the instruction
+    /// pointer must be at most the amount of instructions, the source map, if any,
+    /// must have a position for every instruction, and the tape must have a cell at
+    /// the data pointer.
+    pub fn build(self) -> Result<BrainfuckProgram<T>, BuildError> {
+        let len = self.instructions.len();
+        if self.instruction_pointer > len {
+            return Err(BuildError::InstructionPointerOutOfRange {
+                instruction_pointer: self.instruction_pointer,
+                len,
+            });
+        }
+        if let Some(source_map) = &self.source_map {
+            if source_map.len() != len {
+                return Err(BuildError::SourceMapLength {
+                    source_map: source_map.len(),
+                    instructions: len,
+                });
+            }
+        }
+
+        let data_pointer = self.data_pointer;
+        let mut program = BrainfuckProgram::from_instructions(self.instructions, self.tape);
+        program.validate().map_err(BuildError::Invalid)?;
+        program
+            .set_data_pointer(data_pointer)
+            .map_err(|_| BuildError::DataPointerOutOfRange { data_pointer })?;
+        program.instruction_pointer = self.instruction_pointer;
+        program.source_map = self.source_map;
+        Ok(program)
+    }
+}
+
+/// Why [`BrainfuckProgramBuilder::build`] failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BuildError {
+    /// The instructions are not valid
+    Invalid(ValidationError),
+    /// The instruction pointer is past the end of the instructions
+    InstructionPointerOutOfRange {
+        /// The instruction pointer
+        instruction_pointer: usize,
+        /// The amount of instructions
+        len: usize,
+    },
+    /// The tape has no cell at the data pointer
+    DataPointerOutOfRange {
+        /// The data pointer
+        data_pointer: usize,
+    },
+    /// The source map does not have a position for every instruction
+    SourceMapLength {
+        /// The amount of positions in the source map
+        source_map: usize,
+        /// The amount of instructions
+        instructions: usize,
+    },
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BuildError::Invalid(error) => error.fmt(f),
+            BuildError::InstructionPointerOutOfRange {
+                instruction_pointer,
+                len,
+            } => write!(
+                f,
+                "Instruction pointer {} is past the end of the {} instructions",
+                instruction_pointer, len
+            ),
+            BuildError::DataPointerOutOfRange { data_pointer } => {
+                write!(f, "The tape has no cell at data pointer {}", data_pointer)
+            }
+            BuildError::SourceMapLength {
+                source_map,
+                instructions,
+            } => write!(
+                f,
+                "The source map has {} positions, but there are {} instructions",
+                source_map, instructions
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BuildError {}
+
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Start building a program that runs on `tape`, see [`BrainfuckProgramBuilder`]
+    pub fn builder(tape: T) -> BrainfuckProgramBuilder<T> {
+        BrainfuckProgramBuilder {
+            tape,
+            instructions: Vec::new(),
+            source_map: None,
+            instruction_pointer: 0,
+            data_pointer: 0,
+        }
+    }
+}
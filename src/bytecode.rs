@@ -0,0 +1,484 @@
+//! Serialization of compiled programs into a binary bytecode format, and the compact
+//! [`Bytecode`] that programs can be run from
+//!
+//! The format is as follows, with all integers in little-endian byte order:
+//!
+//! | Size      | Contents                                                     |
+//! |-----------|--------------------------------------------------------------|
+//! | 4         | The magic number, `BFBC`                                     |
+//! | 2         | The format version, currently `1`                            |
+//! | 1         | The size of a cell in bytes                                  |
+//! | 1         | Reserved, must be `0`                                        |
+//! | 4         | The amount of instructions                                   |
+//! | ...       | The instructions: an opcode byte, followed by the 4-byte index of the matching bracket for `[`, `]` and `(` |
+//! | 4         | The FNV-1a hash of all preceding bytes                       |
+
+use alloc::vec::Vec;
+use core::{convert::TryFrom, fmt::Display};
+
+use crate::{
+    io::{Input, Output},
+    result::{Counted, RunStart},
+    BrainfuckInstruction, BrainfuckProgram, RunOutcome, RunResult, RuntimeError, Tape,
+};
+
+/// The magic number that every bytecode file starts with
+pub const BYTECODE_MAGIC: [u8; 4] = *b"BFBC";
+
+/// The version of the bytecode format produced by [`BrainfuckProgram::to_bytecode`]
+pub const BYTECODE_VERSION: u16 = 1;
+
+const HEADER_LEN: usize = 12;
+const CHECKSUM_LEN: usize = 4;
+
+/// An error that can occur while loading bytecode
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BytecodeError {
+    /// The bytecode ended before the end of the program
+    UnexpectedEnd,
+    /// The bytecode does not start with [`BYTECODE_MAGIC`]
+    BadMagic,
+    /// The bytecode has a version that is not supported
+    UnsupportedVersion(u16),
+    /// The bytecode was compiled for a different cell size
+    CellWidthMismatch {
+        /// The cell size of the tape, in bytes
+        expected: u8,
+        /// The cell size of the bytecode, in bytes
+        found: u8,
+    },
+    /// The hash at the end of the bytecode does not match its contents
+    ChecksumMismatch,
+    /// An instruction has an unknown opcode
+    UnknownOpcode {
+        /// The index of the instruction
+        index: usize,
+        /// The opcode
+        opcode: u8,
+    },
+    /// A jump does not land on its matching bracket
+    InvalidJump {
+        /// The index of the instruction
+        index: usize,
+    },
+    /// There is data after the checksum
+    TrailingData,
+}
+
+impl Display for BytecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BytecodeError::UnexpectedEnd => write!(f, "Bytecode ended unexpectedly"),
+            BytecodeError::BadMagic => write!(f, "Data is not Brainfuck bytecode"),
+            BytecodeError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported bytecode version {}", version)
+            }
+            BytecodeError::CellWidthMismatch { expected, found } => write!(
+                f,
+                "Bytecode was compiled for {}-byte cells, but the tape has {}-byte cells",
+                found, expected
+            ),
+            BytecodeError::ChecksumMismatch => write!(f, "Bytecode checksum does not match"),
+            BytecodeError::UnknownOpcode { index, opcode } => {
+                write!(f, "Unknown opcode {} at instruction {}", opcode, index)
+            }
+            BytecodeError::InvalidJump { index } => {
+                write!(f, "Jump at instruction {} has no matching bracket", index)
+            }
+            BytecodeError::TrailingData => write!(f, "Bytecode has trailing data"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BytecodeError {}
+
+/// An error that can occur while serializing a program into bytecode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BytecodeEncodeError {
+    /// The program has more instructions than fit in the 4-byte count
+    TooManyInstructions,
+    /// A jump targets an index that does not fit in 4 bytes
+    TargetTooLarge {
+        /// The index of the instruction
+        index: usize,
+    },
+    /// The program has an [`initial_tape`](BrainfuckProgram::initial_tape), which
+    /// bytecode cannot hold
+    InitialTape,
+}
+
+impl Display for BytecodeEncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BytecodeEncodeError::TooManyInstructions => {
+                write!(f, "Program has too many instructions for bytecode")
+            }
+            BytecodeEncodeError::TargetTooLarge { index } => write!(
+                f,
+                "Jump at instruction {} targets an index that does not fit in bytecode",
+                index
+            ),
+            BytecodeEncodeError::InitialTape => {
+                write!(f, "Program has an initial tape, which bytecode cannot hold")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BytecodeEncodeError {}
+
+fn fnv1a(data: &[u8]) -> u32 {
+    data.iter().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ u32::from(*byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+fn opcode(instruction: &BrainfuckInstruction) -> u8 {
+    match instruction {
+        BrainfuckInstruction::IncrementDataPointer => 0,
+        BrainfuckInstruction::DecrementDataPointer => 1,
+        BrainfuckInstruction::IncreaseData => 2,
+        BrainfuckInstruction::DecreaseData => 3,
+        BrainfuckInstruction::Output => 4,
+        BrainfuckInstruction::Input => 5,
+        BrainfuckInstruction::JumpForward(_) => 6,
+        BrainfuckInstruction::JumpBackwards(_) => 7,
+        BrainfuckInstruction::DefineProcedure(_) => 8,
+        BrainfuckInstruction::EndProcedure => 9,
+        BrainfuckInstruction::CallProcedure => 10,
+        BrainfuckInstruction::HostCall => 11,
+        BrainfuckInstruction::MoveUp => 12,
+        BrainfuckInstruction::MoveDown => 13,
+        BrainfuckInstruction::PreviousTape => 14,
+        BrainfuckInstruction::NextTape => 15,
+        BrainfuckInstruction::Push => 16,
+        BrainfuckInstruction::Pop => 17,
+    }
+}
+
+/// Check that every jump in `instructions` targets its matching bracket,
+/// returning the index of the first jump that does not
+fn check_jumps(instructions: &[BrainfuckInstruction]) -> Result<(), usize> {
+    let mut open = Vec::new();
+    for (index, instruction) in instructions.iter().enumerate() {
+        match instruction {
+            BrainfuckInstruction::JumpForward(_) | BrainfuckInstruction::DefineProcedure(_) => {
+                open.push(index)
+            }
+            BrainfuckInstruction::JumpBackwards(target) => {
+                let opener = open.pop().ok_or(index)?;
+                match instructions[opener] {
+                    BrainfuckInstruction::JumpForward(target) if target == index => {}
+                    _ => return Err(opener),
+                }
+                if *target != opener {
+                    return Err(index);
+                }
+            }
+            BrainfuckInstruction::EndProcedure => {
+                let opener = open.pop().ok_or(index)?;
+                match instructions[opener] {
+                    BrainfuckInstruction::DefineProcedure(target) if target == index => {}
+                    _ => return Err(opener),
+                }
+            }
+            _ => {}
+        }
+    }
+    match open.pop() {
+        Some(index) => Err(index),
+        None => Ok(()),
+    }
+}
+
+/// The amount of bits of a [`Bytecode`] word that hold the opcode
+const OPCODE_BITS: u32 = 5;
+
+/// Instructions encoded as one 32-bit word each, for running them with
+/// [`BrainfuckProgram::try_run_bytecode`]
+///
+/// Every word holds the opcode of an instruction in its lowest 5 bits, and the target
+/// of a jump in the remaining bits. This takes a quarter of the memory of
+/// [`BrainfuckInstruction`]s, so that more of a program fits in the cache while it
+/// runs. The instructions themselves stay the representation for everything else, and
+/// are decoded by [`get`](Self::get).
+///
+/// ```
+/// # use brainfuck_interpreter::{BrainfuckInstruction, BrainfuckProgram, Bytecode};
+/// let program = BrainfuckProgram::compile("+[-]", vec![0u8]).unwrap();
+/// let bytecode = Bytecode::from_instructions(program.instructions()).unwrap();
+/// assert_eq!(bytecode.len(), 4);
+/// assert_eq!(bytecode.get(1), Some(BrainfuckInstruction::JumpForward(3)));
+/// assert_eq!(bytecode.to_instructions(), program.instructions());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Bytecode {
+    words: Vec<u32>,
+}
+
+impl Bytecode {
+    /// The largest jump target that fits in a word
+    pub const MAX_TARGET: usize = (u32::MAX >> OPCODE_BITS) as usize;
+
+    /// Encode `instructions`, or return `None` if a jump targets an index larger than
+    /// [`MAX_TARGET`](Self::MAX_TARGET)
+    pub fn from_instructions(instructions: &[BrainfuckInstruction]) -> Option<Self> {
+        let words = instructions
+            .iter()
+            .map(|instruction| {
+                let target = instruction.target().unwrap_or(0);
+                if target > Self::MAX_TARGET {
+                    return None;
+                }
+                Some(((target as u32) << OPCODE_BITS) | u32::from(opcode(instruction)))
+            })
+            .collect::<Option<_>>()?;
+        Some(Self { words })
+    }
+
+    /// The instruction at `index`
+    #[inline(always)]
+    pub fn get(&self, index: usize) -> Option<BrainfuckInstruction> {
+        let word = *self.words.get(index)?;
+        let target = (word >> OPCODE_BITS) as usize;
+        let instruction = match word & ((1 << OPCODE_BITS) - 1) {
+            0 => BrainfuckInstruction::IncrementDataPointer,
+            1 => BrainfuckInstruction::DecrementDataPointer,
+            2 => BrainfuckInstruction::IncreaseData,
+            3 => BrainfuckInstruction::DecreaseData,
+            4 => BrainfuckInstruction::Output,
+            5 => BrainfuckInstruction::Input,
+            6 => BrainfuckInstruction::JumpForward(target),
+            7 => BrainfuckInstruction::JumpBackwards(target),
+            8 => BrainfuckInstruction::DefineProcedure(target),
+            9 => BrainfuckInstruction::EndProcedure,
+            10 => BrainfuckInstruction::CallProcedure,
+            11 => BrainfuckInstruction::HostCall,
+            12 => BrainfuckInstruction::MoveUp,
+            13 => BrainfuckInstruction::MoveDown,
+            14 => BrainfuckInstruction::PreviousTape,
+            15 => BrainfuckInstruction::NextTape,
+            16 => BrainfuckInstruction::Push,
+            17 => BrainfuckInstruction::Pop,
+            _ => unreachable!("bytecode only contains valid opcodes"),
+        };
+        Some(instruction)
+    }
+
+    /// Decode all instructions
+    pub fn to_instructions(&self) -> Vec<BrainfuckInstruction> {
+        (0..self.len())
+            .filter_map(|index| self.get(index))
+            .collect()
+    }
+
+    /// The encoded words
+    pub fn words(&self) -> &[u32] {
+        &self.words
+    }
+
+    /// The amount of instructions
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Whether there are no instructions
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BytecodeError> {
+        if self.data.len() < len {
+            return Err(BytecodeError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, BytecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, BytecodeError> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+}
+
+#[allow(deprecated)]
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Serialize the instructions of this program into bytecode
+    ///
+    /// The tape and execution state are not included. Returns an error if the amount of
+    /// instructions or a jump target does not fit in the format, or if the program has
+    /// an [`initial_tape`](Self::initial_tape), because its prefix has been folded:
+    /// without it, the loaded program would start from the wrong cells.
+    pub fn to_bytecode(&self) -> Result<Vec<u8>, BytecodeEncodeError> {
+        if self.initial_tape.is_some() {
+            return Err(BytecodeEncodeError::InitialTape);
+        }
+        let count = u32::try_from(self.instructions.len())
+            .map_err(|_| BytecodeEncodeError::TooManyInstructions)?;
+        let mut bytecode = Vec::with_capacity(HEADER_LEN + self.instructions.len() + CHECKSUM_LEN);
+        bytecode.extend_from_slice(&BYTECODE_MAGIC);
+        bytecode.extend_from_slice(&BYTECODE_VERSION.to_le_bytes());
+        bytecode.push(core::mem::size_of::<T::Data>() as u8);
+        bytecode.push(0);
+        bytecode.extend_from_slice(&count.to_le_bytes());
+
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            bytecode.push(opcode(instruction));
+            match instruction {
+                BrainfuckInstruction::JumpForward(target)
+                | BrainfuckInstruction::JumpBackwards(target)
+                | BrainfuckInstruction::DefineProcedure(target) => {
+                    let target = u32::try_from(*target)
+                        .map_err(|_| BytecodeEncodeError::TargetTooLarge { index })?;
+                    bytecode.extend_from_slice(&target.to_le_bytes());
+                }
+                _ => {}
+            }
+        }
+
+        let checksum = fnv1a(&bytecode);
+        bytecode.extend_from_slice(&checksum.to_le_bytes());
+        Ok(bytecode)
+    }
+
+    /// Load a program from bytecode produced by [`to_bytecode`](Self::to_bytecode),
+    /// running on `tape`
+    pub fn from_bytecode(bytecode: &[u8], tape: T) -> Result<Self, BytecodeError> {
+        let instructions = decode(bytecode, core::mem::size_of::<T::Data>() as u8)?;
+        Ok(Self::from_instructions(instructions, tape))
+    }
+}
+
+/// Decode the instructions in `bytecode`, which must have been compiled for cells of
+/// `cell_size` bytes
+pub(crate) fn decode(
+    bytecode: &[u8],
+    cell_size: u8,
+) -> Result<Vec<BrainfuckInstruction>, BytecodeError> {
+    let mut reader = Reader { data: bytecode };
+
+    if reader.take(4).map_err(|_| BytecodeError::BadMagic)? != BYTECODE_MAGIC {
+        return Err(BytecodeError::BadMagic);
+    }
+
+    let version = u16::from_le_bytes([reader.u8()?, reader.u8()?]);
+    if version != BYTECODE_VERSION {
+        return Err(BytecodeError::UnsupportedVersion(version));
+    }
+
+    let expected = cell_size;
+    let found = reader.u8()?;
+    if found != expected {
+        return Err(BytecodeError::CellWidthMismatch { expected, found });
+    }
+    reader.u8()?;
+
+    if bytecode.len() < HEADER_LEN + CHECKSUM_LEN {
+        return Err(BytecodeError::UnexpectedEnd);
+    }
+    let (contents, checksum) = bytecode.split_at(bytecode.len() - CHECKSUM_LEN);
+
+    let count = reader.u32()? as usize;
+    let mut instructions = Vec::with_capacity(count.min(contents.len()));
+    for index in 0..count {
+        let instruction = match reader.u8()? {
+            0 => BrainfuckInstruction::IncrementDataPointer,
+            1 => BrainfuckInstruction::DecrementDataPointer,
+            2 => BrainfuckInstruction::IncreaseData,
+            3 => BrainfuckInstruction::DecreaseData,
+            4 => BrainfuckInstruction::Output,
+            5 => BrainfuckInstruction::Input,
+            6 => BrainfuckInstruction::JumpForward(reader.u32()? as usize),
+            7 => BrainfuckInstruction::JumpBackwards(reader.u32()? as usize),
+            8 => BrainfuckInstruction::DefineProcedure(reader.u32()? as usize),
+            9 => BrainfuckInstruction::EndProcedure,
+            10 => BrainfuckInstruction::CallProcedure,
+            11 => BrainfuckInstruction::HostCall,
+            12 => BrainfuckInstruction::MoveUp,
+            13 => BrainfuckInstruction::MoveDown,
+            14 => BrainfuckInstruction::PreviousTape,
+            15 => BrainfuckInstruction::NextTape,
+            16 => BrainfuckInstruction::Push,
+            17 => BrainfuckInstruction::Pop,
+            opcode => {
+                if fnv1a(contents).to_le_bytes() != checksum {
+                    return Err(BytecodeError::ChecksumMismatch);
+                }
+                return Err(BytecodeError::UnknownOpcode { index, opcode });
+            }
+        };
+        instructions.push(instruction);
+    }
+
+    if reader.data.len() < CHECKSUM_LEN {
+        return Err(BytecodeError::UnexpectedEnd);
+    }
+    if fnv1a(contents).to_le_bytes() != checksum {
+        return Err(BytecodeError::ChecksumMismatch);
+    }
+    if reader.data.len() > CHECKSUM_LEN {
+        return Err(BytecodeError::TrailingData);
+    }
+
+    check_jumps(&instructions).map_err(|index| BytecodeError::InvalidJump { index })?;
+
+    Ok(instructions)
+}
+
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Run the Brainfuck program to completion, or until it fails, reading its
+    /// instructions from `bytecode`
+    ///
+    /// `bytecode` must have been encoded from the instructions of this program. This
+    /// behaves exactly like [`try_run`](Self::try_run), including the execution count,
+    /// and continues from the current instruction pointer.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckProgram, Bytecode};
+    /// let mut program = BrainfuckProgram::compile(",[.,]", vec![0u8; 1]).unwrap();
+    /// let bytecode = Bytecode::from_instructions(program.instructions()).unwrap();
+    /// let mut input = b"echo".iter().copied();
+    /// let mut output = Vec::new();
+    /// program
+    ///     .try_run_bytecode(&bytecode, &mut |data| output.push(data), &mut || {
+    ///         input.next().unwrap_or(0)
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(output, b"echo");
+    /// ```
+    pub fn try_run_bytecode<FnOut, FnIn>(
+        &mut self,
+        bytecode: &Bytecode,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<RunResult<T::Data>, RuntimeError>
+    where
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
+    {
+        let start = RunStart::new(self);
+        let (mut output, mut input) = (Counted::new(output), Counted::new(input));
+        self.execute(Some(bytecode), false, &mut output, &mut input)?;
+        Ok(start.finish(self, RunOutcome::Halted, &output, &input))
+    }
+}
@@ -0,0 +1,332 @@
+//! Remembering compiled programs, so that the same source is only parsed and optimized once
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::{
+    bytecode::decode, optimize::is_zeroed, BrainfuckInstruction, BrainfuckProgram, CompileOptions,
+    Error, SourcePos, Tape, TapeData,
+};
+
+/// The magic number that every cache entry starts with
+const CACHE_MAGIC: [u8; 4] = *b"BFCE";
+
+/// The version of the format of cache entries. Entries with another version, or that
+/// were written by another version of this crate, are ignored and replaced
+pub const CACHE_VERSION: u16 = 1;
+
+/// The extension of the files in a cache directory
+#[cfg(feature = "std")]
+const EXTENSION: &str = "bfc";
+
+fn fnv1a64(hash: u64, data: &[u8]) -> u64 {
+    data.iter().fold(hash, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Reads the parts of a cache entry, returning `None` once it runs out
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(taken)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let bytes = self.take(4)?;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+/// A cache of compiled programs, keyed by a hash of their source and the options that
+/// they were compiled with
+///
+/// [`compile`](Self::compile) behaves exactly like
+/// [`BrainfuckProgram::compile_with_options`], but only parses and optimizes a source
+/// the first time that it is compiled with the same [`Dialect`](crate::Dialect),
+/// [`OptimizationLevel`](crate::OptimizationLevel), cell size and whether cells wrap
+/// around, on a tape that is zeroed or not. After that, the
+/// program is loaded from its [bytecode](BrainfuckProgram::to_bytecode), together with
+/// its source map. Folding the prefix of a program depends on its tape, so
+/// [`CompileOptions::fold_prefix`] is applied every time.
+///
+/// Entries are kept in memory, and, if the cache was created with
+/// [`with_directory`](Self::with_directory), in files in a directory, so that they
+/// survive between runs. Every entry records [`CACHE_VERSION`] and the version of this
+/// crate, so entries written by an older version are treated as missing and
+/// overwritten. The key is a 64-bit hash, so two different sources could share an
+/// entry, but that is very unlikely to happen by accident.
+///
+/// ```
+/// # use brainfuck_interpreter::{programs, CompilationCache, CompileOptions, OptimizationLevel};
+/// let options = CompileOptions {
+///     optimization: OptimizationLevel::Peeling,
+///     ..CompileOptions::default()
+/// };
+/// let mut cache = CompilationCache::new();
+///
+/// let source = programs::HELLO_WORLD.source;
+/// let first = cache.compile(source, vec![0u8; 16], &options).unwrap();
+/// let second = cache.compile(source, vec![0u8; 16], &options).unwrap();
+/// assert_eq!(first.instructions(), second.instructions());
+/// assert_eq!((cache.hits(), cache.misses()), (1, 1));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CompilationCache {
+    entries: BTreeMap<u64, Vec<u8>>,
+    #[cfg(feature = "std")]
+    directory: Option<std::path::PathBuf>,
+    hits: u64,
+    misses: u64,
+}
+
+impl CompilationCache {
+    /// Create a cache that only keeps its entries in memory
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a cache that stores its entries in `directory` as well, which is created
+    /// when the first entry is written
+    ///
+    /// Reading and writing the directory is best-effort: entries that cannot be read
+    /// are compiled again, and entries that cannot be written are only kept in memory.
+    #[cfg(feature = "std")]
+    pub fn with_directory<P>(directory: P) -> Self
+    where
+        P: Into<std::path::PathBuf>,
+    {
+        Self {
+            directory: Some(directory.into()),
+            ..Self::default()
+        }
+    }
+
+    /// The amount of times that a program was loaded from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// The amount of times that a program had to be compiled
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// The amount of entries in memory
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no entries in memory
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Forget all entries in memory. The entries in the cache directory are kept
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Remove all entries from the cache directory, if there is one
+    #[cfg(feature = "std")]
+    pub fn clear_directory(&self) -> std::io::Result<()> {
+        let directory = match &self.directory {
+            Some(directory) => directory,
+            None => return Ok(()),
+        };
+        let entries = match std::fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == EXTENSION)
+            {
+                std::fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The key of `input` compiled with `options` for cells of `cell_size` bytes that
+    /// `wrap` or not, on a tape that is `zeroed` or not
+    fn key(input: &str, options: &CompileOptions, cell_size: u8, wraps: bool, zeroed: bool) -> u64 {
+        let mut hash = fnv1a64(0xcbf2_9ce4_8422_2325, &CACHE_VERSION.to_le_bytes());
+        hash = fnv1a64(hash, env!("CARGO_PKG_VERSION").as_bytes());
+        hash = fnv1a64(
+            hash,
+            &[
+                cell_size,
+                options.dialect as u8,
+                options.optimization as u8,
+                wraps as u8,
+                zeroed as u8,
+            ],
+        );
+        fnv1a64(hash, input.as_bytes())
+    }
+
+    /// Encode the instructions and source map of `program` as an entry, if they fit in
+    /// bytecode
+    fn encode<T>(program: &BrainfuckProgram<T>) -> Option<Vec<u8>>
+    where
+        T: Tape,
+    {
+        let bytecode = program.to_bytecode().ok()?;
+        let source_map = program.source_map.as_deref().unwrap_or(&[]);
+        let version = env!("CARGO_PKG_VERSION").as_bytes();
+        let mut entry = Vec::new();
+        entry.extend_from_slice(&CACHE_MAGIC);
+        entry.extend_from_slice(&CACHE_VERSION.to_le_bytes());
+        entry.push(version.len() as u8);
+        entry.extend_from_slice(version);
+        entry.extend_from_slice(&(source_map.len() as u32).to_le_bytes());
+        for position in source_map {
+            entry.extend_from_slice(&(position.line as u32).to_le_bytes());
+            entry.extend_from_slice(&(position.character as u32).to_le_bytes());
+        }
+        entry.extend_from_slice(&bytecode);
+        Some(entry)
+    }
+
+    /// The instructions and source map in `entry`, if it was written by this version of
+    /// the crate for cells of `cell_size` bytes, and is intact
+    fn decode(entry: &[u8], cell_size: u8) -> Option<(Vec<BrainfuckInstruction>, Vec<SourcePos>)> {
+        let mut reader = Reader { data: entry };
+        if reader.take(4)? != CACHE_MAGIC || reader.take(2)? != CACHE_VERSION.to_le_bytes() {
+            return None;
+        }
+        let len = reader.take(1)?[0] as usize;
+        if reader.take(len)? != env!("CARGO_PKG_VERSION").as_bytes() {
+            return None;
+        }
+
+        let count = reader.u32()? as usize;
+        let mut source_map = Vec::with_capacity(count.min(reader.data.len() / 8));
+        for _ in 0..count {
+            source_map.push(SourcePos {
+                line: reader.u32()? as usize,
+                character: reader.u32()? as usize,
+            });
+        }
+
+        let instructions = decode(reader.data, cell_size).ok()?;
+        if source_map.len() != instructions.len() {
+            return None;
+        }
+        Some((instructions, source_map))
+    }
+
+    /// The file in the cache directory that stores the entry for `key`
+    #[cfg(feature = "std")]
+    fn path(&self, key: u64) -> Option<std::path::PathBuf> {
+        let directory = self.directory.as_ref()?;
+        Some(directory.join(alloc::format!("{:016x}.{}", key, EXTENSION)))
+    }
+
+    /// Find the instructions and source map for `key` in memory, or in the cache
+    /// directory
+    fn load(
+        &mut self,
+        key: u64,
+        cell_size: u8,
+    ) -> Option<(Vec<BrainfuckInstruction>, Vec<SourcePos>)> {
+        if let Some(entry) = self.entries.get(&key) {
+            return Self::decode(entry, cell_size);
+        }
+
+        #[cfg(feature = "std")]
+        if let Some(entry) = self.path(key).and_then(|path| std::fs::read(path).ok()) {
+            let decoded = Self::decode(&entry, cell_size)?;
+            self.entries.insert(key, entry);
+            return Some(decoded);
+        }
+        None
+    }
+
+    /// Remember `entry` for `key`, in memory and in the cache directory
+    fn store(&mut self, key: u64, entry: Vec<u8>) {
+        #[cfg(feature = "std")]
+        if let (Some(directory), Some(path)) = (self.directory.as_ref(), self.path(key)) {
+            // Write to a temporary file first, so that other processes never read a
+            // partially written entry
+            let temporary = path.with_extension("tmp");
+            let written = std::fs::create_dir_all(directory)
+                .and_then(|_| std::fs::write(&temporary, &entry))
+                .and_then(|_| std::fs::rename(&temporary, &path));
+            if written.is_err() {
+                std::fs::remove_file(&temporary).ok();
+            }
+        }
+        self.entries.insert(key, entry);
+    }
+
+    /// Compile `input` using `options`, or load it from the cache if it has been
+    /// compiled with the same options before
+    ///
+    /// Programs that fail to compile are not cached.
+    pub fn compile<'a, T>(
+        &mut self,
+        input: &'a str,
+        mut tape: T,
+        options: &CompileOptions,
+    ) -> Result<BrainfuckProgram<T>, Error<'a>>
+    where
+        T: Tape,
+    {
+        let cell_size = core::mem::size_of::<T::Data>() as u8;
+        let key = Self::key(
+            input,
+            options,
+            cell_size,
+            T::Data::WRAPS,
+            is_zeroed(&mut tape),
+        );
+
+        let mut program = match self.load(key, cell_size) {
+            Some((instructions, source_map)) => {
+                self.hits += 1;
+                let mut program = BrainfuckProgram::from_instructions(instructions, tape);
+                program.source_map = Some(source_map);
+                program.source = Some(input.into());
+                program
+            }
+            None => {
+                self.misses += 1;
+                let program = BrainfuckProgram::compile_with_options(
+                    input,
+                    tape,
+                    &CompileOptions {
+                        source_map: true,
+                        fold_prefix: None,
+                        ..options.clone()
+                    },
+                )?;
+                if let Some(entry) = Self::encode(&program) {
+                    self.store(key, entry);
+                }
+                program
+            }
+        };
+
+        program.strict = options.strict;
+        if !options.source_map && !options.strict {
+            program.source_map = None;
+            program.source = None;
+        }
+        if let Some(fuel) = options.fold_prefix {
+            program.fold_prefix(fuel);
+        }
+        Ok(program)
+    }
+}
@@ -0,0 +1,158 @@
+//! Capturing the output of Brainfuck programs
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+
+use crate::{io::Input, BrainfuckProgram, Tape, TapeData};
+
+/// Output collected from a Brainfuck program
+#[derive(Clone, Debug, Default)]
+pub struct CapturedOutput<D> {
+    data: Vec<D>,
+    stop_at: Option<Vec<D>>,
+}
+
+/// The reason that [`BrainfuckProgram::run_capture`] returned
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptureEnd {
+    /// The program has finished
+    Halted,
+    /// The captured output ends with the sequence the capture should stop at
+    Breakpoint,
+}
+
+impl<D> CapturedOutput<D>
+where
+    D: TapeData,
+{
+    /// Create an empty capture, that captures until the program finishes
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::CapturedOutput;
+    /// let mut capture = CapturedOutput::<u8>::new();
+    /// capture.push(1);
+    /// capture.push(2);
+    /// assert_eq!(capture.data(), [1, 2]);
+    /// assert!(!capture.at_breakpoint());
+    ///
+    /// capture.clear();
+    /// capture.push(3);
+    /// assert_eq!(capture.into_inner(), [3]);
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            data: Vec::new(),
+            stop_at: None,
+        }
+    }
+
+    /// Create an empty capture, that stops the program as soon as the captured
+    /// output ends with `sequence`
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::CapturedOutput;
+    /// let mut capture = CapturedOutput::stopping_at(b"\n");
+    /// capture.push(b'a');
+    /// assert!(!capture.at_breakpoint());
+    /// capture.push(b'\n');
+    /// assert!(capture.at_breakpoint());
+    /// ```
+    pub fn stopping_at(sequence: &[D]) -> Self {
+        Self {
+            data: Vec::new(),
+            stop_at: Some(sequence.to_vec()),
+        }
+    }
+
+    /// Add `data` to the captured output
+    pub fn push(&mut self, data: D) {
+        self.data.push(data);
+    }
+
+    /// Whether the captured output ends with the sequence this capture stops at
+    pub fn at_breakpoint(&self) -> bool {
+        match &self.stop_at {
+            Some(sequence) => !sequence.is_empty() && self.data.ends_with(sequence),
+            None => false,
+        }
+    }
+
+    /// The captured output
+    pub fn data(&self) -> &[D] {
+        &self.data
+    }
+
+    /// Discard all captured output
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    /// Consume this capture, returning the captured output
+    pub fn into_inner(self) -> Vec<D> {
+        self.data
+    }
+}
+
+impl CapturedOutput<u8> {
+    /// The captured output as a string, if it is valid UTF-8
+    pub fn as_str(&self) -> Result<&str, core::str::Utf8Error> {
+        core::str::from_utf8(&self.data)
+    }
+
+    /// The captured output as a string, replacing invalid UTF-8 sequences
+    /// with `U+FFFD REPLACEMENT CHARACTER`
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::CapturedOutput;
+    /// let mut capture = CapturedOutput::new();
+    /// capture.push(b'h');
+    /// capture.push(b'i');
+    /// assert_eq!(capture.as_str(), Ok("hi"));
+    ///
+    /// capture.push(0xFF);
+    /// assert!(capture.as_str().is_err());
+    /// assert_eq!(capture.to_string_lossy(), "hi\u{FFFD}");
+    /// ```
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.data)
+    }
+}
+
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Run the Brainfuck program, collecting all output into `capture`
+    ///
+    /// If `capture` has a sequence to stop at, execution stops as soon as the output
+    /// ends with that sequence. Calling this function again continues the program.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckProgram, CaptureEnd, CapturedOutput};
+    /// let source = "++++++++++[>+++++>+<<-]>-.>.>+++++++[<<+>>-]<<.";
+    /// let mut program = BrainfuckProgram::compile(source, vec![0u8; 3]).unwrap();
+    /// let mut capture = CapturedOutput::stopping_at(b"\n");
+    ///
+    /// assert_eq!(program.run_capture(&mut capture, &mut || 0), CaptureEnd::Breakpoint);
+    /// assert_eq!(capture.as_str(), Ok("1\n"));
+    /// assert_eq!(program.run_capture(&mut capture, &mut || 0), CaptureEnd::Halted);
+    /// assert_eq!(capture.as_str(), Ok("1\n8"));
+    /// ```
+    pub fn run_capture<FnIn>(
+        &mut self,
+        capture: &mut CapturedOutput<T::Data>,
+        input: &mut FnIn,
+    ) -> CaptureEnd
+    where
+        FnIn: Input<T::Data>,
+    {
+        loop {
+            let captured = capture.data.len();
+            if !self.step(&mut |data| capture.push(data), input) {
+                return CaptureEnd::Halted;
+            }
+            if capture.data.len() != captured && capture.at_breakpoint() {
+                return CaptureEnd::Breakpoint;
+            }
+        }
+    }
+}
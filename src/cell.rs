@@ -0,0 +1,106 @@
+//! Cells that do not wrap around
+
+use crate::TapeData;
+
+/// Cell data that stays at its largest value when it is increased, and at zero when
+/// it is decreased, instead of wrapping around
+///
+/// ```
+/// # use brainfuck_interpreter::{BrainfuckProgram, Saturating};
+/// let mut program = BrainfuckProgram::compile("-->+++", vec![Saturating(0u8); 2]).unwrap();
+/// program.run(&mut |_| {}, &mut || Saturating(0));
+/// assert_eq!(program.tape, [Saturating(0), Saturating(3)]);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Saturating<T>(pub T);
+
+/// Cell data for which increasing it past its largest value, or decreasing it past
+/// zero, is an error
+///
+/// [`BrainfuckProgram::try_step`](crate::BrainfuckProgram::try_step) returns a
+/// [`RuntimeError::CellOverflow`](crate::RuntimeError::CellOverflow) in that case, which
+/// helps to find mistakes in programs that are not meant to rely on wrapping.
+/// [`increase`](TapeData::increase) and [`decrease`](TapeData::decrease) themselves
+/// leave the data unchanged.
+///
+/// ```
+/// # use brainfuck_interpreter::{BrainfuckProgram, RuntimeError, Trapping};
+/// let mut program = BrainfuckProgram::compile("+--", vec![Trapping(0u8); 1]).unwrap();
+/// let error = program.try_run(&mut |_| {}, &mut || Trapping(0)).unwrap_err();
+/// assert!(matches!(error, RuntimeError::CellOverflow { instruction_pointer: 2, .. }));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Trapping<T>(pub T);
+
+/// Implement [`TapeData`] for [`Saturating`] and [`Trapping`] unsigned integers
+macro_rules! impl_cells {
+    ($($ty:ty),*) => {
+        $(
+            impl TapeData for Saturating<$ty> {
+                const WRAPS: bool = false;
+
+                fn zero() -> Self {
+                    Saturating(0)
+                }
+
+                fn increase(&mut self) {
+                    self.0 = self.0.saturating_add(1);
+                }
+
+                fn decrease(&mut self) {
+                    self.0 = self.0.saturating_sub(1);
+                }
+            }
+
+            impl TapeData for Trapping<$ty> {
+                const WRAPS: bool = false;
+
+                fn zero() -> Self {
+                    Trapping(0)
+                }
+
+                fn increase(&mut self) {
+                    self.try_increase();
+                }
+
+                fn decrease(&mut self) {
+                    self.try_decrease();
+                }
+
+                fn try_increase(&mut self) -> bool {
+                    match self.0.checked_add(1) {
+                        Some(value) => {
+                            self.0 = value;
+                            true
+                        }
+                        None => false,
+                    }
+                }
+
+                fn try_decrease(&mut self) -> bool {
+                    match self.0.checked_sub(1) {
+                        Some(value) => {
+                            self.0 = value;
+                            true
+                        }
+                        None => false,
+                    }
+                }
+            }
+
+            impl From<Saturating<$ty>> for u32 {
+                fn from(data: Saturating<$ty>) -> u32 {
+                    data.0.into()
+                }
+            }
+
+            impl From<Trapping<$ty>> for u32 {
+                fn from(data: Trapping<$ty>) -> u32 {
+                    data.0.into()
+                }
+            }
+        )*
+    };
+}
+
+impl_cells!(u8, u16, u32);
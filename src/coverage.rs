@@ -0,0 +1,202 @@
+//! Finding out which instructions of a program are executed
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::Display;
+
+use crate::{
+    io::{Input, Output},
+    parse,
+    result::{Counted, RunStart},
+    BrainfuckProgram, RunOutcome, RunResult, SourcePos, Tape,
+};
+
+/// Records which instructions of a program have been executed
+#[derive(Clone, Debug)]
+pub struct Coverage {
+    executed: Vec<bool>,
+}
+
+/// Which instructions of a program have been executed, as returned by
+/// [`Coverage::report`]
+///
+/// Its [`Display`] implementation shows the source of the program, with every
+/// instruction that has not been executed marked by a `^` below it. Lines whose
+/// instructions have all been executed are not marked.
+#[derive(Clone, Debug)]
+pub struct CoverageReport<'a> {
+    /// The source code of the program
+    pub source: &'a str,
+    /// The amount of instructions in the program
+    pub instructions: usize,
+    /// The amount of instructions that have been executed
+    pub executed: usize,
+    /// The positions of the instructions that have not been executed, in the order of
+    /// their instructions
+    pub not_executed: Vec<SourcePos>,
+}
+
+#[allow(deprecated)]
+impl Coverage {
+    /// Create a coverage recorder for `program`, in which no instruction has been
+    /// executed yet
+    pub fn new<T>(program: &BrainfuckProgram<T>) -> Self
+    where
+        T: Tape,
+    {
+        Self {
+            executed: alloc::vec![false; program.instructions.len()],
+        }
+    }
+
+    /// Whether every instruction has been executed, by instruction index
+    pub fn executed(&self) -> &[bool] {
+        &self.executed
+    }
+
+    /// The indices of the instructions that have not been executed
+    pub fn not_executed(&self) -> impl Iterator<Item = usize> + '_ {
+        self.executed
+            .iter()
+            .enumerate()
+            .filter(|(_, executed)| !**executed)
+            .map(|(index, _)| index)
+    }
+
+    /// Perform a step in `program`, recording the instruction that is executed
+    pub fn step<T, FnOut, FnIn>(
+        &mut self,
+        program: &mut BrainfuckProgram<T>,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> bool
+    where
+        T: Tape,
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
+    {
+        if let Some(executed) = self.executed.get_mut(program.instruction_pointer) {
+            *executed = true;
+        }
+        program.step(output, input)
+    }
+
+    /// Run `program` to completion, recording every instruction that is executed
+    pub fn run<T, FnOut, FnIn>(
+        &mut self,
+        program: &mut BrainfuckProgram<T>,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> RunResult<T::Data>
+    where
+        T: Tape,
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
+    {
+        let start = RunStart::new(program);
+        let (mut output, mut input) = (Counted::new(output), Counted::new(input));
+        while self.step(program, &mut output, &mut input) {}
+        start.finish(program, RunOutcome::Halted, &output, &input)
+    }
+
+    /// Create a report of the instructions that have not been executed, given the
+    /// `source` that the program was compiled from
+    ///
+    /// If `program` has a source map, it is used to find the position of the
+    /// instructions. Otherwise, `source` is parsed as plain Brainfuck.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckProgram, Coverage};
+    /// let source = "+[-]\n[>+<-]>.";
+    /// let mut program = BrainfuckProgram::compile(source, vec![0u8; 2]).unwrap();
+    ///
+    /// let mut coverage = Coverage::new(&program);
+    /// coverage.run(&mut program, &mut |_| {}, &mut || 0);
+    ///
+    /// let report = coverage.report(&program, source);
+    /// assert_eq!((report.executed, report.instructions), (7, 12));
+    /// assert_eq!(
+    ///     report.to_string(),
+    ///     "7 of 12 instructions executed (58.33%)\n    \
+    ///          0 | +[-]\n    \
+    ///          1 | [>+<-]>.\n      \
+    ///            |  ^^^^^\n"
+    /// );
+    /// ```
+    pub fn report<'a, T>(
+        &self,
+        program: &BrainfuckProgram<T>,
+        source: &'a str,
+    ) -> CoverageReport<'a>
+    where
+        T: Tape,
+    {
+        let positions: Vec<SourcePos> = match &program.source_map {
+            Some(map) => map.clone(),
+            None => parse(source).map(|span| span.get_position()).collect(),
+        };
+
+        let not_executed = self
+            .not_executed()
+            .filter_map(|index| positions.get(index).copied())
+            .collect();
+
+        CoverageReport {
+            source,
+            instructions: self.executed.len(),
+            executed: self.executed.iter().filter(|executed| **executed).count(),
+            not_executed,
+        }
+    }
+}
+
+impl<'a> CoverageReport<'a> {
+    /// The percentage of instructions that have been executed
+    pub fn percentage(&self) -> f64 {
+        if self.instructions == 0 {
+            100.0
+        } else {
+            self.executed as f64 * 100.0 / self.instructions as f64
+        }
+    }
+}
+
+impl<'a> Display for CoverageReport<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(
+            f,
+            "{} of {} instructions executed ({:.2}%)",
+            self.executed,
+            self.instructions,
+            self.percentage()
+        )?;
+
+        let mut not_executed = self.not_executed.clone();
+        not_executed.sort_unstable();
+        let mut not_executed = not_executed.iter().peekable();
+
+        let mut marks = String::new();
+        for (line, text) in self.source.lines().enumerate() {
+            writeln!(f, "{:>5} | {}", line, text)?;
+
+            marks.clear();
+            for (index, character) in text.chars().enumerate() {
+                let position = SourcePos {
+                    line,
+                    character: index + 1,
+                };
+                if not_executed.next_if_eq(&&position).is_some() {
+                    marks.push('^');
+                } else if character == '\t' {
+                    // Keep tabs, so that the marks line up with the characters above them
+                    marks.push('\t');
+                } else {
+                    marks.push(' ');
+                }
+            }
+            if marks.contains('^') {
+                writeln!(f, "{:>5} | {}", "", marks.trim_end())?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,346 @@
+//! A GDB-style command line front-end for the [`Runner`]
+//!
+//! The commands are read line by line, and their results are written to an output.
+//! Program output is written as soon as the program produces it. The `bf-debug`
+//! binary runs this front-end on standard input and output.
+//!
+//! | Command                               | Effect                                          |
+//! |---------------------------------------|-------------------------------------------------|
+//! | `break INSTRUCTION`, `break L:C`      | Add a breakpoint at an instruction or position  |
+//! | `delete INSTRUCTION`                  | Remove a breakpoint                             |
+//! | `run`                                 | Restart the program, and run to a breakpoint    |
+//! | `continue`                            | Run to the next breakpoint                      |
+//! | `step [N]`                            | Execute `N` instructions                        |
+//! | `reverse-step [N]`                    | Undo `N` instructions                           |
+//! | `diff FROM [TO]`                      | Show the cells that changed between two steps   |
+//! | `print tape[A..B]`, `print tape[A]`   | Show a hex dump of the tape                     |
+//! | `watch EXPRESSION`                    | Show a [`Watch`] every time the program stops   |
+//! | `unwatch N`                           | Remove the `N`th watch                          |
+//! | `where`                               | Show the instruction that is executed next      |
+//! | `quit`                                | Stop debugging                                  |
+//!
+//! ```
+//! use brainfuck_interpreter::debugger::cli::Cli;
+//!
+//! let mut cli = Cli::new("+++[>++<-]>.", Vec::new()).unwrap();
+//! let mut output = Vec::new();
+//! cli.execute("break 7", &mut output).unwrap();
+//! cli.execute("run", &mut output).unwrap();
+//! cli.execute("print tape[0..2]", &mut output).unwrap();
+//!
+//! let output = String::from_utf8(output).unwrap();
+//! assert!(output.contains("00000000   03 [02] "));
+//! ```
+
+use std::io::{self, BufRead, Write};
+
+use crate::{BrainfuckProgram, Error, Span};
+
+use super::{Runner, StopReason, Watch};
+
+/// The length of the tape that the debugged program starts with. The tape grows as needed
+const TAPE_LEN: usize = 30_000;
+
+/// A command line debugger for a single program
+pub struct Cli<'a> {
+    source: &'a str,
+    program: BrainfuckProgram<Vec<u8>>,
+    runner: Runner<Vec<u8>>,
+    input: Vec<u8>,
+    input_read: usize,
+    output_written: usize,
+}
+
+#[allow(deprecated)]
+impl<'a> Cli<'a> {
+    /// Compile `source` for debugging, with `input` as the input of the program
+    ///
+    /// Reading past the end of `input` reads zero.
+    pub fn new(source: &'a str, input: Vec<u8>) -> Result<Self, Error<'a>> {
+        let program = BrainfuckProgram::compile_with_source_map(source, vec![0; TAPE_LEN])?;
+        Ok(Self {
+            source,
+            runner: Runner::new(program.clone()),
+            program,
+            input,
+            input_read: 0,
+            output_written: 0,
+        })
+    }
+
+    /// The runner that executes the program
+    pub fn runner(&self) -> &Runner<Vec<u8>> {
+        &self.runner
+    }
+
+    /// Read commands from `commands` until it runs out or `quit` is entered, writing
+    /// a prompt and the results of the commands to `out`
+    pub fn run_repl<R, W>(&mut self, commands: R, mut out: W) -> io::Result<()>
+    where
+        R: BufRead,
+        W: Write,
+    {
+        let mut lines = commands.lines();
+        loop {
+            write!(out, "(bf) ")?;
+            out.flush()?;
+            let line = match lines.next() {
+                Some(line) => line?,
+                None => return Ok(()),
+            };
+            if !self.execute(&line, &mut out)? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Execute a single command, writing its result to `out`
+    ///
+    /// Returns `false` if the command was `quit`.
+    pub fn execute<W>(&mut self, command: &str, out: &mut W) -> io::Result<bool>
+    where
+        W: Write,
+    {
+        let command = command.trim();
+        let (name, argument) = match command.split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, argument.trim()),
+            None => (command, ""),
+        };
+
+        match name {
+            "" => {}
+            "break" | "b" => match self.instruction_at(argument) {
+                Some(instruction) => {
+                    self.runner.add_breakpoint(instruction);
+                    write!(out, "Breakpoint at instruction {}", instruction)?;
+                    if let Some(position) = self.program.source_of(instruction) {
+                        write!(out, " ({})", position)?;
+                    }
+                    writeln!(out)?;
+                }
+                None => writeln!(out, "No instruction at `{}`", argument)?,
+            },
+            "delete" | "d" => match argument.parse() {
+                Ok(instruction) if self.runner.remove_breakpoint(instruction) => {
+                    writeln!(out, "Deleted the breakpoint at instruction {}", instruction)?
+                }
+                _ => writeln!(out, "No breakpoint at `{}`", argument)?,
+            },
+            "run" | "r" => {
+                self.restart();
+                self.resume(out)?;
+            }
+            "continue" | "c" => self.resume(out)?,
+            "step" | "s" => match count(argument) {
+                Some(steps) => {
+                    {
+                        let mut input = input(&self.input, &mut self.input_read);
+                        for _ in 0..steps {
+                            if !self.runner.step(&mut input) {
+                                break;
+                            }
+                        }
+                    }
+                    self.stopped(out, None)?;
+                }
+                None => writeln!(out, "Invalid amount of steps `{}`", argument)?,
+            },
+            "reverse-step" | "rs" => match count(argument) {
+                Some(steps) => {
+                    let undone = self.runner.rewind(steps);
+                    if undone < steps {
+                        writeln!(out, "Can only go back {} steps", undone)?;
+                    }
+                    // Rewinding discards the output of the undone steps
+                    self.output_written = self.output_written.min(self.runner.output().len());
+                    self.stopped(out, None)?;
+                }
+                None => writeln!(out, "Invalid amount of steps `{}`", argument)?,
+            },
+            "diff" => {
+                let mut steps = argument.split_whitespace().map(str::parse::<usize>);
+                let current = self.runner.steps();
+                let diff = match (steps.next(), steps.next(), steps.next()) {
+                    (Some(Ok(from)), None, None) => self.runner.diff_steps(from, current),
+                    (Some(Ok(from)), Some(Ok(to)), None) => self.runner.diff_steps(from, to),
+                    _ => {
+                        writeln!(out, "Expected `diff FROM [TO]`")?;
+                        return Ok(true);
+                    }
+                };
+                match diff {
+                    Some(diff) => write!(out, "{}", diff)?,
+                    None => writeln!(
+                        out,
+                        "Can only compare steps {} to {}",
+                        self.runner.earliest_step(),
+                        current
+                    )?,
+                }
+            }
+            "print" | "p" => match parse_tape_range(argument) {
+                Some((start, end)) => write!(out, "{}", self.runner.dump(start..end))?,
+                None => writeln!(out, "Expected `tape[START..END]` or `tape[INDEX]`")?,
+            },
+            "watch" | "w" => match argument.parse::<Watch>() {
+                Ok(watch) => {
+                    let index = self.runner.watches().len();
+                    let report = self.runner.add_watch(watch);
+                    writeln!(out, "Watch {}: {}", index, report)?;
+                }
+                Err(error) => writeln!(out, "{}", error)?,
+            },
+            "unwatch" => match argument
+                .parse()
+                .ok()
+                .and_then(|i| self.runner.remove_watch(i))
+            {
+                Some(watch) => writeln!(out, "Removed watch `{}`", watch)?,
+                None => writeln!(out, "No watch `{}`", argument)?,
+            },
+            "where" => self.write_where(out)?,
+            "quit" | "q" => return Ok(false),
+            "help" | "h" => writeln!(
+                out,
+                "Commands: break, delete, run, continue, step, reverse-step, diff, print, \
+                 watch, unwatch, where, quit"
+            )?,
+            _ => writeln!(out, "Unknown command `{}`, try `help`", name)?,
+        }
+        Ok(true)
+    }
+
+    /// Start the program over from the beginning, keeping breakpoints and watches
+    fn restart(&mut self) {
+        let mut runner = Runner::new(self.program.clone());
+        for breakpoint in self.runner.breakpoints() {
+            runner.add_breakpoint(breakpoint);
+        }
+        for watch in self.runner.watches() {
+            runner.add_watch(*watch);
+        }
+        self.runner = runner;
+        self.input_read = 0;
+        self.output_written = 0;
+    }
+
+    fn resume<W>(&mut self, out: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let reason = self
+            .runner
+            .resume(&mut input(&self.input, &mut self.input_read));
+        self.stopped(out, Some(reason))
+    }
+
+    /// Write the new output of the program, why it stopped, and the watches
+    fn stopped<W>(&mut self, out: &mut W, reason: Option<StopReason>) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let output = &self.runner.output()[self.output_written..];
+        if !output.is_empty() {
+            out.write_all(output)?;
+            writeln!(out)?;
+            self.output_written += output.len();
+        }
+
+        match reason {
+            Some(StopReason::Breakpoint(instruction)) => {
+                writeln!(out, "Breakpoint at instruction {}", instruction)?;
+                self.write_where(out)?;
+            }
+            Some(StopReason::Halted) => writeln!(out, "The program has finished")?,
+            None => self.write_where(out)?,
+        }
+
+        for (index, report) in self.runner.watch_reports().iter().enumerate() {
+            writeln!(out, "Watch {}: {}", index, report)?;
+        }
+        Ok(())
+    }
+
+    /// Write the position of the next instruction, with an arrow pointing at it
+    fn write_where<W>(&self, out: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let program = self.runner.program();
+        let instruction_pointer = program.instruction_pointer;
+        let (instruction, position) = match (
+            program.instructions.get(instruction_pointer),
+            program.source_of(instruction_pointer),
+        ) {
+            (Some(instruction), Some(position)) => (*instruction, position),
+            _ => return writeln!(out, "The program has finished"),
+        };
+
+        let span = Span {
+            instruction,
+            text: self.source,
+            line: position.line,
+            character: position.character,
+        };
+        writeln!(
+            out,
+            "Instruction {}, step {} ({}):\n{}",
+            instruction_pointer,
+            self.runner.steps(),
+            position,
+            span
+        )
+    }
+
+    /// The instruction at `argument`, which is either an instruction index or a
+    /// `LINE:CHARACTER` position in the source code
+    ///
+    /// A position refers to the first instruction at or after it.
+    fn instruction_at(&self, argument: &str) -> Option<usize> {
+        let (line, character) = match argument.split_once(':') {
+            Some((line, character)) => (line.trim().parse().ok()?, character.trim().parse().ok()?),
+            None => {
+                let instruction = argument.parse().ok()?;
+                return (instruction < self.program.instructions.len()).then_some(instruction);
+            }
+        };
+        let source_map = self.program.source_map.as_ref()?;
+        source_map
+            .iter()
+            .position(|position| (position.line, position.character) >= (line, character))
+    }
+}
+
+/// The input of the program, reading zero past its end
+fn input<'i>(data: &'i [u8], read: &'i mut usize) -> impl FnMut() -> u8 + 'i {
+    move || {
+        let value = data.get(*read).copied().unwrap_or(0);
+        *read += 1;
+        value
+    }
+}
+
+/// Parse the amount of times to repeat a command, which defaults to one
+fn count(argument: &str) -> Option<usize> {
+    if argument.is_empty() {
+        Some(1)
+    } else {
+        argument.parse().ok()
+    }
+}
+
+/// Parse `tape[START..END]` or `tape[INDEX]` into the range of cells it refers to
+fn parse_tape_range(argument: &str) -> Option<(usize, usize)> {
+    let range = argument
+        .strip_prefix("tape")?
+        .trim()
+        .strip_prefix('[')?
+        .strip_suffix(']')?;
+    match range.split_once("..") {
+        Some((start, end)) => Some((start.trim().parse().ok()?, end.trim().parse().ok()?)),
+        None => {
+            let index: usize = range.trim().parse().ok()?;
+            Some((index, index.checked_add(1)?))
+        }
+    }
+}
@@ -0,0 +1,497 @@
+//! A [Debug Adapter Protocol] server for the [`Runner`]
+//!
+//! [Debug Adapter Protocol]: https://microsoft.github.io/debug-adapter-protocol/
+//!
+//! The server lets editors such as VS Code debug Brainfuck programs. It talks to the
+//! editor over a reader and a writer, which the `bf-dap` binary connects to standard
+//! input and output.
+//!
+//! Breakpoints are mapped to the first instruction at or after their position in the
+//! source code. The stack frames are the loops that the next instruction is nested in,
+//! innermost first, and the variables are the cells around the data pointer and the
+//! registers of the program.
+//!
+//! The `launch` request takes the path of the program as `program`, and optionally
+//! the input of the program as a string (`input`) or the path of a file (`inputFile`).
+//! With `stopOnEntry`, the program stops before executing its first instruction.
+
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+};
+
+use serde_json::{json, Value};
+
+use crate::{protocol, BrainfuckInstruction, BrainfuckProgram};
+
+use super::{Runner, StopReason};
+
+/// The length of the tape that the debugged program starts with. The tape grows as needed
+const TAPE_LEN: usize = 30_000;
+
+/// The amount of cells on either side of the data pointer that are shown as variables
+const TAPE_WINDOW: usize = 8;
+
+/// The only thread of a Brainfuck program
+const THREAD_ID: u64 = 1;
+
+/// The variables reference of the tape window
+const TAPE_REFERENCE: u64 = 1;
+
+/// The variables reference of the registers
+const REGISTERS_REFERENCE: u64 = 2;
+
+/// A program that is being debugged
+struct Session {
+    path: String,
+    program: BrainfuckProgram<Vec<u8>>,
+    runner: Runner<Vec<u8>>,
+    input: Vec<u8>,
+    input_read: usize,
+    output_written: usize,
+    stop_on_entry: bool,
+}
+
+#[allow(deprecated)]
+impl Session {
+    /// Execute at least one instruction, and keep running until the program finishes,
+    /// reaches a breakpoint or `stop` returns true
+    fn run_until<F>(&mut self, mut stop: F) -> StopReason
+    where
+        F: FnMut(&BrainfuckProgram<Vec<u8>>) -> bool,
+    {
+        let Self {
+            runner,
+            input,
+            input_read,
+            ..
+        } = self;
+        let mut input = || {
+            let value = input.get(*input_read).copied().unwrap_or(0);
+            *input_read += 1;
+            value
+        };
+
+        while runner.step(&mut input) {
+            let instruction_pointer = runner.program().instruction_pointer;
+            if runner.breakpoints().any(|b| b == instruction_pointer) {
+                return StopReason::Breakpoint(instruction_pointer);
+            }
+            if stop(runner.program()) {
+                return StopReason::Breakpoint(instruction_pointer);
+            }
+        }
+        StopReason::Halted
+    }
+
+    /// The indices of the `[` of every loop that the next instruction is in, innermost
+    /// first
+    fn loops(&self) -> Vec<usize> {
+        let program = self.runner.program();
+        let instruction_pointer = program.instruction_pointer;
+        let mut loops: Vec<usize> = program
+            .instructions
+            .iter()
+            .enumerate()
+            .take(instruction_pointer)
+            .filter_map(|(index, instruction)| match instruction {
+                BrainfuckInstruction::JumpForward(end) if instruction_pointer <= *end => {
+                    Some(index)
+                }
+                _ => None,
+            })
+            .collect();
+        loops.reverse();
+        loops
+    }
+}
+
+/// A Debug Adapter Protocol server, debugging a single program
+pub struct DapServer<R, W> {
+    reader: R,
+    writer: W,
+    seq: u64,
+    lines_start_at_1: bool,
+    columns_start_at_1: bool,
+    session: Option<Session>,
+    events: Vec<(&'static str, Value)>,
+}
+
+#[allow(deprecated)]
+impl<R, W> DapServer<R, W>
+where
+    R: BufRead,
+    W: Write,
+{
+    /// Create a server that reads requests from `reader`, and writes responses and
+    /// events to `writer`
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            reader,
+            writer,
+            seq: 0,
+            lines_start_at_1: true,
+            columns_start_at_1: true,
+            session: None,
+            events: Vec::new(),
+        }
+    }
+
+    /// Handle requests until the client disconnects or closes the connection
+    pub fn run(&mut self) -> io::Result<()> {
+        while let Some(request) = protocol::read_message(&mut self.reader)? {
+            let command = request["command"].as_str().unwrap_or_default().to_string();
+            let arguments = request.get("arguments").cloned().unwrap_or(Value::Null);
+
+            let result = self.handle(&command, &arguments);
+            let (success, body, message) = match result {
+                Ok(body) => (true, body, None),
+                Err(message) => (false, Value::Null, Some(message)),
+            };
+            let mut response = json!({
+                "type": "response",
+                "request_seq": request["seq"],
+                "success": success,
+                "command": command,
+            });
+            if !body.is_null() {
+                response["body"] = body;
+            }
+            if let Some(message) = message {
+                response["message"] = json!(message);
+            }
+            self.send(response)?;
+
+            for (event, body) in core::mem::take(&mut self.events) {
+                let mut message = json!({ "type": "event", "event": event });
+                if !body.is_null() {
+                    message["body"] = body;
+                }
+                self.send(message)?;
+            }
+
+            if command == "disconnect" {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle a single request, returning the body of its response
+    fn handle(&mut self, command: &str, arguments: &Value) -> Result<Value, String> {
+        match command {
+            "initialize" => {
+                self.lines_start_at_1 = arguments["linesStartAt1"].as_bool().unwrap_or(true);
+                self.columns_start_at_1 = arguments["columnsStartAt1"].as_bool().unwrap_or(true);
+                self.events.push(("initialized", Value::Null));
+                Ok(json!({
+                    "supportsConfigurationDoneRequest": true,
+                    "supportsStepBack": true,
+                }))
+            }
+            "launch" => {
+                self.launch(arguments)?;
+                Ok(Value::Null)
+            }
+            "setBreakpoints" => self.set_breakpoints(arguments),
+            "configurationDone" => {
+                if self.session_mut()?.stop_on_entry {
+                    self.stopped("entry");
+                } else {
+                    self.resume(|_| false, "breakpoint");
+                }
+                Ok(Value::Null)
+            }
+            "threads" => Ok(json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] })),
+            "stackTrace" => self.stack_trace(),
+            "scopes" => Ok(json!({
+                "scopes": [
+                    { "name": "Tape", "variablesReference": TAPE_REFERENCE, "expensive": false },
+                    {
+                        "name": "Registers",
+                        "variablesReference": REGISTERS_REFERENCE,
+                        "expensive": false,
+                    },
+                ]
+            })),
+            "variables" => self.variables(arguments["variablesReference"].as_u64()),
+            "continue" => {
+                self.session_mut()?;
+                self.resume(|_| false, "breakpoint");
+                Ok(json!({ "allThreadsContinued": true }))
+            }
+            "next" | "stepIn" => {
+                self.session_mut()?;
+                self.resume(|_| true, "step");
+                Ok(Value::Null)
+            }
+            "stepOut" => {
+                let end = match self.session_mut()?.loops().first() {
+                    Some(&start) => match self.session_mut()?.program.instructions[start] {
+                        BrainfuckInstruction::JumpForward(end) => Some(end),
+                        _ => None,
+                    },
+                    None => None,
+                };
+                // Outside of a loop, stepping out runs the program until it stops
+                self.resume(
+                    |program| end.is_some_and(|end| program.instruction_pointer > end),
+                    "step",
+                );
+                Ok(Value::Null)
+            }
+            "stepBack" => {
+                self.session_mut()?.runner.rewind(1);
+                self.stopped("step");
+                Ok(Value::Null)
+            }
+            "reverseContinue" => {
+                let session = self.session_mut()?;
+                while session.runner.rewind(1) == 1 {
+                    let instruction_pointer = session.runner.program().instruction_pointer;
+                    if session
+                        .runner
+                        .breakpoints()
+                        .any(|b| b == instruction_pointer)
+                    {
+                        break;
+                    }
+                }
+                self.stopped("breakpoint");
+                Ok(Value::Null)
+            }
+            "pause" => Ok(Value::Null),
+            "disconnect" | "terminate" => {
+                self.session = None;
+                Ok(Value::Null)
+            }
+            _ => Err(format!("Unsupported request `{}`", command)),
+        }
+    }
+
+    fn launch(&mut self, arguments: &Value) -> Result<(), String> {
+        let path = arguments["program"]
+            .as_str()
+            .ok_or("The launch request needs a `program`")?
+            .to_string();
+        let source = fs::read_to_string(&path)
+            .map_err(|error| format!("Could not read {}: {}", path, error))?;
+        let program = BrainfuckProgram::compile_with_source_map(&source, vec![0; TAPE_LEN])
+            .map_err(|error| error.to_string())?;
+
+        let input = match (arguments["input"].as_str(), arguments["inputFile"].as_str()) {
+            (Some(input), _) => input.as_bytes().to_vec(),
+            (None, Some(file)) => {
+                fs::read(file).map_err(|error| format!("Could not read {}: {}", file, error))?
+            }
+            (None, None) => Vec::new(),
+        };
+
+        self.session = Some(Session {
+            path,
+            runner: Runner::new(program.clone()),
+            program,
+            input,
+            input_read: 0,
+            output_written: 0,
+            stop_on_entry: arguments["stopOnEntry"].as_bool().unwrap_or(false),
+        });
+        Ok(())
+    }
+
+    fn set_breakpoints(&mut self, arguments: &Value) -> Result<Value, String> {
+        let line_base = self.lines_start_at_1 as usize;
+        let column_base = self.columns_start_at_1 as usize;
+        let Session {
+            program, runner, ..
+        } = self.session_mut()?;
+
+        let existing: Vec<usize> = runner.breakpoints().collect();
+        for breakpoint in existing {
+            runner.remove_breakpoint(breakpoint);
+        }
+
+        let source_map = program.source_map.as_deref().unwrap_or(&[]);
+        let requested = arguments["breakpoints"].as_array().map(Vec::as_slice);
+        let breakpoints: Vec<Value> = requested
+            .unwrap_or(&[])
+            .iter()
+            .map(|breakpoint| {
+                let line = breakpoint["line"].as_u64().unwrap_or(0) as usize;
+                let column = breakpoint["column"].as_u64().map(|c| c as usize);
+                // Source positions count lines from 0, and characters from 1
+                let line = line.saturating_sub(line_base);
+                let character = column.map_or(1, |c| c.saturating_sub(column_base) + 1);
+
+                let instruction = source_map
+                    .iter()
+                    .position(|pos| (pos.line, pos.character) >= (line, character));
+                match instruction {
+                    Some(instruction) => {
+                        runner.add_breakpoint(instruction);
+                        let pos = source_map[instruction];
+                        json!({
+                            "verified": true,
+                            "line": pos.line + line_base,
+                            "column": pos.character - 1 + column_base,
+                        })
+                    }
+                    None => json!({
+                        "verified": false,
+                        "message": "There are no instructions at or after this line",
+                    }),
+                }
+            })
+            .collect();
+
+        Ok(json!({ "breakpoints": breakpoints }))
+    }
+
+    fn stack_trace(&mut self) -> Result<Value, String> {
+        let line_base = self.lines_start_at_1 as usize;
+        let column_base = self.columns_start_at_1 as usize;
+        let session = self.session_mut()?;
+        let program = session.runner.program();
+
+        let frame = |id: usize, name: String, instruction: usize| {
+            let pos = program.source_of(instruction);
+            json!({
+                "id": id,
+                "name": name,
+                "source": { "path": session.path },
+                "line": pos.map_or(0, |pos| pos.line + line_base),
+                "column": pos.map_or(0, |pos| pos.character - 1 + column_base),
+            })
+        };
+
+        let current = program.instruction_pointer;
+        let name = match program.instructions.get(current) {
+            Some(instruction) => format!("{} (instruction {})", instruction.as_char(), current),
+            None => "finished".to_string(),
+        };
+        let mut frames = vec![frame(0, name, current)];
+        for (depth, start) in session.loops().into_iter().enumerate() {
+            frames.push(frame(
+                depth + 1,
+                format!("loop (instruction {})", start),
+                start,
+            ));
+        }
+
+        Ok(json!({ "stackFrames": frames, "totalFrames": frames.len() }))
+    }
+
+    fn variables(&mut self, reference: Option<u64>) -> Result<Value, String> {
+        let session = self.session_mut()?;
+        let data_pointer = session.runner.program().data_pointer;
+        let steps = session.runner.steps();
+
+        let variables = match reference {
+            Some(TAPE_REFERENCE) => {
+                let start = data_pointer.saturating_sub(TAPE_WINDOW);
+                let end = data_pointer + TAPE_WINDOW + 1;
+                let tape = &session.runner.program().tape;
+                (start..end.min(tape.len()))
+                    .map(|index| {
+                        let value = tape[index];
+                        let name = if index == data_pointer {
+                            format!("[{}] (pointer)", index)
+                        } else {
+                            format!("[{}]", index)
+                        };
+                        let printable = value.is_ascii_graphic() || value == b' ';
+                        let value = if printable {
+                            format!("{} (0x{:02x}, '{}')", value, value, value as char)
+                        } else {
+                            format!("{} (0x{:02x})", value, value)
+                        };
+                        json!({ "name": name, "value": value, "variablesReference": 0 })
+                    })
+                    .collect()
+            }
+            Some(REGISTERS_REFERENCE) => {
+                let program = session.runner.program();
+                vec![
+                    json!({
+                        "name": "data pointer",
+                        "value": data_pointer.to_string(),
+                        "variablesReference": 0,
+                    }),
+                    json!({
+                        "name": "instruction pointer",
+                        "value": program.instruction_pointer.to_string(),
+                        "variablesReference": 0,
+                    }),
+                    json!({ "name": "steps", "value": steps.to_string(), "variablesReference": 0 }),
+                ]
+            }
+            _ => return Err("Unknown variables reference".to_string()),
+        };
+
+        Ok(json!({ "variables": variables }))
+    }
+
+    fn session_mut(&mut self) -> Result<&mut Session, String> {
+        self.session
+            .as_mut()
+            .ok_or_else(|| "No program has been launched".to_string())
+    }
+
+    /// Run the program until it stops, or `stop` returns true, and queue the events
+    /// that report why it stopped
+    fn resume<F>(&mut self, stop: F, reason: &'static str)
+    where
+        F: FnMut(&BrainfuckProgram<Vec<u8>>) -> bool,
+    {
+        let stopped = match self.session.as_mut() {
+            Some(session) => session.run_until(stop),
+            None => return,
+        };
+        match stopped {
+            StopReason::Breakpoint(instruction) => {
+                let breakpoint = self
+                    .session
+                    .as_ref()
+                    .is_some_and(|s| s.runner.breakpoints().any(|b| b == instruction));
+                self.stopped(if breakpoint { "breakpoint" } else { reason });
+            }
+            StopReason::Halted => {
+                self.queue_output();
+                self.events.push(("exited", json!({ "exitCode": 0 })));
+                self.events.push(("terminated", Value::Null));
+            }
+        }
+    }
+
+    /// Queue the new output of the program and a `stopped` event
+    fn stopped(&mut self, reason: &str) {
+        self.queue_output();
+        self.events.push((
+            "stopped",
+            json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }),
+        ));
+    }
+
+    fn queue_output(&mut self) {
+        let session = match self.session.as_mut() {
+            Some(session) => session,
+            None => return,
+        };
+        // Rewinding discards the output of the undone steps
+        let output = session.runner.output();
+        session.output_written = session.output_written.min(output.len());
+        let new = &output[session.output_written..];
+        if !new.is_empty() {
+            let text = String::from_utf8_lossy(new).into_owned();
+            session.output_written = output.len();
+            self.events
+                .push(("output", json!({ "category": "stdout", "output": text })));
+        }
+    }
+
+    fn send(&mut self, mut message: Value) -> io::Result<()> {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+        protocol::write_message(&mut self.writer, &message)
+    }
+}
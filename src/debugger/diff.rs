@@ -0,0 +1,156 @@
+//! Comparing the state of a program at two points in its execution
+
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use crate::{Tape, TapeData};
+
+/// The state of a program after a certain amount of steps, as taken by
+/// [`Runner::snapshot`](super::Runner::snapshot)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Snapshot<D> {
+    /// The amount of steps that had been executed
+    pub step: usize,
+    /// The instruction pointer
+    pub instruction_pointer: usize,
+    /// The data pointer
+    pub data_pointer: usize,
+    /// The cells of the tape, up to its [`len_hint`](Tape::len_hint)
+    pub cells: Vec<D>,
+}
+
+impl<D> Snapshot<D>
+where
+    D: TapeData,
+{
+    /// Take a snapshot of a program with the given state
+    pub(crate) fn new<T>(
+        step: usize,
+        instruction_pointer: usize,
+        data_pointer: usize,
+        tape: &mut T,
+    ) -> Self
+    where
+        T: Tape<Data = D>,
+    {
+        let len = tape.len_hint().unwrap_or(0);
+        let cells = (0..len)
+            .map_while(|index| tape.get_data_at(index).cloned())
+            .collect();
+        Self {
+            step,
+            instruction_pointer,
+            data_pointer,
+            cells,
+        }
+    }
+}
+
+/// A cell whose value differs between two snapshots
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CellChange<D> {
+    /// The index of the cell
+    pub index: usize,
+    /// The value of the cell in the first snapshot
+    pub before: D,
+    /// The value of the cell in the second snapshot
+    pub after: D,
+}
+
+/// The differences between two snapshots, as returned by [`diff_state`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StateDiff<D> {
+    /// The step of the first snapshot
+    pub from_step: usize,
+    /// The step of the second snapshot
+    pub to_step: usize,
+    /// The instruction pointer in the first and second snapshot
+    pub instruction_pointer: (usize, usize),
+    /// The data pointer in the first and second snapshot
+    pub data_pointer: (usize, usize),
+    /// The cells whose value differs, ordered by index
+    pub cells: Vec<CellChange<D>>,
+}
+
+/// Find the cells that differ between `before` and `after`
+///
+/// Cells that are missing from one of the snapshots, because its tape was shorter, are
+/// considered to be zero.
+///
+/// ```
+/// # use brainfuck_interpreter::{debugger::{diff_state, Runner}, BrainfuckProgram};
+/// let program = BrainfuckProgram::compile("+++[>++<-]", vec![0u8; 2]).unwrap();
+/// let mut runner = Runner::new(program);
+///
+/// let before = runner.snapshot();
+/// runner.run(&mut || 0);
+/// let after = runner.snapshot();
+///
+/// let diff = diff_state(&before, &after);
+/// assert_eq!(diff.cells.len(), 1);
+/// assert_eq!(runner.diff_steps(0, runner.steps()), Some(diff.clone()));
+/// assert_eq!((diff.cells[0].index, diff.cells[0].after), (1, 6));
+/// assert_eq!(
+///     diff.to_string(),
+///     "step 0 -> step 22\n\
+///      instruction pointer: 0 -> 10\n\
+///      data pointer: 0 -> 0\n\
+///      cell 1: 00 -> 06\n"
+/// );
+/// ```
+pub fn diff_state<D>(before: &Snapshot<D>, after: &Snapshot<D>) -> StateDiff<D>
+where
+    D: TapeData,
+{
+    let len = before.cells.len().max(after.cells.len());
+    let cell = |snapshot: &Snapshot<D>, index: usize| {
+        snapshot.cells.get(index).cloned().unwrap_or_else(D::zero)
+    };
+
+    let cells = (0..len)
+        .filter_map(|index| {
+            let (before, after) = (cell(before, index), cell(after, index));
+            (before != after).then_some(CellChange {
+                index,
+                before,
+                after,
+            })
+        })
+        .collect();
+
+    StateDiff {
+        from_step: before.step,
+        to_step: after.step,
+        instruction_pointer: (before.instruction_pointer, after.instruction_pointer),
+        data_pointer: (before.data_pointer, after.data_pointer),
+        cells,
+    }
+}
+
+impl Display for StateDiff<u8> {
+    /// Formats the pointers and every changed cell on its own line, with the cells in hex
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "step {} -> step {}", self.from_step, self.to_step)?;
+        writeln!(
+            f,
+            "instruction pointer: {} -> {}",
+            self.instruction_pointer.0, self.instruction_pointer.1
+        )?;
+        writeln!(
+            f,
+            "data pointer: {} -> {}",
+            self.data_pointer.0, self.data_pointer.1
+        )?;
+        if self.cells.is_empty() {
+            return writeln!(f, "no cells changed");
+        }
+        for cell in &self.cells {
+            writeln!(
+                f,
+                "cell {}: {:02x} -> {:02x}",
+                cell.index, cell.before, cell.after
+            )?;
+        }
+        Ok(())
+    }
+}
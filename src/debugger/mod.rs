@@ -0,0 +1,468 @@
+//! Stepping through Brainfuck programs, forwards and backwards
+//!
+//! A [`Runner`] executes a program one instruction at a time, and periodically takes
+//! a checkpoint of its state. To step backwards, the runner restores the closest
+//! checkpoint before the target and executes the remaining instructions again, feeding
+//! the program the same input it read the first time.
+//!
+//! The runner can stop at breakpoints, and every time it stops it evaluates its
+//! [`Watch`]es, highlighting the cells that changed since the previous stop. To see
+//! everything that changed between two steps, compare two [`Snapshot`]s with
+//! [`diff_state`].
+//!
+//! ```
+//! use brainfuck_interpreter::{debugger::Runner, io::SliceInput, BrainfuckProgram};
+//!
+//! let program = BrainfuckProgram::compile(",[.-]", vec![0u8; 1]).unwrap();
+//! let mut runner = Runner::new(program);
+//! runner.run(&mut SliceInput::new(&[3]));
+//! assert_eq!(runner.output(), [3, 2, 1]);
+//!
+//! // Go back to just after the first `.`
+//! runner.rewind(runner.steps() - 3);
+//! assert_eq!(runner.output(), [3]);
+//! assert_eq!(runner.program().tape[0], 3);
+//! ```
+
+use alloc::{
+    collections::{BTreeSet, VecDeque},
+    vec::Vec,
+};
+use core::{mem::size_of, ops::RangeBounds};
+
+use crate::{io::Input, BrainfuckProgram, Tape, TapeData};
+
+#[cfg(feature = "cli-debugger")]
+pub mod cli;
+
+#[cfg(feature = "dap")]
+pub mod dap;
+
+mod diff;
+mod watch;
+
+pub use diff::*;
+pub use watch::*;
+
+/// How often a [`Runner`] takes checkpoints, and how many it keeps
+#[derive(Clone, Debug)]
+pub struct CheckpointConfig {
+    /// The amount of instructions executed between two checkpoints
+    ///
+    /// Rewinding executes up to this many instructions again, so a smaller interval
+    /// makes rewinding faster at the cost of memory.
+    pub interval: usize,
+    /// The estimated amount of memory, in bytes, that checkpoints may use
+    ///
+    /// Once checkpoints use more than this, the oldest ones are discarded. The program
+    /// cannot be rewound to before the oldest remaining checkpoint.
+    pub memory_budget: usize,
+}
+
+impl Default for CheckpointConfig {
+    fn default() -> Self {
+        Self {
+            interval: 1 << 10,
+            memory_budget: 64 << 20,
+        }
+    }
+}
+
+/// The reason that [`Runner::resume`] returned
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    /// The program has finished
+    Halted,
+    /// The instruction at the given index has a breakpoint, and is about to be executed
+    Breakpoint(usize),
+}
+
+/// The state of a program after a certain amount of steps
+#[derive(Clone, Debug)]
+struct Checkpoint<T>
+where
+    T: Tape,
+{
+    step: usize,
+    instruction_pointer: usize,
+    data_pointer: usize,
+    execution_count: usize,
+    tape: T,
+    procedures: Vec<(T::Data, usize)>,
+    call_stack: Vec<usize>,
+    stack: Vec<T::Data>,
+    input_read: usize,
+    output_len: usize,
+}
+
+impl<T> Checkpoint<T>
+where
+    T: Tape,
+{
+    /// An estimate of the amount of memory used by this checkpoint
+    fn size(&self) -> usize {
+        size_of::<Self>()
+            + self.tape.len_hint().unwrap_or(0) * size_of::<T::Data>()
+            + self.procedures.len() * size_of::<(T::Data, usize)>()
+            + self.call_stack.len() * size_of::<usize>()
+            + self.stack.len() * size_of::<T::Data>()
+    }
+}
+
+/// Runs a Brainfuck program step by step, and can step backwards
+///
+/// Checkpoints contain a clone of the tape, so the tape must implement [`Clone`].
+#[derive(Clone, Debug)]
+pub struct Runner<T>
+where
+    T: Tape,
+{
+    program: BrainfuckProgram<T>,
+    config: CheckpointConfig,
+    steps: usize,
+    input: Vec<T::Data>,
+    input_read: usize,
+    output: Vec<T::Data>,
+    checkpoints: VecDeque<Checkpoint<T>>,
+    checkpoint_memory: usize,
+    breakpoints: BTreeSet<usize>,
+    watches: Vec<Watch>,
+    reports: Vec<WatchReport<T::Data>>,
+}
+
+#[allow(deprecated)]
+impl<T> Runner<T>
+where
+    T: Tape + Clone,
+{
+    /// Create a runner for `program` that takes checkpoints as configured by
+    /// [`CheckpointConfig::default`]
+    pub fn new(program: BrainfuckProgram<T>) -> Self {
+        Self::with_config(program, CheckpointConfig::default())
+    }
+
+    /// Create a runner for `program` that takes checkpoints as configured by `config`
+    pub fn with_config(program: BrainfuckProgram<T>, config: CheckpointConfig) -> Self {
+        let mut runner = Self {
+            program,
+            config,
+            steps: 0,
+            input: Vec::new(),
+            input_read: 0,
+            output: Vec::new(),
+            checkpoints: VecDeque::new(),
+            checkpoint_memory: 0,
+            breakpoints: BTreeSet::new(),
+            watches: Vec::new(),
+            reports: Vec::new(),
+        };
+        runner.checkpoint();
+        runner
+    }
+
+    /// The program being run
+    pub fn program(&self) -> &BrainfuckProgram<T> {
+        &self.program
+    }
+
+    /// Consume this runner, returning the program
+    pub fn into_program(self) -> BrainfuckProgram<T> {
+        self.program
+    }
+
+    /// The amount of steps that have been executed since the runner was created
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Everything the program has output so far
+    pub fn output(&self) -> &[T::Data] {
+        &self.output
+    }
+
+    /// The amount of checkpoints that are currently kept
+    pub fn checkpoints(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    /// The estimated amount of memory, in bytes, used by the kept checkpoints
+    pub fn checkpoint_memory(&self) -> usize {
+        self.checkpoint_memory
+    }
+
+    /// The earliest step that the program can be rewound to
+    pub fn earliest_step(&self) -> usize {
+        self.checkpoints
+            .front()
+            .map(|c| c.step)
+            .unwrap_or(self.steps)
+    }
+
+    /// Stop before executing the instruction at `instruction`, returning `false` if
+    /// there already was a breakpoint there
+    pub fn add_breakpoint(&mut self, instruction: usize) -> bool {
+        self.breakpoints.insert(instruction)
+    }
+
+    /// Remove the breakpoint at `instruction`, returning `false` if there was none
+    pub fn remove_breakpoint(&mut self, instruction: usize) -> bool {
+        self.breakpoints.remove(&instruction)
+    }
+
+    /// The instructions that have a breakpoint, in order
+    pub fn breakpoints(&self) -> impl Iterator<Item = usize> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    /// Start watching `watch`, and report its current value
+    pub fn add_watch(&mut self, watch: Watch) -> &WatchReport<T::Data> {
+        let values = watch.evaluate(self.program.data_pointer, &mut self.program.tape);
+        self.watches.push(watch);
+        self.reports.push(WatchReport::new(watch, values, None));
+        &self.reports[self.reports.len() - 1]
+    }
+
+    /// Stop watching the watch at `index` in [`watches`](Self::watches)
+    pub fn remove_watch(&mut self, index: usize) -> Option<Watch> {
+        if index < self.watches.len() {
+            self.reports.remove(index);
+            Some(self.watches.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// The watches that are reported every time the runner stops
+    pub fn watches(&self) -> &[Watch] {
+        &self.watches
+    }
+
+    /// The value of every watch the last time the runner stopped, in the same order as
+    /// [`watches`](Self::watches)
+    pub fn watch_reports(&self) -> &[WatchReport<T::Data>] {
+        &self.reports
+    }
+
+    /// Execute a single instruction, returning `false` if the program has finished
+    ///
+    /// After a [`rewind`](Self::rewind), the program reads the input that it read the
+    /// first time it executed these steps, before reading from `input` again.
+    pub fn step<FnIn>(&mut self, input: &mut FnIn) -> bool
+    where
+        FnIn: Input<T::Data>,
+    {
+        let stepped = self.step_once(input);
+        self.evaluate_watches();
+        stepped
+    }
+
+    /// Run the program until it reaches a breakpoint or finishes
+    ///
+    /// At least one instruction is executed, so that calling this function again
+    /// continues past the breakpoint it stopped at.
+    pub fn resume<FnIn>(&mut self, input: &mut FnIn) -> StopReason
+    where
+        FnIn: Input<T::Data>,
+    {
+        let mut reason = StopReason::Halted;
+        while self.step_once(input) {
+            let instruction_pointer = self.program.instruction_pointer;
+            if self.breakpoints.contains(&instruction_pointer) {
+                reason = StopReason::Breakpoint(instruction_pointer);
+                break;
+            }
+        }
+        self.evaluate_watches();
+        reason
+    }
+
+    /// Execute a single instruction without evaluating the watches
+    fn step_once<FnIn>(&mut self, input: &mut FnIn) -> bool
+    where
+        FnIn: Input<T::Data>,
+    {
+        let Self {
+            program,
+            input: recorded,
+            input_read,
+            output,
+            ..
+        } = self;
+
+        let stepped = program.step(&mut |data| output.push(data), &mut || {
+            let data = match recorded.get(*input_read) {
+                Some(data) => data.clone(),
+                None => {
+                    let data = input.input();
+                    recorded.push(data.clone());
+                    data
+                }
+            };
+            *input_read += 1;
+            data
+        });
+
+        if stepped {
+            self.steps += 1;
+            if self.steps.is_multiple_of(self.config.interval.max(1)) {
+                self.checkpoint();
+            }
+        }
+        stepped
+    }
+
+    /// Run the program until it finishes, ignoring breakpoints
+    pub fn run<FnIn>(&mut self, input: &mut FnIn)
+    where
+        FnIn: Input<T::Data>,
+    {
+        while self.step_once(input) {}
+        self.evaluate_watches();
+    }
+
+    /// Undo the last `steps` steps, returning the amount of steps that were undone
+    ///
+    /// Fewer steps are undone if that would go back further than
+    /// [`earliest_step`](Self::earliest_step).
+    pub fn rewind(&mut self, steps: usize) -> usize {
+        let target = self.steps.saturating_sub(steps).max(self.earliest_step());
+
+        while self.checkpoints.len() > 1 && self.checkpoints.back().is_some_and(|c| c.step > target)
+        {
+            self.pop_checkpoint_back();
+        }
+        let checkpoint = match self.checkpoints.back() {
+            Some(checkpoint) => checkpoint.clone(),
+            None => return 0,
+        };
+
+        let rewound = self.steps - target;
+        self.restore(checkpoint);
+
+        // Every step up to `target` has been executed before, so all input that these
+        // steps read has been recorded
+        let Self {
+            program,
+            input,
+            input_read,
+            output,
+            ..
+        } = self;
+        for _ in self.steps..target {
+            program.step(&mut |data| output.push(data), &mut || {
+                let data = input[*input_read].clone();
+                *input_read += 1;
+                data
+            });
+        }
+        self.steps = target;
+        self.evaluate_watches();
+
+        rewound
+    }
+
+    /// Take a snapshot of the current state of the program
+    pub fn snapshot(&mut self) -> Snapshot<T::Data> {
+        Snapshot::new(
+            self.steps,
+            self.program.instruction_pointer,
+            self.program.data_pointer,
+            &mut self.program.tape,
+        )
+    }
+
+    /// Take a snapshot of the state of the program after `step` steps, or return `None`
+    /// if `step` has not been executed yet or lies before
+    /// [`earliest_step`](Self::earliest_step)
+    ///
+    /// The program is rewound to `step`, and then executes the steps after it again,
+    /// so that it ends up in the same state as before.
+    pub fn snapshot_at(&mut self, step: usize) -> Option<Snapshot<T::Data>> {
+        if step > self.steps || step < self.earliest_step() {
+            return None;
+        }
+
+        let current = self.steps;
+        let reports = self.reports.clone();
+        self.rewind(current - step);
+        let snapshot = self.snapshot();
+
+        // Every step up to `current` has been executed before, so no new input is read
+        while self.steps < current && self.step_once(&mut T::Data::zero) {}
+        self.reports = reports;
+
+        Some(snapshot)
+    }
+
+    /// Compare the state of the program after `from` steps with its state after `to`
+    /// steps, or return `None` if either of them cannot be
+    /// [snapshotted](Self::snapshot_at)
+    pub fn diff_steps(&mut self, from: usize, to: usize) -> Option<StateDiff<T::Data>> {
+        let before = self.snapshot_at(from)?;
+        let after = self.snapshot_at(to)?;
+        Some(diff_state(&before, &after))
+    }
+
+    fn evaluate_watches(&mut self) {
+        for (watch, report) in self.watches.iter().zip(self.reports.iter_mut()) {
+            let values = watch.evaluate(self.program.data_pointer, &mut self.program.tape);
+            *report = WatchReport::new(*watch, values, Some(report));
+        }
+    }
+
+    fn restore(&mut self, checkpoint: Checkpoint<T>) {
+        self.steps = checkpoint.step;
+        self.program.instruction_pointer = checkpoint.instruction_pointer;
+        self.program.data_pointer = checkpoint.data_pointer;
+        self.program.execution_count = checkpoint.execution_count;
+        self.program.tape = checkpoint.tape;
+        self.program.procedures = checkpoint.procedures;
+        self.program.call_stack = checkpoint.call_stack;
+        self.program.stack = checkpoint.stack;
+        self.input_read = checkpoint.input_read;
+        self.output.truncate(checkpoint.output_len);
+    }
+
+    fn checkpoint(&mut self) {
+        let checkpoint = Checkpoint {
+            step: self.steps,
+            instruction_pointer: self.program.instruction_pointer,
+            data_pointer: self.program.data_pointer,
+            execution_count: self.program.execution_count,
+            tape: self.program.tape.clone(),
+            procedures: self.program.procedures.clone(),
+            call_stack: self.program.call_stack.clone(),
+            stack: self.program.stack.clone(),
+            input_read: self.input_read,
+            output_len: self.output.len(),
+        };
+        self.checkpoint_memory += checkpoint.size();
+        self.checkpoints.push_back(checkpoint);
+
+        // Always keep the newest checkpoint, so that the most recent steps can be undone
+        while self.checkpoint_memory > self.config.memory_budget && self.checkpoints.len() > 1 {
+            if let Some(oldest) = self.checkpoints.pop_front() {
+                self.checkpoint_memory -= oldest.size();
+            }
+        }
+    }
+
+    fn pop_checkpoint_back(&mut self) {
+        if let Some(newest) = self.checkpoints.pop_back() {
+            self.checkpoint_memory -= newest.size();
+        }
+    }
+}
+
+impl<T> Runner<T>
+where
+    T: Tape<Data = u8> + Clone,
+{
+    /// Format the cells in `range` as a hex dump, as done by [`BrainfuckProgram::dump`]
+    pub fn dump<R>(&mut self, range: R) -> alloc::string::String
+    where
+        R: RangeBounds<usize>,
+    {
+        self.program.dump(range)
+    }
+}
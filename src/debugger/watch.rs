@@ -0,0 +1,245 @@
+//! Watching cells of the tape while stepping through a program
+
+use alloc::vec::Vec;
+use core::{fmt::Display, str::FromStr};
+
+use crate::Tape;
+
+/// A position on the tape
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CellPosition {
+    /// The cell at a fixed index
+    Absolute(usize),
+    /// The cell at an offset from the data pointer
+    Pointer(isize),
+}
+
+impl CellPosition {
+    /// The index of the cell at this position, if it is not before the start of the tape
+    pub fn resolve(&self, data_pointer: usize) -> Option<usize> {
+        match *self {
+            CellPosition::Absolute(index) => Some(index),
+            CellPosition::Pointer(offset) => data_pointer.checked_add_signed(offset),
+        }
+    }
+
+    /// The position `cells` further along the tape
+    fn offset(&self, cells: usize) -> Self {
+        match *self {
+            CellPosition::Absolute(index) => CellPosition::Absolute(index.saturating_add(cells)),
+            CellPosition::Pointer(offset) => {
+                CellPosition::Pointer(offset.saturating_add_unsigned(cells))
+            }
+        }
+    }
+}
+
+impl Display for CellPosition {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match *self {
+            CellPosition::Absolute(index) => write!(f, "{}", index),
+            CellPosition::Pointer(0) => f.write_str("pointer"),
+            CellPosition::Pointer(offset) if offset < 0 => write!(f, "pointer{}", offset),
+            CellPosition::Pointer(offset) => write!(f, "pointer+{}", offset),
+        }
+    }
+}
+
+impl FromStr for CellPosition {
+    type Err = ParseWatchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let offset = match s.strip_prefix("pointer") {
+            Some(offset) => offset.trim(),
+            None => {
+                return s
+                    .parse()
+                    .map(CellPosition::Absolute)
+                    .map_err(|_| ParseWatchError)
+            }
+        };
+
+        let offset = if offset.is_empty() {
+            0
+        } else if let Some(offset) = offset.strip_prefix('+') {
+            offset.trim().parse().map_err(|_| ParseWatchError)?
+        } else if let Some(offset) = offset.strip_prefix('-') {
+            let offset: isize = offset.trim().parse().map_err(|_| ParseWatchError)?;
+            -offset
+        } else {
+            return Err(ParseWatchError);
+        };
+        Ok(CellPosition::Pointer(offset))
+    }
+}
+
+/// A range of cells that is reported every time a [`Runner`](super::Runner) stops
+///
+/// Watches can be parsed from expressions like `cell 5`, `cells 10..20`,
+/// `cell at pointer-1` or `cells pointer-2..pointer+2`. The end of a range is exclusive.
+///
+/// ```
+/// # use brainfuck_interpreter::debugger::{CellPosition, Watch};
+/// let watch: Watch = "cell at pointer-1".parse().unwrap();
+/// assert_eq!(watch, Watch::cell(CellPosition::Pointer(-1)));
+/// assert_eq!(watch.to_string(), "cell pointer-1");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Watch {
+    /// The first cell that is watched
+    pub start: CellPosition,
+    /// The position after the last cell that is watched
+    pub end: CellPosition,
+}
+
+impl Watch {
+    /// Watch the single cell at `position`
+    pub fn cell(position: CellPosition) -> Self {
+        Self {
+            start: position,
+            end: position.offset(1),
+        }
+    }
+
+    /// Watch the cells from `start` up to, but not including, `end`
+    pub fn cells(start: CellPosition, end: CellPosition) -> Self {
+        Self { start, end }
+    }
+
+    /// Read the watched cells from `tape`, without growing it
+    pub(crate) fn evaluate<T>(&self, data_pointer: usize, tape: &mut T) -> Vec<(usize, T::Data)>
+    where
+        T: Tape,
+    {
+        let start = self.start.resolve(data_pointer).unwrap_or(0);
+        let mut end = self.end.resolve(data_pointer).unwrap_or(0);
+        if let Some(len) = tape.len_hint() {
+            end = end.min(len);
+        }
+
+        (start..end)
+            .map_while(|index| Some((index, tape.get_data_at(index)?.clone())))
+            .collect()
+    }
+}
+
+impl Display for Watch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.end == self.start.offset(1) {
+            write!(f, "cell {}", self.start)
+        } else {
+            write!(f, "cells {}..{}", self.start, self.end)
+        }
+    }
+}
+
+impl FromStr for Watch {
+    type Err = ParseWatchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let range = s
+            .strip_prefix("cells")
+            .or_else(|| s.strip_prefix("cell"))
+            .ok_or(ParseWatchError)?
+            .trim_start();
+        let range = range.strip_prefix("at ").unwrap_or(range);
+
+        match range.split_once("..") {
+            Some((start, end)) => Ok(Watch::cells(start.parse()?, end.parse()?)),
+            None => Ok(Watch::cell(range.parse()?)),
+        }
+    }
+}
+
+/// An error returned when parsing a [`Watch`] or [`CellPosition`] fails
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseWatchError;
+
+impl Display for ParseWatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(
+            "invalid watch expression, expected e.g. `cell 5` or `cells pointer-2..pointer+2`",
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseWatchError {}
+
+/// A single cell in a [`WatchReport`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WatchedCell<D> {
+    /// The index of the cell
+    pub index: usize,
+    /// The value of the cell
+    pub value: D,
+    /// Whether the value is different from the previous report of the same watch
+    pub changed: bool,
+}
+
+/// The value of a [`Watch`] at the moment a [`Runner`](super::Runner) stopped
+///
+/// Cells that lie outside of the tape are left out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WatchReport<D> {
+    /// The watch that was evaluated
+    pub watch: Watch,
+    /// The watched cells
+    pub cells: Vec<WatchedCell<D>>,
+}
+
+impl<D> WatchReport<D>
+where
+    D: PartialEq + Clone,
+{
+    /// Create a report of `values`, marking the cells whose value differs from the same
+    /// cell in `previous`
+    pub(crate) fn new(
+        watch: Watch,
+        values: Vec<(usize, D)>,
+        previous: Option<&WatchReport<D>>,
+    ) -> Self {
+        let previous = previous
+            .map(|report| report.cells.as_slice())
+            .unwrap_or(&[]);
+        let cells = values
+            .into_iter()
+            .map(|(index, value)| {
+                let changed = previous
+                    .iter()
+                    .find(|cell| cell.index == index)
+                    .is_some_and(|cell| cell.value != value);
+                WatchedCell {
+                    index,
+                    value,
+                    changed,
+                }
+            })
+            .collect();
+        Self { watch, cells }
+    }
+
+    /// Whether any of the watched cells has changed since the previous report
+    pub fn changed(&self) -> bool {
+        self.cells.iter().any(|cell| cell.changed)
+    }
+}
+
+impl Display for WatchReport<u8> {
+    /// Formats the cells in hex, marking changed cells with a `*`
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} =", self.watch)?;
+        if self.cells.is_empty() {
+            return f.write_str(" (outside of the tape)");
+        }
+        for cell in &self.cells {
+            write!(f, " {:02x}", cell.value)?;
+            if cell.changed {
+                f.write_str("*")?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+//! Rendering messages together with the source code they are about
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::{Display, Write};
+
+use crate::{write_padding, Error, SourcePos};
+
+/// A message about a single character of the source code of a [`Diagnostic`], which is
+/// shown below it
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Label {
+    /// The position of the character
+    pub position: SourcePos,
+    /// The message, which may be empty
+    pub message: String,
+}
+
+/// A message about a piece of source code, rendered with the lines that it refers to
+///
+/// Every labelled line is shown with its line number in a gutter, followed by a caret
+/// below every labelled character and its message. Line numbers start at 1, like they
+/// do in text editors, even though the lines of a [`SourcePos`] start at 0. Lines
+/// around the labelled lines can be shown as well, and lines that are skipped are
+/// replaced with `...`.
+///
+/// ```
+/// # use brainfuck_interpreter::{Diagnostic, SourcePos};
+/// let source = "+++\n[>+\n<-]\n>.";
+/// let diagnostic = Diagnostic::new(source, "Moves a value")
+///     .with_label(SourcePos { line: 1, character: 1 }, "starts here")
+///     .with_label(SourcePos { line: 2, character: 3 }, "ends here")
+///     .with_context(1, 0);
+/// assert_eq!(
+///     diagnostic.to_string(),
+///     "Moves a value\n  |\n1 | +++\n2 | [>+\n  | ^ starts here\n3 | <-]\n  |   ^ ends here",
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Diagnostic<'a> {
+    source: &'a str,
+    message: String,
+    labels: Vec<Label>,
+    context_before: usize,
+    context_after: usize,
+}
+
+impl<'a> Diagnostic<'a> {
+    /// A diagnostic about `source` with `message`, without labels or context lines
+    pub fn new<M>(source: &'a str, message: M) -> Self
+    where
+        M: Into<String>,
+    {
+        Self {
+            source,
+            message: message.into(),
+            labels: Vec::new(),
+            context_before: 0,
+            context_after: 0,
+        }
+    }
+
+    /// Label the character at `position` with `message`
+    ///
+    /// Labels for positions that are not in the source code are not shown.
+    pub fn with_label<M>(mut self, position: SourcePos, message: M) -> Self
+    where
+        M: Into<String>,
+    {
+        self.labels.push(Label {
+            position,
+            message: message.into(),
+        });
+        self
+    }
+
+    /// Show `before` lines before and `after` lines after every labelled line
+    pub fn with_context(mut self, before: usize, after: usize) -> Self {
+        self.context_before = before;
+        self.context_after = after;
+        self
+    }
+
+    /// The source code that this diagnostic is about
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// The message of this diagnostic
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The labels of this diagnostic, in the order in which they were added
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+}
+
+/// The amount of digits of `number`
+fn digits(number: usize) -> usize {
+    let mut digits = 1;
+    let mut number = number / 10;
+    while number > 0 {
+        digits += 1;
+        number /= 10;
+    }
+    digits
+}
+
+impl Display for Diagnostic<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.message)?;
+
+        let lines: Vec<&str> = self.source.lines().collect();
+        let mut labels: Vec<&Label> = self
+            .labels
+            .iter()
+            .filter(|label| label.position.line < lines.len())
+            .collect();
+        labels.sort_by_key(|label| label.position);
+
+        // The first and last line of every piece of the source code that is shown,
+        // merged where they overlap or touch
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for label in &labels {
+            let line = label.position.line;
+            let start = line.saturating_sub(self.context_before);
+            let end = (line + self.context_after).min(lines.len() - 1);
+            match ranges.last_mut() {
+                Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+                _ => ranges.push((start, end)),
+            }
+        }
+
+        let width = match ranges.last() {
+            Some((_, end)) => digits(end + 1),
+            None => return Ok(()),
+        };
+        write!(f, "\n{:width$} |", "", width = width)?;
+        for (index, (start, end)) in ranges.into_iter().enumerate() {
+            if index > 0 {
+                f.write_str("\n...")?;
+            }
+            for (line, text) in lines.iter().enumerate().take(end + 1).skip(start) {
+                write!(f, "\n{:>width$} |", line + 1, width = width)?;
+                if !text.is_empty() {
+                    write!(f, " {}", text)?;
+                }
+                for label in labels.iter().filter(|label| label.position.line == line) {
+                    write!(f, "\n{:width$} | ", "", width = width)?;
+                    write_padding(f, text, label.position.character)?;
+                    f.write_char('^')?;
+                    if !label.message.is_empty() {
+                        write!(f, " {}", label.message)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Error<'a> {
+    /// Render this error as a [`Diagnostic`], which labels the unmatched bracket
+    ///
+    /// A `[` without a `]` is labelled where it is opened, and where the source code
+    /// ends without closing it.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::BrainfuckProgram;
+    /// let error = BrainfuckProgram::compile("+[>+\n<-", vec![0u8; 2]).unwrap_err();
+    /// assert_eq!(
+    ///     error.diagnostic().to_string(),
+    ///     concat!(
+    ///         "Missing closing brace\n",
+    ///         "  |\n",
+    ///         "1 | +[>+\n",
+    ///         "  |  ^ opened here\n",
+    ///         "2 | <-\n",
+    ///         "  |   ^ never closed",
+    ///     ),
+    /// );
+    /// ```
+    pub fn diagnostic(&self) -> Diagnostic<'a> {
+        match self {
+            Error::MissingClosingBrace(span) => {
+                let last = span.text.lines().count().saturating_sub(1);
+                let end = SourcePos {
+                    line: last,
+                    character: span
+                        .text
+                        .lines()
+                        .nth(last)
+                        .map_or(0, |line| line.chars().count())
+                        + 1,
+                };
+                Diagnostic::new(span.text, "Missing closing brace")
+                    .with_label(span.get_position(), "opened here")
+                    .with_label(end, "never closed")
+            }
+            Error::MissingOpeningBrace(span) => Diagnostic::new(span.text, "Missing opening brace")
+                .with_label(span.get_position(), "closed here, but never opened"),
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Languages that are trivial substitutions of Brainfuck
+
+use crate::{
+    brainfuck_instruction, parse_chars, BrainfuckInstruction, BrainfuckLexer, HostedLexer, Lexer,
+    MultiTapeLexer, OokLexer, PBrainLexer, SourcePos, Span, StackLexer, TwoDLexer,
+};
+
+/// A language whose programs can be compiled to Brainfuck instructions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Dialect {
+    /// Plain Brainfuck, as parsed by [`parse`](crate::parse)
+    #[default]
+    Brainfuck,
+    /// [Ook!](https://esolangs.org/wiki/Ook!), as parsed by [`parse_ook`]
+    Ook,
+    /// [pbrain](https://esolangs.org/wiki/Pbrain), Brainfuck with procedures,
+    /// as parsed by [`parse_pbrain`]
+    PBrain,
+    /// Brainfuck with `%`, which calls into the application that runs the program,
+    /// as parsed by [`parse_hosted`]
+    ///
+    /// See [`Host`](crate::Host).
+    Hosted,
+    /// Brainfuck with `^` and `v`, which move the data pointer up and down on a tape that
+    /// is a grid, as parsed by [`parse_two_d`]
+    ///
+    /// See [`GridTape`](crate::GridTape).
+    TwoD,
+    /// Brainfuck with `{` and `}`, which switch to the previous and next tape, as
+    /// parsed by [`parse_multi_tape`]
+    ///
+    /// See [`MultiTape`](crate::MultiTape).
+    MultiTape,
+    /// Brainfuck with `@` and `$`, which push the current cell onto a stack and pop it
+    /// back, as parsed by [`parse_stack`]
+    ///
+    /// See [`BrainfuckProgram::stack`](crate::BrainfuckProgram::stack).
+    Stack,
+}
+
+impl Lexer for Dialect {
+    fn lex(&self, input: &str, token: &mut dyn FnMut(BrainfuckInstruction, SourcePos)) {
+        match self {
+            Dialect::Brainfuck => BrainfuckLexer.lex(input, token),
+            Dialect::Ook => OokLexer.lex(input, token),
+            Dialect::PBrain => PBrainLexer.lex(input, token),
+            Dialect::Hosted => HostedLexer.lex(input, token),
+            Dialect::TwoD => TwoDLexer.lex(input, token),
+            Dialect::MultiTape => MultiTapeLexer.lex(input, token),
+            Dialect::Stack => StackLexer.lex(input, token),
+        }
+    }
+}
+
+/// Parse Ook! `input` into its instructions, together with the location at which they occur.
+///
+/// Every instruction consists of two consecutive words, `Ook.`, `Ook?` or `Ook!`. All other
+/// text is ignored, as are pairs that do not form an instruction (`Ook? Ook?`). The location
+/// of an instruction is that of its first word.
+///
+/// This does not allocate, and does not match brackets: all jumps have a target of 0
+pub fn parse_ook(input: &str) -> impl Iterator<Item = Span<'_>> {
+    let mut words = input.lines().enumerate().flat_map(|(line, text)| {
+        text.char_indices()
+            .enumerate()
+            .filter_map(move |(index, (offset, _))| {
+                let punctuation = text[offset..].strip_prefix("Ook")?.chars().next()?;
+                match punctuation {
+                    '.' | '?' | '!' => Some((punctuation, line, index + 1)),
+                    _ => None,
+                }
+            })
+    });
+
+    core::iter::from_fn(move || loop {
+        let (first, line, character) = words.next()?;
+        let (second, _, _) = words.next()?;
+
+        let instruction = match (first, second) {
+            ('.', '?') => BrainfuckInstruction::IncrementDataPointer,
+            ('?', '.') => BrainfuckInstruction::DecrementDataPointer,
+            ('.', '.') => BrainfuckInstruction::IncreaseData,
+            ('!', '!') => BrainfuckInstruction::DecreaseData,
+            ('!', '.') => BrainfuckInstruction::Output,
+            ('.', '!') => BrainfuckInstruction::Input,
+            ('!', '?') => BrainfuckInstruction::JumpForward(0),
+            ('?', '!') => BrainfuckInstruction::JumpBackwards(0),
+            _ => continue,
+        };
+
+        return Some(Span {
+            instruction,
+            text: input,
+            line,
+            character,
+        });
+    })
+}
+
+/// Parse pbrain `input` into its instructions, together with the location at which they occur.
+///
+/// In addition to the Brainfuck commands, `(` and `)` define a procedure and `:` calls one.
+/// All non-valid characters are ignored
+///
+/// This does not allocate, and does not match brackets: all jumps have a target of 0
+pub fn parse_pbrain(input: &str) -> impl Iterator<Item = Span<'_>> {
+    parse_chars(input, |character| match character {
+        '(' => Some(BrainfuckInstruction::DefineProcedure(0)),
+        ')' => Some(BrainfuckInstruction::EndProcedure),
+        ':' => Some(BrainfuckInstruction::CallProcedure),
+        _ => brainfuck_instruction(character),
+    })
+}
+
+/// Parse `input`, Brainfuck with host calls, into its instructions, together with the
+/// location at which they occur.
+///
+/// In addition to the Brainfuck commands, `%` calls a host function. All non-valid
+/// characters are ignored
+///
+/// This does not allocate, and does not match brackets: all jumps have a target of 0
+pub fn parse_hosted(input: &str) -> impl Iterator<Item = Span<'_>> {
+    parse_chars(input, |character| match character {
+        '%' => Some(BrainfuckInstruction::HostCall),
+        _ => brainfuck_instruction(character),
+    })
+}
+
+/// Parse `input`, Brainfuck on a grid, into its instructions, together with the location
+/// at which they occur.
+///
+/// In addition to the Brainfuck commands, `^` moves the data pointer up and `v` moves it
+/// down, so a lowercase `v` in a comment is a command as well. All non-valid characters
+/// are ignored
+///
+/// This does not allocate, and does not match brackets: all jumps have a target of 0
+pub fn parse_two_d(input: &str) -> impl Iterator<Item = Span<'_>> {
+    parse_chars(input, |character| match character {
+        '^' => Some(BrainfuckInstruction::MoveUp),
+        'v' => Some(BrainfuckInstruction::MoveDown),
+        _ => brainfuck_instruction(character),
+    })
+}
+
+/// Parse `input`, Brainfuck with several tapes, into its instructions, together with the
+/// location at which they occur.
+///
+/// In addition to the Brainfuck commands, `{` switches to the previous tape and `}` to
+/// the next one. All non-valid characters are ignored
+///
+/// This does not allocate, and does not match brackets: all jumps have a target of 0
+pub fn parse_multi_tape(input: &str) -> impl Iterator<Item = Span<'_>> {
+    parse_chars(input, |character| match character {
+        '{' => Some(BrainfuckInstruction::PreviousTape),
+        '}' => Some(BrainfuckInstruction::NextTape),
+        _ => brainfuck_instruction(character),
+    })
+}
+
+/// Parse `input`, Brainfuck with a stack, into its instructions, together with the
+/// location at which they occur.
+///
+/// In addition to the Brainfuck commands, `@` pushes the current cell onto the stack and
+/// `$` pops the top of the stack into the current cell. All non-valid characters are
+/// ignored
+///
+/// This does not allocate, and does not match brackets: all jumps have a target of 0
+pub fn parse_stack(input: &str) -> impl Iterator<Item = Span<'_>> {
+    parse_chars(input, |character| match character {
+        '@' => Some(BrainfuckInstruction::Push),
+        '$' => Some(BrainfuckInstruction::Pop),
+        _ => brainfuck_instruction(character),
+    })
+}
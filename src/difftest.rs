@@ -0,0 +1,342 @@
+//! Differential testing of the execution backends
+//!
+//! Runs the same program with the same input through several backends, and checks
+//! that they all produce the same output and leave the tape in the same state.
+//!
+//! ```
+//! use brainfuck_interpreter::difftest::{assert_agree, Backend};
+//!
+//! assert_agree(",[.,]", b"echo", Backend::ALL);
+//! ```
+
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use crate::{
+    BrainfuckProgram, CompileOptions, Error, Executor, Memoizer, OptimizationLevel, SourcePos,
+    TieredExecutor,
+};
+
+/// The length of the tape that every backend starts with. Tapes grow as needed
+pub const TAPE_LEN: usize = 30_000;
+
+/// A way of executing a program
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// The interpreter, running the unoptimized program
+    Interpreter,
+    /// The interpreter, running the program optimized at the given level
+    Optimized(OptimizationLevel),
+    /// The interpreter, running the unoptimized program with a [`Memoizer`]
+    Memoized,
+    /// [`BrainfuckProgram::run_threaded`], running the unoptimized program
+    Threaded,
+    /// [`BrainfuckProgram::run_fast`], running the unoptimized program
+    Fast,
+    /// A [`TieredExecutor`] that compiles loops as soon as they jump back, running the
+    /// unoptimized program
+    Tiered,
+    /// [`BrainfuckProgram::run_native`], if the `native-jit` feature is enabled
+    #[cfg(feature = "native-jit")]
+    Native,
+}
+
+impl Backend {
+    /// All backends, with the unoptimized interpreter first
+    pub const ALL: &'static [Backend] = &[
+        Backend::Interpreter,
+        Backend::Optimized(OptimizationLevel::Basic),
+        Backend::Optimized(OptimizationLevel::Aggressive),
+        Backend::Optimized(OptimizationLevel::Peeling),
+        Backend::Memoized,
+        Backend::Threaded,
+        Backend::Fast,
+        Backend::Tiered,
+        #[cfg(feature = "native-jit")]
+        Backend::Native,
+    ];
+}
+
+impl Display for Backend {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Backend::Interpreter => write!(f, "interpreter"),
+            Backend::Optimized(level) => write!(f, "interpreter ({:?} optimization)", level),
+            Backend::Memoized => write!(f, "interpreter (memoized)"),
+            Backend::Threaded => write!(f, "threaded"),
+            Backend::Fast => write!(f, "fast"),
+            Backend::Tiered => write!(f, "tiered"),
+            #[cfg(feature = "native-jit")]
+            Backend::Native => write!(f, "native"),
+        }
+    }
+}
+
+/// The result of running a program on a single backend
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BackendRun {
+    /// The backend that ran the program
+    pub backend: Backend,
+    /// Everything the program output
+    pub output: Vec<u8>,
+    /// The position in the source code of the instruction that produced each
+    /// byte of `output`, if the backend keeps track of it
+    pub output_sources: Vec<Option<SourcePos>>,
+    /// The final tape, without trailing zero cells
+    pub tape: Vec<u8>,
+}
+
+#[allow(deprecated)]
+impl BackendRun {
+    /// Run the program given by `source` with `input` on `backend`
+    ///
+    /// Once `input` has run out, `,` reads zero.
+    pub fn new<'a>(source: &'a str, input: &[u8], backend: Backend) -> Result<Self, Error<'a>> {
+        let optimization = match backend {
+            Backend::Optimized(level) => level,
+            _ => OptimizationLevel::None,
+        };
+        let options = CompileOptions {
+            optimization,
+            source_map: true,
+            ..CompileOptions::default()
+        };
+        let mut program =
+            BrainfuckProgram::compile_with_options(source, alloc::vec![0u8; TAPE_LEN], &options)?;
+
+        let mut input = input.iter().copied();
+        let mut output = Vec::new();
+        let mut output_sources = Vec::new();
+        let mut memoizer = match backend {
+            Backend::Memoized => Some(Memoizer::new(&program)),
+            _ => None,
+        };
+
+        match backend {
+            Backend::Threaded => {
+                program.run_threaded(&mut |data| output.push(data), &mut || {
+                    input.next().unwrap_or(0)
+                });
+                output_sources.resize(output.len(), None);
+            }
+            Backend::Fast => {
+                program.run_fast(&mut |data| output.push(data), &mut || {
+                    input.next().unwrap_or(0)
+                });
+                output_sources.resize(output.len(), None);
+            }
+            Backend::Tiered => {
+                let mut executor = TieredExecutor::with_threshold(program, 1);
+                let result = executor.run(&mut |data| output.push(data), &mut || {
+                    input.next().unwrap_or(0)
+                });
+                if let Err(error) = result {
+                    panic!("{}", error);
+                }
+                program = executor.into_inner();
+                output_sources.resize(output.len(), None);
+            }
+            #[cfg(feature = "native-jit")]
+            Backend::Native => {
+                program.run_native(&mut |data| output.push(data), &mut || {
+                    input.next().unwrap_or(0)
+                });
+                output_sources.resize(output.len(), None);
+            }
+            _ => loop {
+                let instruction_pointer = program.instruction_pointer;
+                let mut produced = false;
+                let mut out = |data| {
+                    output.push(data);
+                    produced = true;
+                };
+                let mut read = || input.next().unwrap_or(0);
+                let running = match &mut memoizer {
+                    Some(memoizer) => memoizer.step(&mut program, &mut out, &mut read),
+                    None => program.step(&mut out, &mut read),
+                };
+                if produced {
+                    output_sources.push(program.source_of(instruction_pointer));
+                }
+                if !running {
+                    break;
+                }
+            },
+        }
+
+        let mut tape = program.tape;
+        let len = tape
+            .iter()
+            .rposition(|cell| *cell != 0)
+            .map_or(0, |last| last + 1);
+        tape.truncate(len);
+
+        Ok(Self {
+            backend,
+            output,
+            output_sources,
+            tape,
+        })
+    }
+}
+
+/// How the state of two backends differs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DivergenceKind {
+    /// The output differs
+    Output {
+        /// The index of the first byte that differs
+        index: usize,
+        /// The byte output by the reference backend, or `None` if its output ended
+        expected: Option<u8>,
+        /// The byte output by the other backend, or `None` if its output ended
+        found: Option<u8>,
+        /// The position in the source code of the instruction that output the
+        /// first differing byte on either backend, if known
+        source: Option<SourcePos>,
+    },
+    /// The output is identical, but the final tape differs
+    Tape {
+        /// The index of the first cell that differs
+        cell: usize,
+        /// The cell on the tape of the reference backend
+        expected: u8,
+        /// The cell on the tape of the other backend
+        found: u8,
+    },
+}
+
+/// A difference between the reference backend and another backend
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// The backend whose results are considered correct
+    pub reference: Backend,
+    /// The backend that produced different results
+    pub backend: Backend,
+    /// What differs
+    pub kind: DivergenceKind,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} diverges from {}: ", self.backend, self.reference)?;
+        match self.kind {
+            DivergenceKind::Output {
+                index,
+                expected,
+                found,
+                source,
+            } => {
+                write!(
+                    f,
+                    "output byte {} is {:?}, expected {:?}",
+                    index, found, expected
+                )?;
+                match source {
+                    Some(source) => write!(f, " (output at {})", source),
+                    None => Ok(()),
+                }
+            }
+            DivergenceKind::Tape {
+                cell,
+                expected,
+                found,
+            } => write!(f, "cell {} is {}, expected {}", cell, found, expected),
+        }
+    }
+}
+
+/// Find the first difference between `reference` and `other`
+pub fn compare(reference: &BackendRun, other: &BackendRun) -> Option<Divergence> {
+    let divergence = |kind| {
+        Some(Divergence {
+            reference: reference.backend,
+            backend: other.backend,
+            kind,
+        })
+    };
+
+    let len = reference.output.len().max(other.output.len());
+    let index = (0..len).find(|index| reference.output.get(*index) != other.output.get(*index));
+    if let Some(index) = index {
+        let source = |run: &BackendRun| run.output_sources.get(index).copied().flatten();
+        return divergence(DivergenceKind::Output {
+            index,
+            expected: reference.output.get(index).copied(),
+            found: other.output.get(index).copied(),
+            source: source(reference).or_else(|| source(other)),
+        });
+    }
+
+    let len = reference.tape.len().max(other.tape.len());
+    let cell = |run: &BackendRun, index: usize| run.tape.get(index).copied().unwrap_or(0);
+    (0..len)
+        .find(|index| cell(reference, *index) != cell(other, *index))
+        .and_then(|index| {
+            divergence(DivergenceKind::Tape {
+                cell: index,
+                expected: cell(reference, index),
+                found: cell(other, index),
+            })
+        })
+}
+
+/// An error that can occur while testing backends against each other
+#[derive(Clone, Debug)]
+pub enum DifftestError<'a> {
+    /// The program could not be compiled
+    Compile(Error<'a>),
+    /// A backend produced different results than the first backend
+    Divergence(Divergence),
+}
+
+impl<'a> Display for DifftestError<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DifftestError::Compile(error) => write!(f, "{}", error),
+            DifftestError::Divergence(divergence) => write!(f, "{}", divergence),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> std::error::Error for DifftestError<'a> {}
+
+impl<'a> From<Error<'a>> for DifftestError<'a> {
+    fn from(error: Error<'a>) -> Self {
+        DifftestError::Compile(error)
+    }
+}
+
+/// Run the program given by `source` with `input` on every backend in `backends`, and
+/// check that all of them produce the same results as the first one
+pub fn difftest<'a>(
+    source: &'a str,
+    input: &[u8],
+    backends: &[Backend],
+) -> Result<(), DifftestError<'a>> {
+    let mut backends = backends.iter();
+    let reference = match backends.next() {
+        Some(backend) => BackendRun::new(source, input, *backend)?,
+        None => return Ok(()),
+    };
+
+    for backend in backends {
+        let run = BackendRun::new(source, input, *backend)?;
+        if let Some(divergence) = compare(&reference, &run) {
+            return Err(DifftestError::Divergence(divergence));
+        }
+    }
+    Ok(())
+}
+
+/// Assert that all `backends` agree on the results of running `source` with `input`
+///
+/// # Panics
+/// If `source` does not compile, or if any backend diverges from the first one
+#[track_caller]
+pub fn assert_agree(source: &str, input: &[u8], backends: &[Backend]) {
+    if let Err(error) = difftest(source, input, backends) {
+        panic!("{}", error);
+    }
+}
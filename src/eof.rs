@@ -0,0 +1,103 @@
+//! Running programs with input that can run out
+
+use crate::{
+    io::Output,
+    result::{Counted, RunStart},
+    BrainfuckInstruction, BrainfuckProgram, RunOutcome, RunResult, RuntimeError, Tape, TapeData,
+};
+
+/// What `,` does once the input has run out
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EofBehavior {
+    /// Set the data at the data pointer to zero
+    #[default]
+    Zero,
+    /// Leave the data at the data pointer unchanged
+    Unchanged,
+}
+
+#[allow(deprecated)]
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Perform a step in the Brainfuck program, with `input` returning `None` once
+    /// the input has run out. What `,` does in that case is determined by `eof`
+    pub fn step_with_eof<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+        eof: EofBehavior,
+    ) -> bool
+    where
+        FnOut: Output<T::Data>,
+        FnIn: FnMut() -> Option<T::Data>,
+    {
+        let at_eof = self.value_at_eof(eof);
+        self.step(output, &mut eof_input(input, at_eof))
+    }
+
+    /// Perform a step in the Brainfuck program like
+    /// [`step_with_eof`](Self::step_with_eof), returning an error instead of panicking
+    /// like [`try_step`](Self::try_step)
+    pub fn try_step_with_eof<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+        eof: EofBehavior,
+    ) -> Result<bool, RuntimeError>
+    where
+        FnOut: Output<T::Data>,
+        FnIn: FnMut() -> Option<T::Data>,
+    {
+        let at_eof = self.value_at_eof(eof);
+        self.try_step(output, &mut eof_input(input, at_eof))
+    }
+
+    /// The data that `,` reads once the input has run out, if the next instruction is
+    /// a `,`
+    fn value_at_eof(&mut self, eof: EofBehavior) -> Option<T::Data> {
+        match self.instructions.get(self.instruction_pointer) {
+            Some(BrainfuckInstruction::Input) => Some(match eof {
+                EofBehavior::Zero => T::Data::zero(),
+                EofBehavior::Unchanged => self
+                    .tape
+                    .get_data_at(self.data_pointer)
+                    .cloned()
+                    .unwrap_or_else(T::Data::zero),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Run the Brainfuck program to completion, with `input` returning `None` once
+    /// the input has run out. What `,` does in that case is determined by `eof`
+    pub fn run_with_eof<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+        eof: EofBehavior,
+    ) -> RunResult<T::Data>
+    where
+        FnOut: Output<T::Data>,
+        FnIn: FnMut() -> Option<T::Data>,
+    {
+        let start = RunStart::new(self);
+        let (mut output, mut input) = (Counted::new(output), Counted::new(input));
+        while self.step_with_eof(&mut output, &mut || input.next_input(), eof) {}
+        start.finish(self, RunOutcome::Halted, &output, &input)
+    }
+}
+
+/// An input that reads from `input`, and reads `at_eof` once it has run out
+fn eof_input<'a, D, FnIn>(input: &'a mut FnIn, mut at_eof: Option<D>) -> impl FnMut() -> D + 'a
+where
+    D: 'a,
+    FnIn: FnMut() -> Option<D>,
+{
+    move || {
+        input()
+            .or_else(|| at_eof.take())
+            .expect("Only `,` reads input")
+    }
+}
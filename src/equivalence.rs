@@ -0,0 +1,207 @@
+//! Checking whether two programs behave the same
+
+use alloc::{vec, vec::Vec};
+use core::mem::discriminant;
+
+use crate::{io::SliceInput, BoundedVecTape, BrainfuckProgram, Error, RuntimeError, SeededRng};
+
+/// How much effort [`semantically_equal`] puts into comparing two programs
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EquivalenceBudget {
+    /// The maximum amount of instructions that a program executes for every input
+    pub max_instructions: usize,
+    /// The maximum amount of cells of the tape of every program
+    pub tape_limit: usize,
+    /// Every input of up to this length, made of the bytes in `alphabet`, is tried
+    pub exhaustive_len: usize,
+    /// The bytes that exhaustive inputs are made of
+    pub alphabet: Vec<u8>,
+    /// The amount of random inputs to try after the exhaustive ones
+    pub random_inputs: usize,
+    /// The maximum length of random inputs
+    pub random_input_len: usize,
+    /// The seed from which random inputs are generated
+    pub seed: u64,
+}
+
+impl Default for EquivalenceBudget {
+    fn default() -> Self {
+        Self {
+            max_instructions: 1 << 16,
+            tape_limit: 1 << 12,
+            exhaustive_len: 2,
+            alphabet: vec![0, 1, 2, b'a', 255],
+            random_inputs: 64,
+            random_input_len: 16,
+            seed: 0,
+        }
+    }
+}
+
+/// How a run of a program ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ending {
+    /// The program finished
+    Halted,
+    /// The program executed [`EquivalenceBudget::max_instructions`] instructions
+    OutOfFuel,
+    /// The program failed
+    Failed(RuntimeError),
+}
+
+/// What a program did with a single input
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Observation {
+    /// Everything that the program output
+    pub output: Vec<u8>,
+    /// How the run ended
+    pub ending: Ending,
+}
+
+impl Observation {
+    /// Run a copy of `program` with `input`, for at most `fuel` instructions
+    fn new(program: &BrainfuckProgram<BoundedVecTape<u8>>, input: &[u8], fuel: usize) -> Self {
+        let mut program = program.clone();
+        let mut output = Vec::new();
+        let mut input = SliceInput::new(input);
+        let mut ending = Ending::OutOfFuel;
+        for _ in 0..fuel {
+            match program.try_step(&mut |data| output.push(data), &mut input) {
+                Ok(true) => {}
+                Ok(false) => {
+                    ending = Ending::Halted;
+                    break;
+                }
+                Err(error) => {
+                    ending = Ending::Failed(error);
+                    break;
+                }
+            }
+        }
+        Self { output, ending }
+    }
+
+    /// Whether this observation certainly differs from `other`
+    ///
+    /// A program that ran out of fuel could still produce more output, so only the
+    /// output that both programs produced is compared in that case.
+    fn differs(&self, other: &Observation) -> bool {
+        let len = self.output.len().min(other.output.len());
+        if self.output[..len] != other.output[..len] {
+            return true;
+        }
+        match (self.ending, other.ending) {
+            (Ending::OutOfFuel, _) | (_, Ending::OutOfFuel) => false,
+            // Equivalent programs can fail at different instructions or cells
+            (Ending::Failed(a), Ending::Failed(b)) if discriminant(&a) != discriminant(&b) => true,
+            (a, b) => {
+                discriminant(&a) != discriminant(&b) || self.output.len() != other.output.len()
+            }
+        }
+    }
+}
+
+/// An input for which two programs behave differently
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Counterexample {
+    /// The input
+    pub input: Vec<u8>,
+    /// What the first program did
+    pub a: Observation,
+    /// What the second program did
+    pub b: Observation,
+}
+
+/// The result of [`semantically_equal`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EquivalenceReport {
+    /// The amount of inputs that were tried
+    pub inputs_checked: usize,
+    /// The amount of inputs for which at least one program ran out of fuel, so that
+    /// only part of their behaviour could be compared
+    pub inconclusive: usize,
+    /// The first input for which the programs behaved differently, if any
+    pub counterexample: Option<Counterexample>,
+}
+
+impl EquivalenceReport {
+    /// Whether the programs behaved the same for every input that was tried
+    pub fn is_equal(&self) -> bool {
+        self.counterexample.is_none()
+    }
+}
+
+/// Compare the observable behaviour of the programs `a` and `b`, by running them
+/// against the same inputs within `budget`
+///
+/// The programs are considered to behave the same if, for every input, they produce
+/// the same output and end in the same way. Input reads zero once it has run out.
+/// This can only show that two programs differ: programs that behave the same for
+/// every input that was tried may still differ for other inputs.
+///
+/// ```
+/// # use brainfuck_interpreter::{semantically_equal, EquivalenceBudget};
+/// let budget = EquivalenceBudget::default();
+/// assert!(semantically_equal(",[.,]", ",[.,]+-", &budget).unwrap().is_equal());
+///
+/// let report = semantically_equal(",.", ",+.", &budget).unwrap();
+/// assert_eq!(report.counterexample.unwrap().input, b"");
+/// ```
+pub fn semantically_equal<'a>(
+    a: &'a str,
+    b: &'a str,
+    budget: &EquivalenceBudget,
+) -> Result<EquivalenceReport, Error<'a>> {
+    let a = BrainfuckProgram::compile(a, BoundedVecTape::with_limit(budget.tape_limit))?;
+    let b = BrainfuckProgram::compile(b, BoundedVecTape::with_limit(budget.tape_limit))?;
+
+    let mut report = EquivalenceReport {
+        inputs_checked: 0,
+        inconclusive: 0,
+        counterexample: None,
+    };
+
+    let mut rng = SeededRng::new(budget.seed);
+    let random = (0..budget.random_inputs).map(|_| {
+        let len = rng.below(budget.random_input_len + 1);
+        (0..len).map(|_| rng.next_u64() as u8).collect()
+    });
+
+    for input in exhaustive_inputs(&budget.alphabet, budget.exhaustive_len).chain(random) {
+        let observed_a = Observation::new(&a, &input, budget.max_instructions);
+        let observed_b = Observation::new(&b, &input, budget.max_instructions);
+        report.inputs_checked += 1;
+        if observed_a.ending == Ending::OutOfFuel || observed_b.ending == Ending::OutOfFuel {
+            report.inconclusive += 1;
+        }
+        if observed_a.differs(&observed_b) {
+            report.counterexample = Some(Counterexample {
+                input,
+                a: observed_a,
+                b: observed_b,
+            });
+            break;
+        }
+    }
+    Ok(report)
+}
+
+/// Every input of up to `max_len` bytes from `alphabet`, ordered by length
+fn exhaustive_inputs(alphabet: &[u8], max_len: usize) -> impl Iterator<Item = Vec<u8>> + '_ {
+    (0..=max_len).flat_map(move |len| {
+        let count = if alphabet.is_empty() && len > 0 {
+            0
+        } else {
+            alphabet.len().pow(len as u32)
+        };
+        (0..count).map(move |mut index| {
+            (0..len)
+                .map(|_| {
+                    let byte = alphabet[index % alphabet.len()];
+                    index /= alphabet.len();
+                    byte
+                })
+                .collect()
+        })
+    })
+}
@@ -0,0 +1,137 @@
+//! An owned version of [`Error`]
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Display;
+
+use crate::{
+    lexer::lex_spans, write_padding, BrainfuckLexer, BrainfuckProgram, Error, Lexer, Tape,
+};
+
+/// The kind of a [`CompileError`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompileErrorKind {
+    /// A `[` does not have a matching `]`
+    MissingClosingBrace,
+    /// A `]` does not have a matching `[`
+    MissingOpeningBrace,
+}
+
+/// An error that can occur while compiling Brainfuck, which does not borrow the source code
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompileError {
+    /// What went wrong
+    pub kind: CompileErrorKind,
+    /// The line on which the error occurred
+    pub line: usize,
+    /// The character within `line` at which the error occurred
+    pub column: usize,
+    /// The line of source code on which the error occurred
+    pub excerpt: String,
+}
+
+impl<'a> From<Error<'a>> for CompileError {
+    fn from(error: Error<'a>) -> Self {
+        let (kind, span) = match error {
+            Error::MissingClosingBrace(span) => (CompileErrorKind::MissingClosingBrace, span),
+            Error::MissingOpeningBrace(span) => (CompileErrorKind::MissingOpeningBrace, span),
+        };
+        let (line, column) = span.get_line_character_number();
+        let excerpt = span.get_text().lines().nth(*line).unwrap_or("").to_string();
+        Self {
+            kind,
+            line: *line,
+            column: *column,
+            excerpt,
+        }
+    }
+}
+
+impl Display for CompileErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CompileErrorKind::MissingClosingBrace => f.write_str("Missing closing brace"),
+            CompileErrorKind::MissingOpeningBrace => f.write_str("Missing opening brace"),
+        }
+    }
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} at line {}, character {}:\n{}\n",
+            self.kind, self.line, self.column, self.excerpt
+        )?;
+        write_padding(f, &self.excerpt, self.column)?;
+        f.write_str("^")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CompileError {}
+
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Compile a Brainfuck program, given by `input`, returning an error that does not
+    /// borrow `input` on failure. All non-valid characters are ignored
+    pub fn compile_owned(input: &str, tape: T) -> Result<Self, CompileError> {
+        Self::compile(input, tape).map_err(CompileError::from)
+    }
+
+    /// Compile a Brainfuck program from arbitrary bytes. Everything that is not a valid
+    /// character, including invalid UTF-8, is ignored
+    ///
+    /// This never panics, whatever `input` contains, which makes it a suitable entry
+    /// point for fuzzing.
+    pub fn compile_unchecked(input: &[u8], tape: T) -> Result<Self, CompileError> {
+        Self::compile_owned(&String::from_utf8_lossy(input), tape)
+    }
+
+    /// Compile a Brainfuck program, given by `input`, reporting every unmatched
+    /// bracket instead of only the first one. All non-valid characters are ignored
+    ///
+    /// The errors are ordered by their position in `input`.
+    pub fn compile_all_errors(input: &str, tape: T) -> Result<Self, Vec<CompileError>> {
+        Self::compile_all_errors_with_lexer(input, tape, &BrainfuckLexer)
+    }
+
+    /// Compile a program, given by `input`, using `lexer` to find its instructions,
+    /// and report every unmatched bracket instead of only the first one
+    ///
+    /// The errors are ordered by their position in `input`.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckProgram, CompileErrorKind, OokLexer};
+    /// let source = "Ook? Ook!\nOok! Ook? Ook. Ook.";
+    /// let errors =
+    ///     BrainfuckProgram::compile_all_errors_with_lexer(source, vec![0u8], &OokLexer)
+    ///         .unwrap_err();
+    ///
+    /// assert_eq!(errors.len(), 2);
+    /// assert_eq!(errors[0].kind, CompileErrorKind::MissingOpeningBrace);
+    /// assert_eq!((errors[0].line, errors[0].excerpt.as_str()), (0, "Ook? Ook!"));
+    /// assert_eq!(errors[1].kind, CompileErrorKind::MissingClosingBrace);
+    /// assert_eq!((errors[1].line, errors[1].column), (1, 1));
+    /// ```
+    pub fn compile_all_errors_with_lexer<L>(
+        input: &str,
+        tape: T,
+        lexer: &L,
+    ) -> Result<Self, Vec<CompileError>>
+    where
+        L: Lexer + ?Sized,
+    {
+        let mut spans = lex_spans(input, lexer);
+        let errors = Self::match_brackets(&mut spans);
+        if !errors.is_empty() {
+            return Err(errors.into_iter().map(CompileError::from).collect());
+        }
+
+        Ok(Self::from_spans(&spans, tape))
+    }
+}
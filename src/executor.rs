@@ -0,0 +1,131 @@
+//! A common interface for the different ways of running programs
+
+use crate::{
+    io::{Input, Output},
+    BrainfuckProgram, RuntimeError, Tape, TapeData,
+};
+
+/// Something that runs a compiled Brainfuck program
+///
+/// [`BrainfuckProgram`] itself is the interpreter, and runs programs compiled with
+/// any [`OptimizationLevel`](crate::OptimizationLevel). Other backends, such as the
+/// native code generator, wrap a program and implement this trait as well, so that
+/// code that runs programs does not need to know which backend it uses.
+///
+/// ```
+/// # use brainfuck_interpreter::{io::VecOutput, BrainfuckProgram, Executor};
+/// fn run_all<E: Executor<Data = u8>>(executor: &mut E) -> Vec<u8> {
+///     let mut output = VecOutput::new();
+///     executor.run(&mut output, &mut || 0).unwrap();
+///     output.into_inner()
+/// }
+///
+/// let mut program = BrainfuckProgram::compile("++++++++[>++++++++<-]>+.", vec![0u8; 2]).unwrap();
+/// assert_eq!(run_all(&mut program), b"A");
+/// ```
+pub trait Executor {
+    /// The type of the cells
+    type Data: TapeData;
+
+    /// Perform a single step, returning whether the program is still running, or the
+    /// error that stopped it
+    fn step<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<bool, RuntimeError>
+    where
+        FnOut: Output<Self::Data>,
+        FnIn: Input<Self::Data>;
+
+    /// Run the program to completion, or until it fails
+    fn run<FnOut, FnIn>(&mut self, output: &mut FnOut, input: &mut FnIn) -> Result<(), RuntimeError>
+    where
+        FnOut: Output<Self::Data>,
+        FnIn: Input<Self::Data>,
+    {
+        while self.step(output, input)? {}
+        Ok(())
+    }
+}
+
+impl<T> Executor for BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    type Data = T::Data;
+
+    fn step<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<bool, RuntimeError>
+    where
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
+    {
+        self.try_step(output, input)
+    }
+}
+
+/// An [`Executor`] that runs programs as native machine code, using
+/// [`BrainfuckProgram::try_run_native`]
+///
+/// Single steps are performed by the interpreter.
+#[cfg(feature = "native-jit")]
+#[derive(Clone, Debug)]
+pub struct NativeExecutor<T>
+where
+    T: Tape<Data = u8>,
+{
+    program: BrainfuckProgram<T>,
+}
+
+#[cfg(feature = "native-jit")]
+impl<T> NativeExecutor<T>
+where
+    T: Tape<Data = u8> + AsMut<[u8]>,
+{
+    /// Create an executor that runs `program`
+    pub fn new(program: BrainfuckProgram<T>) -> Self {
+        Self { program }
+    }
+
+    /// The program that is run
+    pub fn program(&mut self) -> &mut BrainfuckProgram<T> {
+        &mut self.program
+    }
+
+    /// Take the program that is run
+    pub fn into_inner(self) -> BrainfuckProgram<T> {
+        self.program
+    }
+}
+
+#[cfg(feature = "native-jit")]
+impl<T> Executor for NativeExecutor<T>
+where
+    T: Tape<Data = u8> + AsMut<[u8]>,
+{
+    type Data = u8;
+
+    fn step<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<bool, RuntimeError>
+    where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        self.program.try_step(output, input)
+    }
+
+    fn run<FnOut, FnIn>(&mut self, output: &mut FnOut, input: &mut FnIn) -> Result<(), RuntimeError>
+    where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        self.program.try_run_native(output, input).map(drop)
+    }
+}
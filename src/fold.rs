@@ -0,0 +1,295 @@
+//! Evaluating programs, or the start of programs, ahead of time
+
+use alloc::vec::Vec;
+
+use crate::{analyze_loops, BrainfuckInstruction, BrainfuckProgram, RuntimeError, Tape, TapeData};
+
+/// The cells and data pointer that a program starts with, after the instructions that
+/// set them up have been evaluated by [`BrainfuckProgram::fold_prefix`]
+///
+/// [`to_bytecode`](BrainfuckProgram::to_bytecode) rejects programs that have one, as
+/// bytecode only holds the instructions.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InitialTape<D> {
+    /// The first cells of the tape. The cells after these are zero
+    pub cells: Vec<D>,
+    /// The data pointer
+    pub data_pointer: usize,
+}
+
+/// The result of [`BrainfuckProgram::precompute`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Precomputed<D> {
+    /// The program finished, and produced this output
+    Finished(Vec<D>),
+    /// The program ran out of fuel, or is about to read input. It produced this
+    /// output so far, and the program itself is the residual program, which
+    /// continues where it stopped
+    Residual(Vec<D>),
+}
+
+/// The cells that have been read from a tape while folding, and the data pointer
+struct Scratch<'a, T>
+where
+    T: Tape,
+{
+    tape: &'a mut T,
+    cells: Vec<T::Data>,
+    data_pointer: usize,
+}
+
+impl<'a, T> Scratch<'a, T>
+where
+    T: Tape,
+{
+    /// The cell at the data pointer, or `None` if the tape has no cell there
+    fn cell(&mut self) -> Option<&mut T::Data> {
+        while self.cells.len() <= self.data_pointer {
+            let next = self.tape.get_data_at(self.cells.len())?.clone();
+            self.cells.push(next);
+        }
+        Some(&mut self.cells[self.data_pointer])
+    }
+
+    /// Evaluate `instructions`, whose jump targets are offset by `start`, or return
+    /// `false` once `fuel` runs out or an instruction would fail
+    fn eval(
+        &mut self,
+        instructions: &[BrainfuckInstruction],
+        start: usize,
+        fuel: &mut usize,
+    ) -> bool {
+        let mut index = 0;
+        while let Some(instruction) = instructions.get(index) {
+            if *fuel == 0 {
+                return false;
+            }
+            *fuel -= 1;
+
+            match instruction {
+                // Like the interpreter, look up the cell as soon as the data pointer moves
+                BrainfuckInstruction::IncrementDataPointer => {
+                    self.data_pointer += 1;
+                    if self.cell().is_none() {
+                        return false;
+                    }
+                }
+                BrainfuckInstruction::DecrementDataPointer => {
+                    match self.data_pointer.checked_sub(1) {
+                        Some(data_pointer) => self.data_pointer = data_pointer,
+                        None => return false,
+                    }
+                    if self.cell().is_none() {
+                        return false;
+                    }
+                }
+                BrainfuckInstruction::IncreaseData => match self.cell() {
+                    Some(data) => {
+                        if !data.try_increase() {
+                            return false;
+                        }
+                    }
+                    None => return false,
+                },
+                BrainfuckInstruction::DecreaseData => match self.cell() {
+                    Some(data) => {
+                        if !data.try_decrease() {
+                            return false;
+                        }
+                    }
+                    None => return false,
+                },
+                BrainfuckInstruction::JumpForward(target) => match self.cell() {
+                    Some(data) if *data == T::Data::zero() => index = target - start,
+                    Some(_) => {}
+                    None => return false,
+                },
+                BrainfuckInstruction::JumpBackwards(target) => match self.cell() {
+                    Some(data) if *data != T::Data::zero() => index = target - start,
+                    Some(_) => {}
+                    None => return false,
+                },
+                _ => return false,
+            }
+            index += 1;
+        }
+        // The cell at the data pointer is checked before the first instruction
+        self.cell().is_some()
+    }
+}
+
+/// Whether `instructions` only move the data pointer, change cells and loop
+fn is_constant(instructions: &[BrainfuckInstruction]) -> bool {
+    instructions.iter().all(|instruction| {
+        matches!(
+            instruction,
+            BrainfuckInstruction::IncrementDataPointer
+                | BrainfuckInstruction::DecrementDataPointer
+                | BrainfuckInstruction::IncreaseData
+                | BrainfuckInstruction::DecreaseData
+                | BrainfuckInstruction::JumpForward(_)
+                | BrainfuckInstruction::JumpBackwards(_)
+        )
+    })
+}
+
+#[allow(deprecated)]
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Evaluate the instructions at the start of the program that do not read input or
+    /// produce output, and start the program after them
+    ///
+    /// Instructions and complete loops are evaluated one after another, until one
+    /// would read input, produce output, use procedures, move the data pointer off the
+    /// tape or overflow a cell, or until `fuel` instructions have been evaluated. The
+    /// evaluated instructions are removed from the program, and the cells that they set
+    /// up are written to the tape and stored in
+    /// [`initial_tape`](Self::initial_tape), which [`reset`](Self::reset) restores.
+    ///
+    /// Evaluation starts from the current contents of the tape. Nothing is folded if
+    /// the program has already been stepped. Returns the amount of instructions that
+    /// were removed, which are not counted in `execution_count`.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::BrainfuckProgram;
+    /// let mut program = BrainfuckProgram::compile("++++++++[>++++++++<-]>+.", vec![0u8]).unwrap();
+    /// assert_eq!(program.fold_prefix(10_000), 23);
+    /// assert_eq!(program.instructions().len(), 1);
+    ///
+    /// let initial = program.initial_tape.as_ref().unwrap();
+    /// assert_eq!(initial.cells, [0, b'A']);
+    /// assert_eq!(initial.data_pointer, 1);
+    ///
+    /// let mut output = Vec::new();
+    /// program.run(&mut |data| output.push(data), &mut || 0);
+    /// assert_eq!(output, b"A");
+    /// ```
+    pub fn fold_prefix(&mut self, fuel: usize) -> usize {
+        if self.instruction_pointer != 0 || !self.call_stack.is_empty() {
+            return 0;
+        }
+
+        let mut fuel = fuel;
+        let mut scratch = Scratch {
+            tape: &mut self.tape,
+            cells: Vec::new(),
+            data_pointer: self.data_pointer,
+        };
+        let mut folded = 0;
+
+        while let Some(instruction) = self.instructions.get(folded) {
+            let end = match instruction {
+                BrainfuckInstruction::JumpForward(end) => *end,
+                _ => folded,
+            };
+            let unit = &self.instructions[folded..=end];
+            if !is_constant(unit) {
+                break;
+            }
+
+            let data_pointer = scratch.data_pointer;
+            let snapshot = match instruction {
+                BrainfuckInstruction::JumpForward(_) => Some(scratch.cells.clone()),
+                _ => None,
+            };
+            if !scratch.eval(unit, folded, &mut fuel) {
+                scratch.data_pointer = data_pointer;
+                if let Some(cells) = snapshot {
+                    scratch.cells = cells;
+                }
+                break;
+            }
+            folded = end + 1;
+        }
+
+        if folded == 0 {
+            return 0;
+        }
+        // Keep the cells of an earlier fold in the initial tape
+        let previous = self
+            .initial_tape
+            .as_ref()
+            .map_or(0, |tape| tape.cells.len());
+        while scratch.cells.len() < previous {
+            match scratch.tape.get_data_at(scratch.cells.len()) {
+                Some(data) => scratch.cells.push(data.clone()),
+                None => break,
+            }
+        }
+
+        let Scratch {
+            cells,
+            data_pointer,
+            ..
+        } = scratch;
+        for (index, data) in cells.iter().enumerate() {
+            if let Some(cell) = self.tape.get_data_at_mut(index) {
+                *cell = data.clone();
+            }
+        }
+        self.data_pointer = data_pointer;
+
+        self.instructions.drain(..folded);
+        for instruction in &mut self.instructions {
+            match instruction {
+                BrainfuckInstruction::JumpForward(target)
+                | BrainfuckInstruction::JumpBackwards(target)
+                | BrainfuckInstruction::DefineProcedure(target) => *target -= folded,
+                _ => {}
+            }
+        }
+        if let Some(source_map) = &mut self.source_map {
+            source_map.drain(..folded);
+        }
+        self.loops = analyze_loops(&self.instructions);
+        self.initial_tape = Some(InitialTape {
+            cells,
+            data_pointer,
+        });
+
+        folded
+    }
+
+    /// Run the program ahead of time, executing at most `fuel` instructions, and
+    /// return the output that it produces
+    ///
+    /// This is meant for programs that do not read input, such as programs that
+    /// generate data that is embedded in a build. If the program finishes, its complete
+    /// output is returned as [`Precomputed::Finished`]. Otherwise, it stops before
+    /// the first `,` that it reaches, or once it runs out of fuel, and the program is
+    /// left where it stopped, as a residual program that produces the rest of the
+    /// output when it is run.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckProgram, Precomputed};
+    /// let source = "++++++++[>++++++++<-]>+.+.+.";
+    /// let mut program = BrainfuckProgram::compile(source, vec![0u8; 2]).unwrap();
+    /// assert_eq!(program.precompute(10_000), Ok(Precomputed::Finished(b"ABC".to_vec())));
+    ///
+    /// let mut program = BrainfuckProgram::compile("+++.,.", vec![0u8; 1]).unwrap();
+    /// assert_eq!(program.precompute(10_000), Ok(Precomputed::Residual(vec![3])));
+    ///
+    /// let mut output = Vec::new();
+    /// program.run(&mut |data| output.push(data), &mut || 7);
+    /// assert_eq!(output, [7]);
+    /// ```
+    pub fn precompute(&mut self, fuel: usize) -> Result<Precomputed<T::Data>, RuntimeError> {
+        let mut output = Vec::new();
+        let mut fuel = fuel;
+        loop {
+            match self.instructions.get(self.instruction_pointer) {
+                Some(BrainfuckInstruction::Input) => return Ok(Precomputed::Residual(output)),
+                Some(_) if fuel == 0 => return Ok(Precomputed::Residual(output)),
+                Some(_) => fuel -= 1,
+                // Stepping past the end still checks that the data pointer is on the tape
+                None => {}
+            }
+            // Input is never read, because the loop stops before every `,`
+            if !self.try_step(&mut |data| output.push(data), &mut T::Data::zero)? {
+                return Ok(Precomputed::Finished(output));
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//! Formatting of Brainfuck source code
+
+use alloc::string::String;
+
+use crate::{ast::Node, BrainfuckProgram, CompileOptions, Error, OptimizationLevel, VALID_CHARS};
+
+/// Options that control how Brainfuck source code is formatted
+#[derive(Clone, Debug)]
+pub struct FormatOptions {
+    /// The amount of spaces to indent loop bodies with
+    pub indent: usize,
+    /// The maximum length of a line of commands, including indentation
+    ///
+    /// Lines are only broken between commands, so comments may exceed this length.
+    pub max_width: usize,
+    /// Whether to remove all comments
+    pub strip_comments: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent: 4,
+            max_width: 80,
+            strip_comments: false,
+        }
+    }
+}
+
+/// Lays out commands and comments
+struct Writer<'o> {
+    options: &'o FormatOptions,
+    output: String,
+    line: String,
+    depth: usize,
+}
+
+impl<'o> Writer<'o> {
+    fn new(options: &'o FormatOptions) -> Self {
+        Self {
+            options,
+            output: String::new(),
+            line: String::new(),
+            depth: 0,
+        }
+    }
+
+    fn indentation(&self) -> usize {
+        self.depth * self.options.indent
+    }
+
+    fn flush(&mut self) {
+        let trimmed = self.line.trim_end().len();
+        self.line.truncate(trimmed);
+        if !self.line.is_empty() {
+            for _ in 0..self.indentation() {
+                self.output.push(' ');
+            }
+            self.output.push_str(&self.line);
+            self.output.push('\n');
+            self.line.clear();
+        }
+    }
+
+    fn command(&mut self, command: char) {
+        if self.indentation() + self.line.len() >= self.options.max_width {
+            self.flush();
+        }
+        self.line.push(command);
+    }
+
+    fn open(&mut self, bracket: char) {
+        self.flush();
+        self.line.push(bracket);
+        self.flush();
+        self.depth += 1;
+    }
+
+    fn close(&mut self, bracket: char) {
+        self.flush();
+        self.depth = self.depth.saturating_sub(1);
+        self.line.push(bracket);
+        self.flush();
+    }
+
+    /// Write a comment, collapsing all whitespace in it. Whether the comment was
+    /// separated from the surrounding commands by whitespace is preserved
+    fn comment(&mut self, comment: &str) {
+        if comment.starts_with(char::is_whitespace) {
+            self.space();
+        }
+        for word in comment.split_whitespace() {
+            if self.indentation() + self.line.len() + 1 + word.len() > self.options.max_width {
+                self.flush();
+            }
+            self.space();
+            self.line.push_str(word);
+        }
+        if comment.ends_with(char::is_whitespace) {
+            self.space();
+        }
+    }
+
+    fn space(&mut self) {
+        if !self.line.is_empty() && !self.line.ends_with(' ') {
+            self.line.push(' ');
+        }
+    }
+
+    fn nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            match node {
+                Node::Op(op) => self.command(op.as_char()),
+                Node::Loop(body) => {
+                    self.open('[');
+                    self.nodes(body);
+                    self.close(']');
+                }
+                Node::Procedure(body) => {
+                    self.open('(');
+                    self.nodes(body);
+                    self.close(')');
+                }
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.flush();
+        self.output
+    }
+}
+
+/// Format a program given as a tree of instructions
+pub fn format_ast(nodes: &[Node], options: &FormatOptions) -> String {
+    let mut writer = Writer::new(options);
+    writer.nodes(nodes);
+    writer.finish()
+}
+
+/// Format Brainfuck source code: every loop bracket is placed on its own line,
+/// loop bodies are indented, and commands are wrapped at
+/// [`FormatOptions::max_width`]
+///
+/// Comments are kept on the line of the surrounding commands with their whitespace
+/// collapsed, unless [`FormatOptions::strip_comments`] is set. Formatting never changes the instructions
+/// of a program.
+///
+/// Returns an error if the brackets in `source` do not match.
+pub fn format_source<'a>(source: &'a str, options: &FormatOptions) -> Result<String, Error<'a>> {
+    let program = BrainfuckProgram::compile(source, [0u8; 0])?;
+
+    if options.strip_comments {
+        return Ok(format_ast(&program.to_ast(), options));
+    }
+
+    let mut writer = Writer::new(options);
+    let mut rest = source;
+    while !rest.is_empty() {
+        let comment_len = rest
+            .find(|c| VALID_CHARS.contains(&c))
+            .unwrap_or(rest.len());
+        let (comment, commands) = rest.split_at(comment_len);
+        writer.comment(comment);
+
+        let mut chars = commands.chars();
+        if let Some(command) = chars.next() {
+            match command {
+                '[' => writer.open('['),
+                ']' => writer.close(']'),
+                command => writer.command(command),
+            }
+        }
+        rest = chars.as_str();
+    }
+    Ok(writer.finish())
+}
+
+/// Remove everything from `source` that is not a command
+pub fn minify(source: &str) -> String {
+    source.chars().filter(|c| VALID_CHARS.contains(c)).collect()
+}
+
+/// Remove everything from `source` that is not a command, and remove the commands
+/// that the optimizer would remove at `level`
+///
+/// Returns an error if the brackets in `source` do not match.
+#[allow(deprecated)]
+pub fn minify_optimized<'a>(
+    source: &'a str,
+    level: OptimizationLevel,
+) -> Result<String, Error<'a>> {
+    let options = CompileOptions {
+        optimization: level,
+        ..CompileOptions::default()
+    };
+    let program = BrainfuckProgram::compile_with_options(source, [0u8; 0], &options)?;
+    Ok(program
+        .instructions
+        .iter()
+        .map(|instruction| instruction.as_char())
+        .collect())
+}
@@ -0,0 +1,96 @@
+//! Generating random programs
+
+use alloc::string::String;
+
+use crate::{SeededRng, VALID_CHARS};
+
+/// Constraints on the programs produced by [`generate_random`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GenConfig {
+    /// The minimum amount of instructions
+    pub min_length: usize,
+    /// The maximum amount of instructions
+    pub max_length: usize,
+    /// The maximum nesting of loops
+    pub max_depth: usize,
+    /// How often every instruction is chosen, relative to the others, in the order of
+    /// [`VALID_CHARS`]
+    ///
+    /// The weight of `]` only applies while a loop is open, and loops that are still
+    /// open at the end of the program are always closed.
+    pub weights: [usize; 8],
+}
+
+impl Default for GenConfig {
+    fn default() -> Self {
+        Self {
+            min_length: 0,
+            max_length: 64,
+            max_depth: 4,
+            weights: [4, 4, 4, 4, 1, 1, 1, 1],
+        }
+    }
+}
+
+/// Generate a random program with matching brackets within the constraints of `config`
+///
+/// The program only depends on `config` and the state of `rng`, so the same seed
+/// always produces the same program.
+///
+/// ```
+/// # use brainfuck_interpreter::{analyze, generate_random, GenConfig, SeededRng};
+/// let config = GenConfig { min_length: 10, max_length: 20, max_depth: 2, ..GenConfig::default() };
+/// let program = generate_random(&config, &mut SeededRng::new(1));
+///
+/// let analysis = analyze(&program).unwrap();
+/// assert!((10..=20).contains(&analysis.instructions()));
+/// assert!(analysis.max_depth <= 2);
+/// assert_eq!(program, generate_random(&config, &mut SeededRng::new(1)));
+/// ```
+pub fn generate_random(config: &GenConfig, rng: &mut SeededRng) -> String {
+    let max_length = config.max_length.max(config.min_length);
+    let length = config.min_length + rng.below(max_length - config.min_length + 1);
+
+    let mut program = String::with_capacity(length);
+    let mut open = 0usize;
+    for position in 0..length {
+        let remaining = length - position;
+        // Every open loop needs a `]` in the remaining instructions
+        let mut weights = config.weights;
+        if remaining <= open {
+            weights = [0; 8];
+            weights[7] = 1;
+        } else {
+            if open >= config.max_depth || remaining < open + 2 {
+                weights[6] = 0;
+            }
+            if open == 0 {
+                weights[7] = 0;
+            }
+            if weights.iter().all(|weight| *weight == 0) {
+                weights[..6].fill(1);
+            }
+        }
+
+        let instruction = VALID_CHARS[choose(&weights, rng)];
+        match instruction {
+            '[' => open += 1,
+            ']' => open -= 1,
+            _ => {}
+        }
+        program.push(instruction);
+    }
+    program
+}
+
+/// Choose an index into `weights`, with a probability proportional to its weight
+fn choose(weights: &[usize], rng: &mut SeededRng) -> usize {
+    let mut choice = rng.below(weights.iter().sum());
+    for (index, weight) in weights.iter().enumerate() {
+        if choice < *weight {
+            return index;
+        }
+        choice -= weight;
+    }
+    unreachable!("At least one weight is not zero")
+}
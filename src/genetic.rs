@@ -0,0 +1,277 @@
+//! Mutation and crossover of programs, for genetic programming
+//!
+//! The operators work on the [tree representation](crate::ast) of programs, so every
+//! program that they produce has matching brackets. Use
+//! [`to_source`](crate::ast::to_source) to turn the result back into source code, or
+//! [`recompile`] to run it.
+
+use alloc::vec::Vec;
+
+use crate::{
+    analyze_loops,
+    ast::{to_instructions, Node, Op},
+    BrainfuckProgram, SeededRng, Tape,
+};
+
+/// The operations that are inserted and substituted
+const OPS: [Op; 6] = [
+    Op::IncrementDataPointer,
+    Op::DecrementDataPointer,
+    Op::IncreaseData,
+    Op::DecreaseData,
+    Op::Output,
+    Op::Input,
+];
+
+/// A change made by [`mutate`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Mutation {
+    /// An operation or a loop was inserted, see [`insert_random`]
+    Insert,
+    /// A node was removed, see [`delete_random`]
+    Delete,
+    /// An operation was replaced by another one, see [`substitute_random`]
+    Substitute,
+}
+
+fn body(node: &Node) -> Option<&Vec<Node>> {
+    match node {
+        Node::Loop(body) | Node::Procedure(body) => Some(body),
+        Node::Op(_) => None,
+    }
+}
+
+/// The amount of nodes in `program`, including the nodes in loops and procedures
+pub fn node_count(program: &[Node]) -> usize {
+    program
+        .iter()
+        .map(|node| 1 + body(node).map_or(0, |body| node_count(body)))
+        .sum()
+}
+
+/// The amount of positions at which a node can be inserted into `program`
+fn slot_count(program: &[Node]) -> usize {
+    program.len()
+        + 1
+        + program
+            .iter()
+            .filter_map(body)
+            .map(|body| slot_count(body))
+            .sum::<usize>()
+}
+
+/// Find the path to the `n`th node for which `matches` is true, in pre-order
+fn nth_node(
+    program: &[Node],
+    n: &mut usize,
+    matches: &impl Fn(&Node) -> bool,
+    path: &mut Vec<usize>,
+) -> bool {
+    for (index, node) in program.iter().enumerate() {
+        path.push(index);
+        if matches(node) {
+            if *n == 0 {
+                return true;
+            }
+            *n -= 1;
+        }
+        if let Some(body) = body(node) {
+            if nth_node(body, n, matches, path) {
+                return true;
+            }
+        }
+        path.pop();
+    }
+    false
+}
+
+/// Find the path to the `n`th position at which a node can be inserted
+fn nth_slot(program: &[Node], n: &mut usize, path: &mut Vec<usize>) -> bool {
+    for index in 0..=program.len() {
+        if *n == 0 {
+            path.push(index);
+            return true;
+        }
+        *n -= 1;
+        if let Some(body) = program.get(index).and_then(body) {
+            path.push(index);
+            if nth_slot(body, n, path) {
+                return true;
+            }
+            path.pop();
+        }
+    }
+    false
+}
+
+/// A random path to a node for which `matches` is true, if there is one
+fn random_node(
+    program: &[Node],
+    rng: &mut SeededRng,
+    matches: impl Fn(&Node) -> bool,
+) -> Option<Vec<usize>> {
+    let count = count_matching(program, &matches);
+    if count == 0 {
+        return None;
+    }
+    let mut path = Vec::new();
+    nth_node(program, &mut rng.below(count), &matches, &mut path);
+    Some(path)
+}
+
+fn count_matching(program: &[Node], matches: &impl Fn(&Node) -> bool) -> usize {
+    program
+        .iter()
+        .map(|node| {
+            usize::from(matches(node)) + body(node).map_or(0, |body| count_matching(body, matches))
+        })
+        .sum()
+}
+
+/// The list of nodes that contains the node at `path`, and the index of that node
+fn parent_mut<'a>(program: &'a mut Vec<Node>, path: &[usize]) -> (&'a mut Vec<Node>, usize) {
+    let (index, parents) = path.split_last().expect("Paths are never empty");
+    let mut nodes = program;
+    for parent in parents {
+        nodes = match &mut nodes[*parent] {
+            Node::Loop(body) | Node::Procedure(body) => body,
+            Node::Op(_) => unreachable!("Paths only pass through loops and procedures"),
+        };
+    }
+    (nodes, *index)
+}
+
+fn node_at<'a>(program: &'a [Node], path: &[usize]) -> &'a Node {
+    let (index, parents) = path.split_last().expect("Paths are never empty");
+    let mut nodes = program;
+    for parent in parents {
+        nodes = body(&nodes[*parent]).expect("Paths only pass through loops and procedures");
+    }
+    &nodes[*index]
+}
+
+fn random_op(rng: &mut SeededRng) -> Op {
+    OPS[rng.below(OPS.len())]
+}
+
+/// Insert a random operation, or a loop containing one, at a random position in
+/// `program`
+pub fn insert_random(program: &mut Vec<Node>, rng: &mut SeededRng) {
+    let mut path = Vec::new();
+    nth_slot(program, &mut rng.below(slot_count(program)), &mut path);
+
+    let op = Node::Op(random_op(rng));
+    let node = if rng.chance(1, OPS.len() + 1) {
+        Node::Loop(alloc::vec![op])
+    } else {
+        op
+    };
+    let (nodes, index) = parent_mut(program, &path);
+    nodes.insert(index, node);
+}
+
+/// Remove a random node from `program`, together with its body if it is a loop or a
+/// procedure
+///
+/// Returns `false` if `program` is empty.
+pub fn delete_random(program: &mut Vec<Node>, rng: &mut SeededRng) -> bool {
+    match random_node(program, rng, |_| true) {
+        Some(path) => {
+            let (nodes, index) = parent_mut(program, &path);
+            nodes.remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Replace a random operation in `program` by a different one
+///
+/// Returns `false` if `program` contains no operations.
+pub fn substitute_random(program: &mut Vec<Node>, rng: &mut SeededRng) -> bool {
+    let path = match random_node(program, rng, |node| matches!(node, Node::Op(_))) {
+        Some(path) => path,
+        None => return false,
+    };
+    let (nodes, index) = parent_mut(program, &path);
+    let current = OPS.iter().position(|op| nodes[index] == Node::Op(*op));
+    // Skip over the current operation, so that the replacement is always different
+    let mut replacement = rng.below(OPS.len() - 1);
+    if current.is_some_and(|current| replacement >= current) {
+        replacement += 1;
+    }
+    nodes[index] = Node::Op(OPS[replacement]);
+    true
+}
+
+/// Apply a random [`Mutation`] to `program`, returning the one that was applied
+///
+/// Deletions and substitutions that are not possible are replaced by an insertion.
+///
+/// ```
+/// # use brainfuck_interpreter::{genetic::mutate, BrainfuckProgram, SeededRng};
+/// let program = BrainfuckProgram::compile("+[->+<]", vec![0u8]).unwrap();
+/// let mut ast = program.to_ast();
+/// let mut rng = SeededRng::new(7);
+/// for _ in 0..100 {
+///     mutate(&mut ast, &mut rng);
+/// }
+///
+/// // The result always has matching brackets
+/// let source = brainfuck_interpreter::ast::to_source(&ast);
+/// assert!(BrainfuckProgram::compile(&source, vec![0u8]).is_ok());
+/// ```
+pub fn mutate(program: &mut Vec<Node>, rng: &mut SeededRng) -> Mutation {
+    match rng.below(3) {
+        1 if delete_random(program, rng) => Mutation::Delete,
+        2 if substitute_random(program, rng) => Mutation::Substitute,
+        _ => {
+            insert_random(program, rng);
+            Mutation::Insert
+        }
+    }
+}
+
+/// Swap a random node of `a` with a random node of `b`, returning the two children
+///
+/// Loops and procedures are swapped together with their bodies, so the children
+/// combine whole subtrees of their parents. If either parent is empty, the children
+/// are copies of their parents.
+///
+/// ```
+/// # use brainfuck_interpreter::{genetic::{crossover, node_count}, BrainfuckProgram, SeededRng};
+/// let a = BrainfuckProgram::compile("++[>+<-]", vec![0u8]).unwrap().to_ast();
+/// let b = BrainfuckProgram::compile(",.", vec![0u8]).unwrap().to_ast();
+/// let (c, d) = crossover(&a, &b, &mut SeededRng::new(3));
+/// assert_eq!(node_count(&a) + node_count(&b), node_count(&c) + node_count(&d));
+/// ```
+pub fn crossover(a: &[Node], b: &[Node], rng: &mut SeededRng) -> (Vec<Node>, Vec<Node>) {
+    let mut child_a = a.to_vec();
+    let mut child_b = b.to_vec();
+    if let (Some(path_a), Some(path_b)) =
+        (random_node(a, rng, |_| true), random_node(b, rng, |_| true))
+    {
+        let (nodes, index) = parent_mut(&mut child_a, &path_a);
+        nodes[index] = node_at(b, &path_b).clone();
+        let (nodes, index) = parent_mut(&mut child_b, &path_b);
+        nodes[index] = node_at(a, &path_a).clone();
+    }
+    (child_a, child_b)
+}
+
+/// Replace the instructions of `program` by `nodes`, and reset it
+///
+/// This reuses the tape of `program`, which avoids allocating a new one for every
+/// program that is evaluated. The source map of `program` is cleared.
+#[allow(deprecated)]
+pub fn recompile<T>(program: &mut BrainfuckProgram<T>, nodes: &[Node])
+where
+    T: Tape,
+{
+    program.instructions = to_instructions(nodes);
+    program.source_map = None;
+    program.loops = analyze_loops(&program.instructions);
+    program.initial_tape = None;
+    program.execution_count = 0;
+    program.reset();
+}
@@ -0,0 +1,179 @@
+//! Rendering the control flow of a program as a graph
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write;
+
+use crate::{BrainfuckInstruction, BrainfuckProgram, Profiler, Tape};
+
+/// The maximum amount of instructions shown in the label of a block
+const MAX_LABEL: usize = 32;
+
+/// The ways that control can leave a block, as `(target, label, dashed)`
+fn successors(
+    instructions: &[BrainfuckInstruction],
+    last: usize,
+) -> Vec<(usize, &'static str, bool)> {
+    let next = last + 1;
+    match instructions[last] {
+        BrainfuckInstruction::JumpForward(end) => {
+            alloc::vec![(next, "non-zero", false), (end + 1, "zero", false)]
+        }
+        BrainfuckInstruction::JumpBackwards(start) => {
+            alloc::vec![(start + 1, "non-zero", false), (next, "zero", false)]
+        }
+        // The body of a procedure is only run when it is called
+        BrainfuckInstruction::DefineProcedure(end) => {
+            alloc::vec![(end + 1, "", false), (next, "body", true)]
+        }
+        // Returns to the caller, or does nothing outside of a call
+        BrainfuckInstruction::EndProcedure => alloc::vec![(next, "no call", true)],
+        BrainfuckInstruction::CallProcedure => alloc::vec![(next, "return", false)],
+        _ => alloc::vec![(next, "", false)],
+    }
+}
+
+/// Whether control can continue somewhere other than the next instruction after
+/// `instruction`
+fn ends_block(instruction: &BrainfuckInstruction) -> bool {
+    matches!(
+        instruction,
+        BrainfuckInstruction::JumpForward(_)
+            | BrainfuckInstruction::JumpBackwards(_)
+            | BrainfuckInstruction::DefineProcedure(_)
+            | BrainfuckInstruction::EndProcedure
+            | BrainfuckInstruction::CallProcedure
+    )
+}
+
+/// Render the basic blocks of `instructions` in the DOT language, with how often every
+/// block was executed taken from `counts`, if given
+pub(crate) fn to_dot(instructions: &[BrainfuckInstruction], counts: Option<&[u64]>) -> String {
+    let len = instructions.len();
+
+    // A block starts at the start of the program, at every jump target, and after
+    // every instruction that ends a block
+    let mut leader = alloc::vec![false; len + 1];
+    leader[0] = true;
+    for (index, instruction) in instructions.iter().enumerate() {
+        if ends_block(instruction) {
+            for (target, _, _) in successors(instructions, index) {
+                leader[target] = true;
+            }
+        }
+    }
+    let starts: Vec<usize> = (0..len).filter(|index| leader[*index]).collect();
+
+    let mut dot = String::from("digraph program {\n    node [shape=box, fontname=monospace];\n");
+    // Writing to a `String` cannot fail
+    writeln!(dot, "    start [shape=circle];").ok();
+    writeln!(dot, "    end [shape=doublecircle];").ok();
+    let name = |index: usize| {
+        if index >= len {
+            String::from("end")
+        } else {
+            alloc::format!("block{}", index)
+        }
+    };
+    writeln!(dot, "    start -> {};", name(0)).ok();
+
+    for (position, start) in starts.iter().enumerate() {
+        let end = starts.get(position + 1).copied().unwrap_or(len);
+
+        let mut code: String = instructions[*start..end]
+            .iter()
+            .take(MAX_LABEL)
+            .map(|instruction| instruction.as_char())
+            .collect();
+        if end - start > MAX_LABEL {
+            code.push_str("...");
+        }
+        write!(
+            dot,
+            "    {} [label=\"{}..{}\\n{}",
+            name(*start),
+            start,
+            end - 1,
+            code
+        )
+        .ok();
+        if let Some(counts) = counts {
+            let count = counts.get(*start).copied().unwrap_or(0);
+            write!(dot, "\\nexecuted {} times", count).ok();
+        }
+        writeln!(dot, "\"];").ok();
+
+        for (target, label, dashed) in successors(instructions, end - 1) {
+            let mut attributes = Vec::new();
+            if !label.is_empty() {
+                attributes.push(alloc::format!("label=\"{}\"", label));
+            }
+            if dashed {
+                attributes.push(String::from("style=dashed"));
+            }
+
+            write!(dot, "    {} -> {}", name(*start), name(target)).ok();
+            if !attributes.is_empty() {
+                write!(dot, " [{}]", attributes.join(", ")).ok();
+            }
+            dot.push_str(";\n");
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+#[allow(deprecated)]
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Render the control flow graph of the compiled program in the DOT language, so that
+    /// it can be drawn by Graphviz
+    ///
+    /// Every node is a basic block, a run of instructions that is always executed from
+    /// start to end, labeled with the indices of its instructions and the instructions
+    /// themselves. Blocks end at brackets and procedure instructions, and the edges
+    /// between them show where execution continues if the cell at the data pointer is
+    /// zero or non-zero. Use [`Profiler::to_dot`] to show how often every block was
+    /// executed.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::BrainfuckProgram;
+    /// let program = BrainfuckProgram::compile("++[>+<-]>.", vec![0u8; 2]).unwrap();
+    /// let dot = program.to_dot();
+    ///
+    /// assert!(dot.starts_with("digraph program {\n"));
+    /// assert!(dot.contains("    block3 [label=\"3..7\\n>+<-]\"];\n"));
+    /// assert!(dot.contains("    block0 -> block8 [label=\"zero\"];\n"));
+    /// assert!(dot.contains("    block3 -> block3 [label=\"non-zero\"];\n"));
+    /// assert!(dot.contains("    block8 -> end;\n"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        to_dot(&self.instructions, None)
+    }
+}
+
+#[allow(deprecated)]
+impl Profiler {
+    /// Render the control flow graph of `program` in the DOT language, with how often
+    /// every block was executed while profiling
+    ///
+    /// See [`BrainfuckProgram::to_dot`].
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckProgram, Profiler};
+    /// let mut program = BrainfuckProgram::compile("++[>+<-]>.", vec![0u8; 2]).unwrap();
+    /// let mut profiler = Profiler::new(&program);
+    /// profiler.run(&mut program, &mut |_| {}, &mut || 0);
+    ///
+    /// let dot = profiler.to_dot(&program);
+    /// assert!(dot.contains("    block3 [label=\"3..7\\n>+<-]\\nexecuted 2 times\"];\n"));
+    /// ```
+    pub fn to_dot<T>(&self, program: &BrainfuckProgram<T>) -> String
+    where
+        T: Tape,
+    {
+        to_dot(&program.instructions, Some(self.counts()))
+    }
+}
@@ -0,0 +1,228 @@
+//! The nesting of the loops of a program
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::{Display, Write};
+
+use crate::{
+    profiler::source_positions, BrainfuckInstruction, BrainfuckLexer, BrainfuckProgram, Profiler,
+    SourcePos, Tape,
+};
+
+/// A loop, and the loops that are nested directly inside of it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoopNode {
+    /// The index of the `[` of this loop
+    pub start: usize,
+    /// The index of the `]` of this loop
+    pub end: usize,
+    /// The position of the `[` of this loop
+    pub start_position: SourcePos,
+    /// The position of the `]` of this loop
+    pub end_position: SourcePos,
+    /// The amount of instructions in this loop, including the brackets and nested loops
+    pub size: usize,
+    /// How often the loop was reached, if the program was profiled
+    pub entries: Option<u64>,
+    /// How often the body of the loop was executed, if the program was profiled
+    pub iterations: Option<u64>,
+    /// The loops nested directly inside of this loop, in program order
+    pub children: Vec<LoopNode>,
+}
+
+/// The loops of a program as a tree, as created by [`LoopTree::new`] or
+/// [`Profiler::loop_tree`]
+///
+/// Its [`Display`] implementation shows every loop on its own line, indented by its
+/// depth. [`to_dot`](Self::to_dot) renders the tree as a Graphviz graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoopTree {
+    /// The amount of instructions in the program
+    pub instructions: usize,
+    /// The loops that are not nested inside of another loop, in program order
+    pub loops: Vec<LoopNode>,
+}
+
+#[allow(deprecated)]
+impl LoopTree {
+    /// Find the loops of `program`, given the `source` that it was compiled from
+    ///
+    /// If `program` has a source map, it is used to find the position of the loops,
+    /// and `source` is ignored.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckProgram, LoopTree};
+    /// let source = "++[>+++[>+<-]<-]\n[-]";
+    /// let program = BrainfuckProgram::compile(source, vec![0u8; 3]).unwrap();
+    ///
+    /// let tree = LoopTree::new(&program, source);
+    /// assert_eq!(tree.loops.len(), 2);
+    /// assert_eq!(tree.loops[0].children[0].size, 6);
+    /// assert_eq!(
+    ///     tree.to_string(),
+    ///     "19 instructions\n\
+    ///      line 0, character 3 to line 0, character 16: 14 instructions\n  \
+    ///        line 0, character 8 to line 0, character 13: 6 instructions\n\
+    ///      line 1, character 1 to line 1, character 3: 3 instructions\n"
+    /// );
+    /// ```
+    pub fn new<T>(program: &BrainfuckProgram<T>, source: &str) -> Self
+    where
+        T: Tape,
+    {
+        Self::build(program, source, None)
+    }
+
+    /// Find the loops of `program`, taking their entries and iterations from the
+    /// execution counts of every instruction in `counts`, if given
+    pub(crate) fn build<T>(
+        program: &BrainfuckProgram<T>,
+        source: &str,
+        counts: Option<&[u64]>,
+    ) -> Self
+    where
+        T: Tape,
+    {
+        let positions = source_positions(program, source, &BrainfuckLexer);
+        let position = |index: usize| {
+            positions.get(index).copied().unwrap_or(SourcePos {
+                line: 0,
+                character: 0,
+            })
+        };
+        let count = |index: usize| counts.map(|counts| counts.get(index).copied().unwrap_or(0));
+
+        let mut loops = Vec::new();
+        let mut open: Vec<LoopNode> = Vec::new();
+        for (index, instruction) in program.instructions.iter().enumerate() {
+            match instruction {
+                BrainfuckInstruction::JumpForward(end) => open.push(LoopNode {
+                    start: index,
+                    end: *end,
+                    start_position: position(index),
+                    end_position: position(*end),
+                    size: end + 1 - index,
+                    // Every time the loop is reached, its `[` is executed
+                    entries: count(index),
+                    // Every iteration ends with the `]`, which jumps past the `[`
+                    iterations: count(*end),
+                    children: Vec::new(),
+                }),
+                BrainfuckInstruction::JumpBackwards(_) => {
+                    if let Some(node) = open.pop() {
+                        match open.last_mut() {
+                            Some(parent) => parent.children.push(node),
+                            None => loops.push(node),
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            instructions: program.instructions.len(),
+            loops,
+        }
+    }
+
+    /// Render this tree as a Graphviz graph, in the DOT language
+    ///
+    /// Every loop is a node, with an edge from the loop or program that it is nested in.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckProgram, LoopTree};
+    /// let program = BrainfuckProgram::compile("+[>[-]<-]", vec![0u8; 2]).unwrap();
+    /// let dot = LoopTree::new(&program, "+[>[-]<-]").to_dot();
+    /// assert!(dot.starts_with("digraph loops {\n"));
+    /// assert!(dot.contains("    loop1 -> loop3;\n"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        fn write_node(dot: &mut String, parent: &str, node: &LoopNode) {
+            let _ = write!(
+                dot,
+                "    loop{} [label=\"line {}, character {}\\n{} instructions",
+                node.start, node.start_position.line, node.start_position.character, node.size
+            );
+            if let (Some(entries), Some(iterations)) = (node.entries, node.iterations) {
+                let _ = write!(dot, "\\n{} entries, {} iterations", entries, iterations);
+            }
+            let _ = writeln!(dot, "\"];");
+            let _ = writeln!(dot, "    {} -> loop{};", parent, node.start);
+
+            let name = alloc::format!("loop{}", node.start);
+            for child in &node.children {
+                write_node(dot, &name, child);
+            }
+        }
+
+        let mut dot = String::from("digraph loops {\n    node [shape=box];\n");
+        let _ = writeln!(
+            dot,
+            "    program [label=\"program\\n{} instructions\"];",
+            self.instructions
+        );
+        for root in &self.loops {
+            write_node(&mut dot, "program", root);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl Display for LoopTree {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        fn write_node(
+            f: &mut core::fmt::Formatter<'_>,
+            node: &LoopNode,
+            depth: usize,
+        ) -> core::fmt::Result {
+            write!(
+                f,
+                "{:indent$}{} to {}: {} instructions",
+                "",
+                node.start_position,
+                node.end_position,
+                node.size,
+                indent = depth * 2
+            )?;
+            if let (Some(entries), Some(iterations)) = (node.entries, node.iterations) {
+                write!(f, ", {} entries, {} iterations", entries, iterations)?;
+            }
+            writeln!(f)?;
+            node.children
+                .iter()
+                .try_for_each(|child| write_node(f, child, depth + 1))
+        }
+
+        writeln!(f, "{} instructions", self.instructions)?;
+        self.loops
+            .iter()
+            .try_for_each(|root| write_node(f, root, 0))
+    }
+}
+
+impl Profiler {
+    /// Find the loops of `program`, with how often they were entered and iterated
+    /// while profiling, given the `source` that the program was compiled from
+    ///
+    /// See [`LoopTree::new`].
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckProgram, Profiler};
+    /// let source = "++[>+++[>+<-]<-]";
+    /// let mut program = BrainfuckProgram::compile(source, vec![0u8; 3]).unwrap();
+    /// let mut profiler = Profiler::new(&program);
+    /// profiler.run(&mut program, &mut |_| {}, &mut || 0);
+    ///
+    /// let tree = profiler.loop_tree(&program, source);
+    /// assert_eq!(tree.loops[0].iterations, Some(2));
+    /// assert_eq!(tree.loops[0].children[0].entries, Some(2));
+    /// assert_eq!(tree.loops[0].children[0].iterations, Some(6));
+    /// ```
+    pub fn loop_tree<T>(&self, program: &BrainfuckProgram<T>, source: &str) -> LoopTree
+    where
+        T: Tape,
+    {
+        LoopTree::build(program, source, Some(self.counts()))
+    }
+}
@@ -0,0 +1,263 @@
+//! Letting programs call functions of the application that runs them
+
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{
+    host_call,
+    io::{Input, Output},
+    result::{Counted, RunStart},
+    BrainfuckInstruction, BrainfuckProgram, HostCallError, RunOutcome, RunResult, RuntimeError,
+    Tape, TapeData,
+};
+
+/// A function of the host application that programs can call with `%`
+///
+/// This is implemented for closures that take the window of the call.
+pub trait HostFunction<D> {
+    /// Handle a call, with `window` being the cells after the data pointer. Return an
+    /// error code to stop the program with a [`HostCallError::Failed`]
+    fn call(&mut self, window: &mut [D]) -> Result<(), u32>;
+}
+
+impl<D, F> HostFunction<D> for F
+where
+    F: FnMut(&mut [D]) -> Result<(), u32>,
+{
+    fn call(&mut self, window: &mut [D]) -> Result<(), u32> {
+        self(window)
+    }
+}
+
+/// A registered host function
+struct Registration<D> {
+    name: D,
+    width: usize,
+    function: Box<dyn HostFunction<D>>,
+}
+
+/// Runs programs in [`Dialect::Hosted`](crate::Dialect::Hosted), calling the registered
+/// host function whenever a program reaches a `%`
+///
+/// Functions are registered under a name, which is the data that the cell at the data
+/// pointer must hold for `%` to call it, like the name of a pbrain procedure, and the
+/// width of their window. A call can only access its window: the cells directly after
+/// the data pointer, which are copied into a slice that the function can read and
+/// change, and copied back onto the tape after the call. Functions cannot move the data
+/// pointer or the instruction pointer, and do not take part in the input and output of
+/// the program, unless the application passes them its own. Every call counts as a
+/// single executed instruction.
+///
+/// Reaching a `%` stops the program with a [`RuntimeError::HostCall`] if no function is
+/// registered under the current cell, if the window does not fit on the tape, if the
+/// function returns an error, or if the program has already made the maximum amount
+/// of calls set by [`set_call_limit`](Self::set_call_limit).
+///
+/// ```
+/// # use brainfuck_interpreter::{BrainfuckProgram, CompileOptions, Dialect, Host};
+/// let options = CompileOptions {
+///     dialect: Dialect::Hosted,
+///     ..CompileOptions::default()
+/// };
+/// // Call function 1 with 20 and 22 in its window, and print the result that it
+/// // stores in front of them
+/// let source = "+>++++[>+++++<-]>>++++++++++++++++++++++<<<%>.";
+/// let mut program =
+///     BrainfuckProgram::compile_with_options(source, vec![0u8; 4], &options).unwrap();
+///
+/// let mut host = Host::new();
+/// host.register(1, 3, |window: &mut [u8]| {
+///     window[0] = window[1] + window[2];
+///     Ok(())
+/// });
+///
+/// let mut output = Vec::new();
+/// host.run(&mut program, &mut |value| output.push(value), &mut || 0)
+///     .unwrap();
+/// assert_eq!(output, [42]);
+/// assert_eq!(host.calls(), 1);
+/// ```
+pub struct Host<D> {
+    functions: Vec<Registration<D>>,
+    calls: u64,
+    call_limit: Option<u64>,
+}
+
+impl<D> core::fmt::Debug for Host<D>
+where
+    D: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Host")
+            .field(
+                "functions",
+                &self
+                    .functions
+                    .iter()
+                    .map(|registration| (&registration.name, registration.width))
+                    .collect::<Vec<_>>(),
+            )
+            .field("calls", &self.calls)
+            .field("call_limit", &self.call_limit)
+            .finish()
+    }
+}
+
+impl<D> Default for Host<D> {
+    fn default() -> Self {
+        Self {
+            functions: Vec::new(),
+            calls: 0,
+            call_limit: None,
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl<D> Host<D>
+where
+    D: TapeData,
+{
+    /// Create a host without any functions or call limit
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `function` under `name`, with a window of `width` cells, replacing the
+    /// function that was registered under `name` before, if any
+    pub fn register<F>(&mut self, name: D, width: usize, function: F)
+    where
+        F: HostFunction<D> + 'static,
+    {
+        let registration = Registration {
+            name,
+            width,
+            function: Box::new(function),
+        };
+        match self
+            .functions
+            .iter_mut()
+            .find(|existing| existing.name == registration.name)
+        {
+            Some(existing) => *existing = registration,
+            None => self.functions.push(registration),
+        }
+    }
+
+    /// Remove the function that is registered under `name`, returning whether there was
+    /// one
+    pub fn unregister(&mut self, name: &D) -> bool {
+        let len = self.functions.len();
+        self.functions
+            .retain(|registration| registration.name != *name);
+        self.functions.len() != len
+    }
+
+    /// Stop programs with a [`HostCallError::CallLimit`] once they have made `limit`
+    /// calls, or never if `limit` is `None`
+    pub fn set_call_limit(&mut self, limit: Option<u64>) {
+        self.call_limit = limit;
+    }
+
+    /// The amount of calls that have been made
+    pub fn calls(&self) -> u64 {
+        self.calls
+    }
+
+    /// Call the function under the cell at the data pointer of `program`
+    fn call<T>(&mut self, program: &mut BrainfuckProgram<T>) -> Result<(), HostCallError>
+    where
+        T: Tape<Data = D>,
+    {
+        if self.call_limit.is_some_and(|limit| self.calls >= limit) {
+            return Err(HostCallError::CallLimit);
+        }
+
+        let data_pointer = program.data_pointer;
+        let name = program
+            .tape
+            .get_data_at_mut(data_pointer)
+            .cloned()
+            .ok_or(HostCallError::UnknownFunction)?;
+        let registration = self
+            .functions
+            .iter_mut()
+            .find(|registration| registration.name == name)
+            .ok_or(HostCallError::UnknownFunction)?;
+
+        let first = data_pointer + 1;
+        let mut window = (first..first + registration.width)
+            .map(|index| program.tape.get_data_at_mut(index).cloned())
+            .collect::<Option<Vec<_>>>()
+            .ok_or(HostCallError::WindowOutOfBounds)?;
+
+        self.calls += 1;
+        registration
+            .function
+            .call(&mut window)
+            .map_err(HostCallError::Failed)?;
+
+        for (index, data) in window.into_iter().enumerate() {
+            if let Some(cell) = program.tape.get_data_at_mut(first + index) {
+                *cell = data;
+            }
+        }
+        Ok(())
+    }
+
+    /// Perform a step in `program`, calling a host function if the instruction pointer
+    /// is at a `%`
+    ///
+    /// Like [`BrainfuckProgram::try_step`], the instruction pointer is left at the `%`
+    /// if the call fails.
+    pub fn step<T, FnOut, FnIn>(
+        &mut self,
+        program: &mut BrainfuckProgram<T>,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<bool, RuntimeError>
+    where
+        T: Tape<Data = D>,
+        FnOut: Output<D>,
+        FnIn: Input<D>,
+    {
+        let instruction_pointer = program.instruction_pointer;
+        let data_pointer = program.data_pointer;
+        // The interpreter reports a data pointer that is not on the tape
+        if program.instructions.get(instruction_pointer) != Some(&BrainfuckInstruction::HostCall)
+            || program.tape.get_data_at_mut(data_pointer).is_none()
+        {
+            return program.try_step(output, input);
+        }
+
+        program.execution_count += 1;
+        self.call(program).map_err(|error| {
+            host_call(
+                instruction_pointer,
+                &program.source_map,
+                &program.source,
+                error,
+            )
+        })?;
+        program.instruction_pointer += 1;
+        Ok(true)
+    }
+
+    /// Run `program` to completion, or until it fails, calling host functions whenever
+    /// it reaches a `%`
+    pub fn run<T, FnOut, FnIn>(
+        &mut self,
+        program: &mut BrainfuckProgram<T>,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<RunResult<D>, RuntimeError>
+    where
+        T: Tape<Data = D>,
+        FnOut: Output<D>,
+        FnIn: Input<D>,
+    {
+        let start = RunStart::new(program);
+        let (mut output, mut input) = (Counted::new(output), Counted::new(input));
+        while self.step(program, &mut output, &mut input)? {}
+        Ok(start.finish(program, RunOutcome::Halted, &output, &input))
+    }
+}
@@ -0,0 +1,94 @@
+//! Looking at the contents of the tape
+
+use alloc::string::String;
+use core::{
+    fmt::Write,
+    ops::{Bound, RangeBounds},
+};
+
+use crate::{BrainfuckProgram, Tape};
+
+/// The amount of cells shown on every line of a dump
+const CELLS_PER_LINE: usize = 16;
+
+#[allow(deprecated)]
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape<Data = u8>,
+{
+    /// Format the cells in `range` as a hex dump, with their ASCII representation
+    ///
+    /// Every line shows the index of its first cell, followed by 16 cells in hex and
+    /// as ASCII, with `.` for unprintable characters. The cell at the data pointer is
+    /// surrounded by `[` and `]`. The range is limited to
+    /// [`len_hint`](Tape::len_hint), if the tape has one, so that dumping does not
+    /// grow the tape.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::BrainfuckProgram;
+    /// let mut program = BrainfuckProgram::compile("+++>>++", vec![0u8; 4]).unwrap();
+    /// program.run(&mut |_| {}, &mut || 0);
+    ///
+    /// let dump = program.dump(..);
+    /// assert!(dump.starts_with("00000000   03  00 [02] 00 "));
+    /// assert!(dump.ends_with(" |....|\n"));
+    /// ```
+    pub fn dump<R>(&mut self, range: R) -> String
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let mut end = match range.end_bound() {
+            Bound::Included(end) => end.saturating_add(1),
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => self
+                .tape
+                .len_hint()
+                .unwrap_or_else(|| self.data_pointer.saturating_add(1)),
+        };
+        if let Some(len) = self.tape.len_hint() {
+            end = end.min(len);
+        }
+
+        let mut dump = String::new();
+        let mut line_start = start;
+        while line_start < end {
+            let line_end = end.min(line_start.saturating_add(CELLS_PER_LINE));
+            let mut ascii = String::with_capacity(CELLS_PER_LINE);
+
+            // Writing to a `String` cannot fail
+            write!(dump, "{:08x}  ", line_start).ok();
+            for index in line_start..line_end {
+                let cell = match self.tape.get_data_at(index) {
+                    Some(cell) => *cell,
+                    None => break,
+                };
+                let (open, close) = if index == self.data_pointer {
+                    ('[', ']')
+                } else {
+                    (' ', ' ')
+                };
+                write!(dump, "{}{:02x}{}", open, cell, close).ok();
+                ascii.push(if cell.is_ascii_graphic() || cell == b' ' {
+                    cell as char
+                } else {
+                    '.'
+                });
+            }
+            for _ in ascii.len()..CELLS_PER_LINE {
+                dump.push_str("    ");
+            }
+            writeln!(dump, " |{}|", ascii).ok();
+
+            if ascii.len() < line_end - line_start {
+                break;
+            }
+            line_start = line_end;
+        }
+        dump
+    }
+}
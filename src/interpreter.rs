@@ -0,0 +1,357 @@
+//! An interpreter that runs a slice of instructions on a tape without allocating, for
+//! targets without a global allocator
+
+use crate::{
+    io::{Input, Output},
+    parse,
+    result::{Counted, RunStart},
+    BrainfuckInstruction, Error, HostCallError, RunOutcome, RunResult, RuntimeError, Tape,
+    TapeData,
+};
+
+/// An error that can occur while compiling a program with [`compile_into`]
+#[derive(Clone, Debug)]
+pub enum CompileIntoError<'a> {
+    /// The brackets of the program do not match
+    Brackets(Error<'a>),
+    /// The buffer cannot hold all instructions of the program
+    BufferTooSmall {
+        /// The amount of instructions in the program
+        needed: usize,
+    },
+}
+
+impl<'a> core::fmt::Display for CompileIntoError<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CompileIntoError::Brackets(error) => write!(f, "{}", error),
+            CompileIntoError::BufferTooSmall { needed } => {
+                write!(f, "The program needs room for {} instructions", needed)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> std::error::Error for CompileIntoError<'a> {}
+
+impl<'a> From<Error<'a>> for CompileIntoError<'a> {
+    fn from(error: Error<'a>) -> Self {
+        CompileIntoError::Brackets(error)
+    }
+}
+
+/// Marks a `[` that has no enclosing `[` while matching brackets
+const NO_PARENT: usize = usize::MAX;
+
+/// Compile the Brainfuck program in `input` into `buffer`, returning the part of
+/// `buffer` that holds its instructions. All non-valid characters are ignored
+///
+/// This does not allocate: while brackets are matched, every open `[` stores the index
+/// of the `[` around it instead of a stack.
+///
+/// ```
+/// # use brainfuck_interpreter::{compile_into, BrainfuckInstruction, CompileIntoError};
+/// let mut buffer = [BrainfuckInstruction::IncreaseData; 8];
+/// let instructions = compile_into("+[->+<]", &mut buffer).unwrap();
+/// assert_eq!(instructions[1], BrainfuckInstruction::JumpForward(6));
+///
+/// let mut small = [BrainfuckInstruction::IncreaseData; 2];
+/// assert!(matches!(
+///     compile_into("+[-]", &mut small),
+///     Err(CompileIntoError::BufferTooSmall { needed: 4 })
+/// ));
+/// ```
+pub fn compile_into<'a, 'b>(
+    input: &'a str,
+    buffer: &'b mut [BrainfuckInstruction],
+) -> Result<&'b [BrainfuckInstruction], CompileIntoError<'a>> {
+    let needed = parse(input).count();
+    if needed > buffer.len() {
+        return Err(CompileIntoError::BufferTooSmall { needed });
+    }
+    let instructions = &mut buffer[..needed];
+
+    let mut open = NO_PARENT;
+    for (index, span) in parse(input).enumerate() {
+        instructions[index] = match span.instruction {
+            BrainfuckInstruction::JumpForward(_) => {
+                let parent = open;
+                open = index;
+                BrainfuckInstruction::JumpForward(parent)
+            }
+            BrainfuckInstruction::JumpBackwards(_) => {
+                if open == NO_PARENT {
+                    return Err(Error::MissingOpeningBrace(span).into());
+                }
+                let opener = open;
+                open = instructions[opener].target().unwrap_or(NO_PARENT);
+                instructions[opener] = BrainfuckInstruction::JumpForward(index);
+                BrainfuckInstruction::JumpBackwards(opener)
+            }
+            instruction => instruction,
+        };
+    }
+
+    if open != NO_PARENT {
+        // Report the outermost unmatched `[`, which comes first in the source
+        while let Some(parent) = instructions[open].target().filter(|p| *p != NO_PARENT) {
+            open = parent;
+        }
+        let span = parse(input)
+            .nth(open)
+            .expect("The unmatched bracket was parsed");
+        return Err(Error::MissingClosingBrace(span).into());
+    }
+
+    Ok(instructions)
+}
+
+/// Runs a slice of instructions on a tape, without allocating
+///
+/// This is the core of the interpreter for `no_std` targets without a global allocator.
+/// The instructions can come from [`compile_into`], or from the `brainfuck!` macro of
+/// the `macros` feature. Procedures and the stack need memory to store them, so
+/// [`BrainfuckInstruction::DefineProcedure`], [`BrainfuckInstruction::CallProcedure`],
+/// [`BrainfuckInstruction::Push`] and [`BrainfuckInstruction::Pop`] fail with a
+/// [`RuntimeError::Unsupported`]. Use `BrainfuckProgram`, which needs the `alloc`
+/// feature, to run them.
+///
+/// ```
+/// # use brainfuck_interpreter::{compile_into, BrainfuckInstruction, Interpreter};
+/// let mut buffer = [BrainfuckInstruction::IncreaseData; 32];
+/// let instructions = compile_into("++++++++[>++++<-]>+.", &mut buffer).unwrap();
+///
+/// let mut interpreter = Interpreter::new(instructions, [0u8; 4]);
+/// let mut output = [0u8; 1];
+/// interpreter.try_run(&mut |data| output[0] = data, &mut || 0).unwrap();
+/// assert_eq!(&output, b"!");
+/// assert_eq!(interpreter.data_pointer(), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Interpreter<'a, T>
+where
+    T: Tape,
+{
+    instructions: &'a [BrainfuckInstruction],
+    tape: T,
+    instruction_pointer: usize,
+    data_pointer: usize,
+    execution_count: usize,
+}
+
+impl<'a, T> Interpreter<'a, T>
+where
+    T: Tape,
+{
+    /// Create an interpreter that runs `instructions` on `tape`
+    ///
+    /// The jump targets of `instructions` are not checked: a jump that does not
+    /// target its matching bracket makes the program misbehave.
+    pub fn new(instructions: &'a [BrainfuckInstruction], tape: T) -> Self {
+        Self {
+            instructions,
+            tape,
+            instruction_pointer: 0,
+            data_pointer: 0,
+            execution_count: 0,
+        }
+    }
+
+    /// The instructions that are run
+    pub fn instructions(&self) -> &'a [BrainfuckInstruction] {
+        self.instructions
+    }
+
+    /// The tape
+    pub fn tape(&self) -> &T {
+        &self.tape
+    }
+
+    /// The tape, mutably
+    pub fn tape_mut(&mut self) -> &mut T {
+        &mut self.tape
+    }
+
+    /// Consume this interpreter, returning its tape
+    pub fn into_tape(self) -> T {
+        self.tape
+    }
+
+    /// The index of the instruction that is executed next
+    pub fn instruction_pointer(&self) -> usize {
+        self.instruction_pointer
+    }
+
+    /// The index of the cell that the program is working on
+    pub fn data_pointer(&self) -> usize {
+        self.data_pointer
+    }
+
+    /// The amount of instructions that have been executed
+    pub fn execution_count(&self) -> usize {
+        self.execution_count
+    }
+
+    /// Start over from the first instruction and cell, and reset the tape
+    pub fn reset(&mut self) {
+        self.instruction_pointer = 0;
+        self.data_pointer = 0;
+        self.execution_count = 0;
+        self.tape.reset();
+    }
+
+    /// Perform a step in the program, returning whether it is still running, or the
+    /// error that stopped it
+    ///
+    /// After an error, the instruction pointer and data pointer are left as they were
+    /// before the step, so that they point at the cause of the error.
+    pub fn try_step<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<bool, RuntimeError>
+    where
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
+    {
+        let instruction_pointer = self.instruction_pointer;
+        let instruction = match self.instructions.get(instruction_pointer) {
+            Some(instruction) => *instruction,
+            None => return Ok(false),
+        };
+
+        let data = match self.tape.get_data_at_mut(self.data_pointer) {
+            Some(data) => data,
+            None => {
+                return Err(RuntimeError::TapeExhausted {
+                    data_pointer: self.data_pointer,
+                    position: None,
+                    excerpt: None,
+                })
+            }
+        };
+
+        match instruction {
+            BrainfuckInstruction::IncrementDataPointer => {
+                self.data_pointer = self.data_pointer.wrapping_add(1)
+            }
+            // Moving left of the first cell is reported when the next instruction is
+            // executed, as there is no cell there
+            BrainfuckInstruction::DecrementDataPointer => {
+                self.data_pointer = self.data_pointer.wrapping_sub(1)
+            }
+            BrainfuckInstruction::IncreaseData => {
+                if !data.try_increase() {
+                    return Err(RuntimeError::CellOverflow {
+                        instruction_pointer,
+                        data_pointer: self.data_pointer,
+                        position: None,
+                        excerpt: None,
+                    });
+                }
+            }
+            BrainfuckInstruction::DecreaseData => {
+                if !data.try_decrease() {
+                    return Err(RuntimeError::CellOverflow {
+                        instruction_pointer,
+                        data_pointer: self.data_pointer,
+                        position: None,
+                        excerpt: None,
+                    });
+                }
+            }
+            BrainfuckInstruction::Output => output.output(data.clone()),
+            BrainfuckInstruction::Input => *data = input.input(),
+            BrainfuckInstruction::JumpForward(target) => {
+                if *data == T::Data::zero() {
+                    self.instruction_pointer = target;
+                }
+            }
+            BrainfuckInstruction::JumpBackwards(target) => {
+                if *data != T::Data::zero() {
+                    self.instruction_pointer = target;
+                }
+            }
+            BrainfuckInstruction::HostCall => {
+                return Err(RuntimeError::HostCall {
+                    instruction_pointer,
+                    position: None,
+                    excerpt: None,
+                    error: HostCallError::NoHost,
+                })
+            }
+            BrainfuckInstruction::MoveUp | BrainfuckInstruction::MoveDown => {
+                let row_len = match self.tape.row_len() {
+                    Some(row_len) => row_len,
+                    None => {
+                        return Err(RuntimeError::NotAGrid {
+                            instruction_pointer,
+                            position: None,
+                            excerpt: None,
+                        })
+                    }
+                };
+                let target = match instruction {
+                    BrainfuckInstruction::MoveUp => self.data_pointer.checked_sub(row_len),
+                    _ => self.data_pointer.checked_add(row_len),
+                };
+                self.data_pointer = target.unwrap_or(usize::MAX);
+            }
+            BrainfuckInstruction::PreviousTape | BrainfuckInstruction::NextTape => {
+                let next = instruction == BrainfuckInstruction::NextTape;
+                match self.tape.switch_tape(next, self.data_pointer) {
+                    Some(target) => self.data_pointer = target,
+                    None => {
+                        return Err(RuntimeError::SingleTape {
+                            instruction_pointer,
+                            position: None,
+                            excerpt: None,
+                        })
+                    }
+                }
+            }
+            // Outside of a call, the end of a procedure does nothing
+            BrainfuckInstruction::EndProcedure => {}
+            BrainfuckInstruction::DefineProcedure(_)
+            | BrainfuckInstruction::CallProcedure
+            | BrainfuckInstruction::Push
+            | BrainfuckInstruction::Pop => {
+                return Err(RuntimeError::Unsupported {
+                    instruction_pointer,
+                    instruction,
+                })
+            }
+        }
+
+        self.execution_count += 1;
+        self.instruction_pointer += 1;
+        Ok(true)
+    }
+
+    /// Run the program to completion, or until it fails
+    ///
+    /// The exit status of the [`RunResult`] is the value of the first cell.
+    pub fn try_run<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<RunResult<T::Data>, RuntimeError>
+    where
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
+    {
+        let start = RunStart::at(self.execution_count);
+        let (mut output, mut input) = (Counted::new(output), Counted::new(input));
+        while self.try_step(&mut output, &mut input)? {}
+        let exit_status = self.tape.get_data_at(0).cloned();
+        Ok(start.finish_at(
+            RunOutcome::Halted,
+            self.execution_count,
+            self.data_pointer,
+            exit_status,
+            &output,
+            &input,
+        ))
+    }
+}
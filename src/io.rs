@@ -0,0 +1,1143 @@
+//! Input and output of Brainfuck programs
+//!
+//! Programs write their output to an [`Output`], and read their input from an [`Input`].
+//! Both are implemented for closures, so `&mut |data| ...` and `&mut || ...` can be
+//! used directly, as well as for the adapters in this module.
+
+#[cfg(feature = "alloc")]
+use alloc::{collections::VecDeque, vec::Vec};
+
+#[cfg(feature = "std")]
+use crate::{BrainfuckProgram, EofBehavior, RunResult, Tape};
+use crate::{SeededRng, TapeData};
+
+/// Something that a Brainfuck program can write its output to
+pub trait Output<D> {
+    /// Write a single piece of data, as produced by `.`
+    fn output(&mut self, data: D);
+}
+
+impl<D, F> Output<D> for F
+where
+    F: FnMut(D),
+{
+    fn output(&mut self, data: D) {
+        self(data)
+    }
+}
+
+/// Something that a Brainfuck program can read its input from
+pub trait Input<D> {
+    /// Read a single piece of data, as requested by `,`
+    fn input(&mut self) -> D;
+}
+
+impl<D, F> Input<D> for F
+where
+    F: FnMut() -> D,
+{
+    fn input(&mut self) -> D {
+        self()
+    }
+}
+
+/// A way of representing the data of a program as bytes, for `.` and `,`
+///
+/// Use a [`CodecOutput`] and a [`CodecInput`] to run a program with a codec.
+pub trait IoCodec<D> {
+    /// Write the bytes that represent `data` to `output`
+    fn encode<O>(&mut self, data: D, output: &mut O)
+    where
+        O: Output<u8>;
+
+    /// Read the next piece of data from `input`, which returns `None` once it has run
+    /// out, or return `None` if the input runs out before any data is read
+    fn decode<I>(&mut self, input: &mut I) -> Option<D>
+    where
+        I: FnMut() -> Option<u8>;
+}
+
+/// An [`IoCodec`] that passes on bytes unchanged
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RawCodec;
+
+impl IoCodec<u8> for RawCodec {
+    fn encode<O>(&mut self, data: u8, output: &mut O)
+    where
+        O: Output<u8>,
+    {
+        output.output(data)
+    }
+
+    fn decode<I>(&mut self, input: &mut I) -> Option<u8>
+    where
+        I: FnMut() -> Option<u8>,
+    {
+        input()
+    }
+}
+
+/// An [`IoCodec`] that represents data as decimal numbers
+///
+/// `.` writes the value of the cell in decimal, followed by the separator. `,` skips
+/// everything up to the next digit, and reads the number that starts there, along
+/// with the byte that ends it. Numbers that do not fit in a cell wrap around.
+///
+/// ```
+/// # use brainfuck_interpreter::{io::*, BrainfuckProgram};
+/// // Add two numbers
+/// let mut program = BrainfuckProgram::compile(",>,[-<+>]<.", vec![0u8; 2]).unwrap();
+/// let mut output = CodecOutput::new(DecimalCodec::default(), VecOutput::new());
+/// let mut input = CodecInput::new(DecimalCodec::default(), b"12 30".iter().copied());
+/// program.run(&mut output, &mut input);
+/// assert_eq!(output.into_inner().data(), b"42\n");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DecimalCodec {
+    /// The byte that is written after every number
+    pub separator: u8,
+}
+
+impl Default for DecimalCodec {
+    fn default() -> Self {
+        Self { separator: b'\n' }
+    }
+}
+
+/// Implement [`DecimalCodec`] for unsigned integers
+macro_rules! impl_decimal_codec {
+    ($($ty:ty),*) => {
+        $(
+            impl IoCodec<$ty> for DecimalCodec {
+                fn encode<O>(&mut self, data: $ty, output: &mut O)
+                where
+                    O: Output<u8>,
+                {
+                    let mut digits = [0u8; 10];
+                    let mut len = 0;
+                    let mut value = data;
+                    loop {
+                        digits[len] = b'0' + (value % 10) as u8;
+                        len += 1;
+                        value /= 10;
+                        if value == 0 {
+                            break;
+                        }
+                    }
+                    for digit in digits[..len].iter().rev() {
+                        output.output(*digit);
+                    }
+                    output.output(self.separator);
+                }
+
+                fn decode<I>(&mut self, input: &mut I) -> Option<$ty>
+                where
+                    I: FnMut() -> Option<u8>,
+                {
+                    let mut byte = input()?;
+                    while !byte.is_ascii_digit() {
+                        byte = input()?;
+                    }
+                    let mut value: $ty = 0;
+                    while byte.is_ascii_digit() {
+                        value = value.wrapping_mul(10).wrapping_add((byte - b'0') as $ty);
+                        byte = match input() {
+                            Some(byte) => byte,
+                            None => break,
+                        };
+                    }
+                    Some(value)
+                }
+            }
+        )*
+    };
+}
+
+impl_decimal_codec!(u8, u16, u32);
+
+/// An [`Output`] that encodes data with an [`IoCodec`], and writes the resulting bytes
+/// to another output
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CodecOutput<C, O> {
+    codec: C,
+    output: O,
+}
+
+impl<C, O> CodecOutput<C, O> {
+    /// Create an output that encodes data with `codec` and writes it to `output`
+    pub fn new(codec: C, output: O) -> Self {
+        Self { codec, output }
+    }
+
+    /// Take the wrapped output
+    pub fn into_inner(self) -> O {
+        self.output
+    }
+}
+
+impl<D, C, O> Output<D> for CodecOutput<C, O>
+where
+    C: IoCodec<D>,
+    O: Output<u8>,
+{
+    fn output(&mut self, data: D) {
+        self.codec.encode(data, &mut self.output)
+    }
+}
+
+/// An [`Input`] that decodes data with an [`IoCodec`] from the bytes of an iterator,
+/// and reads zero once they have run out
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CodecInput<C, I> {
+    codec: C,
+    bytes: I,
+}
+
+impl<C, I> CodecInput<C, I>
+where
+    I: Iterator<Item = u8>,
+{
+    /// Create an input that decodes `bytes` with `codec`
+    pub fn new(codec: C, bytes: I) -> Self {
+        Self { codec, bytes }
+    }
+
+    /// Decode the next piece of data, returning `None` once the bytes have run out
+    pub fn next_input<D>(&mut self) -> Option<D>
+    where
+        C: IoCodec<D>,
+    {
+        let bytes = &mut self.bytes;
+        self.codec.decode(&mut || bytes.next())
+    }
+}
+
+impl<D, C, I> Input<D> for CodecInput<C, I>
+where
+    C: IoCodec<D>,
+    I: Iterator<Item = u8>,
+    D: TapeData,
+{
+    fn input(&mut self) -> D {
+        self.next_input().unwrap_or_else(D::zero)
+    }
+}
+
+/// An [`Output`] that discards everything
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct NullOutput;
+
+impl<D> Output<D> for NullOutput {
+    fn output(&mut self, _: D) {}
+}
+
+/// An [`Output`] that collects everything into a `Vec`
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct VecOutput<D> {
+    data: Vec<D>,
+}
+
+#[cfg(feature = "alloc")]
+impl<D> VecOutput<D> {
+    /// Create a new, empty output
+    pub fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    /// Everything that has been output so far
+    pub fn data(&self) -> &[D] {
+        &self.data
+    }
+
+    /// Take the collected output
+    pub fn into_inner(self) -> Vec<D> {
+        self.data
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<D> Output<D> for VecOutput<D> {
+    fn output(&mut self, data: D) {
+        self.data.push(data)
+    }
+}
+
+/// How an [`OutputDecoder`] turns the data that a program outputs into bytes
+#[cfg(feature = "alloc")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DecodeMode {
+    /// Pass on the lowest byte of every piece of data unchanged
+    #[default]
+    RawBytes,
+    /// Treat the lowest byte of every piece of data as part of UTF-8 text, replacing
+    /// invalid sequences with `U+FFFD REPLACEMENT CHARACTER`
+    Utf8Lossy,
+    /// Treat every piece of data as a Unicode scalar value, and encode it as UTF-8,
+    /// replacing values that are not scalar values with `U+FFFD REPLACEMENT CHARACTER`
+    ///
+    /// This lets programs with cells wider than a byte print any character with a
+    /// single `.`.
+    CodePoint,
+}
+
+/// An [`Output`] that decodes the output of a program according to a [`DecodeMode`],
+/// and collects the resulting bytes
+///
+/// In [`DecodeMode::Utf8Lossy`], bytes that may be the start of a character are held
+/// back until the character is complete, so that the decoded output is always valid
+/// UTF-8. Call [`finish`](Self::finish) once the program has finished to flush them.
+///
+/// ```
+/// # use brainfuck_interpreter::{io::{DecodeMode, OutputDecoder}, BrainfuckProgram};
+/// // Print U+00E9 LATIN SMALL LETTER E WITH ACUTE with a single `.`
+/// let source = "+++++++++++++++[>+++++++++++++++<-]>++++++++.";
+/// let mut program = BrainfuckProgram::compile(source, vec![0u32; 2]).unwrap();
+/// let mut output = OutputDecoder::new(DecodeMode::CodePoint);
+/// program.run(&mut output, &mut || 0);
+/// assert_eq!(output.finish(), "é".as_bytes());
+///
+/// let mut output = OutputDecoder::new(DecodeMode::Utf8Lossy);
+/// for byte in [0xc3, 0xa9, 0xff] {
+///     output.push(byte);
+/// }
+/// assert_eq!(output.finish(), "é\u{fffd}".as_bytes());
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct OutputDecoder {
+    mode: DecodeMode,
+    /// The bytes of an incomplete character, in [`DecodeMode::Utf8Lossy`]
+    pending: Vec<u8>,
+    decoded: Vec<u8>,
+}
+
+#[cfg(feature = "alloc")]
+impl OutputDecoder {
+    /// The UTF-8 encoding of `U+FFFD REPLACEMENT CHARACTER`
+    const REPLACEMENT: &'static [u8] = "\u{fffd}".as_bytes();
+
+    /// Create a decoder without any output
+    pub fn new(mode: DecodeMode) -> Self {
+        Self {
+            mode,
+            pending: Vec::new(),
+            decoded: Vec::new(),
+        }
+    }
+
+    /// The mode of this decoder
+    pub fn mode(&self) -> DecodeMode {
+        self.mode
+    }
+
+    /// The output that has been decoded so far
+    pub fn decoded(&self) -> &[u8] {
+        &self.decoded
+    }
+
+    /// Take the output that has been decoded so far, for instance to write it to a
+    /// terminal, leaving any incomplete character in the decoder
+    pub fn take(&mut self) -> Vec<u8> {
+        core::mem::take(&mut self.decoded)
+    }
+
+    /// Decode a single piece of data
+    pub fn push(&mut self, data: u32) {
+        match self.mode {
+            DecodeMode::RawBytes => self.decoded.push(data as u8),
+            DecodeMode::Utf8Lossy => {
+                self.pending.push(data as u8);
+                self.decode_pending();
+            }
+            DecodeMode::CodePoint => {
+                let character = char::from_u32(data).unwrap_or(char::REPLACEMENT_CHARACTER);
+                let mut buffer = [0; 4];
+                self.decoded
+                    .extend_from_slice(character.encode_utf8(&mut buffer).as_bytes());
+            }
+        }
+    }
+
+    /// Move the complete characters in `pending` to `decoded`
+    fn decode_pending(&mut self) {
+        while !self.pending.is_empty() {
+            match core::str::from_utf8(&self.pending) {
+                Ok(text) => {
+                    self.decoded.extend_from_slice(text.as_bytes());
+                    self.pending.clear();
+                }
+                Err(error) => {
+                    let valid = error.valid_up_to();
+                    self.decoded.extend_from_slice(&self.pending[..valid]);
+                    match error.error_len() {
+                        Some(len) => {
+                            self.decoded.extend_from_slice(Self::REPLACEMENT);
+                            self.pending.drain(..valid + len);
+                        }
+                        // The character is not complete yet
+                        None => {
+                            self.pending.drain(..valid);
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Take all decoded output, replacing an incomplete character at the end with
+    /// `U+FFFD REPLACEMENT CHARACTER`
+    pub fn finish(mut self) -> Vec<u8> {
+        if !self.pending.is_empty() {
+            self.decoded.extend_from_slice(Self::REPLACEMENT);
+        }
+        self.decoded
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<D> Output<D> for OutputDecoder
+where
+    D: Into<u32>,
+{
+    fn output(&mut self, data: D) {
+        self.push(data.into())
+    }
+}
+
+/// An [`Input`] that reads from a slice, and reads zero once the slice has run out
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SliceInput<'a, D> {
+    data: &'a [D],
+}
+
+impl<'a, D> SliceInput<'a, D> {
+    /// Create an input that reads `data`
+    pub fn new(data: &'a [D]) -> Self {
+        Self { data }
+    }
+
+    /// The data that has not been read yet
+    pub fn remaining(&self) -> &'a [D] {
+        self.data
+    }
+}
+
+impl<'a, D> Input<D> for SliceInput<'a, D>
+where
+    D: TapeData,
+{
+    fn input(&mut self) -> D {
+        match self.data.split_first() {
+            Some((first, rest)) => {
+                self.data = rest;
+                first.clone()
+            }
+            None => D::zero(),
+        }
+    }
+}
+
+/// An [`Input`] that plays back a script of data and ends of input
+///
+/// Every end of input in the script, and every read after the script has run out, reads
+/// zero. Use [`next_input`](Self::next_input) together with
+/// [`BrainfuckProgram::run_with_eof`](crate::BrainfuckProgram::run_with_eof) to handle
+/// them differently.
+///
+/// ```
+/// # use brainfuck_interpreter::{io::ScriptedInput, BrainfuckProgram};
+/// // Echo every line of input, until an empty one is read
+/// let mut program = BrainfuckProgram::compile(",[[.,].,]", vec![0u8; 1]).unwrap();
+/// let mut input = ScriptedInput::new().data(*b"ab").eof().data(*b"c").eof().eof();
+///
+/// let mut output = Vec::new();
+/// program.run(&mut |data| output.push(data), &mut input);
+/// assert_eq!(output, b"ab\0c\0");
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ScriptedInput<D> {
+    script: VecDeque<Option<D>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<D> ScriptedInput<D> {
+    /// Create an input with an empty script
+    pub fn new() -> Self {
+        Self {
+            script: VecDeque::new(),
+        }
+    }
+
+    /// Append `data` to the script
+    pub fn data<I>(mut self, data: I) -> Self
+    where
+        I: IntoIterator<Item = D>,
+    {
+        self.script.extend(data.into_iter().map(Some));
+        self
+    }
+
+    /// Append an end of input to the script
+    pub fn eof(mut self) -> Self {
+        self.script.push_back(None);
+        self
+    }
+
+    /// Read the next entry of the script, returning `None` at an end of input or once
+    /// the script has run out
+    pub fn next_input(&mut self) -> Option<D> {
+        self.script.pop_front().flatten()
+    }
+
+    /// Whether the entire script has been read
+    pub fn is_finished(&self) -> bool {
+        self.script.is_empty()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<D> Input<D> for ScriptedInput<D>
+where
+    D: TapeData,
+{
+    fn input(&mut self) -> D {
+        self.next_input().unwrap_or_else(D::zero)
+    }
+}
+
+/// An input that reads a fixed prefix, and then continues with another input
+///
+/// This is how command-line arguments or other parameters are usually handed to a
+/// Brainfuck program: they are read before the real input, as if they had been typed
+/// first. Running out of the prefix is not an end of input. The first end of input is
+/// the one of the wrapped input, which [`next_input`](Self::next_input) passes on when
+/// the wrapped input reports ends of input as `None`.
+///
+/// ```
+/// # use brainfuck_interpreter::{io::ChainedInput, BrainfuckProgram, EofBehavior};
+/// let mut program = BrainfuckProgram::compile(",[.,]", vec![0u8; 1]).unwrap();
+/// let mut stdin = b"def".iter().copied();
+/// let mut input = ChainedInput::new(*b"abc", || stdin.next());
+///
+/// let mut output = Vec::new();
+/// let output_data = &mut |data| output.push(data);
+/// program.run_with_eof(output_data, &mut || input.next_input(), EofBehavior::Zero);
+/// assert_eq!(output, b"abcdef");
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ChainedInput<D, I> {
+    prefix: VecDeque<D>,
+    inner: I,
+}
+
+#[cfg(feature = "alloc")]
+impl<D, I> ChainedInput<D, I> {
+    /// Create an input that reads `prefix`, and then reads from `inner`
+    pub fn new<P>(prefix: P, inner: I) -> Self
+    where
+        P: IntoIterator<Item = D>,
+    {
+        Self {
+            prefix: prefix.into_iter().collect(),
+            inner,
+        }
+    }
+
+    /// The amount of data in the prefix that has not been read yet
+    pub fn remaining_prefix(&self) -> usize {
+        self.prefix.len()
+    }
+
+    /// The wrapped input
+    pub fn get_ref(&self) -> &I {
+        &self.inner
+    }
+
+    /// Consume this input, returning the wrapped input
+    ///
+    /// Any part of the prefix that has not been read yet is discarded.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+
+    /// Read the next data of the prefix, or once it has run out, of the wrapped input,
+    /// returning `None` at an end of input of the wrapped input
+    pub fn next_input(&mut self) -> Option<D>
+    where
+        I: FnMut() -> Option<D>,
+    {
+        match self.prefix.pop_front() {
+            Some(data) => Some(data),
+            None => (self.inner)(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<D, I> Input<D> for ChainedInput<D, I>
+where
+    I: Input<D>,
+{
+    fn input(&mut self) -> D {
+        match self.prefix.pop_front() {
+            Some(data) => data,
+            None => self.inner.input(),
+        }
+    }
+}
+
+/// An [`Input`] that reads a reproducible stream of pseudorandom bytes, followed by
+/// an end of input
+///
+/// Together with a limit on the amount of instructions, as in
+/// [`RunConfig`](crate::RunConfig), this can be used to check how a program copes with
+/// arbitrary input.
+///
+/// ```
+/// # use brainfuck_interpreter::io::RandomInput;
+/// let mut a = RandomInput::seeded(7, 3);
+/// let mut b = RandomInput::seeded(7, 3);
+/// for _ in 0..3 {
+///     assert_eq!(a.next_input(), b.next_input());
+/// }
+/// assert_eq!(a.next_input(), None);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RandomInput {
+    rng: SeededRng,
+    remaining: usize,
+}
+
+impl RandomInput {
+    /// Create an input that reads `len` pseudorandom bytes generated from `seed`
+    /// before reaching its end
+    pub fn seeded(seed: u64, len: usize) -> Self {
+        Self {
+            rng: SeededRng::new(seed),
+            remaining: len,
+        }
+    }
+
+    /// The amount of bytes that can be read before reaching the end of the input
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Read the next byte, returning `None` once the end of the input has been reached
+    pub fn next_input(&mut self) -> Option<u8> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(self.rng.next_u64() as u8)
+    }
+}
+
+impl Input<u8> for RandomInput {
+    fn input(&mut self) -> u8 {
+        self.next_input().unwrap_or(0)
+    }
+}
+
+/// When an [`InteractiveOutput`] flushes the writer that it wraps
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FlushPolicy {
+    /// Flush after every byte
+    #[default]
+    EveryByte,
+    /// Flush after every newline
+    Newline,
+}
+
+/// An [`Output`] that writes bytes to a [`Write`](std::io::Write) as soon as the
+/// program produces them, flushing it according to a [`FlushPolicy`]
+///
+/// Once writing fails, all further output is discarded. The error can be retrieved
+/// with [`take_error`](Self::take_error).
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct InteractiveOutput<W> {
+    writer: W,
+    policy: FlushPolicy,
+    error: Option<std::io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W> InteractiveOutput<W> {
+    /// Create an output that writes to `writer`
+    pub fn new(writer: W, policy: FlushPolicy) -> Self {
+        Self {
+            writer,
+            policy,
+            error: None,
+        }
+    }
+
+    /// Take the error that made writing fail, if any
+    pub fn take_error(&mut self) -> Option<std::io::Error> {
+        self.error.take()
+    }
+
+    /// Take the wrapped writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W> Output<u8> for InteractiveOutput<W>
+where
+    W: std::io::Write,
+{
+    fn output(&mut self, data: u8) {
+        if self.error.is_some() {
+            return;
+        }
+        let flush = match self.policy {
+            FlushPolicy::EveryByte => true,
+            FlushPolicy::Newline => data == b'\n',
+        };
+        let mut result = self.writer.write_all(&[data]);
+        if flush {
+            result = result.and_then(|_| self.writer.flush());
+        }
+        self.error = result.err();
+    }
+}
+
+/// An [`Input`] that reads a single byte from a [`Read`](std::io::Read) whenever the
+/// program asks for one, without reading ahead
+///
+/// The end of the input, and any error other than an interrupted read, is reported as
+/// `None` by [`next_input`](Self::next_input), and reads zero through [`Input`]. The
+/// error can be retrieved with [`take_error`](Self::take_error).
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct InteractiveInput<R> {
+    reader: R,
+    error: Option<std::io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<R> InteractiveInput<R>
+where
+    R: std::io::Read,
+{
+    /// Create an input that reads from `reader`
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            error: None,
+        }
+    }
+
+    /// Read the next byte, or `None` if the input has run out or reading failed
+    pub fn next_input(&mut self) -> Option<u8> {
+        if self.error.is_some() {
+            return None;
+        }
+        let mut byte = [0u8];
+        loop {
+            match self.reader.read(&mut byte) {
+                Ok(0) => return None,
+                Ok(_) => return Some(byte[0]),
+                Err(error) if error.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(error) => {
+                    self.error = Some(error);
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Take the error that made reading fail, if any
+    pub fn take_error(&mut self) -> Option<std::io::Error> {
+        self.error.take()
+    }
+
+    /// Take the wrapped reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R> Input<u8> for InteractiveInput<R>
+where
+    R: std::io::Read,
+{
+    fn input(&mut self) -> u8 {
+        self.next_input().unwrap_or(0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape<Data = u8>,
+{
+    /// Run the Brainfuck program to completion, reading from stdin and writing to
+    /// stdout as described in [`run_interactive_with`](Self::run_interactive_with)
+    ///
+    /// Every byte of input is passed to the program as soon as the terminal hands it
+    /// over, which usually happens once a line has been entered.
+    pub fn run_interactive(
+        &mut self,
+        policy: FlushPolicy,
+        eof: EofBehavior,
+    ) -> std::io::Result<RunResult<u8>> {
+        let stdout = std::io::stdout();
+        self.run_interactive_with(std::io::stdin().lock(), stdout.lock(), policy, eof)
+    }
+
+    /// Run the Brainfuck program to completion, reading every byte of input from
+    /// `reader` only when `,` asks for it, and writing every byte of output to `writer`
+    /// as soon as `.` produces it, flushing according to `policy`
+    ///
+    /// What `,` does once `reader` has run out is determined by `eof`. If reading or
+    /// writing fails, the program still runs to completion as if the input had run
+    /// out or its output was discarded, after which the first error is returned.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{io::FlushPolicy, programs::ROT13, BrainfuckProgram};
+    /// let mut program = BrainfuckProgram::compile(ROT13.source, vec![0u8; 16]).unwrap();
+    /// let mut output = Vec::new();
+    /// program
+    ///     .run_interactive_with(&b"Hello"[..], &mut output, FlushPolicy::Newline, ROT13.eof)
+    ///     .unwrap();
+    /// assert_eq!(output, b"Uryyb");
+    /// ```
+    pub fn run_interactive_with<R, W>(
+        &mut self,
+        reader: R,
+        writer: W,
+        policy: FlushPolicy,
+        eof: EofBehavior,
+    ) -> std::io::Result<RunResult<u8>>
+    where
+        R: std::io::Read,
+        W: std::io::Write,
+    {
+        let mut input = InteractiveInput::new(reader);
+        let mut output = InteractiveOutput::new(writer, policy);
+        let result = self.run_with_eof(&mut output, &mut || input.next_input(), eof);
+
+        if let Some(error) = input.take_error().or_else(|| output.take_error()) {
+            return Err(error);
+        }
+        // Output that was not followed by a newline may not have been flushed yet
+        output.writer.flush()?;
+        Ok(result)
+    }
+}
+
+/// The result of polling a [`TimeoutInput`]
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Polled<D> {
+    /// Data arrived in time
+    Data(D),
+    /// No data arrived before the timeout
+    TimedOut,
+    /// No more data will arrive
+    Closed,
+}
+
+/// An [`Input`] that waits a limited time for data to arrive on a channel, and reads
+/// a sentinel value if none arrives in time
+///
+/// This lets a program that polls for input, such as a game, keep running while
+/// nothing is being entered. Once every sender has been dropped and the channel is
+/// empty, the input reads zero.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use brainfuck_interpreter::{io::TimeoutInput, BrainfuckProgram};
+/// let (sender, mut input) = TimeoutInput::channel(Duration::from_millis(10), b'?');
+/// sender.send(b'a').unwrap();
+///
+/// let mut program = BrainfuckProgram::compile(",.,.", vec![0u8; 1]).unwrap();
+/// let mut output = Vec::new();
+/// program.run(&mut |data| output.push(data), &mut input);
+/// assert_eq!(output, b"a?");
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct TimeoutInput<D> {
+    receiver: std::sync::mpsc::Receiver<D>,
+    timeout: std::time::Duration,
+    sentinel: D,
+}
+
+#[cfg(feature = "std")]
+impl<D> TimeoutInput<D>
+where
+    D: TapeData,
+{
+    /// Create an input that waits up to `timeout` for data from `receiver`, and reads
+    /// `sentinel` if none arrives in time
+    pub fn new(
+        receiver: std::sync::mpsc::Receiver<D>,
+        timeout: std::time::Duration,
+        sentinel: D,
+    ) -> Self {
+        Self {
+            receiver,
+            timeout,
+            sentinel,
+        }
+    }
+
+    /// Create an input together with the sender that feeds it
+    pub fn channel(
+        timeout: std::time::Duration,
+        sentinel: D,
+    ) -> (std::sync::mpsc::Sender<D>, Self) {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        (sender, Self::new(receiver, timeout, sentinel))
+    }
+
+    /// The time that `,` waits for data
+    pub fn timeout(&self) -> std::time::Duration {
+        self.timeout
+    }
+
+    /// Change the time that `,` waits for data
+    pub fn set_timeout(&mut self, timeout: std::time::Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Wait up to the timeout for the next piece of data
+    pub fn poll(&mut self) -> Polled<D> {
+        use std::sync::mpsc::RecvTimeoutError;
+
+        match self.receiver.recv_timeout(self.timeout) {
+            Ok(data) => Polled::Data(data),
+            Err(RecvTimeoutError::Timeout) => Polled::TimedOut,
+            Err(RecvTimeoutError::Disconnected) => Polled::Closed,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl TimeoutInput<u8> {
+    /// Create an input that is fed by a new thread that reads `reader` byte by byte,
+    /// such as [`std::io::stdin`]
+    ///
+    /// The thread stops once `reader` has run out, reading fails, or the input has
+    /// been dropped and another byte has been read.
+    pub fn spawn_reader<R>(reader: R, timeout: std::time::Duration, sentinel: u8) -> Self
+    where
+        R: std::io::Read + Send + 'static,
+    {
+        let (sender, input) = Self::channel(timeout, sentinel);
+        std::thread::spawn(move || {
+            let mut input = InteractiveInput::new(reader);
+            while let Some(byte) = input.next_input() {
+                if sender.send(byte).is_err() {
+                    break;
+                }
+            }
+        });
+        input
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D> Input<D> for TimeoutInput<D>
+where
+    D: TapeData,
+{
+    fn input(&mut self) -> D {
+        match self.poll() {
+            Polled::Data(data) => data,
+            Polled::TimedOut => self.sentinel.clone(),
+            Polled::Closed => D::zero(),
+        }
+    }
+}
+
+/// The bytes that every recording made by [`InputRecorder::recording`] starts with
+#[cfg(feature = "alloc")]
+const RECORDING_MAGIC: &[u8; 4] = b"BFIR";
+
+/// The version of the recording format written by [`InputRecorder::recording`]
+#[cfg(feature = "alloc")]
+pub const RECORDING_VERSION: u8 = 1;
+
+/// An [`Input`] that passes on the data of another input, and keeps a copy of
+/// everything that was read
+///
+/// A recording of the input of one run can be fed back with an [`InputReplayer`] to
+/// reproduce that run exactly.
+///
+/// ```
+/// # use brainfuck_interpreter::{io::{InputRecorder, InputReplayer}, BrainfuckProgram};
+/// let mut program = BrainfuckProgram::compile(",[.,]", vec![0u8; 1]).unwrap();
+/// let mut bytes = b"echo".iter().copied();
+/// let mut input = InputRecorder::new(|| bytes.next().unwrap_or(0));
+/// program.run(&mut |_| {}, &mut input);
+/// let recording = input.recording();
+///
+/// let mut program = BrainfuckProgram::compile(",[.,]", vec![0u8; 1]).unwrap();
+/// let mut output = Vec::new();
+/// let mut input = InputReplayer::from_recording(&recording).unwrap();
+/// program.run(&mut |data| output.push(data), &mut input);
+/// assert_eq!(output, b"echo");
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct InputRecorder<I, D> {
+    inner: I,
+    recorded: Vec<D>,
+}
+
+#[cfg(feature = "alloc")]
+impl<I, D> InputRecorder<I, D> {
+    /// Create a recorder that reads from `inner`
+    pub fn new(inner: I) -> Self {
+        Self {
+            inner,
+            recorded: Vec::new(),
+        }
+    }
+
+    /// Everything that has been read so far
+    pub fn recorded(&self) -> &[D] {
+        &self.recorded
+    }
+
+    /// Take the wrapped input and everything that has been read
+    pub fn into_parts(self) -> (I, Vec<D>) {
+        (self.inner, self.recorded)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I> InputRecorder<I, u8> {
+    /// Everything that has been read so far, in the recording format
+    ///
+    /// The format consists of the bytes `BFIR`, the version [`RECORDING_VERSION`], the
+    /// amount of bytes that were read as a little-endian `u64`, and those bytes.
+    pub fn recording(&self) -> Vec<u8> {
+        let mut recording = Vec::with_capacity(13 + self.recorded.len());
+        recording.extend_from_slice(RECORDING_MAGIC);
+        recording.push(RECORDING_VERSION);
+        recording.extend_from_slice(&(self.recorded.len() as u64).to_le_bytes());
+        recording.extend_from_slice(&self.recorded);
+        recording
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I, D> Input<D> for InputRecorder<I, D>
+where
+    I: Input<D>,
+    D: Clone,
+{
+    fn input(&mut self) -> D {
+        let data = self.inner.input();
+        self.recorded.push(data.clone());
+        data
+    }
+}
+
+/// A problem with a recording passed to [`InputReplayer::from_recording`]
+#[cfg(feature = "alloc")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RecordingError {
+    /// The recording does not start with `BFIR`
+    NotARecording,
+    /// The recording has a version that is not supported
+    UnsupportedVersion(u8),
+    /// The recording is shorter than its header says
+    Truncated,
+    /// The recording is longer than its header says
+    TrailingData,
+}
+
+#[cfg(feature = "alloc")]
+impl core::fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RecordingError::NotARecording => write!(f, "The data is not an input recording"),
+            RecordingError::UnsupportedVersion(version) => {
+                write!(
+                    f,
+                    "Input recordings of version {} are not supported",
+                    version
+                )
+            }
+            RecordingError::Truncated => write!(f, "The input recording is truncated"),
+            RecordingError::TrailingData => {
+                write!(f, "The input recording has data after its end")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RecordingError {}
+
+/// An [`Input`] that feeds back the data read through an [`InputRecorder`], and reads
+/// zero once it has run out
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct InputReplayer<D> {
+    data: VecDeque<D>,
+}
+
+#[cfg(feature = "alloc")]
+impl<D> InputReplayer<D> {
+    /// Create an input that feeds back `data`
+    pub fn new(data: Vec<D>) -> Self {
+        Self { data: data.into() }
+    }
+
+    /// The amount of data that has not been fed back yet
+    pub fn remaining(&self) -> usize {
+        self.data.len()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl InputReplayer<u8> {
+    /// Create an input that feeds back the data in `recording`, as created by
+    /// [`InputRecorder::recording`]
+    pub fn from_recording(recording: &[u8]) -> Result<Self, RecordingError> {
+        let rest = recording
+            .strip_prefix(RECORDING_MAGIC)
+            .ok_or(RecordingError::NotARecording)?;
+        let (version, rest) = rest.split_first().ok_or(RecordingError::Truncated)?;
+        if *version != RECORDING_VERSION {
+            return Err(RecordingError::UnsupportedVersion(*version));
+        }
+        if rest.len() < 8 {
+            return Err(RecordingError::Truncated);
+        }
+        let (len_bytes, data) = rest.split_at(8);
+        let mut len = [0; 8];
+        len.copy_from_slice(len_bytes);
+        let len = u64::from_le_bytes(len);
+        match (data.len() as u64).cmp(&len) {
+            core::cmp::Ordering::Less => Err(RecordingError::Truncated),
+            core::cmp::Ordering::Greater => Err(RecordingError::TrailingData),
+            core::cmp::Ordering::Equal => Ok(Self::new(data.to_vec())),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<D> Input<D> for InputReplayer<D>
+where
+    D: TapeData,
+{
+    fn input(&mut self) -> D {
+        self.data.pop_front().unwrap_or_else(D::zero)
+    }
+}
@@ -0,0 +1,217 @@
+//! Turning source code into Brainfuck instructions
+
+use crate::{
+    parse, parse_hosted, parse_multi_tape, parse_ook, parse_pbrain, parse_stack, parse_two_d,
+    BrainfuckInstruction, SourcePos,
+};
+#[cfg(feature = "alloc")]
+use crate::{BrainfuckProgram, Error, Span, Tape};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// A lexer, which maps source code to Brainfuck instructions
+///
+/// Implementing this trait lets programs in languages that are isomorphic to Brainfuck
+/// be compiled using [`BrainfuckProgram::compile_with_lexer`].
+pub trait Lexer {
+    /// Call `token` with every instruction in `input`, in order, together with the
+    /// position in `input` at which it occurs
+    ///
+    /// The targets of jumps are ignored: they are set when the brackets are matched.
+    fn lex(&self, input: &str, token: &mut dyn FnMut(BrainfuckInstruction, SourcePos));
+}
+
+impl<L> Lexer for &L
+where
+    L: Lexer + ?Sized,
+{
+    fn lex(&self, input: &str, token: &mut dyn FnMut(BrainfuckInstruction, SourcePos)) {
+        (**self).lex(input, token)
+    }
+}
+
+/// The lexer for plain Brainfuck. All non-valid characters are ignored
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BrainfuckLexer;
+
+impl Lexer for BrainfuckLexer {
+    fn lex(&self, input: &str, token: &mut dyn FnMut(BrainfuckInstruction, SourcePos)) {
+        parse(input).for_each(|span| token(span.get_instruction(), span.get_position()))
+    }
+}
+
+/// The lexer for [Ook!](https://esolangs.org/wiki/Ook!), see [`parse_ook`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct OokLexer;
+
+impl Lexer for OokLexer {
+    fn lex(&self, input: &str, token: &mut dyn FnMut(BrainfuckInstruction, SourcePos)) {
+        parse_ook(input).for_each(|span| token(span.get_instruction(), span.get_position()))
+    }
+}
+
+/// The lexer for [pbrain](https://esolangs.org/wiki/Pbrain), see [`parse_pbrain`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PBrainLexer;
+
+impl Lexer for PBrainLexer {
+    fn lex(&self, input: &str, token: &mut dyn FnMut(BrainfuckInstruction, SourcePos)) {
+        parse_pbrain(input).for_each(|span| token(span.get_instruction(), span.get_position()))
+    }
+}
+
+/// The lexer for Brainfuck with host calls, see [`parse_hosted`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct HostedLexer;
+
+impl Lexer for HostedLexer {
+    fn lex(&self, input: &str, token: &mut dyn FnMut(BrainfuckInstruction, SourcePos)) {
+        parse_hosted(input).for_each(|span| token(span.get_instruction(), span.get_position()))
+    }
+}
+
+/// The lexer for Brainfuck on a grid, see [`parse_two_d`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TwoDLexer;
+
+impl Lexer for TwoDLexer {
+    fn lex(&self, input: &str, token: &mut dyn FnMut(BrainfuckInstruction, SourcePos)) {
+        parse_two_d(input).for_each(|span| token(span.get_instruction(), span.get_position()))
+    }
+}
+
+/// The lexer for Brainfuck with a stack, see [`parse_stack`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct StackLexer;
+
+impl Lexer for StackLexer {
+    fn lex(&self, input: &str, token: &mut dyn FnMut(BrainfuckInstruction, SourcePos)) {
+        parse_stack(input).for_each(|span| token(span.get_instruction(), span.get_position()))
+    }
+}
+
+/// The lexer for Brainfuck with several tapes, see [`parse_multi_tape`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MultiTapeLexer;
+
+impl Lexer for MultiTapeLexer {
+    fn lex(&self, input: &str, token: &mut dyn FnMut(BrainfuckInstruction, SourcePos)) {
+        parse_multi_tape(input).for_each(|span| token(span.get_instruction(), span.get_position()))
+    }
+}
+
+/// A lexer for languages that replace every Brainfuck command with a different token
+///
+/// At every position, the longest token that matches is used. All text that is not a token
+/// is ignored. Tokens cannot span multiple lines, and empty tokens never match.
+///
+/// ```
+/// # use brainfuck_interpreter::{BrainfuckInstruction, BrainfuckLexer, Lexer, SubstitutionLexer};
+/// // Alphuck
+/// let lexer = SubstitutionLexer::new(["a", "c", "e", "i", "j", "o", "p", "s"]);
+///
+/// let mut instructions = Vec::new();
+/// lexer.lex("pia", &mut |instruction, _| instructions.push(instruction));
+///
+/// let mut expected = Vec::new();
+/// BrainfuckLexer.lex("[->", &mut |instruction, _| expected.push(instruction));
+/// assert_eq!(instructions, expected);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SubstitutionLexer<'t> {
+    tokens: [&'t str; 8],
+}
+
+impl<'t> SubstitutionLexer<'t> {
+    /// Create a new lexer, given the tokens that replace `>`, `<`, `+`, `-`, `.`, `,`, `[`
+    /// and `]`, in that order
+    pub fn new(tokens: [&'t str; 8]) -> Self {
+        Self { tokens }
+    }
+
+    /// The lexer for [ReverseFuck](https://esolangs.org/wiki/ReverseFuck), which swaps every
+    /// pair of opposite commands
+    pub fn reversefuck() -> Self {
+        Self::new(["<", ">", "-", "+", ",", ".", "]", "["])
+    }
+
+    /// The tokens of this lexer, in the order given to [`new`](Self::new)
+    pub fn tokens(&self) -> &[&'t str; 8] {
+        &self.tokens
+    }
+}
+
+impl<'t> Lexer for SubstitutionLexer<'t> {
+    fn lex(&self, input: &str, token: &mut dyn FnMut(BrainfuckInstruction, SourcePos)) {
+        const INSTRUCTIONS: [BrainfuckInstruction; 8] = [
+            BrainfuckInstruction::IncrementDataPointer,
+            BrainfuckInstruction::DecrementDataPointer,
+            BrainfuckInstruction::IncreaseData,
+            BrainfuckInstruction::DecreaseData,
+            BrainfuckInstruction::Output,
+            BrainfuckInstruction::Input,
+            BrainfuckInstruction::JumpForward(0),
+            BrainfuckInstruction::JumpBackwards(0),
+        ];
+
+        for (line, text) in input.lines().enumerate() {
+            let mut end = 0;
+            for (index, (offset, _)) in text.char_indices().enumerate() {
+                if offset < end {
+                    continue;
+                }
+
+                let longest = self
+                    .tokens
+                    .iter()
+                    .zip(INSTRUCTIONS.iter())
+                    .filter(|(t, _)| !t.is_empty() && text[offset..].starts_with(**t))
+                    .max_by_key(|(t, _)| t.len());
+
+                if let Some((matched, instruction)) = longest {
+                    end = offset + matched.len();
+                    token(
+                        *instruction,
+                        SourcePos {
+                            line,
+                            character: index + 1,
+                        },
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Compile a program, given by `input`, using `lexer` to find its instructions
+    pub fn compile_with_lexer<'a, L>(input: &'a str, tape: T, lexer: &L) -> Result<Self, Error<'a>>
+    where
+        L: Lexer + ?Sized,
+    {
+        let spans = Self::compile_spans(input, lexer)?;
+        Ok(Self::from_spans(&spans, tape))
+    }
+}
+
+/// Collect the instructions that `lexer` finds in `input` into spans
+#[cfg(feature = "alloc")]
+pub(crate) fn lex_spans<'a, L>(input: &'a str, lexer: &L) -> Vec<Span<'a>>
+where
+    L: Lexer + ?Sized,
+{
+    let mut spans = Vec::new();
+    lexer.lex(input, &mut |instruction, position| {
+        spans.push(Span {
+            instruction,
+            text: input,
+            line: position.line,
+            character: position.character,
+        })
+    });
+    spans
+}
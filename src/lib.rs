@@ -1,21 +1,390 @@
 //! A simple (and maybe not super efficient) JIT compiler for [`Brainfuck`]
 //!
 //! [`Brainfuck`]: https://en.wikipedia.org/wiki/Brainfuck
+//!
+//! The crate is `no_std` compatible: disable the default `std` feature to use it
+//! without the standard library. The instruction set, [`Span`], [`Error`], the
+//! array and slice tapes, and the [`Interpreter`] that runs the instructions compiled
+//! by [`compile_into`] never allocate. [`BrainfuckProgram`] and the `Vec` tape store
+//! their data on the heap, and are available when the `alloc` feature is enabled.
+//!
+//! Enabling the `forbid-unsafe` feature guarantees that the crate contains no `unsafe`
+//! code, except for the optional `native-jit` backend and `mmap` tape.
+//!
+//! # Concurrency
+//!
+//! A [`BrainfuckProgram`] owns all of its state, so it is [`Send`] and [`Sync`]
+//! whenever its tape and the data on it are. This is the case for all tapes in this
+//! crate, so a program can be moved to a worker thread to run it there. Programs
+//! running on different threads can communicate through the cells of a
+//! [`SharedTape`].
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(missing_docs)]
 #![deny(warnings)]
+// The native JIT backend and the memory-mapped tape are the only code that needs
+// `unsafe`, and are allowed to use it
+#![cfg_attr(
+    all(
+        feature = "forbid-unsafe",
+        not(any(feature = "native-jit", feature = "mmap"))
+    ),
+    forbid(unsafe_code)
+)]
+#![cfg_attr(
+    all(
+        feature = "forbid-unsafe",
+        any(feature = "native-jit", feature = "mmap")
+    ),
+    deny(unsafe_code)
+)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 mod tape;
 
+#[cfg(feature = "alloc")]
+mod analysis;
+
+#[cfg(feature = "animation")]
+mod animation;
+
+#[cfg(feature = "async")]
+mod asynchronous;
+
+#[cfg(feature = "alloc")]
+mod auto;
+
+#[cfg(feature = "alloc")]
+pub mod ast;
+
+#[cfg(feature = "alloc")]
+mod backend;
+
+#[cfg(feature = "std")]
+mod batch;
+
+#[cfg(feature = "alloc")]
+mod buffered;
+
+#[cfg(feature = "alloc")]
+mod builder;
+
+#[cfg(feature = "alloc")]
+mod bytecode;
+
+#[cfg(feature = "alloc")]
+mod cache;
+
+#[cfg(feature = "alloc")]
+mod capture;
+
+mod cell;
+
+#[cfg(feature = "alloc")]
+mod coverage;
+
+#[cfg(feature = "alloc")]
+pub mod debugger;
+
+#[cfg(feature = "alloc")]
+mod diagnostic;
+
+mod dialect;
+
+#[cfg(feature = "alloc")]
+pub mod difftest;
+
+#[cfg(feature = "alloc")]
+mod eof;
+
+#[cfg(feature = "alloc")]
+mod equivalence;
+
+#[cfg(feature = "alloc")]
+mod error;
+
+#[cfg(feature = "alloc")]
+mod executor;
+
+#[cfg(feature = "alloc")]
+mod fold;
+
+#[cfg(feature = "alloc")]
+mod formatter;
+
+#[cfg(feature = "alloc")]
+mod generate;
+
+#[cfg(feature = "alloc")]
+pub mod genetic;
+
+#[cfg(feature = "alloc")]
+mod graph;
+
+#[cfg(feature = "alloc")]
+mod hierarchy;
+
+#[cfg(feature = "alloc")]
+mod host;
+
+#[cfg(feature = "alloc")]
+mod inspect;
+
+mod interpreter;
+
+pub mod io;
+
+mod lexer;
+
+#[cfg(feature = "alloc")]
+mod link;
+
+#[cfg(feature = "lsp")]
+pub mod lsp;
+
+#[cfg(feature = "alloc")]
+mod machine;
+
+#[cfg(feature = "alloc")]
+mod memoize;
+
+#[cfg(feature = "alloc")]
+mod metadata;
+
+#[cfg(feature = "alloc")]
+mod nested;
+
+#[cfg(feature = "alloc")]
+mod observe;
+
+#[cfg(feature = "alloc")]
+mod optimize;
+
+#[cfg(feature = "alloc")]
+mod options;
+
+#[cfg(feature = "alloc")]
+mod pipeline;
+
+#[cfg(feature = "alloc")]
+mod profiler;
+
+#[cfg(feature = "alloc")]
+pub mod programs;
+
+#[cfg(any(feature = "dap", feature = "lsp"))]
+mod protocol;
+
+mod random;
+
+mod result;
+
+#[cfg(feature = "std")]
+mod run;
+
+mod runtime;
+
+#[cfg(feature = "std")]
+pub mod testing;
+
+#[cfg(feature = "alloc")]
+mod threaded;
+
+#[cfg(feature = "alloc")]
+mod tiered;
+
+mod token;
+
+#[cfg(feature = "std")]
+mod trace;
+
+#[cfg(feature = "alloc")]
+mod validate;
+
+#[cfg(feature = "visualize")]
+mod visualize;
+
+#[cfg(feature = "alloc")]
+mod warnings;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "alloc")]
+pub use analysis::*;
+#[cfg(feature = "animation")]
+pub use animation::*;
+#[cfg(feature = "alloc")]
+pub use auto::*;
+#[cfg(feature = "llvm-ir")]
+pub use backend::LlvmOptions;
+#[cfg(feature = "std")]
+pub use batch::*;
+#[cfg(feature = "alloc")]
+pub use buffered::*;
+#[cfg(feature = "alloc")]
+pub use builder::*;
+#[cfg(feature = "alloc")]
+pub use bytecode::*;
+#[cfg(feature = "alloc")]
+pub use cache::*;
+#[cfg(feature = "alloc")]
+pub use capture::*;
+pub use cell::*;
+#[cfg(feature = "alloc")]
+pub use coverage::*;
+#[cfg(feature = "alloc")]
+pub use diagnostic::*;
+pub use dialect::*;
+#[cfg(feature = "alloc")]
+pub use eof::*;
+#[cfg(feature = "alloc")]
+pub use equivalence::*;
+#[cfg(feature = "alloc")]
+pub use error::*;
+#[cfg(feature = "alloc")]
+pub use executor::*;
+#[cfg(feature = "alloc")]
+pub use fold::*;
+#[cfg(feature = "alloc")]
+pub use formatter::*;
+#[cfg(feature = "alloc")]
+pub use generate::*;
+#[cfg(feature = "alloc")]
+pub use hierarchy::*;
+#[cfg(feature = "alloc")]
+pub use host::*;
+pub use interpreter::*;
+pub use lexer::*;
+#[cfg(feature = "alloc")]
+pub use link::*;
+#[cfg(feature = "alloc")]
+pub use machine::*;
+#[cfg(feature = "alloc")]
+pub use memoize::*;
+#[cfg(feature = "alloc")]
+pub use metadata::*;
+#[cfg(feature = "alloc")]
+pub use nested::*;
+#[cfg(feature = "alloc")]
+pub use observe::*;
+#[cfg(feature = "alloc")]
+pub use optimize::{OptimizationLevel, PEEL_LIMIT};
+#[cfg(feature = "alloc")]
+pub use options::*;
+#[cfg(feature = "alloc")]
+pub use pipeline::*;
+#[cfg(feature = "alloc")]
+pub use profiler::*;
+pub use random::*;
+pub use result::*;
+#[cfg(feature = "std")]
+pub use run::*;
+pub use runtime::*;
 pub use tape::*;
+#[cfg(feature = "alloc")]
+pub use threaded::*;
+#[cfg(feature = "alloc")]
+pub use tiered::*;
+pub use token::*;
+#[cfg(feature = "std")]
+pub use trace::*;
+#[cfg(feature = "alloc")]
+pub use validate::*;
+#[cfg(feature = "visualize")]
+pub use visualize::*;
+#[cfg(feature = "alloc")]
+pub use warnings::*;
 
-use std::fmt::Display;
+/// The program can use any string literal, including escapes and raw strings:
+///
+/// ```
+/// use brainfuck_interpreter::{brainfuck, BrainfuckInstruction, BrainfuckInstruction::*};
+///
+/// static ADD: &[BrainfuckInstruction] = brainfuck!("[->+<]");
+/// assert_eq!(
+///     ADD,
+///     [
+///         JumpForward(5),
+///         DecreaseData,
+///         IncrementDataPointer,
+///         IncreaseData,
+///         DecrementDataPointer,
+///         JumpBackwards(0),
+///     ]
+/// );
+/// assert_eq!(brainfuck!("+\n\x2e\u{2C}"), &[IncreaseData, Output, Input]);
+/// assert_eq!(
+///     brainfuck!(r#""[-]" is a loop"#),
+///     &[JumpForward(2), DecreaseData, JumpBackwards(0)]
+/// );
+/// ```
+///
+/// Unmatched brackets fail the build:
+///
+/// ```compile_fail
+/// use brainfuck_interpreter::{brainfuck, BrainfuckInstruction};
+///
+/// static UNMATCHED: &[BrainfuckInstruction] = brainfuck!("+\n[[-]");
+/// ```
+///
+/// ```compile_fail
+/// use brainfuck_interpreter::{brainfuck, BrainfuckInstruction};
+///
+/// static UNMATCHED: &[BrainfuckInstruction] = brainfuck!(r"-]");
+/// ```
+#[cfg(feature = "macros")]
+pub use brainfuck_interpreter_macros::brainfuck;
+
+#[cfg(feature = "alloc")]
+use alloc::{sync::Arc, vec::Vec};
+use core::fmt::{Display, Write};
+#[cfg(feature = "alloc")]
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "alloc")]
+use io::{Input, Output};
+#[cfg(feature = "alloc")]
+use result::{Counted, RunStart};
+
+/// The amount of executed instructions between two progress events, if the `tracing`
+/// feature is enabled
+#[cfg(feature = "tracing")]
+pub const TRACING_PROGRESS_INTERVAL: usize = 1 << 20;
+
+/// The amount of executed instructions between two checks of the cancellation flag in
+/// [`BrainfuckProgram::run_cancellable`]
+#[cfg(feature = "alloc")]
+pub const CANCELLATION_CHECK_INTERVAL: usize = 1 << 10;
 
 /// All valid characters for a Brainfuck program
 pub const VALID_CHARS: [char; 8] = ['>', '<', '+', '-', '.', ',', '[', ']'];
 
 /// All of the instructions available in Brainfuck
-#[derive(Clone, Copy, Debug)]
+///
+/// # Jump targets
+///
+/// [`JumpForward`](Self::JumpForward), [`JumpBackwards`](Self::JumpBackwards) and
+/// [`DefineProcedure`](Self::DefineProcedure) contain the absolute index of their
+/// matching bracket in [`BrainfuckProgram::instructions`], not an offset from their
+/// own index. A jump sets the instruction pointer to its target, after which the
+/// instruction pointer is incremented as usual, so execution continues at the
+/// instruction after the matching bracket. [`target`](Self::target) returns the
+/// target of any instruction, and [`relative_target`](Self::relative_target)
+/// converts it to an offset, for tools that expect one.
+///
+/// Instructions that were not compiled, such as those returned by [`parse`], have a
+/// target of 0.
+///
+/// ```
+/// # use brainfuck_interpreter::{BrainfuckInstruction, BrainfuckProgram};
+/// let program = BrainfuckProgram::compile("+[->+<]", vec![0u8]).unwrap();
+/// assert_eq!(program.instructions()[1], BrainfuckInstruction::JumpForward(6));
+/// assert_eq!(program.instructions()[6].target(), Some(1));
+/// assert_eq!(program.instructions()[6].relative_target(6), Some(-5));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum BrainfuckInstruction {
     /// `>` command, to increment the data pointer
     IncrementDataPointer,
@@ -31,10 +400,103 @@ pub enum BrainfuckInstruction {
     Input,
     /// `[`, to jump to the matching `]` if the data at the data pointer
     /// is zero
+    ///
+    /// Contains the index of the matching `]`. Execution continues at the
+    /// instruction after it.
     JumpForward(usize),
     /// `]`, to jump to the matching `[` if the data at the data pointer
     /// is non-zero
+    ///
+    /// Contains the index of the matching `[`. Execution continues at the
+    /// instruction after it.
     JumpBackwards(usize),
+    /// `(`, to define a procedure, only available in [`Dialect::PBrain`]
+    ///
+    /// The body of the procedure, up to the matching `)`, is stored under the
+    /// data at the data pointer, and execution continues after the matching `)`.
+    /// Contains the index of the matching `)`.
+    DefineProcedure(usize),
+    /// `)`, to return from the current procedure, only available in [`Dialect::PBrain`]
+    EndProcedure,
+    /// `:`, to call the procedure stored under the data at the data pointer,
+    /// only available in [`Dialect::PBrain`]
+    CallProcedure,
+    /// `%`, to call the host function registered under the data at the data pointer,
+    /// only available in [`Dialect::Hosted`]
+    ///
+    /// Host functions are only called when the program is run by a [`Host`]. Otherwise,
+    /// this is a [`RuntimeError::HostCall`].
+    HostCall,
+    /// `^`, to move the data pointer one row up, only available in [`Dialect::TwoD`]
+    ///
+    /// This requires a tape that is a grid, like [`GridTape`], see [`Tape::row_len`].
+    /// Otherwise, this is a [`RuntimeError::NotAGrid`].
+    MoveUp,
+    /// `v`, to move the data pointer one row down, only available in [`Dialect::TwoD`]
+    ///
+    /// This requires a tape that is a grid, like [`GridTape`], see [`Tape::row_len`].
+    /// Otherwise, this is a [`RuntimeError::NotAGrid`].
+    MoveDown,
+    /// `{`, to switch to the previous tape, only available in [`Dialect::MultiTape`]
+    ///
+    /// This requires a tape that holds several tapes, like [`MultiTape`], see
+    /// [`Tape::switch_tape`]. Otherwise, this is a [`RuntimeError::SingleTape`].
+    PreviousTape,
+    /// `}`, to switch to the next tape, only available in [`Dialect::MultiTape`]
+    ///
+    /// This requires a tape that holds several tapes, like [`MultiTape`], see
+    /// [`Tape::switch_tape`]. Otherwise, this is a [`RuntimeError::SingleTape`].
+    NextTape,
+    /// `@`, to push the data at the data pointer onto the
+    /// [stack](BrainfuckProgram::stack), only available in [`Dialect::Stack`]
+    Push,
+    /// `$`, to pop the top of the [stack](BrainfuckProgram::stack) into the cell at the
+    /// data pointer, only available in [`Dialect::Stack`]
+    ///
+    /// Popping from an empty stack is a [`RuntimeError::StackUnderflow`].
+    Pop,
+}
+
+impl BrainfuckInstruction {
+    /// The character that represents this instruction in source code
+    pub fn as_char(&self) -> char {
+        match self {
+            BrainfuckInstruction::IncrementDataPointer => '>',
+            BrainfuckInstruction::DecrementDataPointer => '<',
+            BrainfuckInstruction::IncreaseData => '+',
+            BrainfuckInstruction::DecreaseData => '-',
+            BrainfuckInstruction::Output => '.',
+            BrainfuckInstruction::Input => ',',
+            BrainfuckInstruction::JumpForward(_) => '[',
+            BrainfuckInstruction::JumpBackwards(_) => ']',
+            BrainfuckInstruction::DefineProcedure(_) => '(',
+            BrainfuckInstruction::EndProcedure => ')',
+            BrainfuckInstruction::CallProcedure => ':',
+            BrainfuckInstruction::HostCall => '%',
+            BrainfuckInstruction::MoveUp => '^',
+            BrainfuckInstruction::MoveDown => 'v',
+            BrainfuckInstruction::PreviousTape => '{',
+            BrainfuckInstruction::NextTape => '}',
+            BrainfuckInstruction::Push => '@',
+            BrainfuckInstruction::Pop => '$',
+        }
+    }
+
+    /// The absolute index of the matching bracket of this instruction, if it has one
+    pub fn target(&self) -> Option<usize> {
+        match self {
+            BrainfuckInstruction::JumpForward(target)
+            | BrainfuckInstruction::JumpBackwards(target)
+            | BrainfuckInstruction::DefineProcedure(target) => Some(*target),
+            _ => None,
+        }
+    }
+
+    /// The offset from `index`, the index of this instruction, to its matching bracket,
+    /// if it has one
+    pub fn relative_target(&self, index: usize) -> Option<isize> {
+        self.target().map(|target| target as isize - index as isize)
+    }
 }
 
 /// An instruction, its position in a piece of source code,
@@ -53,32 +515,60 @@ pub struct Span<'a> {
 /// I.e.:
 /// [[..,,>><<]]
 ///       ^
+///
+/// See [`Diagnostic`] for line numbers, context and messages.
 impl<'a> Display for Span<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut arrow = String::with_capacity(self.character);
-        for _ in 0..self.character - 1 {
-            arrow.push(' ');
-        }
-        arrow.push('^');
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let text_line = self.text.lines().nth(self.line).unwrap_or("");
+        f.write_str(text_line)?;
+        f.write_char('\n')?;
+        write_padding(f, text_line, self.character)?;
+        f.write_char('^')
+    }
+}
 
-        let mut text_line = None;
-        for (index, line) in self.text.lines().enumerate() {
-            if index == self.line {
-                text_line = Some(line);
-                break;
-            }
-        }
+/// Write the whitespace that puts the next character below `character`, starting at 1,
+/// of `line`
+///
+/// Tabs are kept, so that the character lines up however wide a tab is displayed.
+pub(crate) fn write_padding(
+    f: &mut core::fmt::Formatter<'_>,
+    line: &str,
+    character: usize,
+) -> core::fmt::Result {
+    line.chars()
+        .chain(core::iter::repeat(' '))
+        .take(character.saturating_sub(1))
+        .try_for_each(|c| f.write_char(if c == '\t' { '\t' } else { ' ' }))
+}
 
-        let text_line = match text_line {
-            Some(text_line) => text_line,
-            None => return Err(std::fmt::Error),
-        };
+/// A position in a piece of source code
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SourcePos {
+    /// The line, starting at 0
+    pub line: usize,
+    /// The character within `line`, starting at 1
+    pub character: usize,
+}
 
-        f.write_str(format!("{}\n{}", text_line, arrow).as_str())
+impl Display for SourcePos {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "line {}, character {}", self.line, self.character)
     }
 }
 
 impl<'a> Span<'a> {
+    /// Get the position in the source code where this span is located
+    pub fn get_position(&self) -> SourcePos {
+        SourcePos {
+            line: self.line,
+            character: self.character,
+        }
+    }
+    /// Get the instruction that this span is about
+    pub fn get_instruction(&self) -> BrainfuckInstruction {
+        self.instruction
+    }
     /// Get the full source code in which this span
     /// is located
     pub fn get_text(&self) -> &str {
@@ -91,22 +581,153 @@ impl<'a> Span<'a> {
     }
 }
 
+/// Parse `input` into its instructions, together with the location at which they occur.
+/// All non-valid characters are ignored
+///
+/// This does not allocate, and does not match brackets: all jumps have a target of 0
+pub fn parse(input: &str) -> impl Iterator<Item = Span<'_>> {
+    parse_chars(input, brainfuck_instruction)
+}
+
+/// The instruction represented by `character` in plain Brainfuck
+pub(crate) fn brainfuck_instruction(character: char) -> Option<BrainfuckInstruction> {
+    Some(match character {
+        '>' => BrainfuckInstruction::IncrementDataPointer,
+        '<' => BrainfuckInstruction::DecrementDataPointer,
+        '+' => BrainfuckInstruction::IncreaseData,
+        '-' => BrainfuckInstruction::DecreaseData,
+        '.' => BrainfuckInstruction::Output,
+        ',' => BrainfuckInstruction::Input,
+        '[' => BrainfuckInstruction::JumpForward(0),
+        ']' => BrainfuckInstruction::JumpBackwards(0),
+        _ => return None,
+    })
+}
+
+/// Parse `input`, in which every instruction is a single character that is
+/// mapped to its instruction by `instruction`
+pub(crate) fn parse_chars<F>(input: &str, instruction: F) -> impl Iterator<Item = Span<'_>>
+where
+    F: Fn(char) -> Option<BrainfuckInstruction> + Copy,
+{
+    input.lines().enumerate().flat_map(move |(line, text)| {
+        text.chars()
+            .enumerate()
+            .filter_map(move |(index, character)| {
+                Some(Span {
+                    instruction: instruction(character)?,
+                    text: input,
+                    line,
+                    character: index + 1,
+                })
+            })
+    })
+}
+
 /// A Brainfuck program
+#[cfg(feature = "alloc")]
 #[derive(Clone, Debug)]
 pub struct BrainfuckProgram<T>
 where
     T: Tape,
 {
     /// The instruction pointer of this program
+    #[deprecated(
+        note = "use `instruction_pointer()` instead, this field will become private in 2.0"
+    )]
     pub instruction_pointer: usize,
     /// The data poitner of this program
+    #[deprecated(
+        note = "use `data_pointer()` and `set_data_pointer()` instead, this field will become private in 2.0"
+    )]
     pub data_pointer: usize,
     /// The instructions of this program
+    ///
+    /// Jumps contain the absolute index of their target in this list, see
+    /// [`BrainfuckInstruction`].
+    #[deprecated(
+        note = "use `instructions()` and `set_instructions()` instead, this field will become private in 2.0"
+    )]
     pub instructions: Vec<BrainfuckInstruction>,
     /// The tape of this program
     pub tape: T,
     /// The amount of instructions that have been executed
     pub execution_count: usize,
+    /// The position in the source code of every instruction, if the program was
+    /// compiled with [`compile_with_source_map`](Self::compile_with_source_map)
+    pub source_map: Option<Vec<SourcePos>>,
+    /// The source code that the program was compiled from, if it has a source map
+    ///
+    /// This is used to show the code around an instruction, as done by
+    /// [`explain`](Self::explain) and [`current_span`](Self::current_span).
+    pub source: Option<Arc<str>>,
+    /// The procedures that have been defined, as the data they are stored under
+    /// and the index of their [`BrainfuckInstruction::DefineProcedure`]
+    pub procedures: Vec<(T::Data, usize)>,
+    /// The instruction pointers of the procedure calls that have not returned yet
+    pub call_stack: Vec<usize>,
+    /// The data that has been pushed with [`BrainfuckInstruction::Push`] and not popped
+    /// yet, with the top of the stack last
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{io::SliceInput, BrainfuckProgram, CompileOptions, Dialect};
+    /// let options = CompileOptions { dialect: Dialect::Stack, ..CompileOptions::default() };
+    /// // Reverse three bytes of input
+    /// let source = ",@,@,@ $.$.$.";
+    /// let mut program = BrainfuckProgram::compile_with_options(source, vec![0u8; 1], &options)
+    ///     .unwrap();
+    ///
+    /// let mut output = Vec::new();
+    /// program.run(&mut |value| output.push(value), &mut SliceInput::new(b"abc"));
+    /// assert_eq!(output, b"cba");
+    /// assert!(program.stack.is_empty());
+    /// ```
+    pub stack: Vec<T::Data>,
+    /// The cell whose value is the exit status of the program, as reported by the
+    /// [`RunResult`] of [`run`](Self::run)
+    pub exit_cell: usize,
+    /// Whether a `<` at the first cell, or a `>` at the last cell of the tape, makes
+    /// [`try_step`](Self::try_step) return a [`RuntimeError::PointerOutOfBounds`]
+    /// with the position of the move
+    ///
+    /// This only has an effect if the program has a source map. Without strict mode,
+    /// the error only shows up at the next instruction, as a
+    /// [`RuntimeError::TapeExhausted`]: a `<` at the first cell wraps the data pointer
+    /// around to a cell that no tape has.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckProgram, CompileOptions, RuntimeError, SourcePos};
+    /// let options = CompileOptions { strict: true, ..CompileOptions::default() };
+    /// let source = "+>\n+<<";
+    /// let mut program = BrainfuckProgram::compile_with_options(source, vec![0u8; 2], &options)
+    ///     .unwrap();
+    /// let error = program.try_run(&mut |_| {}, &mut || 0).unwrap_err();
+    /// assert!(matches!(
+    ///     error,
+    ///     RuntimeError::PointerOutOfBounds {
+    ///         instruction_pointer: 4,
+    ///         position: SourcePos { line: 1, character: 3 },
+    ///         ..
+    ///     }
+    /// ));
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "Data pointer moved out of bounds at line 1, character 3\n+<<\n  ^",
+    /// );
+    /// ```
+    pub strict: bool,
+    /// How every loop moves the data pointer, ordered by the index of their `[`, as
+    /// determined by [`analyze_loops`]
+    ///
+    /// This is updated when the instructions are replaced with
+    /// [`set_instructions`](Self::set_instructions), but not when the deprecated
+    /// `instructions` field is changed directly.
+    pub loops: Vec<LoopInfo>,
+    /// The cells and data pointer that the program starts with, if instructions at its
+    /// start have been evaluated by [`fold_prefix`](Self::fold_prefix)
+    ///
+    /// [`reset`](Self::reset) restores these.
+    pub initial_tape: Option<InitialTape<T::Data>>,
 }
 
 /// An error that can occur while interpreting/compiling Brainfuck
@@ -118,205 +739,819 @@ pub enum Error<'a> {
     MissingOpeningBrace(Span<'a>),
 }
 
+#[cfg(feature = "std")]
+impl<'a> std::error::Error for Error<'a> {}
+
+impl<'a> Display for Error<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (description, span) = match self {
+            Error::MissingClosingBrace(span) => ("Missing closing brace", span),
+            Error::MissingOpeningBrace(span) => ("Missing opening brace", span),
+        };
+        let (line, character) = span.get_line_character_number();
+        write!(
+            f,
+            "{} at line {}, character {}:\n{}",
+            description, line, character, span
+        )
+    }
+}
+
+/// The position of the instruction at `instruction_pointer`, if the program has a
+/// source map, and the line of `source` that it is on
+#[cfg(feature = "alloc")]
+fn locate(
+    instruction_pointer: usize,
+    source_map: &Option<Vec<SourcePos>>,
+    source: &Option<Arc<str>>,
+) -> (Option<SourcePos>, Option<SourceExcerpt>) {
+    let position = source_map
+        .as_ref()
+        .and_then(|map| map.get(instruction_pointer))
+        .copied();
+    let excerpt = position
+        .zip(source.as_deref())
+        .and_then(|(position, source)| SourceExcerpt::new(source, position));
+    (position, excerpt)
+}
+
+/// The error for a data pointer that the tape has no cell for
+#[cfg(feature = "alloc")]
+#[cold]
+fn out_of_bounds(
+    data_pointer: usize,
+    instruction_pointer: usize,
+    source_map: &Option<Vec<SourcePos>>,
+    source: &Option<Arc<str>>,
+) -> RuntimeError {
+    // Moves never jump, so the move that went out of bounds is the previous instruction
+    let (position, excerpt) = match instruction_pointer.checked_sub(1) {
+        Some(instruction_pointer) => locate(instruction_pointer, source_map, source),
+        None => (None, None),
+    };
+    RuntimeError::TapeExhausted {
+        data_pointer,
+        position,
+        excerpt,
+    }
+}
+
+/// The error for a move of the data pointer off the tape in strict mode, if the
+/// program has a source map
+#[cfg(feature = "alloc")]
+#[cold]
+fn pointer_out_of_bounds(
+    instruction_pointer: usize,
+    source_map: &Option<Vec<SourcePos>>,
+    source: &Option<Arc<str>>,
+) -> Option<RuntimeError> {
+    let (position, excerpt) = locate(instruction_pointer, source_map, source);
+    Some(RuntimeError::PointerOutOfBounds {
+        instruction_pointer,
+        position: position?,
+        excerpt,
+    })
+}
+
+/// The error for a change of a cell that overflowed
+#[cfg(feature = "alloc")]
+#[cold]
+fn cell_overflow(
+    instruction_pointer: usize,
+    data_pointer: usize,
+    source_map: &Option<Vec<SourcePos>>,
+    source: &Option<Arc<str>>,
+) -> RuntimeError {
+    let (position, excerpt) = locate(instruction_pointer, source_map, source);
+    RuntimeError::CellOverflow {
+        instruction_pointer,
+        data_pointer,
+        position,
+        excerpt,
+    }
+}
+
+/// The error for a call of a procedure that has not been defined
+#[cfg(feature = "alloc")]
+#[cold]
+fn undefined_procedure(
+    instruction_pointer: usize,
+    source_map: &Option<Vec<SourcePos>>,
+    source: &Option<Arc<str>>,
+) -> RuntimeError {
+    let (position, excerpt) = locate(instruction_pointer, source_map, source);
+    RuntimeError::UndefinedProcedure {
+        instruction_pointer,
+        position,
+        excerpt,
+    }
+}
+
+/// The error for a `^` or `v` on a tape that is not a grid
+#[cfg(feature = "alloc")]
+#[cold]
+fn not_a_grid(
+    instruction_pointer: usize,
+    source_map: &Option<Vec<SourcePos>>,
+    source: &Option<Arc<str>>,
+) -> RuntimeError {
+    let (position, excerpt) = locate(instruction_pointer, source_map, source);
+    RuntimeError::NotAGrid {
+        instruction_pointer,
+        position,
+        excerpt,
+    }
+}
+
+/// The error for a `{` or `}` on a tape that does not hold several tapes
+#[cfg(feature = "alloc")]
+#[cold]
+fn single_tape(
+    instruction_pointer: usize,
+    source_map: &Option<Vec<SourcePos>>,
+    source: &Option<Arc<str>>,
+) -> RuntimeError {
+    let (position, excerpt) = locate(instruction_pointer, source_map, source);
+    RuntimeError::SingleTape {
+        instruction_pointer,
+        position,
+        excerpt,
+    }
+}
+
+/// The error for a `$` on an empty stack
+#[cfg(feature = "alloc")]
+#[cold]
+fn stack_underflow(
+    instruction_pointer: usize,
+    source_map: &Option<Vec<SourcePos>>,
+    source: &Option<Arc<str>>,
+) -> RuntimeError {
+    let (position, excerpt) = locate(instruction_pointer, source_map, source);
+    RuntimeError::StackUnderflow {
+        instruction_pointer,
+        position,
+        excerpt,
+    }
+}
+
+/// The error for a `%` that could not call a host function
+#[cfg(feature = "alloc")]
+#[cold]
+pub(crate) fn host_call(
+    instruction_pointer: usize,
+    source_map: &Option<Vec<SourcePos>>,
+    source: &Option<Arc<str>>,
+    error: HostCallError,
+) -> RuntimeError {
+    let (position, excerpt) = locate(instruction_pointer, source_map, source);
+    RuntimeError::HostCall {
+        instruction_pointer,
+        position,
+        excerpt,
+        error,
+    }
+}
+
+// The deprecated public fields are still used internally until they become private
+#[cfg(feature = "alloc")]
+#[allow(deprecated)]
 impl<T> BrainfuckProgram<T>
 where
     T: Tape,
 {
-    fn parse_input(input: &str) -> Result<Vec<Span>, Error> {
-        let mut result = Vec::new();
-        let mut line_ind = 0;
-        let mut char_ind = 0;
-        for line in input.lines() {
-            for character in line.chars() {
-                char_ind += 1;
-                if VALID_CHARS.contains(&character) {
-                    let instr = match character {
-                        '>' => BrainfuckInstruction::IncrementDataPointer,
-                        '<' => BrainfuckInstruction::DecrementDataPointer,
-                        '+' => BrainfuckInstruction::IncreaseData,
-                        '-' => BrainfuckInstruction::DecreaseData,
-                        '.' => BrainfuckInstruction::Output,
-                        ',' => BrainfuckInstruction::Input,
-                        '[' => BrainfuckInstruction::JumpForward(0),
-                        ']' => BrainfuckInstruction::JumpBackwards(0),
-                        _ => unreachable!(),
-                    };
-
-                    result.push(Span {
-                        instruction: instr,
-                        text: input,
-                        line: line_ind,
-                        character: char_ind,
-                    });
-                } else {
-                    continue;
-                }
-            }
-            char_ind = 0;
-            line_ind += 1;
-        }
-        Ok(result)
-    }
-
-    /// Find matching `[` for a `]` located at `index` in `instructions`
-    ///
-    /// Returns the offset required for the jump on success, and else an error
-    fn find_closer<'a>(index: usize, instructions: &[Span<'a>]) -> Result<usize, Error<'a>> {
-        let mut calculated_offset = 0;
-        let mut extra_openers = 0;
-        let iterator = instructions.iter().skip(index + 1);
-        for next_instr in iterator {
-            calculated_offset += 1;
-            match next_instr.instruction {
-                BrainfuckInstruction::JumpBackwards(_) => {
-                    if extra_openers == 0 {
-                        return Ok(calculated_offset + 1);
-                    } else {
-                        extra_openers -= 1;
-                    }
+    /// Match all brackets in `spans` in a single stack-based pass, setting the
+    /// targets of their jumps
+    ///
+    /// Returns an error for every unmatched bracket, ordered by position
+    fn match_brackets<'a>(spans: &mut [Span<'a>]) -> Vec<Error<'a>> {
+        let mut open = Vec::new();
+        let mut unmatched = Vec::new();
+
+        for index in 0..spans.len() {
+            let top = open.last().map(|opener: &usize| spans[*opener].instruction);
+            match (spans[index].instruction, top) {
+                (BrainfuckInstruction::JumpForward(_), _)
+                | (BrainfuckInstruction::DefineProcedure(_), _) => open.push(index),
+                (
+                    BrainfuckInstruction::JumpBackwards(_),
+                    Some(BrainfuckInstruction::JumpForward(_)),
+                ) => {
+                    let opener = open.pop().unwrap();
+                    spans[opener].instruction = BrainfuckInstruction::JumpForward(index);
+                    spans[index].instruction = BrainfuckInstruction::JumpBackwards(opener);
                 }
-                BrainfuckInstruction::JumpForward(_) => {
-                    extra_openers += 1;
+                (
+                    BrainfuckInstruction::EndProcedure,
+                    Some(BrainfuckInstruction::DefineProcedure(_)),
+                ) => {
+                    let opener = open.pop().unwrap();
+                    spans[opener].instruction = BrainfuckInstruction::DefineProcedure(index);
                 }
+                // A closing bracket that does not match the innermost open bracket
+                (BrainfuckInstruction::JumpBackwards(_), _)
+                | (BrainfuckInstruction::EndProcedure, _) => unmatched.push(index),
                 _ => {}
             }
         }
-        unsafe {
-            Err(Error::MissingClosingBrace(
-                instructions.get_unchecked(index).clone(),
-            ))
-        }
-    }
 
-    /// Find matching `]` for a `[` located at `index` in `instructions`
-    ///
-    /// Returns the offset required for the jump on success, and else an error
-    fn find_opener<'a>(index: usize, instructions: &[Span<'a>]) -> Result<usize, Error<'a>> {
-        let mut calculated_offset = 0;
-        let mut extra_closers = 0;
-        let iterator = instructions.iter().rev().skip(instructions.len() - index);
-        for next_instr in iterator {
-            calculated_offset += 1;
-            match next_instr.instruction {
-                BrainfuckInstruction::JumpForward(_) => {
-                    if extra_closers == 0 {
-                        return Ok(calculated_offset - 1);
-                    } else {
-                        extra_closers -= 1;
-                    }
-                }
-                BrainfuckInstruction::JumpBackwards(_) => {
-                    extra_closers += 1;
+        unmatched.extend(open);
+        unmatched.sort_unstable();
+        unmatched
+            .into_iter()
+            .map(|index| {
+                let span = spans[index].clone();
+                match span.instruction {
+                    BrainfuckInstruction::JumpForward(_)
+                    | BrainfuckInstruction::DefineProcedure(_) => Error::MissingClosingBrace(span),
+                    _ => Error::MissingOpeningBrace(span),
                 }
-                _ => {}
-            }
+            })
+            .collect()
+    }
+
+    /// Compile a Brainfuck program, given by `input`. All non-valid characters are ignored
+    pub fn compile(input: &str, tape: T) -> Result<Self, Error<'_>> {
+        let spans = Self::compile_spans(input, &BrainfuckLexer)?;
+        Ok(Self::from_spans(&spans, tape))
+    }
+
+    /// Compile a Brainfuck program, given by `input`, retaining the position in `input`
+    /// of every instruction. All non-valid characters are ignored
+    ///
+    /// The positions can be looked up using [`source_of`](Self::source_of).
+    pub fn compile_with_source_map(input: &str, tape: T) -> Result<Self, Error<'_>> {
+        let options = CompileOptions {
+            source_map: true,
+            ..CompileOptions::default()
+        };
+        Self::compile_with_options(input, tape, &options)
+    }
+
+    /// Get the position in the source code of the instruction at `instruction_pointer`
+    ///
+    /// Returns `None` if the program was not compiled with a source map, or if
+    /// `instruction_pointer` is out of bounds.
+    pub fn source_of(&self, instruction_pointer: usize) -> Option<SourcePos> {
+        self.source_map
+            .as_ref()
+            .and_then(|map| map.get(instruction_pointer))
+            .copied()
+    }
+
+    /// Display `error`, which was returned by this program, followed by the line of the
+    /// source code that caused it with a caret below the instruction
+    ///
+    /// The source code is only shown if the program kept its [`source`](Self::source),
+    /// otherwise this displays the same as `error`. The panicking ways to run a
+    /// program panic with this message.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::BrainfuckProgram;
+    /// let mut program = BrainfuckProgram::compile_with_source_map("+\n>>+", [0u8; 2])
+    ///     .unwrap();
+    /// let error = program.try_run(&mut |_| {}, &mut || 0).unwrap_err();
+    /// assert_eq!(
+    ///     program.explain(&error).to_string(),
+    ///     "Data pointer went out of bounds! 2 (moved at line 1, character 2)\n>>+\n ^",
+    /// );
+    /// ```
+    pub fn explain<'a>(&'a self, error: &'a RuntimeError) -> RuntimeErrorWithSource<'a> {
+        error.with_source(self.source.as_deref().unwrap_or(""))
+    }
+
+    /// The instruction that is executed next, with its position in the source code, if
+    /// the program kept its [`source`](Self::source)
+    ///
+    /// Displaying the span shows where a program stopped, for example once it has
+    /// reached [`RunConfig::max_instructions`](crate::RunConfig::max_instructions).
+    pub fn current_span(&self) -> Option<Span<'_>> {
+        let position = self.source_of(self.instruction_pointer)?;
+        Some(Span {
+            instruction: self.instructions[self.instruction_pointer],
+            text: self.source.as_deref()?,
+            line: position.line,
+            character: position.character,
+        })
+    }
+
+    /// Lex `input` using `lexer`, and match its brackets
+    fn compile_spans<'a, L>(input: &'a str, lexer: &L) -> Result<Vec<Span<'a>>, Error<'a>>
+    where
+        L: Lexer + ?Sized,
+    {
+        #[cfg(feature = "tracing")]
+        let _compile = tracing::debug_span!("compile", source_len = input.len()).entered();
+
+        #[cfg(feature = "tracing")]
+        let parse_span = tracing::debug_span!("parse").entered();
+
+        let mut parse_result = lex_spans(input, lexer);
+
+        #[cfg(feature = "tracing")]
+        {
+            tracing::debug!(instructions = parse_result.len(), "parsed");
+            parse_span.exit();
         }
-        unsafe {
-            return Err(Error::MissingOpeningBrace(
-                instructions.get_unchecked(index).clone(),
-            ));
+
+        #[cfg(feature = "tracing")]
+        let _matching = tracing::debug_span!("bracket_matching").entered();
+
+        if let Some(error) = Self::match_brackets(&mut parse_result).into_iter().next() {
+            return Err(error);
         }
+
+        Ok(parse_result)
     }
 
-    /// Compile a Brainfuck program, given by `input`. All non-valid characters are ignored
-    pub fn compile(input: &str, tape: T) -> Result<Self, Error> {
-        let mut parse_result = Self::parse_input(input)?;
+    /// The index of the instruction that is executed next
+    pub fn instruction_pointer(&self) -> usize {
+        self.instruction_pointer
+    }
 
-        let clone = parse_result.clone();
+    /// The index of the cell that the program is working on
+    pub fn data_pointer(&self) -> usize {
+        self.data_pointer
+    }
 
-        for (index, span) in parse_result.iter_mut().enumerate() {
-            match &mut span.instruction {
-                BrainfuckInstruction::JumpForward(offset) => {
-                    *offset = Self::find_closer(index, &clone)?;
-                }
-                BrainfuckInstruction::JumpBackwards(offset) => {
-                    *offset = Self::find_opener(index, &clone)?;
-                }
-                _ => {}
-            }
+    /// Move the data pointer to `data_pointer`
+    ///
+    /// Returns [`RuntimeError::TapeExhausted`], and leaves the data pointer unchanged,
+    /// if the tape has no cell at `data_pointer`.
+    pub fn set_data_pointer(&mut self, data_pointer: usize) -> Result<(), RuntimeError> {
+        if self.tape.get_data_at(data_pointer).is_none() {
+            return Err(RuntimeError::TapeExhausted {
+                data_pointer,
+                position: None,
+                excerpt: None,
+            });
         }
+        self.data_pointer = data_pointer;
+        Ok(())
+    }
+
+    /// The instructions of this program
+    ///
+    /// Jumps contain the absolute index of their target in this list, see
+    /// [`BrainfuckInstruction`].
+    pub fn instructions(&self) -> &[BrainfuckInstruction] {
+        &self.instructions
+    }
 
-        Ok(Self {
+    /// Replace the instructions of this program, and start executing them from the
+    /// first one
+    ///
+    /// Returns an error, and leaves the program unchanged, if `instructions` do not
+    /// pass [`validate`](Self::validate). The source map is cleared, because it
+    /// describes the previous instructions.
+    pub fn set_instructions(
+        &mut self,
+        instructions: Vec<BrainfuckInstruction>,
+    ) -> Result<(), ValidationError> {
+        let previous = core::mem::replace(&mut self.instructions, instructions);
+        if let Err(error) = self.validate() {
+            self.instructions = previous;
+            return Err(error);
+        }
+        self.instruction_pointer = 0;
+        self.source_map = None;
+        self.source = None;
+        self.loops = analyze_loops(&self.instructions);
+        self.initial_tape = None;
+        self.procedures.clear();
+        self.call_stack.clear();
+        self.stack.clear();
+        Ok(())
+    }
+
+    /// Create a program from a list of instructions, running on `tape`
+    ///
+    /// The jump targets of `instructions` are not checked: a jump that does not
+    /// target its matching bracket makes the program misbehave, or panic while running.
+    /// Use [`validate`](Self::validate) to check them.
+    pub fn from_instructions(instructions: Vec<BrainfuckInstruction>, tape: T) -> Self {
+        let loops = analyze_loops(&instructions);
+        Self {
             instruction_pointer: 0,
             data_pointer: 0,
-            instructions: parse_result.iter().map(|span| span.instruction).collect(),
+            instructions,
             tape,
             execution_count: 0,
-        })
+            source_map: None,
+            source: None,
+            procedures: Vec::new(),
+            call_stack: Vec::new(),
+            stack: Vec::new(),
+            exit_cell: 0,
+            strict: false,
+            loops,
+            initial_tape: None,
+        }
+    }
+
+    /// Create a program from spans whose brackets have been matched
+    fn from_spans(spans: &[Span], tape: T) -> Self {
+        Self::from_instructions(spans.iter().map(|span| span.instruction).collect(), tape)
     }
 
     /// Perform a step in the Brainfuck program
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`try_step`](Self::try_step) returns an error.
     pub fn step<FnOut, FnIn>(&mut self, output: &mut FnOut, input: &mut FnIn) -> bool
     where
-        FnOut: FnMut(T::Data),
-        FnIn: FnMut() -> T::Data,
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
     {
-        let data_pointer = &mut self.data_pointer;
-        let instruction_pointer = &mut self.instruction_pointer;
+        match self.try_step(output, input) {
+            Ok(running) => running,
+            Err(error) => panic!("{}", self.explain(&error)),
+        }
+    }
 
-        let data = match self.tape.get_data_at_mut(*data_pointer) {
-            Some(data) => data,
-            None => panic!("Data pointer went out of bounds! {}", data_pointer),
-        };
+    /// Perform a step in the Brainfuck program, returning whether the program is still
+    /// running, or the error that stopped it
+    ///
+    /// After an error, the instruction pointer and data pointer are left as they were
+    /// before the step, so that they point at the cause of the error. If the program
+    /// kept its [`source`](Self::source), the error displays the line of source code
+    /// that caused it as well.
+    pub fn try_step<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<bool, RuntimeError>
+    where
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
+    {
+        self.execute(None, true, output, input)
+    }
 
-        self.execution_count += 1;
+    /// Execute instructions from the instruction pointer on, reading them from
+    /// `bytecode` if it is given, and from the instructions of this program otherwise
+    ///
+    /// The current cell is looked up once, and only looked up again after the data
+    /// pointer has moved. If `single` is set, this returns after one instruction, like
+    /// [`try_step`](Self::try_step). Otherwise, it returns once the program has finished.
+    #[inline(always)]
+    pub(crate) fn execute<FnOut, FnIn>(
+        &mut self,
+        bytecode: Option<&Bytecode>,
+        single: bool,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<bool, RuntimeError>
+    where
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
+    {
+        loop {
+            let data_pointer = &mut self.data_pointer;
+            let instruction_pointer = &mut self.instruction_pointer;
 
-        let instructions = &self.instructions;
+            let data = match self.tape.get_data_at_mut(*data_pointer) {
+                Some(data) => data,
+                None => {
+                    return Err(out_of_bounds(
+                        *data_pointer,
+                        *instruction_pointer,
+                        &self.source_map,
+                        &self.source,
+                    ))
+                }
+            };
 
-        let instruction = match instructions.get(*instruction_pointer) {
-            Some(instr) => instr,
-            None => return false,
-        };
+            // Runs until the data pointer moves, after which the cell is looked up again
+            loop {
+                self.execution_count += 1;
 
-        match instruction {
-            BrainfuckInstruction::IncrementDataPointer => {
-                *data_pointer += 1;
-            }
-            BrainfuckInstruction::DecrementDataPointer => {
-                *data_pointer -= 1;
-            }
-            BrainfuckInstruction::IncreaseData => {
-                data.increase();
-            }
-            BrainfuckInstruction::DecreaseData => {
-                data.decrease();
-            }
-            BrainfuckInstruction::Output => {
-                output(data.clone());
-            }
-            BrainfuckInstruction::Input => {
-                *data = input();
-            }
-            BrainfuckInstruction::JumpForward(offset) => {
-                if *data == T::Data::zero() {
-                    *instruction_pointer += offset;
-                    return true;
+                let instruction = match bytecode {
+                    Some(bytecode) => bytecode.get(*instruction_pointer),
+                    None => self.instructions.get(*instruction_pointer).copied(),
+                };
+                let instruction = match &instruction {
+                    Some(instr) => instr,
+                    None => return Ok(false),
+                };
+
+                match instruction {
+                    BrainfuckInstruction::IncrementDataPointer => {
+                        if self.strict && self.tape.get_data_at(*data_pointer + 1).is_none() {
+                            if let Some(error) = pointer_out_of_bounds(
+                                *instruction_pointer,
+                                &self.source_map,
+                                &self.source,
+                            ) {
+                                return Err(error);
+                            }
+                        }
+                        *data_pointer += 1;
+                        *instruction_pointer += 1;
+                        if single {
+                            return Ok(true);
+                        }
+                        break;
+                    }
+                    BrainfuckInstruction::DecrementDataPointer => {
+                        if self.strict && *data_pointer == 0 {
+                            if let Some(error) = pointer_out_of_bounds(
+                                *instruction_pointer,
+                                &self.source_map,
+                                &self.source,
+                            ) {
+                                return Err(error);
+                            }
+                        }
+                        // Moving left of the first cell is reported when the next
+                        // instruction is executed, as there is no cell there
+                        *data_pointer = data_pointer.wrapping_sub(1);
+                        *instruction_pointer += 1;
+                        if single {
+                            return Ok(true);
+                        }
+                        break;
+                    }
+                    BrainfuckInstruction::IncreaseData => {
+                        if !data.try_increase() {
+                            return Err(cell_overflow(
+                                *instruction_pointer,
+                                *data_pointer,
+                                &self.source_map,
+                                &self.source,
+                            ));
+                        }
+                    }
+                    BrainfuckInstruction::DecreaseData => {
+                        if !data.try_decrease() {
+                            return Err(cell_overflow(
+                                *instruction_pointer,
+                                *data_pointer,
+                                &self.source_map,
+                                &self.source,
+                            ));
+                        }
+                    }
+                    BrainfuckInstruction::Output => {
+                        output.output(data.clone());
+                    }
+                    BrainfuckInstruction::Input => {
+                        *data = input.input();
+                    }
+                    BrainfuckInstruction::JumpForward(target) => {
+                        if *data == T::Data::zero() {
+                            *instruction_pointer = *target;
+                        }
+                    }
+                    BrainfuckInstruction::JumpBackwards(target) => {
+                        if *data != T::Data::zero() {
+                            *instruction_pointer = *target;
+                        }
+                    }
+                    BrainfuckInstruction::DefineProcedure(end) => {
+                        let start = *instruction_pointer;
+                        match self.procedures.iter_mut().find(|(name, _)| name == data) {
+                            Some(procedure) => procedure.1 = start,
+                            None => self.procedures.push((data.clone(), start)),
+                        }
+                        *instruction_pointer = *end;
+                    }
+                    BrainfuckInstruction::EndProcedure => {
+                        // Outside of a call, the end of a procedure does nothing
+                        if let Some(caller) = self.call_stack.pop() {
+                            *instruction_pointer = caller;
+                        }
+                    }
+                    BrainfuckInstruction::CallProcedure => {
+                        match self.procedures.iter().find(|(name, _)| name == data) {
+                            Some((_, start)) => {
+                                self.call_stack.push(*instruction_pointer);
+                                *instruction_pointer = *start;
+                            }
+                            None => {
+                                return Err(undefined_procedure(
+                                    *instruction_pointer,
+                                    &self.source_map,
+                                    &self.source,
+                                ))
+                            }
+                        }
+                    }
+                    BrainfuckInstruction::HostCall => {
+                        return Err(host_call(
+                            *instruction_pointer,
+                            &self.source_map,
+                            &self.source,
+                            HostCallError::NoHost,
+                        ))
+                    }
+                    BrainfuckInstruction::MoveUp | BrainfuckInstruction::MoveDown => {
+                        let row_len = match self.tape.row_len() {
+                            Some(row_len) => row_len,
+                            None => {
+                                return Err(not_a_grid(
+                                    *instruction_pointer,
+                                    &self.source_map,
+                                    &self.source,
+                                ))
+                            }
+                        };
+                        let target = match instruction {
+                            BrainfuckInstruction::MoveUp => data_pointer.checked_sub(row_len),
+                            _ => data_pointer.checked_add(row_len),
+                        };
+                        if self.strict {
+                            let on_tape = match target {
+                                Some(target) => self.tape.get_data_at(target).is_some(),
+                                None => false,
+                            };
+                            if !on_tape {
+                                if let Some(error) = pointer_out_of_bounds(
+                                    *instruction_pointer,
+                                    &self.source_map,
+                                    &self.source,
+                                ) {
+                                    return Err(error);
+                                }
+                            }
+                        }
+                        // Moving above the first row is reported when the next instruction
+                        // is executed, as there is no cell there
+                        *data_pointer = target.unwrap_or(usize::MAX);
+                        *instruction_pointer += 1;
+                        if single {
+                            return Ok(true);
+                        }
+                        break;
+                    }
+                    BrainfuckInstruction::Push => self.stack.push(data.clone()),
+                    BrainfuckInstruction::Pop => match self.stack.pop() {
+                        Some(top) => *data = top,
+                        None => {
+                            return Err(stack_underflow(
+                                *instruction_pointer,
+                                &self.source_map,
+                                &self.source,
+                            ))
+                        }
+                    },
+                    BrainfuckInstruction::PreviousTape | BrainfuckInstruction::NextTape => {
+                        let next = *instruction == BrainfuckInstruction::NextTape;
+                        match self.tape.switch_tape(next, *data_pointer) {
+                            Some(target) => *data_pointer = target,
+                            None => {
+                                return Err(single_tape(
+                                    *instruction_pointer,
+                                    &self.source_map,
+                                    &self.source,
+                                ))
+                            }
+                        }
+                        *instruction_pointer += 1;
+                        if single {
+                            return Ok(true);
+                        }
+                        break;
+                    }
                 }
-            }
-            BrainfuckInstruction::JumpBackwards(offset) => {
-                if *data != T::Data::zero() {
-                    *instruction_pointer -= offset;
-                    return true;
+                *instruction_pointer += 1;
+                if single {
+                    return Ok(true);
                 }
             }
         }
-        *instruction_pointer += 1;
-        true
     }
 
     /// Reset the program
+    ///
+    /// The tape is cleared, and set to the [`initial_tape`](Self::initial_tape) if the
+    /// program has one.
     pub fn reset(&mut self) {
         self.data_pointer = 0;
         self.instruction_pointer = 0;
+        self.procedures.clear();
+        self.call_stack.clear();
+        self.stack.clear();
         self.tape.reset();
+        if let Some(initial) = &self.initial_tape {
+            for (index, data) in initial.cells.iter().enumerate() {
+                if let Some(cell) = self.tape.get_data_at_mut(index) {
+                    *cell = data.clone();
+                }
+            }
+            self.data_pointer = initial.data_pointer;
+        }
     }
 
     /// Run the Brainfuck program to completion
-    pub fn run<FnOut, FnIn>(&mut self, output: &mut FnOut, input: &mut FnIn)
+    pub fn run<FnOut, FnIn>(&mut self, output: &mut FnOut, input: &mut FnIn) -> RunResult<T::Data>
     where
-        FnOut: FnMut(T::Data),
-        FnIn: FnMut() -> T::Data,
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
     {
-        while self.step(output, input) {}
+        #[cfg(feature = "tracing")]
+        let _run = tracing::debug_span!("run").entered();
+
+        // Progress is only traced between single steps
+        #[cfg(not(feature = "tracing"))]
+        match self.try_run(output, input) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", self.explain(&error)),
+        }
+
+        #[cfg(feature = "tracing")]
+        let start = RunStart::new(self);
+        #[cfg(feature = "tracing")]
+        let (mut output, mut input) = (Counted::new(output), Counted::new(input));
+        #[cfg(feature = "tracing")]
+        while self.step(&mut output, &mut input) {
+            if self
+                .execution_count
+                .is_multiple_of(TRACING_PROGRESS_INTERVAL)
+            {
+                tracing::trace!(
+                    execution_count = self.execution_count,
+                    instruction_pointer = self.instruction_pointer,
+                    data_pointer = self.data_pointer,
+                    "progress"
+                );
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            tracing::debug!(execution_count = self.execution_count, "finished");
+            start.finish(self, RunOutcome::Halted, &output, &input)
+        }
+    }
+
+    /// Run the Brainfuck program to completion, or until it fails
+    ///
+    /// Unlike [`run`](Self::run), this returns an error instead of panicking, which
+    /// lets the host stop untrusted programs gracefully, for instance when they run
+    /// out of a [`BoundedVecTape`].
+    ///
+    /// Code that does not need the [`RunResult`] can discard it with
+    /// [`Result::map`], as in `program.try_run(output, input).map(drop)`. Errors can be
+    /// shown with their line of source code using [`explain`](Self::explain).
+    pub fn try_run<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<RunResult<T::Data>, RuntimeError>
+    where
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
+    {
+        let start = RunStart::new(self);
+        let (mut output, mut input) = (Counted::new(output), Counted::new(input));
+        self.execute(None, false, &mut output, &mut input)?;
+        Ok(start.finish(self, RunOutcome::Halted, &output, &input))
+    }
+
+    /// Run the Brainfuck program until it completes, or until `cancel` is set
+    ///
+    /// `cancel` is checked before the first instruction, and then every
+    /// [`CANCELLATION_CHECK_INTERVAL`] instructions, so that a signal handler or
+    /// another thread can stop a long-running program. A cancelled program stops
+    /// between two instructions, and can be resumed by calling this function, or any
+    /// other way of running it, again. `cancel` is not cleared.
+    ///
+    /// Returns `true` if the program completed, and `false` if it was cancelled. Like
+    /// [`try_run`](Self::try_run), this returns an error instead of panicking if the
+    /// program fails.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::BrainfuckProgram;
+    /// # use std::sync::atomic::{AtomicBool, Ordering};
+    /// let mut program = BrainfuckProgram::compile("+[]", vec![0u8]).unwrap();
+    /// let cancel = AtomicBool::new(false);
+    ///
+    /// std::thread::scope(|scope| {
+    ///     scope.spawn(|| cancel.store(true, Ordering::Relaxed));
+    ///     assert!(!program.run_cancellable(&cancel, &mut |_| {}, &mut || 0).unwrap());
+    /// });
+    /// ```
+    pub fn run_cancellable<FnOut, FnIn>(
+        &mut self,
+        cancel: &AtomicBool,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<bool, RuntimeError>
+    where
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
+    {
+        loop {
+            if cancel.load(Ordering::Relaxed) {
+                return Ok(false);
+            }
+            for _ in 0..CANCELLATION_CHECK_INTERVAL {
+                if !self.try_step(output, input)? {
+                    return Ok(true);
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,300 @@
+//! Combining compiled programs into a single program
+
+use alloc::{string::String, sync::Arc, vec::Vec};
+use core::fmt::Display;
+
+use crate::{
+    analyze_loops, BrainfuckInstruction, BrainfuckLexer, BrainfuckProgram, Error, SourcePos, Tape,
+};
+
+/// The error returned when no tape-reset barrier can follow a program, because where
+/// it leaves the data pointer or which cells it changes depends on the data on the
+/// tape, as it does for `[>]` or a procedure call
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct UnknownExtent;
+
+impl Display for UnknownExtent {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("The cells that the program changes depend on the data on the tape")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownExtent {}
+
+/// An error that can occur in [`BrainfuckProgram::link`]
+#[derive(Clone, Debug)]
+pub enum LinkError<'a> {
+    /// The source at `index` does not compile
+    Compile {
+        /// The index of the source
+        index: usize,
+        /// Why it does not compile
+        error: Error<'a>,
+    },
+    /// No barrier can follow the source at `index`, see [`UnknownExtent`]
+    UnknownExtent {
+        /// The index of the source
+        index: usize,
+    },
+}
+
+impl Display for LinkError<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LinkError::Compile { index, error } => write!(f, "In source {}: {}", index, error),
+            LinkError::UnknownExtent { index } => {
+                write!(f, "In source {}: {}", index, UnknownExtent)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LinkError<'_> {}
+
+/// Append `other` to `instructions`, moving the targets of its jumps along
+fn extend(instructions: &mut Vec<BrainfuckInstruction>, other: &[BrainfuckInstruction]) {
+    let base = instructions.len();
+    instructions.extend(other.iter().map(|instruction| match *instruction {
+        BrainfuckInstruction::JumpForward(target) => {
+            BrainfuckInstruction::JumpForward(base + target)
+        }
+        BrainfuckInstruction::JumpBackwards(target) => {
+            BrainfuckInstruction::JumpBackwards(base + target)
+        }
+        BrainfuckInstruction::DefineProcedure(target) => {
+            BrainfuckInstruction::DefineProcedure(base + target)
+        }
+        instruction => instruction,
+    }));
+}
+
+/// Where `instructions` leave the data pointer, and the smallest and largest offset
+/// that they move it to, relative to where it starts, if those do not depend on the
+/// data on the tape
+fn extent(instructions: &[BrainfuckInstruction]) -> Option<(isize, isize, isize)> {
+    let loops = analyze_loops(instructions);
+    let (mut offset, mut min, mut max) = (0, 0, 0);
+    let mut index = 0;
+    while let Some(instruction) = instructions.get(index) {
+        match instruction {
+            BrainfuckInstruction::IncrementDataPointer => offset += 1,
+            BrainfuckInstruction::DecrementDataPointer => offset -= 1,
+            BrainfuckInstruction::JumpForward(end) => {
+                let info = loops.iter().find(|info| info.start == index)?;
+                match (info.delta, info.offsets) {
+                    (Some(0), Some((low, high))) => {
+                        min = min.min(offset + low);
+                        max = max.max(offset + high);
+                    }
+                    _ => return None,
+                }
+                index = *end;
+            }
+            // The body of a procedure does not run where it is defined
+            BrainfuckInstruction::DefineProcedure(end) => index = *end,
+            BrainfuckInstruction::CallProcedure
+            | BrainfuckInstruction::HostCall
+            | BrainfuckInstruction::MoveUp
+            | BrainfuckInstruction::MoveDown
+            | BrainfuckInstruction::PreviousTape
+            | BrainfuckInstruction::NextTape => return None,
+            _ => {}
+        }
+        min = min.min(offset);
+        max = max.max(offset);
+        index += 1;
+    }
+    Some((offset, min, max))
+}
+
+/// Join `first` and `second` so that `second` starts on a line of its own, returning
+/// the joined source and the line on which `second` starts
+fn join_sources(first: &str, second: &str) -> (Arc<str>, usize) {
+    let mut joined = String::with_capacity(first.len() + second.len() + 1);
+    joined.push_str(first);
+    if !first.is_empty() && !first.ends_with('\n') {
+        joined.push('\n');
+    }
+    let lines = joined.matches('\n').count();
+    joined.push_str(second);
+    (joined.into(), lines)
+}
+
+/// Append a barrier to `instructions`, which returns the data pointer to where they
+/// start and clears every cell that they change
+fn push_barrier(instructions: &mut Vec<BrainfuckInstruction>) -> Result<(), UnknownExtent> {
+    let (offset, min, max) = extent(instructions).ok_or(UnknownExtent)?;
+    let moves = |count: isize, instruction| (0..count).map(move |_| instruction);
+
+    instructions.extend(moves(
+        offset - min,
+        BrainfuckInstruction::DecrementDataPointer,
+    ));
+    for cell in min..=max {
+        let start = instructions.len();
+        instructions.extend([
+            BrainfuckInstruction::JumpForward(start + 2),
+            BrainfuckInstruction::DecreaseData,
+            BrainfuckInstruction::JumpBackwards(start),
+        ]);
+        if cell != max {
+            instructions.push(BrainfuckInstruction::IncrementDataPointer);
+        }
+    }
+    instructions.extend(moves(max, BrainfuckInstruction::DecrementDataPointer));
+    Ok(())
+}
+
+#[allow(deprecated)]
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Append the instructions of `other` to the instructions of this program, so that
+    /// they run once this program would have finished
+    ///
+    /// The data pointer and the cells are left as this program leaves them. Like
+    /// [`set_instructions`](Self::set_instructions), this restarts the program. The
+    /// source map is kept if both programs have one. If both programs kept their
+    /// [`source`](Self::source) as well, the source of `other` is added on the lines
+    /// after the source of this program, so that errors can still be
+    /// [explained](Self::explain). Otherwise, the positions in the source map refer to
+    /// the source code of either program.
+    ///
+    /// `other` runs on the cells that this program leaves behind. If it was optimized
+    /// on a zeroed tape, the optimizations assume that it starts on zeroed cells, see
+    /// [`OptimizationLevel`](crate::OptimizationLevel), so append it with
+    /// [`append_with_barrier`](Self::append_with_barrier) instead.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::BrainfuckProgram;
+    /// let mut program = BrainfuckProgram::compile("++[>+<-]", vec![0u8; 2]).unwrap();
+    /// let print = BrainfuckProgram::compile(">[-<+>]<.", vec![0u8; 2]).unwrap();
+    /// program.append(&print);
+    ///
+    /// let mut output = Vec::new();
+    /// program.run(&mut |value| output.push(value), &mut || 0);
+    /// assert_eq!(output, [2]);
+    ///
+    /// let mut program = BrainfuckProgram::compile_with_source_map("+", [0u8; 1]).unwrap();
+    /// let fail = BrainfuckProgram::compile_with_source_map("-\n>>", [0u8; 1]).unwrap();
+    /// program.append(&fail);
+    /// let error = program.try_run(&mut |_| {}, &mut || 0).unwrap_err();
+    /// assert_eq!(
+    ///     program.explain(&error).to_string(),
+    ///     "Data pointer went out of bounds! 1 (moved at line 2, character 1)\n>>\n^",
+    /// );
+    /// ```
+    pub fn append<U>(&mut self, other: &BrainfuckProgram<U>)
+    where
+        U: Tape,
+    {
+        let mut source = None;
+        let source_map = match (self.source_map.take(), &other.source_map) {
+            (Some(mut source_map), Some(other_map)) => {
+                match (&self.source, &other.source) {
+                    (Some(first), Some(second)) => {
+                        let (joined, lines) = join_sources(first, second);
+                        source_map.extend(other_map.iter().map(|position| SourcePos {
+                            line: position.line + lines,
+                            character: position.character,
+                        }));
+                        source = Some(joined);
+                    }
+                    _ => source_map.extend_from_slice(other_map),
+                }
+                Some(source_map)
+            }
+            _ => None,
+        };
+        let mut instructions = self.instructions.clone();
+        extend(&mut instructions, &other.instructions);
+        self.set_program(instructions);
+        self.source_map = source_map;
+        self.source = source;
+    }
+
+    /// Append the instructions of `other` like [`append`](Self::append), after a
+    /// barrier that resets the tape
+    ///
+    /// The barrier returns the data pointer to where this program starts, and clears
+    /// every cell that this program can change, so that `other` runs on the same tape
+    /// as this program does. The barrier consists of ordinary instructions, which are
+    /// only possible if the cells that this program changes do not depend on the data
+    /// on the tape. Otherwise, nothing is appended.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckProgram, UnknownExtent};
+    /// let mut program = BrainfuckProgram::compile("+++>++", vec![0u8; 2]).unwrap();
+    /// let print = BrainfuckProgram::compile(".>.", vec![0u8; 2]).unwrap();
+    /// program.append_with_barrier(&print).unwrap();
+    ///
+    /// let mut output = Vec::new();
+    /// program.run(&mut |value| output.push(value), &mut || 0);
+    /// assert_eq!(output, [0, 0]);
+    ///
+    /// let mut program = BrainfuckProgram::compile("+[>+]", vec![0u8; 2]).unwrap();
+    /// assert_eq!(program.append_with_barrier(&print), Err(UnknownExtent));
+    /// ```
+    pub fn append_with_barrier<U>(
+        &mut self,
+        other: &BrainfuckProgram<U>,
+    ) -> Result<(), UnknownExtent>
+    where
+        U: Tape,
+    {
+        let mut instructions = self.instructions.clone();
+        push_barrier(&mut instructions)?;
+        extend(&mut instructions, &other.instructions);
+        self.set_program(instructions);
+        Ok(())
+    }
+
+    /// Compile every source in `sources`, and concatenate their instructions into a
+    /// single program running on `tape`, with a barrier that resets the tape between
+    /// every two of them if `barriers` is set
+    ///
+    /// The barriers are described in [`append_with_barrier`](Self::append_with_barrier).
+    /// The program does not have a source map.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckProgram, LinkError};
+    /// let sources = [",[->+<]", ">[-<++>]<", "."];
+    /// let mut program = BrainfuckProgram::link(&sources, vec![0u8; 2], false).unwrap();
+    ///
+    /// let mut output = Vec::new();
+    /// program.run(&mut |value| output.push(value), &mut || 21);
+    /// assert_eq!(output, [42]);
+    ///
+    /// let mut program = BrainfuckProgram::link(&sources, vec![0u8; 2], true).unwrap();
+    /// let mut output = Vec::new();
+    /// program.run(&mut |value| output.push(value), &mut || 21);
+    /// assert_eq!(output, [0]);
+    ///
+    /// let error = BrainfuckProgram::link(&["+[>+]", "."], vec![0u8; 2], true).unwrap_err();
+    /// assert!(matches!(error, LinkError::UnknownExtent { index: 0 }));
+    /// ```
+    pub fn link<'a>(sources: &[&'a str], tape: T, barriers: bool) -> Result<Self, LinkError<'a>> {
+        let mut instructions = Vec::new();
+        for (index, source) in sources.iter().enumerate() {
+            if barriers && index > 0 {
+                push_barrier(&mut instructions)
+                    .map_err(|_| LinkError::UnknownExtent { index: index - 1 })?;
+            }
+            let spans = Self::compile_spans(source, &BrainfuckLexer)
+                .map_err(|error| LinkError::Compile { index, error })?;
+            let compiled: Vec<_> = spans.iter().map(|span| span.instruction).collect();
+            extend(&mut instructions, &compiled);
+        }
+        Ok(Self::from_instructions(instructions, tape))
+    }
+
+    /// Replace the instructions of this program with `instructions`, which have matching
+    /// brackets, and restart it
+    fn set_program(&mut self, instructions: Vec<BrainfuckInstruction>) {
+        self.set_instructions(instructions)
+            .expect("Concatenated programs have matching brackets");
+    }
+}
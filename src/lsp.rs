@@ -0,0 +1,393 @@
+//! A [Language Server Protocol] server for Brainfuck
+//!
+//! [Language Server Protocol]: https://microsoft.github.io/language-server-protocol/
+//!
+//! The server publishes diagnostics for every unmatched bracket and for likely mistakes,
+//! jumps from a bracket to its matching bracket with "go to definition", and shows the
+//! loop depth of an instruction on hover. Semantic tokens, produced by
+//! [`tokenize`](crate::tokenize), tell commands apart from comments. It talks to the editor over a reader and a
+//! writer, which the `bf-lsp` binary connects to standard input and output.
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    parse, protocol, tokenize, BrainfuckInstruction, BrainfuckProgram, CompileError, SourcePos,
+    Span, TokenKind,
+};
+
+/// The JSON-RPC error code for requests that the server does not support
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// The JSON-RPC error code for requests that arrive after a shutdown request
+const INVALID_REQUEST: i64 = -32600;
+
+/// The LSP severity of errors
+const SEVERITY_ERROR: u64 = 1;
+
+/// The LSP severity of warnings
+const SEVERITY_WARNING: u64 = 2;
+
+/// The semantic token types, in the order of their LSP index
+const TOKEN_TYPES: [&str; 5] = ["comment", "keyword", "operator", "number", "function"];
+
+/// A Language Server Protocol server for Brainfuck source files
+pub struct LanguageServer<R, W> {
+    reader: R,
+    writer: W,
+    documents: HashMap<String, String>,
+    shutdown: bool,
+}
+
+impl<R, W> LanguageServer<R, W>
+where
+    R: BufRead,
+    W: Write,
+{
+    /// Create a server that reads messages from `reader`, and writes messages to `writer`
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            reader,
+            writer,
+            documents: HashMap::new(),
+            shutdown: false,
+        }
+    }
+
+    /// Handle messages until the client sends `exit` or closes the connection
+    pub fn run(&mut self) -> io::Result<()> {
+        while let Some(message) = protocol::read_message(&mut self.reader)? {
+            let method = message["method"].as_str().unwrap_or_default();
+            let params = &message["params"];
+
+            match message.get("id") {
+                Some(id) => {
+                    let result = if self.shutdown {
+                        Err((INVALID_REQUEST, "The server is shutting down".to_string()))
+                    } else {
+                        self.request(method, params)
+                    };
+                    let response = match result {
+                        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                        Err((code, message)) => json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "error": { "code": code, "message": message },
+                        }),
+                    };
+                    protocol::write_message(&mut self.writer, &response)?;
+                }
+                None if method == "exit" => break,
+                None => self.notification(method, params)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle a request, returning its result or an error code and message
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    // Full document synchronization
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "semanticTokensProvider": {
+                        "legend": { "tokenTypes": TOKEN_TYPES, "tokenModifiers": [] },
+                        "full": true,
+                    },
+                },
+                "serverInfo": { "name": "bf-lsp" },
+            })),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/hover" => Ok(self
+                .document_span(params)
+                .map(|(text, spans, index)| hover(text, &spans, index))
+                .unwrap_or(Value::Null)),
+            "textDocument/definition" => {
+                let uri = params["textDocument"]["uri"].clone();
+                Ok(self
+                    .document_span(params)
+                    .and_then(|(text, spans, index)| {
+                        let matching = &spans[matching_bracket(&spans, index)?];
+                        Some(json!({ "uri": uri, "range": range(text, matching.get_position()) }))
+                    })
+                    .unwrap_or(Value::Null))
+            }
+            "textDocument/semanticTokens/full" => {
+                let text = params["textDocument"]["uri"]
+                    .as_str()
+                    .and_then(|uri| self.documents.get(uri));
+                Ok(json!({ "data": text.map(|text| semantic_tokens(text)).unwrap_or_default() }))
+            }
+            _ => Err((
+                METHOD_NOT_FOUND,
+                format!("Unsupported request `{}`", method),
+            )),
+        }
+    }
+
+    /// Handle a notification, which does not get a response
+    fn notification(&mut self, method: &str, params: &Value) -> io::Result<()> {
+        let uri = match params["textDocument"]["uri"].as_str() {
+            Some(uri) => uri.to_string(),
+            None => return Ok(()),
+        };
+
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents.insert(uri.clone(), text.to_string());
+            }
+            "textDocument/didChange" => {
+                // With full synchronization, the last change contains the whole document
+                let changes = params["contentChanges"].as_array();
+                if let Some(text) = changes.and_then(|c| c.last()?["text"].as_str()) {
+                    self.documents.insert(uri.clone(), text.to_string());
+                }
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                return self.publish_diagnostics(&uri, Vec::new());
+            }
+            _ => return Ok(()),
+        }
+
+        let diagnostics = self.documents.get(&uri).map(|text| diagnostics(text));
+        self.publish_diagnostics(&uri, diagnostics.unwrap_or_default())
+    }
+
+    fn publish_diagnostics(&mut self, uri: &str, diagnostics: Vec<Value>) -> io::Result<()> {
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        });
+        protocol::write_message(&mut self.writer, &notification)
+    }
+
+    /// The text of the document that `params` refers to, its instructions with matched
+    /// brackets, and the index of the instruction at the position in `params`
+    fn document_span(&self, params: &Value) -> Option<(&str, Vec<Span<'_>>, usize)> {
+        let text = self
+            .documents
+            .get(params["textDocument"]["uri"].as_str()?)?;
+        let line = params["position"]["line"].as_u64()? as usize;
+        let character = params["position"]["character"].as_u64()? as usize;
+        let position = source_pos(text, line, character)?;
+
+        let spans = matched_spans(text);
+        let index = spans
+            .binary_search_by_key(&position, Span::get_position)
+            .ok()?;
+        Some((text, spans, index))
+    }
+}
+
+/// The instructions in `text`, with the targets of all matched brackets set
+fn matched_spans(text: &str) -> Vec<Span<'_>> {
+    let mut spans: Vec<Span> = parse(text).collect();
+    BrainfuckProgram::<Vec<u8>>::match_brackets(&mut spans);
+    spans
+}
+
+/// The index of the bracket that matches the bracket at `index`, if it is matched
+fn matching_bracket(spans: &[Span], index: usize) -> Option<usize> {
+    // Unmatched brackets keep a target of zero, so check that the target points back
+    match spans[index].get_instruction() {
+        BrainfuckInstruction::JumpForward(target) => match spans.get(target)?.get_instruction() {
+            BrainfuckInstruction::JumpBackwards(back) if back == index => Some(target),
+            _ => None,
+        },
+        BrainfuckInstruction::JumpBackwards(target) => match spans.get(target)?.get_instruction() {
+            BrainfuckInstruction::JumpForward(forward) if forward == index => Some(target),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The diagnostics for `text`: its unmatched brackets, or if there are none, its warnings
+fn diagnostics(text: &str) -> Vec<Value> {
+    if let Err(errors) = BrainfuckProgram::compile_all_errors(text, Vec::<u8>::new()) {
+        let spans: Vec<Span> = parse(text).collect();
+        return errors
+            .iter()
+            .map(|error: &CompileError| {
+                let position = SourcePos {
+                    line: error.line,
+                    character: error.column,
+                };
+                let instruction = spans
+                    .binary_search_by_key(&position, Span::get_position)
+                    .ok()
+                    .map(|index| spans[index].get_instruction().as_char());
+                let message = match instruction {
+                    Some(bracket) => format!("{}: this `{}` is unmatched", error.kind, bracket),
+                    None => error.kind.to_string(),
+                };
+                json!({
+                    "range": range(text, position),
+                    "severity": SEVERITY_ERROR,
+                    "source": "brainfuck",
+                    "message": message,
+                })
+            })
+            .collect();
+    }
+
+    match BrainfuckProgram::compile_with_warnings(text, Vec::<u8>::new()) {
+        Ok((_, warnings)) => warnings
+            .iter()
+            .map(|warning| {
+                let start = range(text, warning.start)["start"].clone();
+                let end = range(text, warning.end)["end"].clone();
+                json!({
+                    "range": { "start": start, "end": end },
+                    "severity": SEVERITY_WARNING,
+                    "source": "brainfuck",
+                    "message": warning.to_string(),
+                })
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// The semantic tokens of `text`, encoded relative to each other as LSP expects
+fn semantic_tokens(text: &str) -> Vec<u32> {
+    let mut data = Vec::new();
+    let (mut previous_line, mut previous_start) = (0, 0);
+    // The UTF-16 offset of the current token in its line
+    let (mut line, mut start) = (0, 0);
+
+    for token in tokenize(text) {
+        if token.position.line != line {
+            line = token.position.line;
+            start = 0;
+        }
+        let length: usize = token.text.chars().map(char::len_utf16).sum();
+
+        let token_type = match token.kind {
+            TokenKind::Whitespace => None,
+            TokenKind::Comment => Some(0),
+            TokenKind::Command(instruction) => Some(match instruction {
+                BrainfuckInstruction::JumpForward(_)
+                | BrainfuckInstruction::JumpBackwards(_)
+                | BrainfuckInstruction::DefineProcedure(_)
+                | BrainfuckInstruction::EndProcedure => 1,
+                BrainfuckInstruction::IncrementDataPointer
+                | BrainfuckInstruction::DecrementDataPointer
+                | BrainfuckInstruction::MoveUp
+                | BrainfuckInstruction::MoveDown
+                | BrainfuckInstruction::PreviousTape
+                | BrainfuckInstruction::NextTape => 2,
+                BrainfuckInstruction::IncreaseData | BrainfuckInstruction::DecreaseData => 3,
+                BrainfuckInstruction::Output
+                | BrainfuckInstruction::Input
+                | BrainfuckInstruction::CallProcedure
+                | BrainfuckInstruction::HostCall
+                | BrainfuckInstruction::Push
+                | BrainfuckInstruction::Pop => 4,
+            }),
+        };
+
+        if let Some(token_type) = token_type {
+            let delta_start = if line == previous_line {
+                start - previous_start
+            } else {
+                start
+            };
+            data.extend([
+                (line - previous_line) as u32,
+                delta_start as u32,
+                length as u32,
+                token_type,
+                0,
+            ]);
+            previous_line = line;
+            previous_start = start;
+        }
+        start += length;
+    }
+    data
+}
+
+/// The hover contents of the instruction at `index`, showing its loop depth
+fn hover(text: &str, spans: &[Span], index: usize) -> Value {
+    let span = &spans[index];
+    let mut depth = 0usize;
+    for span in &spans[..index] {
+        match span.get_instruction() {
+            BrainfuckInstruction::JumpForward(_) => depth += 1,
+            BrainfuckInstruction::JumpBackwards(_) => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    let instruction = span.get_instruction();
+    if let BrainfuckInstruction::JumpBackwards(_) = instruction {
+        // A `]` is at the same depth as its `[`
+        depth = depth.saturating_sub(1);
+    }
+
+    let mut contents = format!("`{}` at loop depth {}", instruction.as_char(), depth);
+    if let BrainfuckInstruction::JumpForward(_) | BrainfuckInstruction::JumpBackwards(_) =
+        instruction
+    {
+        match matching_bracket(spans, index) {
+            Some(matching) => contents.push_str(&format!(
+                ", matching the `{}` at {}",
+                spans[matching].get_instruction().as_char(),
+                spans[matching].get_position()
+            )),
+            None => contents.push_str(", unmatched"),
+        }
+    }
+
+    json!({
+        "contents": { "kind": "markdown", "value": contents },
+        "range": range(text, span.get_position()),
+    })
+}
+
+/// The LSP range of the single character at `position`
+///
+/// LSP counts characters in UTF-16 code units, while [`SourcePos`] counts `char`s.
+fn range(text: &str, position: SourcePos) -> Value {
+    let line = text.lines().nth(position.line).unwrap_or_default();
+    let mut chars = line.chars();
+    let start: usize = chars
+        .by_ref()
+        .take(position.character.saturating_sub(1))
+        .map(char::len_utf16)
+        .sum();
+    let end = start + chars.next().map_or(0, char::len_utf16);
+    json!({
+        "start": { "line": position.line, "character": start },
+        "end": { "line": position.line, "character": end },
+    })
+}
+
+/// The source position of the LSP position at `line` and UTF-16 offset `character`
+fn source_pos(text: &str, line: usize, character: usize) -> Option<SourcePos> {
+    let mut offset = 0;
+    for (index, c) in text.lines().nth(line)?.chars().enumerate() {
+        if offset >= character {
+            return Some(SourcePos {
+                line,
+                character: index + 1,
+            });
+        }
+        offset += c.len_utf16();
+    }
+    None
+}
@@ -0,0 +1,546 @@
+//! A single entry point for running programs with a configuration chosen at runtime
+
+use alloc::vec::Vec;
+
+use crate::{
+    io::Output,
+    result::{Counted, RunStart},
+    BoundedVecTape, BrainfuckInstruction, BrainfuckProgram, CompileOptions, EofBehavior, Error,
+    GrowingTape, OptimizationLevel, RunOutcome, RunResult, RuntimeError, Saturating, Tape,
+    TapeData, Trapping,
+};
+
+/// The width of the cells of a [`Machine`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CellWidth {
+    /// 8-bit cells
+    #[default]
+    U8,
+    /// 16-bit cells
+    U16,
+    /// 32-bit cells
+    U32,
+}
+
+/// What happens when a cell of a [`Machine`] is increased past its largest value, or
+/// decreased past zero
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum WrapPolicy {
+    /// The cell wraps around to zero, or to its largest value
+    #[default]
+    Wrap,
+    /// The cell stays at its largest value, or at zero, as with [`Saturating`] cells
+    Saturate,
+    /// Running the program stops with a [`RuntimeError::CellOverflow`], as with
+    /// [`Trapping`] cells
+    Trap,
+}
+
+/// How a [`Machine`] runs its program
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// Interpret the program as it is written
+    #[default]
+    Interp,
+    /// Interpret the program after optimizing it with
+    /// [`OptimizationLevel::Aggressive`]
+    ///
+    /// Optimizations keep every `+` and `-` on [`WrapPolicy::Saturate`] and
+    /// [`WrapPolicy::Trap`] cells, but may change when the data pointer leaves the tape.
+    Optimized,
+    /// Optimize the program like [`Backend::Optimized`], and run it as
+    /// [`ThreadedCode`](crate::ThreadedCode)
+    ///
+    /// Threaded code is only used with 8-bit wrapping cells and [`EofBehavior::Zero`].
+    /// Otherwise, and for single steps, the program is interpreted like
+    /// [`Backend::Optimized`].
+    Threaded,
+    /// Optimize the program like [`Backend::Optimized`], and run it as native machine
+    /// code with a [`NativeExecutor`](crate::NativeExecutor)
+    ///
+    /// Native code is only used with the `native-jit` feature, 8-bit wrapping cells, a
+    /// [`TapeKind::Fixed`] tape and [`EofBehavior::Zero`]. Otherwise, and for single
+    /// steps, the program is interpreted like [`Backend::Optimized`]. The output and
+    /// input passed to [`Machine::run`] must not panic while running natively.
+    Jit,
+}
+
+/// The tape of a [`Machine`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TapeKind {
+    /// A tape with this many cells. Moving past its end is a
+    /// [`RuntimeError::TapeExhausted`]
+    Fixed(usize),
+    /// A tape that grows as needed
+    Growing,
+}
+
+impl Default for TapeKind {
+    fn default() -> Self {
+        TapeKind::Fixed(30_000)
+    }
+}
+
+/// The configuration of a [`Machine`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MachineConfig {
+    /// The width of every cell
+    pub cell: CellWidth,
+    /// What happens when a cell is increased or decreased past its bounds
+    pub cell_wrap: WrapPolicy,
+    /// The kind of tape
+    pub tape: TapeKind,
+    /// What `,` does once the input has run out
+    pub eof: EofBehavior,
+    /// How the program is run
+    pub backend: Backend,
+}
+
+/// Data that can be stored in the cells of a [`Machine`]
+trait MachineCell: TapeData + Copy + Into<u32> {
+    /// Convert `value`, keeping only the bits that fit
+    fn truncate(value: u32) -> Self;
+}
+
+/// Implement [`MachineCell`] for unsigned integers
+macro_rules! impl_machine_cell {
+    ($($ty:ty),*) => {
+        $(
+            impl MachineCell for $ty {
+                fn truncate(value: u32) -> Self {
+                    value as $ty
+                }
+            }
+
+            impl MachineCell for Saturating<$ty> {
+                fn truncate(value: u32) -> Self {
+                    Saturating(value as $ty)
+                }
+            }
+
+            impl MachineCell for Trapping<$ty> {
+                fn truncate(value: u32) -> Self {
+                    Trapping(value as $ty)
+                }
+            }
+        )*
+    };
+}
+
+impl_machine_cell!(u8, u16, u32);
+
+/// The tape of a [`Machine`], as chosen by [`TapeKind`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum MachineTape<D> {
+    Fixed(BoundedVecTape<D>),
+    Growing(GrowingTape<D>),
+}
+
+impl<D> MachineTape<D>
+where
+    D: TapeData,
+{
+    fn new(kind: TapeKind) -> Self {
+        match kind {
+            TapeKind::Fixed(cells) => MachineTape::Fixed(BoundedVecTape::with_limit(cells)),
+            TapeKind::Growing => MachineTape::Growing(GrowingTape::new()),
+        }
+    }
+}
+
+impl<D> AsMut<[D]> for MachineTape<D> {
+    fn as_mut(&mut self) -> &mut [D] {
+        match self {
+            MachineTape::Fixed(tape) => tape.as_mut(),
+            // Native code finds the data pointer out of bounds immediately, and leaves
+            // running the program to the interpreter, which grows the tape
+            MachineTape::Growing(_) => &mut [],
+        }
+    }
+}
+
+impl<D> Tape for MachineTape<D>
+where
+    D: TapeData,
+{
+    type Data = D;
+
+    fn get_data_at(&mut self, index: usize) -> Option<&D> {
+        match self {
+            MachineTape::Fixed(tape) => tape.get_data_at(index),
+            MachineTape::Growing(tape) => tape.get_data_at(index),
+        }
+    }
+
+    fn get_data_at_mut(&mut self, index: usize) -> Option<&mut D> {
+        match self {
+            MachineTape::Fixed(tape) => tape.get_data_at_mut(index),
+            MachineTape::Growing(tape) => tape.get_data_at_mut(index),
+        }
+    }
+
+    fn reset(&mut self) {
+        match self {
+            MachineTape::Fixed(tape) => tape.reset(),
+            MachineTape::Growing(tape) => tape.reset(),
+        }
+    }
+
+    fn len_hint(&self) -> Option<usize> {
+        match self {
+            MachineTape::Fixed(tape) => tape.len_hint(),
+            MachineTape::Growing(tape) => tape.len_hint(),
+        }
+    }
+
+    fn as_mut_slice(&mut self) -> Option<&mut [D]> {
+        match self {
+            MachineTape::Fixed(tape) => tape.as_mut_slice(),
+            MachineTape::Growing(tape) => tape.as_mut_slice(),
+        }
+    }
+}
+
+/// Why [`Machine::resume`] suspended the program
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MachineEvent {
+    /// The program wrote this data with `.`
+    Output(u32),
+    /// The program is about to read with `,`, which needs a [`MachineReply::Input`] or
+    /// [`MachineReply::Eof`]
+    Input,
+    /// The program finished
+    Halted,
+}
+
+/// What [`Machine::resume`] passes to the program before it continues
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MachineReply {
+    /// Nothing, as after a [`MachineEvent::Output`]
+    #[default]
+    Continue,
+    /// The data that the next `,` reads
+    Input(u32),
+    /// The input has run out, so the next `,` does what [`MachineConfig::eof`] says
+    Eof,
+}
+
+/// The program of a [`Machine`], for every kind of cells
+#[derive(Clone, Debug)]
+enum Program {
+    U8(BrainfuckProgram<MachineTape<u8>>),
+    U16(BrainfuckProgram<MachineTape<u16>>),
+    U32(BrainfuckProgram<MachineTape<u32>>),
+    SaturatingU8(BrainfuckProgram<MachineTape<Saturating<u8>>>),
+    SaturatingU16(BrainfuckProgram<MachineTape<Saturating<u16>>>),
+    SaturatingU32(BrainfuckProgram<MachineTape<Saturating<u32>>>),
+    TrappingU8(BrainfuckProgram<MachineTape<Trapping<u8>>>),
+    TrappingU16(BrainfuckProgram<MachineTape<Trapping<u16>>>),
+    TrappingU32(BrainfuckProgram<MachineTape<Trapping<u32>>>),
+}
+
+/// Evaluate `$body` with `$program` bound to the program of `$machine`, whatever the
+/// kind of its cells
+macro_rules! with_program {
+    ($machine:expr, $program:ident => $body:expr) => {
+        match $machine {
+            Program::U8($program) => $body,
+            Program::U16($program) => $body,
+            Program::U32($program) => $body,
+            Program::SaturatingU8($program) => $body,
+            Program::SaturatingU16($program) => $body,
+            Program::SaturatingU32($program) => $body,
+            Program::TrappingU8($program) => $body,
+            Program::TrappingU16($program) => $body,
+            Program::TrappingU32($program) => $body,
+        }
+    };
+}
+
+/// A Brainfuck program together with a tape, configured by a [`MachineConfig`]
+///
+/// This chooses the type of the cells and the tape at runtime, so that applications
+/// can let their users configure them without dealing with the generic
+/// [`BrainfuckProgram`]. Cells are read and written as `u32`, of which only the bits
+/// that fit in a cell are used.
+///
+/// ```
+/// # use brainfuck_interpreter::{CellWidth, Machine, MachineConfig, RuntimeError, WrapPolicy};
+/// let config = MachineConfig { cell: CellWidth::U16, ..MachineConfig::default() };
+/// let mut machine = Machine::new(config, "-.").unwrap();
+/// let mut output = Vec::new();
+/// machine.run(&mut |data| output.push(data), &mut || None).unwrap();
+/// assert_eq!(output, [u16::MAX as u32]);
+///
+/// let mut machine = Machine::new(MachineConfig::default(), ",[.,]").unwrap();
+/// assert_eq!(machine.run_bytes(b"echo").unwrap(), b"echo");
+///
+/// let config = MachineConfig { cell_wrap: WrapPolicy::Trap, ..MachineConfig::default() };
+/// let mut machine = Machine::new(config, "+--").unwrap();
+/// assert!(matches!(machine.run_bytes(b""), Err(RuntimeError::CellOverflow { .. })));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Machine {
+    config: MachineConfig,
+    program: Program,
+    /// The reply that the next `,` reads, with `None` at the end of input
+    pending_input: Option<Option<u32>>,
+}
+
+impl Machine {
+    /// Compile `source` into a machine configured by `config`
+    pub fn new(config: MachineConfig, source: &str) -> Result<Self, Error<'_>> {
+        let program = match (config.cell_wrap, config.cell) {
+            (WrapPolicy::Wrap, CellWidth::U8) => Program::U8(Self::compile(&config, source)?),
+            (WrapPolicy::Wrap, CellWidth::U16) => Program::U16(Self::compile(&config, source)?),
+            (WrapPolicy::Wrap, CellWidth::U32) => Program::U32(Self::compile(&config, source)?),
+            (WrapPolicy::Saturate, CellWidth::U8) => {
+                Program::SaturatingU8(Self::compile(&config, source)?)
+            }
+            (WrapPolicy::Saturate, CellWidth::U16) => {
+                Program::SaturatingU16(Self::compile(&config, source)?)
+            }
+            (WrapPolicy::Saturate, CellWidth::U32) => {
+                Program::SaturatingU32(Self::compile(&config, source)?)
+            }
+            (WrapPolicy::Trap, CellWidth::U8) => {
+                Program::TrappingU8(Self::compile(&config, source)?)
+            }
+            (WrapPolicy::Trap, CellWidth::U16) => {
+                Program::TrappingU16(Self::compile(&config, source)?)
+            }
+            (WrapPolicy::Trap, CellWidth::U32) => {
+                Program::TrappingU32(Self::compile(&config, source)?)
+            }
+        };
+        Ok(Self {
+            config,
+            program,
+            pending_input: None,
+        })
+    }
+
+    fn compile<'a, D>(
+        config: &MachineConfig,
+        source: &'a str,
+    ) -> Result<BrainfuckProgram<MachineTape<D>>, Error<'a>>
+    where
+        D: TapeData,
+    {
+        let options = CompileOptions {
+            optimization: match config.backend {
+                Backend::Interp => OptimizationLevel::None,
+                Backend::Optimized | Backend::Threaded | Backend::Jit => {
+                    OptimizationLevel::Aggressive
+                }
+            },
+            ..CompileOptions::default()
+        };
+        let mut tape = MachineTape::new(config.tape);
+        if let (Backend::Jit, TapeKind::Fixed(cells)) = (config.backend, config.tape) {
+            // Native code only uses the cells that exist when it starts
+            if let Some(last) = cells.checked_sub(1) {
+                tape.get_data_at(last);
+            }
+        }
+        BrainfuckProgram::compile_with_options(source, tape, &options)
+    }
+
+    /// The configuration of this machine
+    pub fn config(&self) -> &MachineConfig {
+        &self.config
+    }
+
+    /// The index of the next instruction
+    pub fn instruction_pointer(&self) -> usize {
+        with_program!(&self.program, program => program.instruction_pointer())
+    }
+
+    /// The index of the current cell
+    pub fn data_pointer(&self) -> usize {
+        with_program!(&self.program, program => program.data_pointer())
+    }
+
+    /// The value of the cell at `index`, or `None` if it is not on the tape
+    pub fn cell(&mut self, index: usize) -> Option<u32> {
+        with_program!(&mut self.program, program => cell(program, index))
+    }
+
+    /// Perform a single step, with `input` returning `None` once the input has run out
+    ///
+    /// Returns `Ok(false)` once the program has finished.
+    pub fn step<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<bool, RuntimeError>
+    where
+        FnOut: Output<u32>,
+        FnIn: FnMut() -> Option<u32>,
+    {
+        let eof = self.config.eof;
+        with_program!(&mut self.program, program => step(program, output, input, eof))
+    }
+
+    /// Run the program until it writes, needs input or finishes, after passing it `reply`
+    ///
+    /// Instead of calling closures, the program suspends whenever it interacts with its
+    /// environment, which makes it easy to drive from an event loop, a GUI or an
+    /// asynchronous task. Once the program wrote, it continues with
+    /// [`MachineReply::Continue`]. Once it needs input, it continues with the input,
+    /// and suspends with [`MachineEvent::Input`] again until it gets some. Input that
+    /// is passed before the program needs it is kept for the next `,`.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{Machine, MachineConfig, MachineEvent, MachineReply};
+    /// let mut machine = Machine::new(MachineConfig::default(), ",+.").unwrap();
+    /// assert_eq!(machine.resume(MachineReply::Continue), Ok(MachineEvent::Input));
+    /// assert_eq!(machine.resume(MachineReply::Input(41)), Ok(MachineEvent::Output(42)));
+    /// assert_eq!(machine.resume(MachineReply::Continue), Ok(MachineEvent::Halted));
+    /// ```
+    pub fn resume(&mut self, reply: MachineReply) -> Result<MachineEvent, RuntimeError> {
+        match reply {
+            MachineReply::Continue => {}
+            MachineReply::Input(data) => self.pending_input = Some(Some(data)),
+            MachineReply::Eof => self.pending_input = Some(None),
+        }
+
+        loop {
+            if self.pending_input.is_none() && self.next_is_input() {
+                return Ok(MachineEvent::Input);
+            }
+
+            let mut pending = self.pending_input.take();
+            let mut written = None;
+            let running = self.step(&mut |data| written = Some(data), &mut || {
+                pending.take().flatten()
+            })?;
+            self.pending_input = pending;
+
+            if let Some(data) = written {
+                return Ok(MachineEvent::Output(data));
+            }
+            if !running {
+                return Ok(MachineEvent::Halted);
+            }
+        }
+    }
+
+    /// Whether the next instruction reads input
+    fn next_is_input(&self) -> bool {
+        with_program!(&self.program, program => matches!(
+            program.instructions().get(program.instruction_pointer()),
+            Some(BrainfuckInstruction::Input)
+        ))
+    }
+
+    /// Run the program to completion, with `input` returning `None` once the input has
+    /// run out
+    ///
+    /// The interpreter runs the program with [`resume`](Self::resume), answering every
+    /// [`MachineEvent`] with `output` and `input`.
+    pub fn run<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<RunResult<u32>, RuntimeError>
+    where
+        FnOut: Output<u32>,
+        FnIn: FnMut() -> Option<u32>,
+    {
+        #[cfg(feature = "native-jit")]
+        if let (Program::U8(program), Backend::Jit, EofBehavior::Zero, None) = (
+            &mut self.program,
+            self.config.backend,
+            self.config.eof,
+            self.pending_input,
+        ) {
+            return program
+                .try_run_native(&mut |data: u8| output.output(data.into()), &mut || {
+                    input().map_or(0, u8::truncate)
+                })
+                .map(|result| result.map_exit_status(u32::from));
+        }
+
+        if let (Program::U8(program), Backend::Threaded, EofBehavior::Zero, None) = (
+            &mut self.program,
+            self.config.backend,
+            self.config.eof,
+            self.pending_input,
+        ) {
+            return program
+                .try_run_fast(&mut |data: u8| output.output(data.into()), &mut || {
+                    input().map_or(0, u8::truncate)
+                })
+                .map(|result| result.map_exit_status(u32::from));
+        }
+
+        let start = with_program!(&self.program, program => RunStart::new(program));
+        let (mut output, mut input) = (Counted::new(output), Counted::new(input));
+        // Input passed to `resume` but not read yet is read by the interpreter
+        let mut reply = MachineReply::Continue;
+        loop {
+            reply = match self.resume(reply)? {
+                MachineEvent::Output(data) => {
+                    output.output(data);
+                    MachineReply::Continue
+                }
+                MachineEvent::Input => input
+                    .next_input()
+                    .map_or(MachineReply::Eof, MachineReply::Input),
+                MachineEvent::Halted => break,
+            };
+        }
+        Ok(with_program!(&mut self.program, program => start
+            .finish(program, RunOutcome::Halted, &output, &input)
+            .map_exit_status(Into::into)))
+    }
+
+    /// Run the program to completion with `input`, returning the lowest byte of every
+    /// piece of output
+    ///
+    /// Use [`run`](Self::run) with an [`OutputDecoder`](crate::io::OutputDecoder) to
+    /// decode the output of wider cells differently.
+    pub fn run_bytes(&mut self, input: &[u8]) -> Result<Vec<u8>, RuntimeError> {
+        let mut input = input.iter().map(|byte| *byte as u32);
+        let mut output = Vec::new();
+        self.run(&mut |data: u32| output.push(data as u8), &mut || {
+            input.next()
+        })?;
+        Ok(output)
+    }
+
+    /// Reset the tape, the pointers and the execution count, and discard input that
+    /// was passed to [`resume`](Self::resume) but not read yet
+    pub fn reset(&mut self) {
+        self.pending_input = None;
+        with_program!(&mut self.program, program => program.reset())
+    }
+}
+
+/// The value of the cell of `program` at `index`
+fn cell<D>(program: &mut BrainfuckProgram<MachineTape<D>>, index: usize) -> Option<u32>
+where
+    D: MachineCell,
+{
+    program.tape.get_data_at(index).map(|data| (*data).into())
+}
+
+/// Perform a single step of `program`, converting its data from and to `u32`
+fn step<D, FnOut, FnIn>(
+    program: &mut BrainfuckProgram<MachineTape<D>>,
+    output: &mut FnOut,
+    input: &mut FnIn,
+    eof: EofBehavior,
+) -> Result<bool, RuntimeError>
+where
+    D: MachineCell,
+    FnOut: Output<u32>,
+    FnIn: FnMut() -> Option<u32>,
+{
+    program.try_step_with_eof(
+        &mut |data: D| output.output(data.into()),
+        &mut || input().map(D::truncate),
+        eof,
+    )
+}
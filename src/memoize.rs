@@ -0,0 +1,328 @@
+//! Skipping loops that have run before with the same cells
+
+use alloc::vec::Vec;
+
+use crate::{
+    io::{Input, Output},
+    result::{Counted, RunStart},
+    BrainfuckInstruction, BrainfuckProgram, RunOutcome, RunResult, Tape, TapeData,
+};
+
+/// The largest amount of cells that a loop may use to be memoized
+pub const MEMO_WINDOW: usize = 8;
+
+/// The default amount of results that are remembered for every loop
+pub const MEMO_ENTRIES: usize = 16;
+
+/// The amount of misses after which a loop is no longer memoized, if fewer than a
+/// quarter of its lookups hit
+const MISS_LIMIT: u64 = 64;
+
+/// The result of running a loop once
+#[derive(Clone, Debug)]
+struct Entry<D> {
+    /// The cells of the window before the loop
+    before: Vec<D>,
+    /// The cells of the window after the loop
+    after: Vec<D>,
+    /// The amount of instructions that were executed by the loop
+    steps: usize,
+}
+
+/// A loop that can be memoized, and the results that are remembered for it
+#[derive(Clone, Debug)]
+struct MemoLoop<D> {
+    /// The index of the `]` of the loop
+    end: usize,
+    /// The offset of the first cell of the window from the cell at the `[`
+    min: isize,
+    /// The amount of cells in the window
+    width: usize,
+    /// The remembered results, most recently used first
+    entries: Vec<Entry<D>>,
+    hits: u64,
+    misses: u64,
+    /// Whether lookups missed too often to be worth it
+    disabled: bool,
+}
+
+/// A loop that is running for the first time with the cells in `before`
+#[derive(Clone, Debug)]
+struct Recording<D> {
+    slot: usize,
+    data_pointer: usize,
+    before: Vec<D>,
+    execution_count: usize,
+}
+
+/// Runs programs while remembering the effect of loops that only change a few cells,
+/// and skipping them when they are reached with the same cells again
+///
+/// This is experimental. A loop can be memoized if every iteration leaves the data
+/// pointer where it started (see [`LoopInfo::is_balanced`](crate::LoopInfo::is_balanced)),
+/// its body only consists of `+`, `-`, `>`, `<` and such loops, and it uses at most
+/// [`MEMO_WINDOW`] cells. Such a loop only depends on those cells, so the first time it
+/// finishes, the cells before and after it are remembered, together with the amount
+/// of instructions that it executed. When it is reached again with the same cells,
+/// the cells are set to the remembered result in a single step, and
+/// [`execution_count`](BrainfuckProgram::execution_count) is increased as if the loop
+/// had run.
+///
+/// Every loop remembers a limited amount of results, and forgets the one that was
+/// used the longest ago to make room for a new one. Loops whose lookups rarely hit are
+/// no longer memoized.
+///
+/// ```
+/// # use brainfuck_interpreter::{BrainfuckProgram, Memoizer};
+/// // Multiplies 10 by 10 and clears the result again, ten times. The outer loop
+/// // produces output, so only the inner loops are memoized
+/// let source = "++++++++++[>++++++++++[>++++++++++<-]>[-]<<.-]";
+/// let mut program = BrainfuckProgram::compile(source, vec![0u8; 3]).unwrap();
+/// let mut memoizer = Memoizer::new(&program);
+/// memoizer.run(&mut program, &mut |_| {}, &mut || 0);
+///
+/// assert_eq!(memoizer.memoizable().collect::<Vec<_>>(), [22, 38]);
+/// assert_eq!((memoizer.hits(), memoizer.misses()), (18, 2));
+///
+/// let mut reference = BrainfuckProgram::compile(source, vec![0u8; 3]).unwrap();
+/// reference.run(&mut |_| {}, &mut || 0);
+/// assert_eq!(program.tape, reference.tape);
+/// assert_eq!(program.execution_count, reference.execution_count);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Memoizer<D> {
+    /// The index in `loops` of the loop that starts at every instruction, if any
+    slots: Vec<Option<usize>>,
+    loops: Vec<MemoLoop<D>>,
+    capacity: usize,
+    recording: Option<Recording<D>>,
+}
+
+#[allow(deprecated)]
+impl<D> Memoizer<D>
+where
+    D: TapeData,
+{
+    /// Create a memoizer for `program`, which remembers [`MEMO_ENTRIES`] results for
+    /// every loop
+    pub fn new<T>(program: &BrainfuckProgram<T>) -> Self
+    where
+        T: Tape<Data = D>,
+    {
+        Self::with_capacity(program, MEMO_ENTRIES)
+    }
+
+    /// Create a memoizer for `program`, which remembers `capacity` results for every
+    /// loop
+    pub fn with_capacity<T>(program: &BrainfuckProgram<T>, capacity: usize) -> Self
+    where
+        T: Tape<Data = D>,
+    {
+        let mut slots = alloc::vec![None; program.instructions.len()];
+        let mut loops = Vec::new();
+        for info in &program.loops {
+            let (min, max) = match (info.is_balanced(), info.offsets) {
+                (true, Some(offsets)) => offsets,
+                _ => continue,
+            };
+            let width = (max - min) as usize + 1;
+            let pure = program.instructions[info.start + 1..info.end]
+                .iter()
+                .all(|instruction| {
+                    matches!(
+                        instruction,
+                        BrainfuckInstruction::IncrementDataPointer
+                            | BrainfuckInstruction::DecrementDataPointer
+                            | BrainfuckInstruction::IncreaseData
+                            | BrainfuckInstruction::DecreaseData
+                            | BrainfuckInstruction::JumpForward(_)
+                            | BrainfuckInstruction::JumpBackwards(_)
+                    )
+                });
+            if !pure || width > MEMO_WINDOW || capacity == 0 {
+                continue;
+            }
+
+            if let Some(slot) = slots.get_mut(info.start) {
+                *slot = Some(loops.len());
+                loops.push(MemoLoop {
+                    end: info.end,
+                    min,
+                    width,
+                    entries: Vec::new(),
+                    hits: 0,
+                    misses: 0,
+                    disabled: false,
+                });
+            }
+        }
+
+        Self {
+            slots,
+            loops,
+            capacity,
+            recording: None,
+        }
+    }
+
+    /// The amount of times that a loop was skipped, because its result was remembered
+    pub fn hits(&self) -> u64 {
+        self.loops.iter().map(|memo| memo.hits).sum()
+    }
+
+    /// The amount of times that a loop had to run, because its result was not
+    /// remembered
+    pub fn misses(&self) -> u64 {
+        self.loops.iter().map(|memo| memo.misses).sum()
+    }
+
+    /// The indices of the `[` of the loops that can be memoized
+    pub fn memoizable(&self) -> impl Iterator<Item = usize> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.is_some())
+            .map(|(start, _)| start)
+    }
+
+    /// The cells of the window of `memo`, if the loop starts at `data_pointer`, or
+    /// `None` if the window is not on the tape
+    fn window<T>(tape: &mut T, memo: &MemoLoop<D>, data_pointer: usize) -> Option<Vec<D>>
+    where
+        T: Tape<Data = D>,
+    {
+        // The window always contains the cell at the `[`, so its offset is never positive
+        let first = data_pointer.checked_sub(memo.min.unsigned_abs())?;
+        (first..first + memo.width)
+            .map(|index| tape.get_data_at(index).cloned())
+            .collect()
+    }
+
+    /// Perform a step in `program`, or skip the loop that starts at the instruction
+    /// pointer if its result is remembered
+    pub fn step<T, FnOut, FnIn>(
+        &mut self,
+        program: &mut BrainfuckProgram<T>,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> bool
+    where
+        T: Tape<Data = D>,
+        FnOut: Output<D>,
+        FnIn: Input<D>,
+    {
+        let slot = self
+            .slots
+            .get(program.instruction_pointer)
+            .copied()
+            .flatten();
+        if let Some(slot) = slot {
+            if self.enter(program, slot) {
+                return true;
+            }
+        }
+
+        let running = program.step(output, input);
+
+        // The body cannot jump out of the loop, so it has finished once execution
+        // continues after its `]`
+        let finished = self.recording.as_ref().is_some_and(|recording| {
+            program.instruction_pointer == self.loops[recording.slot].end + 1
+        });
+        let recorded = if finished {
+            self.recording.take()
+        } else {
+            None
+        };
+        if let Some(recording) = recorded {
+            let memo = &mut self.loops[recording.slot];
+            if let Some(after) = Self::window(&mut program.tape, memo, recording.data_pointer) {
+                if memo.entries.len() >= self.capacity {
+                    memo.entries.pop();
+                }
+                memo.entries.insert(
+                    0,
+                    Entry {
+                        before: recording.before,
+                        after,
+                        steps: program.execution_count - recording.execution_count,
+                    },
+                );
+            }
+        }
+        running
+    }
+
+    /// Reach the loop in `slot`, and skip it if its result is remembered. Otherwise,
+    /// start recording it, unless another loop is being recorded
+    fn enter<T>(&mut self, program: &mut BrainfuckProgram<T>, slot: usize) -> bool
+    where
+        T: Tape<Data = D>,
+    {
+        let memo = &mut self.loops[slot];
+        if memo.disabled {
+            return false;
+        }
+        let data_pointer = program.data_pointer;
+        let before = match Self::window(&mut program.tape, memo, data_pointer) {
+            Some(before) => before,
+            None => return false,
+        };
+        // A loop that is not entered is not worth remembering
+        if before[memo.min.unsigned_abs()] == D::zero() {
+            return false;
+        }
+
+        match memo.entries.iter().position(|entry| entry.before == before) {
+            Some(position) => {
+                memo.hits += 1;
+                let entry = memo.entries.remove(position);
+                let first = data_pointer - memo.min.unsigned_abs();
+                for (index, data) in entry.after.iter().enumerate() {
+                    if let Some(cell) = program.tape.get_data_at_mut(first + index) {
+                        *cell = data.clone();
+                    }
+                }
+                program.instruction_pointer = memo.end + 1;
+                program.execution_count += entry.steps;
+                memo.entries.insert(0, entry);
+                true
+            }
+            // Loops inside of the loop that is being recorded are not recorded themselves
+            None if self.recording.is_some() => false,
+            None => {
+                memo.misses += 1;
+                if memo.misses >= MISS_LIMIT && memo.hits * 3 < memo.misses {
+                    memo.disabled = true;
+                    memo.entries = Vec::new();
+                } else {
+                    self.recording = Some(Recording {
+                        slot,
+                        data_pointer,
+                        before,
+                        execution_count: program.execution_count,
+                    });
+                }
+                false
+            }
+        }
+    }
+
+    /// Run `program` to completion, skipping loops whose result is remembered
+    pub fn run<T, FnOut, FnIn>(
+        &mut self,
+        program: &mut BrainfuckProgram<T>,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> RunResult<D>
+    where
+        T: Tape<Data = D>,
+        FnOut: Output<D>,
+        FnIn: Input<D>,
+    {
+        let start = RunStart::new(program);
+        let (mut output, mut input) = (Counted::new(output), Counted::new(input));
+        while self.step(program, &mut output, &mut input) {}
+        start.finish(program, RunOutcome::Halted, &output, &input)
+    }
+}
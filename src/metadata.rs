@@ -0,0 +1,150 @@
+//! Information about programs that is not needed to run them
+
+use alloc::{string::String, vec::Vec};
+
+use crate::{tokenize, BrainfuckInstruction, SourcePos, Token, TokenKind};
+
+/// Where a [`Comment`] was found
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CommentKind {
+    /// Text that is not a command
+    Text,
+    /// The contents of a loop at the very start of the program
+    ///
+    /// All cells are zero when a program starts, so such a loop is never entered, and
+    /// is conventionally used for a comment that may contain command characters.
+    CommentLoop,
+}
+
+/// A comment in the source code of a program
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comment {
+    /// Where the comment was found
+    pub kind: CommentKind,
+    /// The text of the comment, without surrounding whitespace
+    pub text: String,
+    /// The position of the first character of the comment
+    pub start: SourcePos,
+    /// The position of the last character of the comment
+    pub end: SourcePos,
+}
+
+/// Comments and other information extracted from the source code of a program
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProgramMetadata {
+    /// All comments, ordered by their position in the source code
+    pub comments: Vec<Comment>,
+    /// The positions of the `[` and `]` of the comment loop at the start of the
+    /// program, if there is one
+    pub comment_loop: Option<(SourcePos, SourcePos)>,
+}
+
+impl ProgramMetadata {
+    /// Extract the comments from `source`
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{CommentKind, ProgramMetadata};
+    /// let metadata = ProgramMetadata::extract("[Prints a +, really.]\n+++ plus three\n");
+    /// assert_eq!(metadata.description(), Some("Prints a +, really."));
+    /// assert_eq!(metadata.comments[1].kind, CommentKind::Text);
+    /// assert_eq!(metadata.comments[1].text, "plus three");
+    /// ```
+    pub fn extract(source: &str) -> Self {
+        let tokens: Vec<Token> = tokenize(source).collect();
+        let first_command = tokens
+            .iter()
+            .position(|token| matches!(token.kind, TokenKind::Command(_)));
+        let comment_loop = first_command
+            .filter(|open| {
+                matches!(
+                    tokens[*open].kind,
+                    TokenKind::Command(BrainfuckInstruction::JumpForward(_))
+                )
+            })
+            .and_then(|open| Some((open, loop_end(&tokens, open)?)));
+
+        let mut metadata = Self::default();
+        for (index, token) in tokens.iter().enumerate() {
+            match comment_loop {
+                Some((open, close)) if index == open => {
+                    let (open, close) = (&tokens[open], &tokens[close]);
+                    let inner = &source[open.offset + 1..close.offset];
+                    metadata.push(CommentKind::CommentLoop, inner, open.offset + 1, source);
+                    metadata.comment_loop = Some((open.position, close.position));
+                }
+                Some((open, close)) if open < index && index <= close => {}
+                _ if token.kind == TokenKind::Comment => {
+                    metadata.push(CommentKind::Text, token.text, token.offset, source)
+                }
+                _ => {}
+            }
+        }
+        metadata
+    }
+
+    /// The text of the comment loop, which conventionally describes the program
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{ProgramMetadata, SourcePos};
+    /// let metadata = ProgramMetadata::extract("  [\n  Adds two cells\n]\n[->+<]");
+    /// assert_eq!(metadata.description(), Some("Adds two cells"));
+    /// assert_eq!(
+    ///     metadata.comment_loop,
+    ///     Some((SourcePos { line: 0, character: 3 }, SourcePos { line: 2, character: 1 }))
+    /// );
+    ///
+    /// assert_eq!(ProgramMetadata::extract("+[-]").description(), None);
+    /// ```
+    pub fn description(&self) -> Option<&str> {
+        self.comments
+            .iter()
+            .find(|comment| comment.kind == CommentKind::CommentLoop)
+            .map(|comment| comment.text.as_str())
+    }
+
+    /// Add the comment `text`, which starts at byte `offset` in `source`
+    fn push(&mut self, kind: CommentKind, text: &str, offset: usize, source: &str) {
+        let leading = text.len() - text.trim_start().len();
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return;
+        }
+        let start = offset + leading;
+        let end = start + trimmed.len() - trimmed.chars().next_back().map_or(0, char::len_utf8);
+        self.comments.push(Comment {
+            kind,
+            text: trimmed.into(),
+            start: position_of(source, start),
+            end: position_of(source, end),
+        });
+    }
+}
+
+/// The index of the `]` that closes the loop starting at `tokens[open]`, if it is a
+/// closed loop
+fn loop_end(tokens: &[Token], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (index, token) in tokens.iter().enumerate().skip(open) {
+        match token.kind {
+            TokenKind::Command(BrainfuckInstruction::JumpForward(_)) => depth += 1,
+            TokenKind::Command(BrainfuckInstruction::JumpBackwards(_)) => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The source position of the character at byte `offset` in `source`
+fn position_of(source: &str, offset: usize) -> SourcePos {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    SourcePos {
+        line: before.matches('\n').count(),
+        character: before[line_start..].chars().count() + 1,
+    }
+}
@@ -0,0 +1,40 @@
+//! Running Brainfuck programs on a self-interpreter written in Brainfuck
+//!
+//! Self-interpreters such as [dbfi](https://brainfuck.org/dbfi.b) read the program
+//! to run from their input, up to a `!`, and pass the rest of their input on to it. Reading
+//! past the end of that input requires a well-defined [`EofBehavior`]: dbfi supports both
+//! [`EofBehavior::Zero`] and [`EofBehavior::Unchanged`].
+
+use alloc::vec::Vec;
+
+use crate::{minify, BrainfuckProgram, EofBehavior, Error};
+
+/// The input for a self-interpreter that runs `program` with `input`: the commands of
+/// `program`, followed by a `!` and `input`
+///
+/// Everything in `program` that is not a command is removed, so that a `!` in a comment
+/// is not mistaken for the end of the program.
+pub fn nested_input(program: &str, input: &[u8]) -> Vec<u8> {
+    let mut nested = minify(program).into_bytes();
+    nested.push(b'!');
+    nested.extend_from_slice(input);
+    nested
+}
+
+/// Run `program` with `input` on the self-interpreter `interpreter`, returning its output
+///
+/// The interpreter runs on a growing tape of bytes, and reads its input as created
+/// by [`nested_input`], with `eof` determining what happens once it has run out.
+pub fn run_nested<'a>(
+    interpreter: &'a str,
+    program: &str,
+    input: &[u8],
+    eof: EofBehavior,
+) -> Result<Vec<u8>, Error<'a>> {
+    let mut interpreter = BrainfuckProgram::compile(interpreter, Vec::new())?;
+    let mut input = nested_input(program, input).into_iter();
+    let mut output = Vec::new();
+
+    interpreter.run_with_eof(&mut |data| output.push(data), &mut || input.next(), eof);
+    Ok(output)
+}
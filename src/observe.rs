@@ -0,0 +1,94 @@
+//! Observing every step of a running program
+
+use crate::{
+    io::{Input, Output},
+    result::{Counted, RunStart},
+    BrainfuckInstruction, BrainfuckProgram, RunOutcome, RunResult, Tape,
+};
+
+/// The state of a program right before it executes an instruction, as passed to the
+/// observer of [`BrainfuckProgram::run_observed`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StepEvent<D> {
+    /// The index of the instruction that is about to be executed
+    pub instruction_pointer: usize,
+    /// The data pointer
+    pub data_pointer: usize,
+    /// The instruction that is about to be executed
+    pub instruction: BrainfuckInstruction,
+    /// The data at the data pointer
+    pub value: D,
+}
+
+#[allow(deprecated)]
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Perform a step in the Brainfuck program, calling `observer` right before the
+    /// instruction is executed
+    ///
+    /// `observer` is not called if the program has finished.
+    pub fn step_observed<FnOut, FnIn, Obs>(
+        &mut self,
+        observer: &mut Obs,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> bool
+    where
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
+        Obs: FnMut(&StepEvent<T::Data>),
+    {
+        if let Some(instruction) = self.instructions.get(self.instruction_pointer) {
+            let instruction = *instruction;
+            if let Some(value) = self.tape.get_data_at(self.data_pointer) {
+                observer(&StepEvent {
+                    instruction_pointer: self.instruction_pointer,
+                    data_pointer: self.data_pointer,
+                    instruction,
+                    value: value.clone(),
+                });
+            }
+        }
+        self.step(output, input)
+    }
+
+    /// Run the Brainfuck program to completion, calling `observer` right before every
+    /// instruction is executed
+    ///
+    /// This lets visualizers and profilers follow the execution of a program. The
+    /// observer is a generic parameter, so [`run`](Self::run) itself is unaffected.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckInstruction, BrainfuckProgram};
+    /// let mut program = BrainfuckProgram::compile("++[-]", vec![0u8]).unwrap();
+    /// let mut decrements = Vec::new();
+    /// program.run_observed(
+    ///     |event| {
+    ///         if event.instruction == BrainfuckInstruction::DecreaseData {
+    ///             decrements.push(event.value);
+    ///         }
+    ///     },
+    ///     &mut |_| {},
+    ///     &mut || 0,
+    /// );
+    /// assert_eq!(decrements, [2, 1]);
+    /// ```
+    pub fn run_observed<FnOut, FnIn, Obs>(
+        &mut self,
+        mut observer: Obs,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> RunResult<T::Data>
+    where
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
+        Obs: FnMut(&StepEvent<T::Data>),
+    {
+        let start = RunStart::new(self);
+        let (mut output, mut input) = (Counted::new(output), Counted::new(input));
+        while self.step_observed(&mut observer, &mut output, &mut input) {}
+        start.finish(self, RunOutcome::Halted, &output, &input)
+    }
+}
@@ -0,0 +1,248 @@
+//! Optimization passes over parsed programs
+//!
+//! The passes operate on spans, so that the source position of every remaining
+//! instruction is retained. Jump targets are not kept up to date, and must be
+//! recomputed afterwards.
+
+use alloc::vec::Vec;
+
+use crate::{known_zero, BrainfuckInstruction, Iterations, KnownCells, Span, Tape, TapeData};
+
+/// How much effort to put into optimizing a program during compilation
+///
+/// Optimizations preserve the behaviour of programs that keep the data pointer in
+/// bounds. Programs that move the data pointer out of bounds may behave differently.
+/// Cells that do not wrap around, such as [`Saturating`](crate::Saturating) and
+/// [`Trapping`](crate::Trapping) cells, keep every `+` and `-`, because `-+` does not
+/// leave such a cell unchanged when it is zero. See [`TapeData::WRAPS`].
+///
+/// The comment loop, loops at the start of the program and peeled loops are only
+/// found if every cell of the tape is zero when the program is compiled, which is
+/// checked for tapes that implement [`Tape::as_mut_slice`](crate::Tape::as_mut_slice).
+/// On other tapes, such as a tape that was filled beforehand or a persisted
+/// `MmapTape`, the cells are not assumed to start at zero.
+/// Programs that are [appended](crate::BrainfuckProgram::append) to another program
+/// start on the cells that it leaves behind, so that optimized programs should only be
+/// appended [with a barrier](crate::BrainfuckProgram::append_with_barrier).
+///
+/// ```
+/// # use brainfuck_interpreter::{BrainfuckProgram, CompileOptions, OptimizationLevel};
+/// let options = CompileOptions {
+///     optimization: OptimizationLevel::Aggressive,
+///     ..CompileOptions::default()
+/// };
+/// let source = "[->+<]>.";
+///
+/// let zeroed = BrainfuckProgram::compile_with_options(source, vec![0u8; 2], &options);
+/// assert_eq!(zeroed.unwrap().instructions().len(), 2);
+///
+/// let mut filled = BrainfuckProgram::compile_with_options(source, vec![3u8, 0], &options)
+///     .unwrap();
+/// let mut output = Vec::new();
+/// filled.run(&mut |value| output.push(value), &mut || 0);
+/// assert_eq!(output, [3]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum OptimizationLevel {
+    /// Do not optimize
+    #[default]
+    None,
+    /// Remove pairs of instructions that cancel each other out, such as `+-` on cells
+    /// that wrap around and `><`, and the comment loop at the start of the program, if there is one
+    Basic,
+    /// Everything in [`OptimizationLevel::Basic`], and remove loops that can never be
+    /// entered because the current cell is known to be zero, such as loops at the
+    /// start of the program and loops that directly follow another loop, and the
+    /// brackets of loops that are known to run exactly once
+    ///
+    /// See [`ProgramAnalysis::known_zero`](crate::ProgramAnalysis::known_zero).
+    Aggressive,
+    /// Everything in [`OptimizationLevel::Aggressive`], and peel the first iteration
+    /// of loops whose condition is known to be true, because the current cell is
+    /// known to be non-zero when the loop is reached
+    ///
+    /// Peeled loops are preceded by a copy of their body, so that the first iteration
+    /// does not check the condition. Only loops with at most [`PEEL_LIMIT`]
+    /// instructions are peeled.
+    Peeling,
+}
+
+/// The maximum amount of instructions in a loop that is peeled by
+/// [`OptimizationLevel::Peeling`]
+pub const PEEL_LIMIT: usize = 64;
+
+/// Whether `b` undoes `a`, where changes to a cell are only undone if it `wraps`
+fn cancels(a: BrainfuckInstruction, b: BrainfuckInstruction, wraps: bool) -> bool {
+    use BrainfuckInstruction::*;
+    match (a, b) {
+        (IncreaseData, DecreaseData) | (DecreaseData, IncreaseData) => wraps,
+        _ => matches!(
+            (a, b),
+            (IncrementDataPointer, DecrementDataPointer)
+                | (DecrementDataPointer, IncrementDataPointer)
+                | (MoveUp, MoveDown)
+                | (MoveDown, MoveUp)
+                | (PreviousTape, NextTape)
+                | (NextTape, PreviousTape)
+        ),
+    }
+}
+
+/// Remove adjacent instructions that cancel each other out, on cells that `wrap` or not
+fn remove_cancelling_pairs<'a>(spans: Vec<Span<'a>>, wraps: bool) -> Vec<Span<'a>> {
+    let mut result: Vec<Span> = Vec::with_capacity(spans.len());
+    for span in spans {
+        match result.last() {
+            Some(last) if cancels(last.get_instruction(), span.get_instruction(), wraps) => {
+                result.pop();
+            }
+            _ => result.push(span),
+        }
+    }
+    result
+}
+
+/// Remove the loop at the very start of the program, which is never entered and
+/// conventionally contains a comment. See [`ProgramMetadata`](crate::ProgramMetadata)
+fn remove_comment_loop<'a>(mut spans: Vec<Span<'a>>) -> Vec<Span<'a>> {
+    let mut depth = 0usize;
+    for (index, span) in spans.iter().enumerate() {
+        match span.get_instruction() {
+            BrainfuckInstruction::JumpForward(_) => depth += 1,
+            BrainfuckInstruction::JumpBackwards(_) => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 {
+            if index > 0 {
+                spans.drain(..=index);
+            }
+            break;
+        }
+    }
+    spans
+}
+
+/// Remove loops that are never entered, and the brackets of loops that run exactly once,
+/// as found by [`known_zero`] starting from the cells in `start`
+fn reduce_loops<'a>(spans: Vec<Span<'a>>, start: &KnownCells) -> Vec<Span<'a>> {
+    let instructions: Vec<_> = spans.iter().map(Span::get_instruction).collect();
+    let analysis = known_zero(&instructions, start);
+
+    let mut result = Vec::with_capacity(spans.len());
+    // The nesting depth inside of a loop that is being removed
+    let mut skipping = 0usize;
+    for (span, iterations) in spans.into_iter().zip(analysis.iterations) {
+        match span.get_instruction() {
+            BrainfuckInstruction::JumpForward(_)
+                if skipping > 0 || iterations == Iterations::Never =>
+            {
+                skipping += 1;
+            }
+            BrainfuckInstruction::JumpBackwards(_) if skipping > 0 => {
+                skipping -= 1;
+            }
+            _ if skipping > 0 => {}
+            BrainfuckInstruction::JumpForward(_) | BrainfuckInstruction::JumpBackwards(_)
+                if iterations == Iterations::Once => {}
+            _ => result.push(span),
+        }
+    }
+    result
+}
+
+/// Copy `spans` to `result`, preceding every loop whose condition is known to be true
+/// by a copy of its body
+fn peel_loops<'a>(spans: &[Span<'a>], known: &mut KnownCells, result: &mut Vec<Span<'a>>) {
+    let mut index = 0;
+    while let Some(span) = spans.get(index) {
+        let instruction = span.get_instruction();
+        if let BrainfuckInstruction::JumpForward(_) = instruction {
+            let end = matching_bracket(spans, index);
+            if end - index <= PEEL_LIMIT && known.current().is_some_and(|value| value != 0) {
+                peel_loops(&spans[index + 1..end], known, result);
+            }
+            result.extend_from_slice(&spans[index..=end]);
+            *known = KnownCells::after_loop();
+            index = end + 1;
+        } else {
+            known.apply(instruction);
+            result.push(span.clone());
+            index += 1;
+        }
+    }
+}
+
+/// The index of the `]` that matches the `[` at `start`
+fn matching_bracket(spans: &[Span], start: usize) -> usize {
+    let mut depth = 0usize;
+    for (index, span) in spans.iter().enumerate().skip(start) {
+        match span.get_instruction() {
+            BrainfuckInstruction::JumpForward(_) => depth += 1,
+            BrainfuckInstruction::JumpBackwards(_) => {
+                depth -= 1;
+                if depth == 0 {
+                    return index;
+                }
+            }
+            _ => {}
+        }
+    }
+    spans.len() - 1
+}
+
+/// Optimize `spans` at `level`, for a tape whose cells are all zero when the program
+/// starts if `zeroed` is set, and whose cells wrap around if `wraps` is set
+pub(crate) fn optimize<'a>(
+    mut spans: Vec<Span<'a>>,
+    level: OptimizationLevel,
+    zeroed: bool,
+    wraps: bool,
+) -> Vec<Span<'a>> {
+    if level == OptimizationLevel::None {
+        return spans;
+    }
+
+    let start = if zeroed {
+        spans = remove_comment_loop(spans);
+        KnownCells::zero()
+    } else {
+        KnownCells::unknown()
+    };
+    spans = simplify(spans, level, &start, wraps);
+    if level >= OptimizationLevel::Peeling {
+        let mut peeled = Vec::with_capacity(spans.len());
+        peel_loops(&spans, &mut start.clone(), &mut peeled);
+        // The copied bodies may cancel out with the instructions before them
+        spans = simplify(peeled, level, &start, wraps);
+    }
+    spans
+}
+
+/// Whether every cell of `tape` is known to be zero
+pub(crate) fn is_zeroed<T>(tape: &mut T) -> bool
+where
+    T: Tape,
+{
+    tape.as_mut_slice()
+        .is_some_and(|cells| cells.iter().all(|cell| *cell == T::Data::zero()))
+}
+
+/// Run the passes that remove instructions at `level`, until they no longer do, with
+/// the cells known as `start` when the program starts
+fn simplify<'a>(
+    mut spans: Vec<Span<'a>>,
+    level: OptimizationLevel,
+    start: &KnownCells,
+    wraps: bool,
+) -> Vec<Span<'a>> {
+    loop {
+        let len = spans.len();
+        spans = remove_cancelling_pairs(spans, wraps);
+        if level >= OptimizationLevel::Aggressive {
+            spans = reduce_loops(spans, start);
+        }
+        if spans.len() == len {
+            return spans;
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Options for compiling Brainfuck programs
+
+use crate::{
+    optimize::{is_zeroed, optimize},
+    BrainfuckProgram, Dialect, Error, OptimizationLevel, Span, Tape, TapeData,
+};
+
+/// Options that control how a Brainfuck program is compiled
+#[derive(Clone, Debug, Default)]
+pub struct CompileOptions {
+    /// The language in which the program is written
+    pub dialect: Dialect,
+    /// How much effort to put into optimizing the program
+    pub optimization: OptimizationLevel,
+    /// Whether to retain the position in the source code of every instruction
+    ///
+    /// See [`BrainfuckProgram::source_of`].
+    pub source_map: bool,
+    /// Whether moving the data pointer off the tape is reported as a
+    /// [`RuntimeError::PointerOutOfBounds`](crate::RuntimeError::PointerOutOfBounds)
+    /// at the move itself
+    ///
+    /// See [`BrainfuckProgram::strict`]. This retains the source map as well.
+    pub strict: bool,
+    /// Evaluate the instructions at the start of the program that do not depend on
+    /// input, with at most this many instructions, using
+    /// [`BrainfuckProgram::fold_prefix`]
+    pub fold_prefix: Option<usize>,
+}
+
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Compile a Brainfuck program, given by `input`, using `options`.
+    /// All non-valid characters are ignored
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{
+    /// #     BrainfuckProgram, CompileOptions, OptimizationLevel, SourcePos,
+    /// # };
+    /// let options = CompileOptions {
+    ///     optimization: OptimizationLevel::Basic,
+    ///     source_map: true,
+    ///     ..CompileOptions::default()
+    /// };
+    /// let source = "[a comment, with +.]\n+-+.";
+    /// let mut program =
+    ///     BrainfuckProgram::compile_with_options(source, vec![0u8; 1], &options).unwrap();
+    ///
+    /// // The comment loop and the `+-` that cancel each other out are removed
+    /// assert_eq!(program.instructions().len(), 2);
+    /// assert_eq!(program.source_of(1), Some(SourcePos { line: 1, character: 4 }));
+    ///
+    /// let mut output = Vec::new();
+    /// program.run(&mut |value| output.push(value), &mut || 0);
+    /// assert_eq!(output, [1]);
+    /// ```
+    pub fn compile_with_options<'a>(
+        input: &'a str,
+        mut tape: T,
+        options: &CompileOptions,
+    ) -> Result<Self, Error<'a>> {
+        let spans = Self::compile_spans(input, &options.dialect)?;
+
+        let spans = if options.optimization == OptimizationLevel::None {
+            spans
+        } else {
+            #[cfg(feature = "tracing")]
+            let _optimize =
+                tracing::debug_span!("optimize", level = ?options.optimization).entered();
+
+            let zeroed = is_zeroed(&mut tape);
+            let mut spans = optimize(spans, options.optimization, zeroed, T::Data::WRAPS);
+            // Removing instructions moves the brackets, so match them again. This
+            // cannot fail: the optimizer only removes or copies complete loops.
+            Self::match_brackets(&mut spans);
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(instructions = spans.len(), "optimized");
+
+            spans
+        };
+
+        let mut program = Self::from_spans(&spans, tape);
+        program.strict = options.strict;
+        if options.source_map || options.strict {
+            program.source_map = Some(spans.iter().map(Span::get_position).collect());
+            program.source = Some(input.into());
+        }
+        if let Some(fuel) = options.fold_prefix {
+            program.fold_prefix(fuel);
+        }
+        Ok(program)
+    }
+}
@@ -0,0 +1,200 @@
+//! Running programs whose output is the input of the next program
+
+use alloc::{collections::VecDeque, vec::Vec};
+use core::fmt::Display;
+
+use crate::{io::Output, BrainfuckInstruction, BrainfuckProgram, EofBehavior, RuntimeError, Tape};
+
+/// The amount of data that a stage of a [`Pipeline`] can write before the next stage
+/// has to read it, unless another capacity is set
+pub const DEFAULT_PIPELINE_CAPACITY: usize = 4096;
+
+/// An error that stopped a stage of a [`Pipeline`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PipelineError {
+    /// The index of the stage
+    pub stage: usize,
+    /// The error that stopped it
+    pub error: RuntimeError,
+}
+
+impl Display for PipelineError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "In stage {}: {}", self.stage, self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PipelineError {}
+
+/// Programs that run together, where the output of every program is the input of the
+/// next one, like the commands of a Unix pipe
+///
+/// The programs, or stages, run in turns on the same thread. The data written by a
+/// stage is buffered until the next stage reads it. A stage that reads while its buffer
+/// is empty waits for the previous stage to write, and a stage that writes while the
+/// buffer of the next stage is full waits for it to read. Once a stage has finished and
+/// the next stage has read everything it wrote, that stage is at the end of its input.
+/// The first stage reads the input of the pipeline, and the output of the last stage is
+/// the output of the pipeline. The pipeline finishes once its last stage does, even if
+/// other stages are still running.
+///
+/// ```
+/// # use brainfuck_interpreter::{BrainfuckProgram, Pipeline};
+/// let increment = BrainfuckProgram::compile(",[+.,]", vec![0u8; 1]).unwrap();
+/// let mut pipeline = Pipeline::new(vec![increment.clone(), increment]);
+///
+/// let mut output = Vec::new();
+/// let mut input = b"abc".iter().copied();
+/// pipeline.run(&mut |data| output.push(data), &mut || input.next());
+/// assert_eq!(output, b"cde");
+/// ```
+#[derive(Clone)]
+pub struct Pipeline<T>
+where
+    T: Tape,
+{
+    stages: Vec<BrainfuckProgram<T>>,
+    capacity: usize,
+    eof: EofBehavior,
+}
+
+impl<T> Pipeline<T>
+where
+    T: Tape,
+{
+    /// Connect `stages`, in order
+    ///
+    /// The buffers between them hold [`DEFAULT_PIPELINE_CAPACITY`] pieces of data, and
+    /// `,` stores zero at the end of input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stages` is empty.
+    pub fn new(stages: Vec<BrainfuckProgram<T>>) -> Self {
+        assert!(
+            !stages.is_empty(),
+            "A Pipeline must have at least one stage"
+        );
+        Self {
+            stages,
+            capacity: DEFAULT_PIPELINE_CAPACITY,
+            eof: EofBehavior::Zero,
+        }
+    }
+
+    /// Let every buffer between two stages hold `capacity` pieces of data, or one if
+    /// `capacity` is zero
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Let every stage use `eof` to determine what `,` does at the end of its input
+    pub fn with_eof(mut self, eof: EofBehavior) -> Self {
+        self.eof = eof;
+        self
+    }
+
+    /// The stages, in order
+    pub fn stages(&self) -> &[BrainfuckProgram<T>] {
+        &self.stages
+    }
+
+    /// The stages, in order, mutably
+    pub fn stages_mut(&mut self) -> &mut [BrainfuckProgram<T>] {
+        &mut self.stages
+    }
+
+    /// Consume this pipeline, returning its stages
+    pub fn into_inner(self) -> Vec<BrainfuckProgram<T>> {
+        self.stages
+    }
+
+    /// Run the stages until the last one finishes, with `input` returning `None` once
+    /// the input has run out
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`try_run`](Self::try_run) returns an error.
+    pub fn run<FnOut, FnIn>(&mut self, output: &mut FnOut, input: &mut FnIn)
+    where
+        FnOut: Output<T::Data>,
+        FnIn: FnMut() -> Option<T::Data>,
+    {
+        if let Err(PipelineError { stage, error }) = self.try_run(output, input) {
+            panic!("In stage {}: {}", stage, self.stages[stage].explain(&error));
+        }
+    }
+
+    /// Run the stages until the last one finishes, or until one of them fails
+    ///
+    /// Like [`run`](Self::run), but returns an error instead of panicking. The data that
+    /// was buffered between the stages is discarded.
+    pub fn try_run<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<(), PipelineError>
+    where
+        FnOut: Output<T::Data>,
+        FnIn: FnMut() -> Option<T::Data>,
+    {
+        let (capacity, eof) = (self.capacity, self.eof);
+        let last = self.stages.len() - 1;
+        let mut finished = alloc::vec![false; self.stages.len()];
+        // The data written by every stage but the last one, which the next stage has
+        // not read yet
+        let mut buffers: Vec<VecDeque<T::Data>> = (0..last).map(|_| VecDeque::new()).collect();
+
+        // Every turn, each stage runs until it waits or finishes. A stage can only wait
+        // for a stage that can run, so every turn makes progress
+        while !finished[last] {
+            for (index, stage) in self.stages.iter_mut().enumerate() {
+                if finished[index] {
+                    continue;
+                }
+                let (read, written) = buffers.split_at_mut(index);
+                let (mut read, mut written) = (read.last_mut(), written.first_mut());
+                let writer_finished = index == 0 || finished[index - 1];
+
+                loop {
+                    let waits = match stage.instructions().get(stage.instruction_pointer()) {
+                        Some(BrainfuckInstruction::Input) => {
+                            read.as_ref().is_some_and(|read| read.is_empty()) && !writer_finished
+                        }
+                        Some(BrainfuckInstruction::Output) => written
+                            .as_ref()
+                            .is_some_and(|written| written.len() >= capacity),
+                        _ => false,
+                    };
+                    if waits {
+                        break;
+                    }
+
+                    let running = stage
+                        .try_step_with_eof(
+                            &mut |data| match written.as_mut() {
+                                Some(written) => written.push_back(data),
+                                None => output.output(data),
+                            },
+                            &mut || match read.as_mut() {
+                                Some(read) => read.pop_front(),
+                                None => input(),
+                            },
+                            eof,
+                        )
+                        .map_err(|error| PipelineError {
+                            stage: index,
+                            error,
+                        })?;
+                    if !running {
+                        finished[index] = true;
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+//! Profiling of Brainfuck programs
+
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use crate::{
+    io::{Input, Output},
+    result::{Counted, RunStart},
+    BrainfuckInstruction, BrainfuckLexer, BrainfuckProgram, Lexer, RunOutcome, RunResult,
+    SourcePos, Tape,
+};
+
+/// Counts how often every instruction of a program is executed
+#[derive(Clone, Debug)]
+pub struct Profiler {
+    counts: Vec<u64>,
+}
+
+/// The profile of a single loop
+#[derive(Clone, Debug)]
+pub struct LoopProfile {
+    /// The index of the `[` of this loop
+    pub start: usize,
+    /// The index of the `]` of this loop
+    pub end: usize,
+    /// The position of the `[` of this loop
+    pub start_position: SourcePos,
+    /// The position of the `]` of this loop
+    pub end_position: SourcePos,
+    /// The amount of instructions executed inside of this loop, including the brackets
+    pub executed: u64,
+    /// The percentage of all executed instructions that were executed inside of this loop
+    pub percentage: f64,
+}
+
+/// A report of the loops in a program, hottest first
+#[derive(Clone, Debug)]
+pub struct ProfileReport {
+    /// The total amount of instructions executed
+    pub total: u64,
+    /// All loops in the program, ordered by the amount of instructions executed in them
+    pub loops: Vec<LoopProfile>,
+}
+
+#[allow(deprecated)]
+impl Profiler {
+    /// Create a profiler for `program`
+    pub fn new<T>(program: &BrainfuckProgram<T>) -> Self
+    where
+        T: Tape,
+    {
+        Self {
+            counts: alloc::vec![0; program.instructions.len()],
+        }
+    }
+
+    /// The amount of times every instruction has been executed, by instruction index
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Perform a step in `program`, counting the instruction that is executed
+    pub fn step<T, FnOut, FnIn>(
+        &mut self,
+        program: &mut BrainfuckProgram<T>,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> bool
+    where
+        T: Tape,
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
+    {
+        if let Some(count) = self.counts.get_mut(program.instruction_pointer) {
+            *count += 1;
+        }
+        program.step(output, input)
+    }
+
+    /// Run `program` to completion, counting every instruction that is executed
+    pub fn run<T, FnOut, FnIn>(
+        &mut self,
+        program: &mut BrainfuckProgram<T>,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> RunResult<T::Data>
+    where
+        T: Tape,
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
+    {
+        let start = RunStart::new(program);
+        let (mut output, mut input) = (Counted::new(output), Counted::new(input));
+        while self.step(program, &mut output, &mut input) {}
+        start.finish(program, RunOutcome::Halted, &output, &input)
+    }
+
+    /// Create a report of the hottest loops, given the `source` that the profiled
+    /// program was compiled from
+    ///
+    /// If `program` has a source map, it is used to find the position of the loops,
+    /// and `source` is ignored. Otherwise `source` is read as plain Brainfuck: use
+    /// [`report_with_lexer`](Self::report_with_lexer) for programs in other dialects.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckProgram, Profiler, SourcePos};
+    /// let source = "+\n[>++[>+<-]<-]";
+    /// let mut program = BrainfuckProgram::compile(source, vec![0u8; 3]).unwrap();
+    /// let mut profiler = Profiler::new(&program);
+    /// profiler.run(&mut program, &mut |_| {}, &mut || 0);
+    ///
+    /// let report = profiler.report(&program, source);
+    /// assert_eq!(report.total, 19);
+    /// let inner = &report.loops[1];
+    /// assert_eq!((inner.start, inner.end, inner.executed), (5, 10, 11));
+    /// assert_eq!(inner.start_position, SourcePos { line: 1, character: 5 });
+    /// assert_eq!(inner.end_position, SourcePos { line: 1, character: 10 });
+    /// ```
+    pub fn report<T>(&self, program: &BrainfuckProgram<T>, source: &str) -> ProfileReport
+    where
+        T: Tape,
+    {
+        self.report_with_lexer(program, source, &BrainfuckLexer)
+    }
+
+    /// Create a report of the hottest loops, given the `source` that the profiled
+    /// program was compiled from and the `lexer` that it was compiled with
+    ///
+    /// If `program` has a source map, it is used to find the position of the loops,
+    /// and `source` and `lexer` are ignored.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckProgram, OokLexer, Profiler, SourcePos};
+    /// let source = "Ook. Ook.\nOok! Ook? Ook! Ook! Ook? Ook!";
+    /// let mut program = BrainfuckProgram::compile_with_lexer(source, vec![0u8], &OokLexer)
+    ///     .unwrap();
+    /// let mut profiler = Profiler::new(&program);
+    /// profiler.run(&mut program, &mut |_| {}, &mut || 0);
+    ///
+    /// let report = profiler.report_with_lexer(&program, source, &OokLexer);
+    /// assert_eq!(report.loops[0].start_position, SourcePos { line: 1, character: 1 });
+    /// assert_eq!(report.loops[0].end_position, SourcePos { line: 1, character: 21 });
+    /// ```
+    pub fn report_with_lexer<T, L>(
+        &self,
+        program: &BrainfuckProgram<T>,
+        source: &str,
+        lexer: &L,
+    ) -> ProfileReport
+    where
+        T: Tape,
+        L: Lexer + ?Sized,
+    {
+        let positions = source_positions(program, source, lexer);
+        let position = |index: usize| {
+            positions.get(index).copied().unwrap_or(SourcePos {
+                line: 0,
+                character: 0,
+            })
+        };
+
+        let total = self.counts.iter().sum();
+
+        let mut loops = Vec::new();
+        let mut open = Vec::new();
+        for (index, instruction) in program.instructions.iter().enumerate() {
+            match instruction {
+                BrainfuckInstruction::JumpForward(_) => open.push(index),
+                BrainfuckInstruction::JumpBackwards(_) => {
+                    if let Some(start) = open.pop() {
+                        let executed: u64 = self.counts[start..=index].iter().sum();
+                        let percentage = if total == 0 {
+                            0.0
+                        } else {
+                            executed as f64 * 100.0 / total as f64
+                        };
+                        loops.push(LoopProfile {
+                            start,
+                            end: index,
+                            start_position: position(start),
+                            end_position: position(index),
+                            executed,
+                            percentage,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        loops.sort_by(|a, b| b.executed.cmp(&a.executed).then(a.start.cmp(&b.start)));
+
+        ProfileReport { total, loops }
+    }
+}
+
+impl Display for ProfileReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "Total instructions executed: {}", self.total)?;
+        for profile in &self.loops {
+            writeln!(
+                f,
+                "{:>6.2}% {:>12} loop at line {}, character {} to line {}, character {}",
+                profile.percentage,
+                profile.executed,
+                profile.start_position.line,
+                profile.start_position.character,
+                profile.end_position.line,
+                profile.end_position.character,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The position of every instruction of `program`, from its source map or else by
+/// lexing `source` with `lexer`
+pub(crate) fn source_positions<T, L>(
+    program: &BrainfuckProgram<T>,
+    source: &str,
+    lexer: &L,
+) -> Vec<SourcePos>
+where
+    T: Tape,
+    L: Lexer + ?Sized,
+{
+    match &program.source_map {
+        Some(map) => map.clone(),
+        None => {
+            let mut positions = Vec::new();
+            lexer.lex(source, &mut |_, position| positions.push(position));
+            positions
+        }
+    }
+}
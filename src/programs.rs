@@ -0,0 +1,132 @@
+//! Well-known Brainfuck programs, with the output that they are expected to produce
+//!
+//! The programs can be shared by examples, benchmarks and tests, so that they do not
+//! each need a copy of the source code.
+//!
+//! ```
+//! # use brainfuck_interpreter::programs::{self, HELLO_WORLD};
+//! assert_eq!(HELLO_WORLD.run(), b"Hello World!\n");
+//!
+//! for fixture in programs::ALL {
+//!     assert_eq!(fixture.run(), fixture.output, "{}", fixture.name);
+//! }
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{BrainfuckProgram, EofBehavior};
+
+/// A Brainfuck program, together with an input and the output that it produces for it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Fixture {
+    /// A short name, in kebab-case
+    pub name: &'static str,
+    /// What the program does
+    pub description: &'static str,
+    /// The source code
+    pub source: &'static str,
+    /// The input to run the program with
+    pub input: &'static [u8],
+    /// The output of the program when it is run with `input`
+    pub output: &'static [u8],
+    /// What `,` should do once `input` has run out
+    pub eof: EofBehavior,
+}
+
+impl Fixture {
+    /// Compile this program and run it to completion on a growing `Vec<u8>` tape with
+    /// [`input`](Self::input), returning its output
+    pub fn run(&self) -> Vec<u8> {
+        self.run_with(self.input)
+    }
+
+    /// Compile this program and run it to completion on a growing `Vec<u8>` tape with
+    /// `input` instead of [`Fixture::input`], returning its output
+    pub fn run_with(&self, input: &[u8]) -> Vec<u8> {
+        let mut program = BrainfuckProgram::compile(self.source, Vec::new())
+            .expect("The programs in this module compile");
+        let mut input = input.iter().copied();
+        let mut output = Vec::new();
+        program.run_with_eof(
+            &mut |data| output.push(data),
+            &mut || input.next(),
+            self.eof,
+        );
+        output
+    }
+}
+
+/// Prints `Hello World!` and a newline
+pub const HELLO_WORLD: Fixture = Fixture {
+    name: "hello-world",
+    description: "Prints `Hello World!` and a newline",
+    source: include_str!("programs/hello-world.b"),
+    input: b"",
+    output: b"Hello World!\n",
+    eof: EofBehavior::Zero,
+};
+
+/// Applies ROT13 to its input, by Daniel B Cristofani
+pub const ROT13: Fixture = Fixture {
+    name: "rot13",
+    description: "Applies ROT13 to its input",
+    source: include_str!("programs/rot13.b"),
+    input: b"Hello, World!\n",
+    output: b"Uryyb, Jbeyq!\n",
+    eof: EofBehavior::Unchanged,
+};
+
+/// Prints a Sierpinski triangle of 32 lines, by Daniel B Cristofani
+pub const SIERPINSKI: Fixture = Fixture {
+    name: "sierpinski",
+    description: "Prints a Sierpinski triangle of 32 lines",
+    source: include_str!("programs/sierpinski.b"),
+    input: b"",
+    output: include_bytes!("programs/sierpinski.out"),
+    eof: EofBehavior::Zero,
+};
+
+/// A Brainfuck interpreter written in Brainfuck, by Daniel B Cristofani
+///
+/// Its input is the source code of a program, followed by a `!` and the input of that
+/// program.
+pub const DBFI: Fixture = Fixture {
+    name: "dbfi",
+    description: "Runs the program in its input, up to a `!`, with the rest of its input",
+    source: include_str!("programs/dbfi.b"),
+    input: b",[.,]!echo",
+    output: b"echo",
+    eof: EofBehavior::Zero,
+};
+
+/// Prints the Mandelbrot set as 19 lines of 40 characters
+///
+/// It was written for this crate, and runs about 600 million instructions, so it is
+/// not part of [`ALL`].
+pub const MANDELBROT: Fixture = Fixture {
+    name: "mandelbrot",
+    description: "Prints the Mandelbrot set as 19 lines of 40 characters",
+    source: include_str!("programs/mandelbrot.b"),
+    input: b"",
+    output: include_bytes!("programs/mandelbrot.out"),
+    eof: EofBehavior::Zero,
+};
+
+/// Prints the moves that solve the Towers of Hanoi for 9 disks
+///
+/// It was written for this crate, and compiles to about 20 thousand instructions, so
+/// it is not part of [`ALL`].
+pub const HANOI: Fixture = Fixture {
+    name: "hanoi",
+    description: "Prints the moves that solve the Towers of Hanoi for 9 disks",
+    source: include_str!("programs/hanoi.b"),
+    input: b"",
+    output: include_bytes!("programs/hanoi.out"),
+    eof: EofBehavior::Zero,
+};
+
+/// All programs in this module, except for [`MANDELBROT`] and [`HANOI`]
+pub const ALL: &[Fixture] = &[HELLO_WORLD, ROT13, SIERPINSKI, DBFI];
+
+/// The programs in this module that are too large or slow for [`ALL`]
+pub const LARGE: &[Fixture] = &[MANDELBROT, HANOI];
@@ -0,0 +1,77 @@
+Prints the Mandelbrot set as 19 lines of 40 characters
+Each character is a point with real part from minus 2 to 7/16 and imaginary part from
+minus 9/8 to 9/8 that is shaded by the number of iterations before it escapes; points
+that do not escape within 16 iterations are printed as @
+It uses fixed point numbers with 4 fractional bits in 8 bit cells that wrap around
+
+Written for the brainfuck_interpreter crate and released under its MIT license
+
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]------------------>[-]+++++++++++++++++++[<<[-
+]-------------------------------->>>[-]++++++++++++++++++++++++++++++++++++++++[
+<<<<<<<<<[-]>>>[-]>>>>>>>[-]+>>>[-]++++++++++++++++<<<[<<<<<<<<<<[-<<<<<<<<<<<<+
+<<<<<<<+>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<
+<<<<<<<<]>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<-<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>]<<<<<<<<<
+<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>[-]++++++++++++
+++++++++++++++++++++<<<<<<<<<[-]>>>>>>>>>>>>[-]<<<<<<<<+<[>>>>+<[>>>>+<[>>>+<<-]
+>[->]<<<<-]>[->]<<<<-]>[->]>>>>>>>[<<<<<<<<<->>>->>>-<<<<<<<<<+>>>>>>>>>>>>[-]<<
+<<<<<<+<[>>>>+<[>>>>+<[>>>+<<-]>[->]<<<<-]>[->]<<<<-]>[->]>>>>>>>]>>>>>>>>>>>>>>
+>>>>>>>>>>>>>>>[-]<<<<<<<<<<[-]<<<<<<<<<<<<<<<<<<<<<<<<<<<+<[>>>>>>>>>>>>>>>>>>>
+>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<-]>[->]>>>>>+<[>-]>[>>>
+>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<->]<<<<<<<<[-]>>>[-]>>>[-]>>>>>>>>>[-<<<
+<<<<<<<<<<<<+<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>
+>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<-<<<<<<<<<
+<+>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<
+<<<<<<<<<<<<<<]>>>>>>>>>>>>>[-]++++++++++++++++++++++++++++++++<<<<<<<<[-]>>>>>>
+>>>>>[-]<<<<<<<<+<[>>>>+<[>>>>+<[>>>+<<-]>[->]<<<<-]>[->]<<<<-]>[->]>>>>>>>[<<<<
+<<<<<->>>->>>-<<<<<<<<+>>>>>>>>>>>[-]<<<<<<<<+<[>>>>+<[>>>>+<[>>>+<<-]>[->]<<<<-
+]>[->]<<<<-]>[->]>>>>>>>]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<<<<<<<<[-]<<<<<<<<<<<
+<<<<<<<<<<<<<<<<<<<+<[>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<<<<<<<<-]>[->]>>>>>+<[>-]>[>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<
+<<<<<<<<<->]<<<<<<<<[-]>>>[-]>>>[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<<<<<+<[>>>>>>
++<<<<<-]>[->]>>+<[>>>+<<-]>[->]>>+<[>-]>[<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+<<[-]<<<<<<[-]++++++++>>>>>>>>>[-<<<<<+>+>>>>]<<<<[->>>>+<<<<]<[->>>>>[-<<<<<<+>
+>+>>>>]<<<<[->>>>+<<<<]<<[-<<<->+<[>-]>[<++++++++++++++++>>>>>>+<<<<<->]>]>]<<<<
+[-]>>>>>>>[-]<<<<<<<[-]++++++++>>>>>>>>>>[-<<<<<<+>+>>>>>]<<<<<[->>>>>+<<<<<]<[-
+>>>>>>[-<<<<<<<+>>+>>>>>]<<<<<[->>>>>+<<<<<]<<[-<<<->+<[>-]>[<++++++++++++++++>>
+>>>>>+<<<<<<->]>]>]<<<<[-]>>>>>>[->>>>>+<<<<<<+>]<[->+<]>>[->>>>+<<<<<<+>>]<<[->
+>+<<]>>>>>>[->+<<<<<<<+>>>>>>]<<<<<<[->>>>>>+<<<<<<]>>>>>>>>>>[-]+++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++>>>>>>[-]<<<<<<<<+<[>>>>+<[>>>
+>>>+<<<<<-]>[->]<<<<-]>[->]>>>>>>>[<<<<<<<<<->>>->>>>>>[-]<<<<<<<<+<[>>>>+<[>>>>
+>>+<<<<<-]>[->]<<<<-]>[->]>>>>>>>]>>>>>>>>>>>>>>>>>>>>>>>>>[-]<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<+<[>-]>[>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+<->]<<<<<[-]>>>[-]<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>->]<+<[<<<<<<<<<<<<
+[-]>>>>>>>>>>>>>-]>[<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]<<<<<<<<[-]+++++++
++>>>>>>>>>[-<<<<<+>+>>>>]<<<<[->>>>+<<<<]<[->>>>>>[-<<<<<<<+>>+>>>>>]<<<<<[->>>>
+>+<<<<<]<<[-<<<->+<[>-]>[<++++++++++++++++>>>>>>>>+<<<<<<<->]>]>]<<<<[-]>>>>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<<[->>+<<<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<
+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
++<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>>>>>>>>>>>[->+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>-<<<<<<<<<<<<<<<<<<
+<<<<<<<[-]>>>>[-<<<<+<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<
+<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<[<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<<<[->>>>>>>>>>>>>>>>>>>++<<<<<<<<<<<<<<<<<<<<<<+>>>]<<<[->>>+<<<]>>>>>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>-]>[<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<<<[->>>>>>>>>>>>>>>>>>>--<<<<<<<<<<<<<<<<<<<<<<+>>>]<<<[->>>+<<<]>>>>>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>->]<<[-]<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]<<
+<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<]>[->>>>>>>>>>>>>>>>>-<<<
+<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<+<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>
+>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<
+<<<<<<<<<<<]>>>[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>->+<[>-]>[<<<<[-]>>>>->]>>>>>>>>-
+>]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]>[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>]>
+>>>>>>>>>>>>>>[-]>>>>>>[-]++++++++++++++++<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>
+-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>>]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<
+<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<[>>>>>>>>>>>>+++++++
++++++++++++++++++++++++++>>>>>>>+<[-<<<<<<++++++++++++++>>>>>>>-]>[->]<+<[-<<<<<
+<-->>>>>>>-]>[->]<+<[-<<<<<<++++++++++++++>>>>>>>-]>[->]<+<[-<<<<<<+>>>>>>>-]>[-
+>]<+<[-<<<<<<-------------->>>>>>>-]>[->]<+<[-<<<<<<++++++++++++++++>>>>>>>-]>[-
+>]<+<[-<<<<<<------------------>>>>>>>-]>[->]<+<[-<<<<<<->>>>>>>-]>[->]<+<[-<<<<
+<<----->>>>>>>-]>[->]<+<[-<<<<<<-->>>>>>>-]>[->]<<<<<<<<<<<<<<<<<<<-]>[>>>>>>>>>
+>>++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++<<<<<<<<<<<->]
+>>>>>>>>>>>>>>>>[-]<<<<<<.[-]<<<<<<<<<<<<<<<<<<<+>>>-]>>>>>>>>>>>>>>>>++++++++++
+.[-]<<<<<<<<<<<<<<<<<<++>-]
@@ -0,0 +1,53 @@
+//! The base protocol shared by the Debug Adapter Protocol and the Language Server
+//! Protocol: JSON messages, each preceded by a `Content-Length` header
+
+use std::io::{self, BufRead, Write};
+
+use serde_json::Value;
+
+/// Read a single message, returning `None` once the connection has been closed
+pub(crate) fn read_message<R>(reader: &mut R) -> io::Result<Option<Value>>
+where
+    R: BufRead,
+{
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let content_length = content_length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header")
+    })?;
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+/// Write a single message
+pub(crate) fn write_message<W>(writer: &mut W, message: &Value) -> io::Result<()>
+where
+    W: Write,
+{
+    let content = message.to_string();
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    writer.flush()
+}
@@ -0,0 +1,49 @@
+//! A small, seedable source of pseudorandom numbers
+
+/// A pseudorandom number generator that produces the same numbers for the same seed
+///
+/// This uses the SplitMix64 algorithm, which is fast and good enough for generating
+/// and mutating programs, but is not suitable for cryptography.
+///
+/// ```
+/// # use brainfuck_interpreter::SeededRng;
+/// let mut a = SeededRng::new(42);
+/// let mut b = SeededRng::new(42);
+/// assert_eq!(a.next_u64(), b.next_u64());
+/// assert!(a.below(10) < 10);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    /// Create a generator from `seed`
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// The next pseudorandom number
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A pseudorandom number below `bound`, or zero if `bound` is zero
+    pub fn below(&mut self, bound: usize) -> usize {
+        if bound == 0 {
+            return 0;
+        }
+        // The bias of taking the remainder is negligible for the small bounds that
+        // are used here
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// `true` with a probability of `numerator / denominator`
+    pub fn chance(&mut self, numerator: usize, denominator: usize) -> bool {
+        self.below(denominator) < numerator
+    }
+}
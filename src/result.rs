@@ -0,0 +1,266 @@
+//! What a run of a Brainfuck program ends with
+
+use core::time::Duration;
+
+use crate::io::{Input, Output};
+#[cfg(feature = "alloc")]
+use crate::{BrainfuckProgram, Tape};
+
+/// Why a run of a Brainfuck program ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RunOutcome {
+    /// The program finished
+    Halted,
+    /// The program executed [`RunConfig::max_instructions`](crate::RunConfig) instructions
+    InstructionLimit,
+    /// The program ran for [`RunConfig::max_duration`](crate::RunConfig)
+    Timeout,
+}
+
+/// What happened during a run of a Brainfuck program
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RunStats {
+    /// The amount of instructions executed by the interpreter
+    ///
+    /// Like [`BrainfuckProgram::execution_count`], this does not include instructions
+    /// that were executed as threaded or native code.
+    pub instructions: usize,
+    /// The amount of data written with `.`
+    pub outputs: usize,
+    /// The amount of data read with `,`, including reads at the end of input
+    pub inputs: usize,
+}
+
+/// The result of a run of a Brainfuck program
+///
+/// Many programs follow the convention that the value of a cell, usually the first
+/// one, is their exit status once they finish. The cell is chosen with
+/// [`BrainfuckProgram::exit_cell`].
+///
+/// ```
+/// # use brainfuck_interpreter::{BrainfuckProgram, RunOutcome};
+/// let mut program = BrainfuckProgram::compile("+++>++.", vec![0u8; 2]).unwrap();
+/// let result = program.run(&mut |_| {}, &mut || 0);
+/// assert_eq!(result.outcome(), RunOutcome::Halted);
+/// assert_eq!(result.stats().outputs, 1);
+/// assert_eq!(result.final_data_pointer(), 1);
+/// assert_eq!(result.exit_code(), Some(3));
+///
+/// program.reset();
+/// program.exit_cell = 1;
+/// let result = program.run(&mut |_| {}, &mut || 0);
+/// assert_eq!(result.exit_code(), Some(2));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RunResult<D> {
+    outcome: RunOutcome,
+    stats: RunStats,
+    final_data_pointer: usize,
+    duration: Option<Duration>,
+    exit_status: Option<D>,
+}
+
+impl<D> RunResult<D> {
+    /// Why the run ended
+    pub fn outcome(&self) -> RunOutcome {
+        self.outcome
+    }
+
+    /// What happened during the run
+    pub fn stats(&self) -> RunStats {
+        self.stats
+    }
+
+    /// The data pointer when the run ended
+    pub fn final_data_pointer(&self) -> usize {
+        self.final_data_pointer
+    }
+
+    /// How long the run took, if it was measured
+    ///
+    /// Without the `std` feature, there is no clock to measure it with.
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// The value of the exit cell when the run ended, if the tape has that cell
+    pub fn exit_status(&self) -> Option<&D> {
+        self.exit_status.as_ref()
+    }
+
+    /// The exit status as the exit code of a process, if the tape has the exit cell
+    ///
+    /// Values that do not fit in an `i32` wrap around, so only values up to 255 are
+    /// passed on unchanged by every operating system.
+    pub fn exit_code(&self) -> Option<i32>
+    where
+        D: Clone + Into<u32>,
+    {
+        self.exit_status
+            .clone()
+            .map(|status| Into::<u32>::into(status) as i32)
+    }
+
+    /// Take the outcome, the stats, the final data pointer, the duration and the exit
+    /// status
+    pub fn into_parts(self) -> (RunOutcome, RunStats, usize, Option<Duration>, Option<D>) {
+        (
+            self.outcome,
+            self.stats,
+            self.final_data_pointer,
+            self.duration,
+            self.exit_status,
+        )
+    }
+
+    /// Convert the exit status with `f`
+    #[cfg(feature = "alloc")]
+    pub(crate) fn map_exit_status<E>(self, f: impl FnOnce(D) -> E) -> RunResult<E> {
+        RunResult {
+            outcome: self.outcome,
+            stats: self.stats,
+            final_data_pointer: self.final_data_pointer,
+            duration: self.duration,
+            exit_status: self.exit_status.map(f),
+        }
+    }
+}
+
+/// Running functions returned a [`RunOutcome`] before they returned a [`RunResult`]
+impl<D> From<RunResult<D>> for RunOutcome {
+    fn from(result: RunResult<D>) -> Self {
+        result.outcome
+    }
+}
+
+/// The state of a program at the start of a run, which its [`RunResult`] is relative to
+pub(crate) struct RunStart {
+    execution_count: usize,
+    #[cfg(feature = "std")]
+    time: std::time::Instant,
+}
+
+#[allow(deprecated)]
+impl RunStart {
+    /// Start a run of `program`
+    #[cfg(feature = "alloc")]
+    pub(crate) fn new<T>(program: &BrainfuckProgram<T>) -> Self
+    where
+        T: Tape,
+    {
+        Self::at(program.execution_count)
+    }
+
+    /// Start a run once `execution_count` instructions were executed
+    pub(crate) fn at(execution_count: usize) -> Self {
+        Self {
+            execution_count,
+            #[cfg(feature = "std")]
+            time: std::time::Instant::now(),
+        }
+    }
+
+    /// The result of the run of `program` that ended with `outcome`, during which the
+    /// data counted by `output` and `input` was passed
+    #[cfg(feature = "alloc")]
+    pub(crate) fn finish<T, O, I>(
+        self,
+        program: &mut BrainfuckProgram<T>,
+        outcome: RunOutcome,
+        output: &Counted<O>,
+        input: &Counted<I>,
+    ) -> RunResult<T::Data>
+    where
+        T: Tape,
+    {
+        let exit_status = program.tape.get_data_at(program.exit_cell).cloned();
+        self.finish_at(
+            outcome,
+            program.execution_count,
+            program.data_pointer,
+            exit_status,
+            output,
+            input,
+        )
+    }
+
+    /// The result of the run that ended with `outcome` once `execution_count`
+    /// instructions were executed, with the data pointer at `final_data_pointer` and
+    /// `exit_status` in the exit cell
+    pub(crate) fn finish_at<D, O, I>(
+        self,
+        outcome: RunOutcome,
+        execution_count: usize,
+        final_data_pointer: usize,
+        exit_status: Option<D>,
+        output: &Counted<O>,
+        input: &Counted<I>,
+    ) -> RunResult<D> {
+        #[cfg(feature = "std")]
+        let duration = Some(self.time.elapsed());
+        #[cfg(not(feature = "std"))]
+        let duration = None;
+
+        RunResult {
+            outcome,
+            stats: RunStats {
+                instructions: execution_count - self.execution_count,
+                outputs: output.count,
+                inputs: input.count,
+            },
+            final_data_pointer,
+            duration,
+            exit_status,
+        }
+    }
+}
+
+/// An input or output that counts the data passed through it
+pub(crate) struct Counted<'a, F> {
+    inner: &'a mut F,
+    count: usize,
+}
+
+impl<'a, F> Counted<'a, F> {
+    /// Count the data passed through `inner`
+    pub(crate) fn new(inner: &'a mut F) -> Self {
+        Self { inner, count: 0 }
+    }
+
+    /// Read from an input that returns `None` once it has run out
+    #[cfg(feature = "alloc")]
+    pub(crate) fn next_input<D>(&mut self) -> Option<D>
+    where
+        F: FnMut() -> Option<D>,
+    {
+        self.count += 1;
+        (self.inner)()
+    }
+
+    /// Count a piece of data that is passed to or from the returned `inner` directly
+    #[cfg(feature = "async")]
+    pub(crate) fn count_one(&mut self) -> &mut F {
+        self.count += 1;
+        self.inner
+    }
+}
+
+impl<D, F> Output<D> for Counted<'_, F>
+where
+    F: Output<D>,
+{
+    fn output(&mut self, data: D) {
+        self.count += 1;
+        self.inner.output(data)
+    }
+}
+
+impl<D, F> Input<D> for Counted<'_, F>
+where
+    F: Input<D>,
+{
+    fn input(&mut self) -> D {
+        self.count += 1;
+        self.inner.input()
+    }
+}
@@ -0,0 +1,166 @@
+//! Running Brainfuck programs with limits on their execution
+
+use std::time::{Duration, Instant};
+
+use crate::{
+    io::{ChainedInput, Input, Output},
+    result::{Counted, RunStart},
+    BrainfuckProgram, RunOutcome, RunResult, RuntimeError, Tape, TapeData,
+};
+
+/// Limits on a single run of a Brainfuck program
+#[derive(Clone, Debug)]
+pub struct RunConfig {
+    /// The maximum amount of instructions to execute, if any
+    pub max_instructions: Option<usize>,
+    /// The maximum amount of time to run for, if any
+    pub max_duration: Option<Duration>,
+    /// The amount of instructions executed between two checks of the elapsed time
+    ///
+    /// Checking the time is expensive compared to executing an instruction, so it is
+    /// only done periodically. `max_duration` may be exceeded by the time it takes
+    /// to execute this many instructions.
+    pub time_check_interval: usize,
+    /// Bytes that the program reads before its input, like its arguments
+    ///
+    /// Every byte is read as the value of a cell that is zero after as many `+`. The
+    /// prefix is read again by every run with this configuration.
+    pub input_prefix: Vec<u8>,
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self {
+            max_instructions: None,
+            max_duration: None,
+            time_check_interval: 1 << 16,
+            input_prefix: Vec::new(),
+        }
+    }
+}
+
+impl RunConfig {
+    /// Append `bytes` to [`input_prefix`](Self::input_prefix), so that the program
+    /// reads them before its input
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckProgram, RunConfig};
+    /// let config = RunConfig::default().prepend_input("abc");
+    /// let mut program = BrainfuckProgram::compile(",[.,]", vec![0u8; 1]).unwrap();
+    /// let mut stdin = b"def".iter().copied();
+    ///
+    /// let mut output = Vec::new();
+    /// let input = &mut || stdin.next().unwrap_or(0);
+    /// program.run_with_config(&config, &mut |data| output.push(data), input);
+    /// assert_eq!(output, b"abcdef");
+    /// ```
+    pub fn prepend_input<B>(mut self, bytes: B) -> Self
+    where
+        B: AsRef<[u8]>,
+    {
+        self.input_prefix.extend_from_slice(bytes.as_ref());
+        self
+    }
+
+    /// Append every argument in `args`, each followed by a newline, to
+    /// [`input_prefix`](Self::input_prefix)
+    ///
+    /// A program reads its arguments one line at a time, and then continues with its
+    /// input, whose end of input is not affected.
+    pub fn args_as_input<A, B>(self, args: A) -> Self
+    where
+        A: IntoIterator<Item = B>,
+        B: AsRef<[u8]>,
+    {
+        args.into_iter().fold(self, |config, arg| {
+            config.prepend_input(arg).prepend_input("\n")
+        })
+    }
+}
+
+/// The value of a cell that is zero after increasing it `byte` times
+fn from_byte<D>(byte: u8) -> D
+where
+    D: TapeData,
+{
+    let mut data = D::zero();
+    (0..byte).for_each(|_| data.increase());
+    data
+}
+
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Run the Brainfuck program until it finishes, or until one of the limits
+    /// in `config` is reached
+    ///
+    /// The limits apply to this call only: if the program is stopped by a limit,
+    /// calling this function again continues where it left off. The program reads
+    /// [`RunConfig::input_prefix`] before `input` in every call, so a run that is
+    /// continued usually uses a configuration without it. Where a program was stopped
+    /// is shown by [`current_span`](Self::current_span).
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`try_run_with_config`](Self::try_run_with_config) returns an error.
+    pub fn run_with_config<FnOut, FnIn>(
+        &mut self,
+        config: &RunConfig,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> RunResult<T::Data>
+    where
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
+    {
+        match self.try_run_with_config(config, output, input) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", self.explain(&error)),
+        }
+    }
+
+    /// Run the Brainfuck program until it finishes, fails, or until one of the limits
+    /// in `config` is reached
+    ///
+    /// Like [`run_with_config`](Self::run_with_config), but returns an error instead
+    /// of panicking. Code that only needs to know why the run ended can convert the
+    /// [`RunResult`] into a [`RunOutcome`] with [`Into::into`].
+    pub fn try_run_with_config<FnOut, FnIn>(
+        &mut self,
+        config: &RunConfig,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<RunResult<T::Data>, RuntimeError>
+    where
+        FnOut: Output<T::Data>,
+        FnIn: Input<T::Data>,
+    {
+        let run = RunStart::new(self);
+        let prefix = config.input_prefix.iter().map(|byte| from_byte(*byte));
+        let mut output = Counted::new(output);
+        let mut chained = ChainedInput::new(prefix, || input.input());
+        let mut input = Counted::new(&mut chained);
+        let start = Instant::now();
+        let check_interval = config.time_check_interval.max(1);
+        let mut executed = 0;
+
+        let outcome = loop {
+            if config.max_instructions == Some(executed) {
+                break RunOutcome::InstructionLimit;
+            }
+
+            if let Some(max_duration) = config.max_duration {
+                if executed % check_interval == 0 && start.elapsed() >= max_duration {
+                    break RunOutcome::Timeout;
+                }
+            }
+
+            if !self.try_step(&mut output, &mut input)? {
+                break RunOutcome::Halted;
+            }
+            executed += 1;
+        };
+        Ok(run.finish(self, outcome, &output, &input))
+    }
+}
@@ -0,0 +1,438 @@
+//! Errors that can occur while running programs
+
+use core::fmt::{Display, Write};
+
+use crate::{write_padding, BrainfuckInstruction, SourcePos, Span};
+
+/// An error that stops a program while it is running, as returned by
+/// [`BrainfuckProgram::try_step`](crate::BrainfuckProgram::try_step)
+///
+/// [`BrainfuckProgram::step`](crate::BrainfuckProgram::step) panics with these errors
+/// instead. If the program kept its [`source`](crate::BrainfuckProgram::source), the
+/// error holds a [`SourceExcerpt`] of the line that caused it, which its [`Display`]
+/// shows with a caret below the instruction. Otherwise, use
+/// [`with_source`](Self::with_source) to show the line as well.
+///
+/// ```
+/// # use brainfuck_interpreter::BrainfuckProgram;
+/// let mut program = BrainfuckProgram::compile_with_source_map("+\n>>+", [0u8; 2])
+///     .unwrap();
+/// let error = program.try_run(&mut |_| {}, &mut || 0).unwrap_err();
+/// assert_eq!(
+///     error.to_string(),
+///     "Data pointer went out of bounds! 2 (moved at line 1, character 2)\n>>+\n ^",
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuntimeError {
+    /// The tape has no cell at the data pointer, because it is out of bounds or
+    /// the tape has reached its limit
+    TapeExhausted {
+        /// The data pointer that the tape has no cell for
+        data_pointer: usize,
+        /// The position of the move that went out of bounds, if the program has a
+        /// source map
+        position: Option<SourcePos>,
+        /// The line of source code that caused the error, if the program kept its
+        /// [`source`](crate::BrainfuckProgram::source)
+        excerpt: Option<SourceExcerpt>,
+    },
+    /// In [strict mode](crate::BrainfuckProgram::strict), the data pointer was moved off
+    /// the tape
+    PointerOutOfBounds {
+        /// The index of the move
+        instruction_pointer: usize,
+        /// The position of the move
+        position: SourcePos,
+        /// The line of source code that caused the error, if the program kept its
+        /// [`source`](crate::BrainfuckProgram::source)
+        excerpt: Option<SourceExcerpt>,
+    },
+    /// A cell was increased past its largest value, or decreased past zero, and its
+    /// data does not allow that, like [`Trapping`](crate::Trapping) cells
+    CellOverflow {
+        /// The index of the instruction that changed the cell
+        instruction_pointer: usize,
+        /// The index of the cell
+        data_pointer: usize,
+        /// The position of the instruction, if the program has a source map
+        position: Option<SourcePos>,
+        /// The line of source code that caused the error, if the program kept its
+        /// [`source`](crate::BrainfuckProgram::source)
+        excerpt: Option<SourceExcerpt>,
+    },
+    /// A procedure that has not been defined was called
+    UndefinedProcedure {
+        /// The index of the call
+        instruction_pointer: usize,
+        /// The position of the call, if the program has a source map
+        position: Option<SourcePos>,
+        /// The line of source code that caused the error, if the program kept its
+        /// [`source`](crate::BrainfuckProgram::source)
+        excerpt: Option<SourceExcerpt>,
+    },
+    /// A `%` could not call a host function
+    HostCall {
+        /// The index of the `%`
+        instruction_pointer: usize,
+        /// The position of the `%`, if the program has a source map
+        position: Option<SourcePos>,
+        /// The line of source code that caused the error, if the program kept its
+        /// [`source`](crate::BrainfuckProgram::source)
+        excerpt: Option<SourceExcerpt>,
+        /// Why the call failed
+        error: HostCallError,
+    },
+    /// A `^` or `v` was executed on a tape that is not a grid, see
+    /// [`Tape::row_len`](crate::Tape::row_len)
+    NotAGrid {
+        /// The index of the move
+        instruction_pointer: usize,
+        /// The position of the move, if the program has a source map
+        position: Option<SourcePos>,
+        /// The line of source code that caused the error, if the program kept its
+        /// [`source`](crate::BrainfuckProgram::source)
+        excerpt: Option<SourceExcerpt>,
+    },
+    /// A `{` or `}` was executed on a tape that does not hold several tapes, see
+    /// [`Tape::switch_tape`](crate::Tape::switch_tape)
+    SingleTape {
+        /// The index of the switch
+        instruction_pointer: usize,
+        /// The position of the switch, if the program has a source map
+        position: Option<SourcePos>,
+        /// The line of source code that caused the error, if the program kept its
+        /// [`source`](crate::BrainfuckProgram::source)
+        excerpt: Option<SourceExcerpt>,
+    },
+    /// A `$` was executed while the [stack](crate::BrainfuckProgram::stack) was empty
+    StackUnderflow {
+        /// The index of the pop
+        instruction_pointer: usize,
+        /// The position of the pop, if the program has a source map
+        position: Option<SourcePos>,
+        /// The line of source code that caused the error, if the program kept its
+        /// [`source`](crate::BrainfuckProgram::source)
+        excerpt: Option<SourceExcerpt>,
+    },
+    /// An [`Interpreter`](crate::Interpreter) executed an instruction that needs memory
+    /// it does not have, such as a procedure definition or a push onto the stack
+    Unsupported {
+        /// The index of the instruction
+        instruction_pointer: usize,
+        /// The instruction
+        instruction: BrainfuckInstruction,
+    },
+}
+
+/// Why a `%` could not call a host function, see [`Host`](crate::Host)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HostCallError {
+    /// The program is not run by a [`Host`](crate::Host)
+    NoHost,
+    /// No function is registered under the data at the data pointer
+    UnknownFunction,
+    /// The window of the function does not fit on the tape
+    WindowOutOfBounds,
+    /// The program has already made the maximum amount of calls
+    CallLimit,
+    /// The function returned an error, with this code
+    Failed(u32),
+}
+
+impl Display for HostCallError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            HostCallError::NoHost => write!(f, "there is no host"),
+            HostCallError::UnknownFunction => write!(f, "no function is registered"),
+            HostCallError::WindowOutOfBounds => write!(f, "the window is not on the tape"),
+            HostCallError::CallLimit => write!(f, "too many calls"),
+            HostCallError::Failed(code) => write!(f, "the function failed with code {}", code),
+        }
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.fmt_message(f)?;
+        match self.excerpt() {
+            Some(excerpt) => write!(f, "\n{}", excerpt),
+            None => Ok(()),
+        }
+    }
+}
+
+impl RuntimeError {
+    /// Write what went wrong, without the excerpt
+    fn fmt_message(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RuntimeError::TapeExhausted {
+                data_pointer,
+                position: Some(position),
+                ..
+            } => write!(
+                f,
+                "Data pointer went out of bounds! {} (moved at {})",
+                data_pointer, position
+            ),
+            RuntimeError::TapeExhausted {
+                data_pointer,
+                position: None,
+                ..
+            } => write!(f, "Data pointer went out of bounds! {}", data_pointer),
+            RuntimeError::PointerOutOfBounds { position, .. } => {
+                write!(f, "Data pointer moved out of bounds at {}", position)
+            }
+            RuntimeError::CellOverflow {
+                data_pointer,
+                position: Some(position),
+                ..
+            } => write!(f, "Cell {} overflowed at {}", data_pointer, position),
+            RuntimeError::CellOverflow {
+                instruction_pointer,
+                data_pointer,
+                position: None,
+                ..
+            } => write!(
+                f,
+                "Cell {} overflowed at instruction {}",
+                data_pointer, instruction_pointer
+            ),
+            RuntimeError::UndefinedProcedure {
+                position: Some(position),
+                ..
+            } => write!(f, "Called an undefined procedure at {}", position),
+            RuntimeError::UndefinedProcedure {
+                instruction_pointer,
+                position: None,
+                ..
+            } => write!(
+                f,
+                "Called an undefined procedure at instruction {}",
+                instruction_pointer
+            ),
+            RuntimeError::HostCall {
+                position: Some(position),
+                error,
+                ..
+            } => write!(f, "Host call at {} failed: {}", position, error),
+            RuntimeError::HostCall {
+                instruction_pointer,
+                position: None,
+                error,
+                ..
+            } => write!(
+                f,
+                "Host call at instruction {} failed: {}",
+                instruction_pointer, error
+            ),
+            RuntimeError::NotAGrid {
+                position: Some(position),
+                ..
+            } => write!(
+                f,
+                "Moved vertically at {} on a tape that is not a grid",
+                position
+            ),
+            RuntimeError::NotAGrid {
+                instruction_pointer,
+                position: None,
+                ..
+            } => write!(
+                f,
+                "Moved vertically at instruction {} on a tape that is not a grid",
+                instruction_pointer
+            ),
+            RuntimeError::SingleTape {
+                position: Some(position),
+                ..
+            } => write!(
+                f,
+                "Switched tapes at {}, but there is only one tape",
+                position
+            ),
+            RuntimeError::SingleTape {
+                instruction_pointer,
+                position: None,
+                ..
+            } => write!(
+                f,
+                "Switched tapes at instruction {}, but there is only one tape",
+                instruction_pointer
+            ),
+            RuntimeError::StackUnderflow {
+                position: Some(position),
+                ..
+            } => write!(f, "Popped from an empty stack at {}", position),
+            RuntimeError::StackUnderflow {
+                instruction_pointer,
+                position: None,
+                ..
+            } => write!(
+                f,
+                "Popped from an empty stack at instruction {}",
+                instruction_pointer
+            ),
+            RuntimeError::Unsupported {
+                instruction_pointer,
+                instruction,
+            } => write!(
+                f,
+                "Instruction {} ({}) is not supported without an allocator",
+                instruction_pointer,
+                instruction.as_char()
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RuntimeError {}
+
+impl RuntimeError {
+    /// The position in the source code of the instruction that caused this error, if
+    /// the program has a source map
+    pub fn position(&self) -> Option<SourcePos> {
+        match self {
+            RuntimeError::TapeExhausted { position, .. }
+            | RuntimeError::CellOverflow { position, .. }
+            | RuntimeError::UndefinedProcedure { position, .. }
+            | RuntimeError::HostCall { position, .. }
+            | RuntimeError::NotAGrid { position, .. }
+            | RuntimeError::SingleTape { position, .. }
+            | RuntimeError::StackUnderflow { position, .. } => *position,
+            RuntimeError::PointerOutOfBounds { position, .. } => Some(*position),
+            RuntimeError::Unsupported { .. } => None,
+        }
+    }
+
+    /// The line of source code that caused this error, if the program kept its
+    /// [`source`](crate::BrainfuckProgram::source)
+    pub fn excerpt(&self) -> Option<&SourceExcerpt> {
+        match self {
+            RuntimeError::TapeExhausted { excerpt, .. }
+            | RuntimeError::PointerOutOfBounds { excerpt, .. }
+            | RuntimeError::CellOverflow { excerpt, .. }
+            | RuntimeError::UndefinedProcedure { excerpt, .. }
+            | RuntimeError::HostCall { excerpt, .. }
+            | RuntimeError::NotAGrid { excerpt, .. }
+            | RuntimeError::SingleTape { excerpt, .. }
+            | RuntimeError::StackUnderflow { excerpt, .. } => excerpt.as_ref(),
+            RuntimeError::Unsupported { .. } => None,
+        }
+    }
+
+    /// Display this error followed by the line of `source` that caused it, with a caret
+    /// below the instruction, if the error has a [`position`](Self::position)
+    ///
+    /// `source` must be the source code that the program was compiled from. An error
+    /// that already has an [`excerpt`](Self::excerpt) is displayed as it is.
+    pub fn with_source<'a>(&'a self, source: &'a str) -> RuntimeErrorWithSource<'a> {
+        RuntimeErrorWithSource {
+            error: self,
+            source,
+        }
+    }
+}
+
+/// A [`RuntimeError`] together with the source code of the program that caused it, as
+/// returned by [`RuntimeError::with_source`]
+#[derive(Clone, Copy, Debug)]
+pub struct RuntimeErrorWithSource<'a> {
+    error: &'a RuntimeError,
+    source: &'a str,
+}
+
+impl<'a> Display for RuntimeErrorWithSource<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.error)?;
+        if self.error.excerpt().is_some() {
+            return Ok(());
+        }
+        let position = match self.error.position() {
+            Some(position) => position,
+            None => return Ok(()),
+        };
+        if self.source.lines().nth(position.line).is_none() || position.character == 0 {
+            return Ok(());
+        }
+        let span = Span {
+            // Only the position of a span is displayed, not its instruction
+            instruction: BrainfuckInstruction::IncreaseData,
+            text: self.source,
+            line: position.line,
+            character: position.character,
+        };
+        write!(f, "\n{}", span)
+    }
+}
+
+/// The longest [`SourceExcerpt`], in bytes
+pub const EXCERPT_LEN: usize = 40;
+
+/// The line of source code that caused a [`RuntimeError`], as shown by its [`Display`]
+///
+/// Errors are [`Copy`], so the excerpt is kept inline: lines longer than
+/// [`EXCERPT_LEN`] bytes are cut to the part around the instruction. Like a [`Span`], it
+/// is displayed as the line with a caret below the instruction.
+///
+/// ```
+/// # use brainfuck_interpreter::{SourceExcerpt, SourcePos};
+/// let position = SourcePos { line: 1, character: 3 };
+/// let excerpt = SourceExcerpt::new("+\n>>>+", position).unwrap();
+/// assert_eq!(excerpt.text(), ">>>+");
+/// assert_eq!(excerpt.to_string(), ">>>+\n  ^");
+///
+/// let line = format!("{}<{}", "+".repeat(100), "-".repeat(100));
+/// let excerpt = SourceExcerpt::new(&line, SourcePos { line: 0, character: 101 }).unwrap();
+/// assert_eq!(excerpt.text(), format!("{}<{}", "+".repeat(20), "-".repeat(19)));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SourceExcerpt {
+    text: [u8; EXCERPT_LEN],
+    len: u8,
+    /// The character of the instruction within `text`, starting at 1
+    character: u8,
+}
+
+impl SourceExcerpt {
+    /// The excerpt of `source` at `position`, or `None` if `source` does not have the
+    /// character at `position`
+    pub fn new(source: &str, position: SourcePos) -> Option<Self> {
+        let line = source.lines().nth(position.line)?;
+        let (instruction, _) = line
+            .char_indices()
+            .nth(position.character.checked_sub(1)?)?;
+        // Keep at most half of the excerpt before the instruction
+        let mut start = instruction.saturating_sub(EXCERPT_LEN / 2);
+        while !line.is_char_boundary(start) {
+            start += 1;
+        }
+        let mut end = line.len().min(start + EXCERPT_LEN);
+        while !line.is_char_boundary(end) {
+            end -= 1;
+        }
+        let mut text = [0; EXCERPT_LEN];
+        text[..end - start].copy_from_slice(&line.as_bytes()[start..end]);
+        Some(Self {
+            text,
+            len: (end - start) as u8,
+            character: (line[start..instruction].chars().count() + 1) as u8,
+        })
+    }
+
+    /// The part of the line that is shown
+    pub fn text(&self) -> &str {
+        core::str::from_utf8(&self.text[..self.len as usize]).unwrap_or("")
+    }
+}
+
+impl Display for SourceExcerpt {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let text = self.text();
+        f.write_str(text)?;
+        f.write_char('\n')?;
+        write_padding(f, text, self.character as usize)?;
+        f.write_char('^')
+    }
+}
@@ -0,0 +1,106 @@
+//! A heap-allocated tape with a limited size
+
+use alloc::vec::Vec;
+
+use crate::{Tape, TapeData};
+
+/// A tape that grows on access, like `Vec<D>`, but never beyond a limit
+///
+/// Accessing a cell at or past the limit returns `None`, which makes
+/// [`BrainfuckProgram::try_step`](crate::BrainfuckProgram::try_step) return
+/// [`RuntimeError::TapeExhausted`](crate::RuntimeError::TapeExhausted). This makes it
+/// suitable for running untrusted programs, which could otherwise allocate an
+/// unbounded amount of memory.
+///
+/// ```
+/// # use brainfuck_interpreter::{BoundedVecTape, BrainfuckProgram, RuntimeError};
+/// let tape = BoundedVecTape::<u8>::with_limit(100);
+/// let mut program = BrainfuckProgram::compile("+[>+]", tape).unwrap();
+/// let error = program.try_run(&mut |_| {}, &mut || 0).unwrap_err();
+/// assert!(matches!(error, RuntimeError::TapeExhausted { data_pointer: 100, .. }));
+/// assert_eq!(program.tape.cells().len(), 100);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoundedVecTape<D> {
+    cells: Vec<D>,
+    limit: usize,
+}
+
+impl<D> BoundedVecTape<D>
+where
+    D: TapeData,
+{
+    /// Create an empty tape that grows to at most `cells` cells
+    pub fn with_limit(cells: usize) -> Self {
+        Self {
+            cells: Vec::new(),
+            limit: cells,
+        }
+    }
+
+    /// The maximum amount of cells of this tape
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// The cells of this tape
+    pub fn cells(&self) -> &[D] {
+        &self.cells
+    }
+
+    /// Consume this tape, returning its cells
+    pub fn into_inner(self) -> Vec<D> {
+        self.cells
+    }
+
+    fn grow_to(&mut self, index: usize) -> bool {
+        if index >= self.limit {
+            return false;
+        }
+        if self.cells.len() <= index {
+            self.cells.resize(index + 1, D::zero());
+        }
+        true
+    }
+}
+
+impl<D> AsMut<[D]> for BoundedVecTape<D> {
+    fn as_mut(&mut self) -> &mut [D] {
+        &mut self.cells
+    }
+}
+
+impl<D> Tape for BoundedVecTape<D>
+where
+    D: TapeData,
+{
+    type Data = D;
+
+    fn get_data_at(&mut self, index: usize) -> Option<&D> {
+        if self.grow_to(index) {
+            self.cells.get(index)
+        } else {
+            None
+        }
+    }
+
+    fn get_data_at_mut(&mut self, index: usize) -> Option<&mut D> {
+        if self.grow_to(index) {
+            self.cells.get_mut(index)
+        } else {
+            None
+        }
+    }
+
+    fn reset(&mut self) {
+        self.cells.iter_mut().for_each(|data| *data = D::zero());
+    }
+
+    fn len_hint(&self) -> Option<usize> {
+        Some(self.cells.len())
+    }
+
+    fn as_mut_slice(&mut self) -> Option<&mut [D]> {
+        Some(&mut self.cells)
+    }
+}
@@ -0,0 +1,132 @@
+//! A tape that can be forked cheaply, sharing its cells until they are changed
+
+use alloc::{sync::Arc, vec::Vec};
+
+use crate::{Tape, TapeData};
+
+/// The amount of cells in a page of a [`CowTape`]
+pub const COW_PAGE_SIZE: usize = 1024;
+
+/// A tape that grows on access, like `Vec<D>`, and that can be [forked](Self::fork)
+/// without copying its cells
+///
+/// The cells are stored in pages of [`COW_PAGE_SIZE`] cells, which are shared between a
+/// tape and its forks. A page is only copied when one of them accesses a cell in it
+/// mutably while it is shared, so forking a tape and running a program on the fork
+/// only copies the pages that the program uses. This makes it cheap to run many
+/// variations of a program from a common state, for example to try out mutants, or
+/// to evaluate what would happen if execution continued. Cloning a
+/// [`BrainfuckProgram`](crate::BrainfuckProgram) that runs on a `CowTape` forks its tape.
+///
+/// The interpreter accesses the current cell mutably, so a page that a program only
+/// reads from is copied as well.
+///
+/// ```
+/// # use brainfuck_interpreter::{CowTape, Tape};
+/// let mut tape = CowTape::<u8>::new();
+/// for index in 0..10_000 {
+///     *tape.get_data_at_mut(index).unwrap() = index as u8;
+/// }
+///
+/// let mut fork = tape.fork();
+/// *fork.get_data_at_mut(5).unwrap() = 0;
+/// assert_eq!(fork.private_pages(), 1);
+/// assert_eq!(tape.get_data_at(5), Some(&5));
+/// assert_eq!(fork.get_data_at(5), Some(&0));
+/// ```
+#[derive(Clone, Debug)]
+pub struct CowTape<D> {
+    pages: Vec<Arc<Vec<D>>>,
+    /// A page of zeroes, which new pages share until they are written
+    zero: Arc<Vec<D>>,
+    /// The amount of cells that have been accessed
+    len: usize,
+    limit: usize,
+}
+
+impl<D> Default for CowTape<D>
+where
+    D: TapeData,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D> CowTape<D>
+where
+    D: TapeData,
+{
+    /// Create an empty tape
+    pub fn new() -> Self {
+        Self::with_limit(usize::MAX)
+    }
+
+    /// Create an empty tape that grows to at most `cells` cells
+    pub fn with_limit(cells: usize) -> Self {
+        Self {
+            pages: Vec::new(),
+            zero: Arc::new(alloc::vec![D::zero(); COW_PAGE_SIZE]),
+            len: 0,
+            limit: cells,
+        }
+    }
+
+    /// Create a tape that shares all of its cells with this one
+    ///
+    /// This is the same as cloning the tape.
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+
+    /// The amount of pages that only this tape uses, because they were copied or
+    /// written since it was forked
+    pub fn private_pages(&self) -> usize {
+        self.pages
+            .iter()
+            .filter(|page| Arc::strong_count(page) == 1)
+            .count()
+    }
+
+    /// The page that holds the cell at `index`, and the offset of the cell in it
+    fn locate(&mut self, index: usize) -> Option<(usize, usize)> {
+        if index >= self.limit {
+            return None;
+        }
+        let (page, offset) = (index / COW_PAGE_SIZE, index % COW_PAGE_SIZE);
+        if self.pages.len() <= page {
+            let zero = &self.zero;
+            self.pages.resize_with(page + 1, || zero.clone());
+        }
+        self.len = self.len.max(index + 1);
+        Some((page, offset))
+    }
+}
+
+impl<D> Tape for CowTape<D>
+where
+    D: TapeData,
+{
+    type Data = D;
+
+    fn get_data_at(&mut self, index: usize) -> Option<&D> {
+        let (page, offset) = self.locate(index)?;
+        Some(&self.pages[page][offset])
+    }
+
+    /// Get the data at a specific index, mutably, copying its page if it is shared
+    fn get_data_at_mut(&mut self, index: usize) -> Option<&mut D> {
+        let (page, offset) = self.locate(index)?;
+        Some(&mut Arc::make_mut(&mut self.pages[page])[offset])
+    }
+
+    /// Reset this tape, without copying or writing any pages
+    fn reset(&mut self) {
+        let zero = &self.zero;
+        self.pages.iter_mut().for_each(|page| *page = zero.clone());
+    }
+
+    fn len_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
@@ -0,0 +1,117 @@
+//! A tape whose cells are laid out in a grid
+
+use alloc::vec::Vec;
+
+use crate::{Tape, TapeData};
+
+/// A tape of `width` by `height` cells, stored row by row, for programs in
+/// [`Dialect::TwoD`](crate::Dialect::TwoD)
+///
+/// `^` and `v` move the data pointer to the cell above or below it. `>` and `<` move it
+/// to the next or previous cell in the same row, or to the first cell of the next row
+/// or the last cell of the previous row at the end of a row. Moving off the grid
+/// behaves like moving off any other tape: the next instruction fails with a
+/// [`RuntimeError::TapeExhausted`](crate::RuntimeError::TapeExhausted), or, in
+/// [strict mode](crate::BrainfuckProgram::strict), the move itself fails.
+///
+/// ```
+/// # use brainfuck_interpreter::{BrainfuckProgram, CompileOptions, Dialect, GridTape};
+/// let options = CompileOptions {
+///     dialect: Dialect::TwoD,
+///     ..CompileOptions::default()
+/// };
+/// let tape = GridTape::<u8>::new(3, 3);
+/// let mut program = BrainfuckProgram::compile_with_options("+>v+>v+", tape, &options).unwrap();
+/// program.run(&mut |_| {}, &mut || 0);
+///
+/// let rows: Vec<_> = program.tape.rows().collect();
+/// assert_eq!(rows, [[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GridTape<D> {
+    cells: Vec<D>,
+    width: usize,
+}
+
+impl<D> GridTape<D>
+where
+    D: TapeData,
+{
+    /// Create a grid of `width` by `height` cells that are zero
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is zero.
+    pub fn new(width: usize, height: usize) -> Self {
+        assert!(width > 0, "A grid must be at least one cell wide");
+        Self {
+            cells: alloc::vec![D::zero(); width * height],
+            width,
+        }
+    }
+
+    /// The amount of cells in a row
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The amount of rows
+    pub fn height(&self) -> usize {
+        self.cells.len() / self.width
+    }
+
+    /// The cells of this grid, row by row
+    pub fn cells(&self) -> &[D] {
+        &self.cells
+    }
+
+    /// The cell in column `x` of row `y`, if it is on the grid
+    pub fn get(&self, x: usize, y: usize) -> Option<&D> {
+        if x >= self.width {
+            return None;
+        }
+        self.cells.get(y.checked_mul(self.width)?.checked_add(x)?)
+    }
+
+    /// Iterate over the rows of this grid, from top to bottom
+    pub fn rows(&self) -> core::slice::Chunks<'_, D> {
+        self.cells.chunks(self.width)
+    }
+}
+
+impl<D> AsMut<[D]> for GridTape<D> {
+    fn as_mut(&mut self) -> &mut [D] {
+        &mut self.cells
+    }
+}
+
+impl<D> Tape for GridTape<D>
+where
+    D: TapeData,
+{
+    type Data = D;
+
+    fn get_data_at(&mut self, index: usize) -> Option<&D> {
+        self.cells.get(index)
+    }
+
+    fn get_data_at_mut(&mut self, index: usize) -> Option<&mut D> {
+        self.cells.get_mut(index)
+    }
+
+    fn reset(&mut self) {
+        self.cells.fill(D::zero());
+    }
+
+    fn len_hint(&self) -> Option<usize> {
+        Some(self.cells.len())
+    }
+
+    fn as_mut_slice(&mut self) -> Option<&mut [D]> {
+        Some(&mut self.cells)
+    }
+
+    fn row_len(&self) -> Option<usize> {
+        Some(self.width)
+    }
+}
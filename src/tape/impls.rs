@@ -2,6 +2,10 @@
 
 use crate::{Tape, TapeData};
 
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
 impl<D> Tape for Vec<D>
 where
     D: TapeData,
@@ -10,20 +14,28 @@ where
 
     fn get_data_at_mut(&mut self, index: usize) -> Option<&mut D> {
         if self.len() <= index {
-            self.resize(index + 1, D::zero());
+            self.resize(index.checked_add(1)?, D::zero());
         }
-        unsafe { Some(self.get_unchecked_mut(index)) }
+        self.get_mut(index)
     }
 
     fn reset(&mut self) {
         self.iter_mut().for_each(|data| *data = D::zero());
     }
 
+    fn len_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn as_mut_slice(&mut self) -> Option<&mut [D]> {
+        Some(self)
+    }
+
     fn get_data_at(&mut self, index: usize) -> Option<&D> {
         if self.len() <= index {
-            self.resize(index + 1, D::zero());
+            self.resize(index.checked_add(1)?, D::zero());
         }
-        unsafe { Some(self.get_unchecked(index)) }
+        self.get(index)
     }
 }
 
@@ -52,6 +64,14 @@ where
     fn reset(&mut self) {
         self.iter_mut().for_each(|val| *val = D::zero());
     }
+
+    fn len_hint(&self) -> Option<usize> {
+        Some(N)
+    }
+
+    fn as_mut_slice(&mut self) -> Option<&mut [D]> {
+        Some(self)
+    }
 }
 
 impl<D> Tape for &mut [D]
@@ -79,4 +99,12 @@ where
     fn reset(&mut self) {
         self.iter_mut().for_each(|d| *d = D::zero());
     }
+
+    fn len_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn as_mut_slice(&mut self) -> Option<&mut [D]> {
+        Some(self)
+    }
 }
@@ -0,0 +1,252 @@
+//! A tape that records its changes, so that they can be undone
+
+use alloc::collections::{BTreeMap, VecDeque};
+
+use crate::Tape;
+
+/// A tape that records the old value of every cell that changes, so that the changes
+/// can be [undone](Self::undo) without keeping full copies of the tape
+///
+/// A change is recorded when the tape is accessed after a cell was accessed mutably
+/// and its value changed. While running a program, the interpreter keeps using the
+/// current cell until the data pointer moves, so a change usually covers all
+/// instructions that changed a cell in a row. [`BrainfuckProgram::step`] accesses the
+/// current cell at the start of every step, so stepping through a program records a
+/// change for every step that changed a cell.
+///
+/// The journal is addressed by [`position`](Self::position), the amount of changes that
+/// have been recorded, and [`rewind_to`](Self::rewind_to) undoes the changes after a
+/// position. To bound the memory that the journal uses, it can be given a limit with
+/// [`set_limit`](Self::set_limit), after which the oldest changes are discarded, and
+/// it can be [compacted](Self::compact), which only keeps the oldest value of every cell
+/// that changed in the compacted part. Positions inside the compacted part cannot be
+/// rewound to anymore, but the start of the journal still can.
+///
+/// Changes made by [resetting](Tape::reset) the tape are not recorded, so resetting
+/// clears the journal.
+///
+/// [`BrainfuckProgram::step`]: crate::BrainfuckProgram::step
+///
+/// ```
+/// # use brainfuck_interpreter::{BrainfuckProgram, JournaledTape};
+/// let tape = JournaledTape::new(vec![0u8; 2]);
+/// let mut program = BrainfuckProgram::compile("+>++<+", tape).unwrap();
+/// while program.step(&mut |_| {}, &mut || 0) {}
+///
+/// let mut tape = program.tape;
+/// assert_eq!(tape.position(), 4);
+/// assert_eq!(tape.undo(1), 1);
+/// assert_eq!(tape.get_ref()[..], [1, 2]);
+/// assert!(tape.rewind_to(0));
+/// assert_eq!(tape.get_ref()[..], [0, 0]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct JournaledTape<T>
+where
+    T: Tape,
+{
+    tape: T,
+    /// The index and old value of every change after the compacted part
+    entries: VecDeque<(usize, T::Data)>,
+    /// The oldest value of every cell that changed in the compacted part
+    compacted: BTreeMap<usize, T::Data>,
+    /// The position of the start of the journal
+    start: usize,
+    /// The position of the end of the compacted part, which is `start` if nothing
+    /// is compacted
+    compacted_end: usize,
+    /// The index and value of the cell that was last accessed mutably, before it was
+    /// accessed
+    pending: Option<(usize, T::Data)>,
+    limit: Option<usize>,
+}
+
+impl<T> JournaledTape<T>
+where
+    T: Tape,
+{
+    /// Record the changes made to `tape`
+    pub fn new(tape: T) -> Self {
+        Self {
+            tape,
+            entries: VecDeque::new(),
+            compacted: BTreeMap::new(),
+            start: 0,
+            compacted_end: 0,
+            pending: None,
+            limit: None,
+        }
+    }
+
+    /// Record the changes made to `tape`, keeping at most `limit` entries
+    pub fn with_limit(tape: T, limit: usize) -> Self {
+        let mut journaled = Self::new(tape);
+        journaled.set_limit(Some(limit));
+        journaled
+    }
+
+    /// Keep at most `limit` entries in the journal, or any amount if `limit` is `None`
+    ///
+    /// A compacted part counts as one entry for every cell in it. Once the journal holds
+    /// more entries than the limit, the compacted part is discarded first, and then the
+    /// oldest changes.
+    pub fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit;
+        self.enforce_limit();
+    }
+
+    /// The tape whose changes are recorded
+    pub fn get_ref(&self) -> &T {
+        &self.tape
+    }
+
+    /// Stop recording changes, and return the tape
+    pub fn into_inner(self) -> T {
+        self.tape
+    }
+
+    /// The amount of changes that have been recorded, including the ones that were
+    /// discarded or compacted
+    pub fn position(&mut self) -> usize {
+        self.commit();
+        self.compacted_end + self.entries.len()
+    }
+
+    /// The earliest position that can be rewound to
+    pub fn earliest(&self) -> usize {
+        self.start
+    }
+
+    /// The amount of entries in the journal
+    pub fn len(&mut self) -> usize {
+        self.commit();
+        self.entries.len() + self.compacted.len()
+    }
+
+    /// Whether the journal has no entries
+    pub fn is_empty(&mut self) -> bool {
+        self.len() == 0
+    }
+
+    /// Undo the last `changes` changes that are not compacted, returning the amount of
+    /// changes that were undone
+    pub fn undo(&mut self, changes: usize) -> usize {
+        let position = self.position();
+        let target = position.saturating_sub(changes).max(self.compacted_end);
+        self.rewind_to(target);
+        position - target
+    }
+
+    /// Undo all changes after `position`, returning whether that position could be
+    /// rewound to
+    ///
+    /// This fails if `position` has not been recorded yet, if it is before
+    /// [`earliest`](Self::earliest), or if it is inside the compacted part.
+    pub fn rewind_to(&mut self, position: usize) -> bool {
+        let current = self.position();
+        let compacted = position < self.compacted_end;
+        if position > current || position < self.start || (compacted && position != self.start) {
+            return false;
+        }
+
+        for _ in position.max(self.compacted_end)..current {
+            if let Some((index, old)) = self.entries.pop_back() {
+                Self::restore(&mut self.tape, index, old);
+            }
+        }
+        if compacted {
+            for (index, old) in core::mem::take(&mut self.compacted) {
+                Self::restore(&mut self.tape, index, old);
+            }
+            self.compacted_end = self.start;
+        }
+        true
+    }
+
+    /// Compact all but the newest `keep` changes
+    pub fn compact(&mut self, keep: usize) {
+        self.commit();
+        let compact = self.entries.len().saturating_sub(keep);
+        for (index, old) in self.entries.drain(..compact) {
+            // Older values of a cell are already in the compacted part
+            self.compacted.entry(index).or_insert(old);
+        }
+        self.compacted_end += compact;
+        self.enforce_limit();
+    }
+
+    /// Discard all entries, so that the current position becomes the earliest one
+    pub fn clear(&mut self) {
+        let position = self.position();
+        self.entries.clear();
+        self.compacted.clear();
+        self.start = position;
+        self.compacted_end = position;
+    }
+
+    fn restore(tape: &mut T, index: usize, old: T::Data) {
+        if let Some(cell) = tape.get_data_at_mut(index) {
+            *cell = old;
+        }
+    }
+
+    /// Record the change to the cell that was last accessed mutably, if it changed
+    fn commit(&mut self) {
+        if let Some((index, old)) = self.pending.take() {
+            if self.tape.get_data_at(index) != Some(&old) {
+                self.entries.push_back((index, old));
+                self.enforce_limit();
+            }
+        }
+    }
+
+    fn enforce_limit(&mut self) {
+        let limit = match self.limit {
+            Some(limit) => limit,
+            None => return,
+        };
+        if self.entries.len() + self.compacted.len() > limit {
+            self.compacted.clear();
+            self.start = self.compacted_end;
+        }
+        while self.entries.len() > limit {
+            self.entries.pop_front();
+            self.start += 1;
+            self.compacted_end += 1;
+        }
+    }
+}
+
+impl<T> Tape for JournaledTape<T>
+where
+    T: Tape,
+{
+    type Data = T::Data;
+
+    fn get_data_at(&mut self, index: usize) -> Option<&T::Data> {
+        self.commit();
+        self.tape.get_data_at(index)
+    }
+
+    fn get_data_at_mut(&mut self, index: usize) -> Option<&mut T::Data> {
+        self.commit();
+        let old = self.tape.get_data_at(index)?.clone();
+        self.pending = Some((index, old));
+        self.tape.get_data_at_mut(index)
+    }
+
+    /// Reset the tape, and clear the journal
+    fn reset(&mut self) {
+        self.pending = None;
+        self.tape.reset();
+        self.clear();
+    }
+
+    fn len_hint(&self) -> Option<usize> {
+        self.tape.len_hint()
+    }
+
+    fn row_len(&self) -> Option<usize> {
+        self.tape.row_len()
+    }
+}
@@ -0,0 +1,283 @@
+//! A tape stored in a memory-mapped file
+
+use core::{convert::TryFrom, ffi::c_void, ptr::NonNull};
+use std::{
+    fs::{File, OpenOptions},
+    io,
+    os::unix::io::AsRawFd,
+    path::Path,
+};
+
+use crate::Tape;
+
+mod sys {
+    use core::ffi::c_void;
+
+    pub const PROT_READ: i32 = 1;
+    pub const PROT_WRITE: i32 = 2;
+    pub const MAP_SHARED: i32 = 0x01;
+    pub const MAP_PRIVATE: i32 = 0x02;
+    pub const MAP_FIXED: i32 = 0x10;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub const MAP_ANONYMOUS: i32 = 0x20;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub const MAP_ANONYMOUS: i32 = 0x1000;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub const MS_SYNC: i32 = 4;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub const MS_SYNC: i32 = 0x10;
+    pub const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+
+    extern "C" {
+        pub fn mmap(
+            addr: *mut c_void,
+            len: usize,
+            prot: i32,
+            flags: i32,
+            fd: i32,
+            offset: i64,
+        ) -> *mut c_void;
+        pub fn msync(addr: *mut c_void, len: usize, flags: i32) -> i32;
+        pub fn munmap(addr: *mut c_void, len: usize) -> i32;
+    }
+}
+
+/// Map `len` bytes of `fd`, or of zeroed memory if `fd` is -1, at `addr` if it is not
+/// null
+///
+/// # Safety
+/// If `addr` is not null, it must be the start of a mapping of at least `len` bytes
+/// that is owned by the caller, and that is not borrowed.
+unsafe fn map(addr: *mut c_void, len: usize, flags: i32, fd: i32) -> io::Result<NonNull<u8>> {
+    if len == 0 {
+        return Ok(NonNull::dangling());
+    }
+    let memory = sys::mmap(addr, len, sys::PROT_READ | sys::PROT_WRITE, flags, fd, 0);
+    if memory == sys::MAP_FAILED {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(NonNull::new_unchecked(memory as *mut u8))
+}
+
+/// A tape of byte-sized cells stored in a memory-mapped file, so that it can be much
+/// larger than the available memory and persists between runs
+///
+/// A tape opened with [`open`](Self::open) is shared with its file: every change to a
+/// cell is a change to the file, which the operating system writes back at some point,
+/// or when [`flush`](Self::flush) is called. A tape opened with
+/// [`open_read_only`](Self::open_read_only) starts out with the contents of its file,
+/// but changes to it are private to the tape, and the file is never written.
+///
+/// The tape does not grow: accessing a cell past its end returns `None`, which the
+/// interpreter reports as a [`RuntimeError::TapeExhausted`](crate::RuntimeError::TapeExhausted).
+/// [`reset`](Tape::reset) sets every cell to zero without touching each of them. A
+/// shared tape does so by truncating its file and extending it again, which makes it
+/// sparse on most file systems.
+///
+/// ```
+/// # use brainfuck_interpreter::{BrainfuckProgram, MmapTape};
+/// let path = std::env::temp_dir().join("brainfuck-interpreter-mmap-doctest.tape");
+/// let tape = MmapTape::open(&path, 1 << 20).unwrap();
+/// let mut program = BrainfuckProgram::compile("++++++[>+++++++<-]", tape).unwrap();
+/// program.run(&mut |_| {}, &mut || 0);
+/// program.tape.flush().unwrap();
+/// drop(program);
+///
+/// let tape = MmapTape::open_read_only(&path).unwrap();
+/// assert_eq!(tape.len(), 1 << 20);
+/// assert_eq!(tape.cells()[..2], [0, 42]);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub struct MmapTape {
+    memory: NonNull<u8>,
+    len: usize,
+    file: File,
+    read_only: bool,
+}
+
+// SAFETY: the tape owns its mapping, which is only accessed through it, like the buffer
+// of a `Vec<u8>`
+unsafe impl Send for MmapTape {}
+// SAFETY: shared references only allow reading the mapping
+unsafe impl Sync for MmapTape {}
+
+impl core::fmt::Debug for MmapTape {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MmapTape")
+            .field("len", &self.len)
+            .field("read_only", &self.read_only)
+            .finish()
+    }
+}
+
+impl MmapTape {
+    /// Open the file at `path`, or create it if it does not exist, and map its first
+    /// `cells` bytes as a tape. The file is extended with zeroes if it is shorter
+    pub fn open<P>(path: P, cells: usize) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        if file.metadata()?.len() < cells as u64 {
+            file.set_len(cells as u64)?;
+        }
+        // SAFETY: the address is null, so a new mapping is created
+        let memory = unsafe {
+            map(
+                core::ptr::null_mut(),
+                cells,
+                sys::MAP_SHARED,
+                file.as_raw_fd(),
+            )?
+        };
+        Ok(Self {
+            memory,
+            len: cells,
+            file,
+            read_only: false,
+        })
+    }
+
+    /// Open the file at `path`, and map all of it as a tape whose changes are not
+    /// written back to the file
+    pub fn open_read_only<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = File::open(path)?;
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "file is too large"))?;
+        // SAFETY: the address is null, so a new mapping is created
+        let memory = unsafe {
+            map(
+                core::ptr::null_mut(),
+                len,
+                sys::MAP_PRIVATE,
+                file.as_raw_fd(),
+            )?
+        };
+        Ok(Self {
+            memory,
+            len,
+            file,
+            read_only: true,
+        })
+    }
+
+    /// The amount of cells of this tape
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether this tape has no cells
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether changes to this tape are kept from its file
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// The cells of this tape
+    pub fn cells(&self) -> &[u8] {
+        // SAFETY: the mapping is `len` bytes long, and lives as long as `self`
+        unsafe { core::slice::from_raw_parts(self.memory.as_ptr(), self.len) }
+    }
+
+    /// Write all changes to the cells to the file, and wait until they are written.
+    /// This does nothing for a read-only tape
+    pub fn flush(&self) -> io::Result<()> {
+        if self.read_only || self.len == 0 {
+            return Ok(());
+        }
+        // SAFETY: the mapping is `len` bytes long
+        let result =
+            unsafe { sys::msync(self.memory.as_ptr() as *mut c_void, self.len, sys::MS_SYNC) };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Set every cell to zero, by recreating the cells in the file or in memory. The
+    /// cells are unchanged if this fails
+    fn zero(&mut self) -> io::Result<()> {
+        if self.read_only {
+            // SAFETY: the mapping is owned by `self`, which is borrowed mutably
+            unsafe {
+                map(
+                    self.memory.as_ptr() as *mut c_void,
+                    self.len,
+                    sys::MAP_PRIVATE | sys::MAP_ANONYMOUS | sys::MAP_FIXED,
+                    -1,
+                )?;
+            }
+            Ok(())
+        } else {
+            // The mapping must not be accessed while the file is shorter than it
+            self.file.set_len(0)?;
+            if let Err(error) = self.file.set_len(self.len as u64) {
+                panic!("Could not extend the file of a tape again: {}", error);
+            }
+            Ok(())
+        }
+    }
+}
+
+impl AsMut<[u8]> for MmapTape {
+    fn as_mut(&mut self) -> &mut [u8] {
+        // SAFETY: the mapping is `len` bytes long, lives as long as `self`, and is only
+        // accessed through `self`, which is borrowed mutably
+        unsafe { core::slice::from_raw_parts_mut(self.memory.as_ptr(), self.len) }
+    }
+}
+
+impl Tape for MmapTape {
+    type Data = u8;
+
+    fn get_data_at(&mut self, index: usize) -> Option<&u8> {
+        self.cells().get(index)
+    }
+
+    fn get_data_at_mut(&mut self, index: usize) -> Option<&mut u8> {
+        self.as_mut().get_mut(index)
+    }
+
+    /// Set every cell to zero
+    ///
+    /// If the cells cannot be recreated, they are overwritten with zeroes instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file of a shared tape was truncated, but cannot be extended again.
+    fn reset(&mut self) {
+        if self.zero().is_err() {
+            self.as_mut().fill(0);
+        }
+    }
+
+    fn len_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+
+    fn as_mut_slice(&mut self) -> Option<&mut [u8]> {
+        Some(self.as_mut())
+    }
+}
+
+impl Drop for MmapTape {
+    fn drop(&mut self) {
+        if self.len > 0 {
+            // SAFETY: the mapping was created by `mmap` with length `len`
+            unsafe {
+                sys::munmap(self.memory.as_ptr() as *mut c_void, self.len);
+            }
+        }
+    }
+}
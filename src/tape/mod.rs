@@ -1,31 +1,111 @@
 #[cfg(feature = "impls")]
 pub mod impls;
 
+#[cfg(feature = "alloc")]
+mod bounded;
+#[cfg(feature = "alloc")]
+pub use bounded::*;
+
+#[cfg(feature = "alloc")]
+mod cow;
+#[cfg(feature = "alloc")]
+pub use cow::*;
+
+#[cfg(feature = "alloc")]
+mod grid;
+#[cfg(feature = "alloc")]
+pub use grid::*;
+
+#[cfg(feature = "alloc")]
+mod journal;
+#[cfg(feature = "alloc")]
+pub use journal::*;
+
+#[cfg(feature = "alloc")]
+mod multi;
+#[cfg(feature = "alloc")]
+pub use multi::*;
+
+#[cfg(all(feature = "mmap", unix))]
+#[allow(unsafe_code)]
+mod mmap;
+#[cfg(all(feature = "mmap", unix))]
+pub use mmap::*;
+
+mod observed;
+pub use observed::*;
+
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "std")]
+pub use shared::*;
+
+#[cfg(feature = "std")]
+mod stream;
+#[cfg(feature = "std")]
+pub use stream::*;
+
 /// Data that can be stored on the tape
 pub trait TapeData: PartialEq + Clone {
+    /// Whether this data wraps around when it is increased past its largest value or
+    /// decreased past zero, so that `+-` and `-+` leave it unchanged
+    ///
+    /// Optimizations only remove such pairs if this is set. It is not for
+    /// [`Saturating`](crate::Saturating) and [`Trapping`](crate::Trapping) cells.
+    const WRAPS: bool = true;
+
     /// `Self` that is considered to be zero
     fn zero() -> Self;
     /// Increase this data
     fn increase(&mut self);
     /// Decrease this data
     fn decrease(&mut self);
-}
 
-impl TapeData for u8 {
-    fn zero() -> Self {
-        const ZERO: u8 = 0;
-        ZERO
+    /// Increase this data, or return `false` and leave it unchanged if increasing it
+    /// is an error, as it is for [`Trapping`](crate::Trapping) cells
+    ///
+    /// The interpreter reports this as a
+    /// [`RuntimeError::CellOverflow`](crate::RuntimeError::CellOverflow).
+    fn try_increase(&mut self) -> bool {
+        self.increase();
+        true
     }
 
-    fn increase(&mut self) {
-        *self = self.wrapping_add(1);
+    /// Decrease this data, or return `false` and leave it unchanged if decreasing it
+    /// is an error, as it is for [`Trapping`](crate::Trapping) cells
+    ///
+    /// The interpreter reports this as a
+    /// [`RuntimeError::CellOverflow`](crate::RuntimeError::CellOverflow).
+    fn try_decrease(&mut self) -> bool {
+        self.decrease();
+        true
     }
+}
 
-    fn decrease(&mut self) {
-        *self = self.wrapping_sub(1);
-    }
+/// Implement [`TapeData`] for unsigned integers, which wrap around when they are
+/// increased or decreased past their bounds
+macro_rules! impl_tape_data {
+    ($($ty:ty),*) => {
+        $(
+            impl TapeData for $ty {
+                fn zero() -> Self {
+                    0
+                }
+
+                fn increase(&mut self) {
+                    *self = self.wrapping_add(1);
+                }
+
+                fn decrease(&mut self) {
+                    *self = self.wrapping_sub(1);
+                }
+            }
+        )*
+    };
 }
 
+impl_tape_data!(u8, u16, u32);
+
 /// An implementation of Tape
 pub trait Tape {
     /// The type of data that is contained by this tape
@@ -38,7 +118,93 @@ pub trait Tape {
     /// Get the data at a specific index, mutably
     ///
     /// This function should return `None` if the index is out of bounds
+    ///
+    /// While running a program, the interpreter looks up the current cell once and
+    /// keeps using it until the data pointer moves, so this is not called for every
+    /// instruction.
     fn get_data_at_mut(&mut self, index: usize) -> Option<&mut Self::Data>;
     /// Reset this tape
     fn reset(&mut self);
+
+    /// The amount of cells that this tape currently holds, if known
+    ///
+    /// Tapes that grow on access should return their current length, so that
+    /// inspecting them does not make them grow.
+    fn len_hint(&self) -> Option<usize> {
+        None
+    }
+
+    /// The cells that this tape currently holds, if they are stored contiguously
+    ///
+    /// Fast paths like [`BrainfuckProgram::try_run_fast`](crate::BrainfuckProgram::try_run_fast)
+    /// use these cells directly, and only call [`get_data_at_mut`](Self::get_data_at_mut)
+    /// to access cells past their end. Tapes that keep track of the cells that are
+    /// accessed should return `None`, which is the default.
+    fn as_mut_slice(&mut self) -> Option<&mut [Self::Data]> {
+        None
+    }
+
+    /// The amount of cells in a row, if this tape is a grid
+    ///
+    /// The `^` and `v` of [`Dialect::TwoD`](crate::Dialect::TwoD) move the data pointer
+    /// by this many cells. On tapes that are not grids, which is the default, they fail
+    /// with a [`RuntimeError::NotAGrid`](crate::RuntimeError::NotAGrid).
+    fn row_len(&self) -> Option<usize> {
+        None
+    }
+
+    /// Switch to the next tape if `next` is set, or to the previous one otherwise, if
+    /// this tape holds several tapes
+    ///
+    /// `data_pointer` is the position of the data pointer on the current tape. Return
+    /// the position of the data pointer on the new tape, or `None` if this tape does not
+    /// hold several tapes, which is the default. This is used by the `{` and `}` of
+    /// [`Dialect::MultiTape`](crate::Dialect::MultiTape).
+    fn switch_tape(&mut self, next: bool, data_pointer: usize) -> Option<usize> {
+        let _ = (next, data_pointer);
+        None
+    }
+
+    /// Iterate over the index and data of every cell that is not zero, within
+    /// [`len_hint`](Self::len_hint)
+    ///
+    /// If the length of the tape is unknown, this yields nothing.
+    fn nonzero_cells(&mut self) -> NonzeroCells<'_, Self>
+    where
+        Self: Sized,
+    {
+        NonzeroCells {
+            len: self.len_hint().unwrap_or(0),
+            tape: self,
+            index: 0,
+        }
+    }
+}
+
+/// An iterator over the cells of a tape that are not zero, see [`Tape::nonzero_cells`]
+#[derive(Debug)]
+pub struct NonzeroCells<'a, T> {
+    tape: &'a mut T,
+    index: usize,
+    len: usize,
+}
+
+impl<'a, T> Iterator for NonzeroCells<'a, T>
+where
+    T: Tape,
+{
+    type Item = (usize, T::Data);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.len {
+            let index = self.index;
+            self.index += 1;
+            match self.tape.get_data_at(index) {
+                Some(data) if *data != T::Data::zero() => return Some((index, data.clone())),
+                Some(_) => {}
+                None => self.index = self.len,
+            }
+        }
+        None
+    }
 }
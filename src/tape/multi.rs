@@ -0,0 +1,142 @@
+//! A tape that holds several tapes, which programs switch between
+
+use alloc::vec::Vec;
+
+use crate::Tape;
+
+/// Several tapes that a program in [`Dialect::MultiTape`](crate::Dialect::MultiTape)
+/// switches between with `{` and `}`
+///
+/// Only the current tape is accessed. Every tape has its own data pointer: switching
+/// away from a tape remembers where the data pointer was on it, and switching back to
+/// it moves the data pointer there again. `}` switches to the next tape, and `{` to
+/// the previous one, wrapping around at either end, so that with two tapes both of
+/// them switch to the other tape. Tapes are identified by their index, and the first
+/// one is current when a program starts.
+///
+/// Wrapping tapes like [`JournaledTape`](crate::JournaledTape) refer to cells by their
+/// index, so they cannot tell the tapes apart and do not switch tapes. Wrap each of the
+/// tapes instead.
+///
+/// ```
+/// # use brainfuck_interpreter::{BrainfuckProgram, CompileOptions, Dialect, MultiTape};
+/// let options = CompileOptions {
+///     dialect: Dialect::MultiTape,
+///     ..CompileOptions::default()
+/// };
+/// let tape = MultiTape::new(vec![vec![0u8; 4], vec![0u8; 4]]);
+/// // Move to the second cell of the first tape, then count on both tapes
+/// let source = ">+++}++++{.}.";
+/// let mut program = BrainfuckProgram::compile_with_options(source, tape, &options).unwrap();
+///
+/// let mut output = Vec::new();
+/// program.run(&mut |value| output.push(value), &mut || 0);
+/// assert_eq!(output, [3, 4]);
+/// assert_eq!(program.tape.tape(0).unwrap()[..2], [0, 3]);
+/// assert_eq!(program.tape.tape(1).unwrap()[..2], [4, 0]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiTape<T> {
+    tapes: Vec<T>,
+    /// The data pointer on every tape, as it was when the program last switched away
+    /// from it
+    pointers: Vec<usize>,
+    current: usize,
+}
+
+impl<T> MultiTape<T>
+where
+    T: Tape,
+{
+    /// Hold `tapes`, starting at the first one
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tapes` is empty.
+    pub fn new(tapes: Vec<T>) -> Self {
+        assert!(!tapes.is_empty(), "A MultiTape must hold at least one tape");
+        Self {
+            pointers: alloc::vec![0; tapes.len()],
+            tapes,
+            current: 0,
+        }
+    }
+
+    /// The amount of tapes
+    pub fn tape_count(&self) -> usize {
+        self.tapes.len()
+    }
+
+    /// The index of the current tape
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// The tape at `index`, if there is one
+    pub fn tape(&self, index: usize) -> Option<&T> {
+        self.tapes.get(index)
+    }
+
+    /// The tape at `index` mutably, if there is one
+    pub fn tape_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.tapes.get_mut(index)
+    }
+
+    /// The data pointer on the tape at `index`, as it was when the program last
+    /// switched away from it, if there is one
+    ///
+    /// The data pointer on the current tape is the data pointer of the program.
+    pub fn data_pointer(&self, index: usize) -> Option<usize> {
+        self.pointers.get(index).copied()
+    }
+
+    /// Consume this tape, returning the tapes that it holds
+    pub fn into_inner(self) -> Vec<T> {
+        self.tapes
+    }
+}
+
+impl<T> Tape for MultiTape<T>
+where
+    T: Tape,
+{
+    type Data = T::Data;
+
+    fn get_data_at(&mut self, index: usize) -> Option<&T::Data> {
+        self.tapes[self.current].get_data_at(index)
+    }
+
+    fn get_data_at_mut(&mut self, index: usize) -> Option<&mut T::Data> {
+        self.tapes[self.current].get_data_at_mut(index)
+    }
+
+    /// Reset every tape, and switch to the first one
+    fn reset(&mut self) {
+        self.tapes.iter_mut().for_each(Tape::reset);
+        self.pointers.fill(0);
+        self.current = 0;
+    }
+
+    fn len_hint(&self) -> Option<usize> {
+        self.tapes[self.current].len_hint()
+    }
+
+    fn as_mut_slice(&mut self) -> Option<&mut [T::Data]> {
+        self.tapes[self.current].as_mut_slice()
+    }
+
+    fn row_len(&self) -> Option<usize> {
+        self.tapes[self.current].row_len()
+    }
+
+    fn switch_tape(&mut self, next: bool, data_pointer: usize) -> Option<usize> {
+        let count = self.tapes.len();
+        self.pointers[self.current] = data_pointer;
+        self.current = if next {
+            (self.current + 1) % count
+        } else {
+            (self.current + count - 1) % count
+        };
+        Some(self.pointers[self.current])
+    }
+}
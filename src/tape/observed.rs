@@ -0,0 +1,276 @@
+//! A tape that reports accesses to its cells
+
+use core::ops::Range;
+
+use crate::Tape;
+
+/// An access to a cell of an [`ObservedTape`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TapeEvent<'a, D> {
+    /// The cell at `index` was accessed, and held `value`
+    Read {
+        /// The index of the cell
+        index: usize,
+        /// The data in the cell
+        value: &'a D,
+    },
+    /// The cell at `index` was changed from `old` to `new`
+    Write {
+        /// The index of the cell
+        index: usize,
+        /// The data in the cell before it was changed
+        old: &'a D,
+        /// The data in the cell after it was changed
+        new: &'a D,
+    },
+}
+
+impl<D> TapeEvent<'_, D> {
+    /// The index of the cell that was accessed
+    pub fn index(&self) -> usize {
+        match self {
+            Self::Read { index, .. } | Self::Write { index, .. } => *index,
+        }
+    }
+}
+
+/// Receives the accesses to the cells of an [`ObservedTape`]
+///
+/// This is implemented for closures that take a [`TapeEvent`], and for `()`, which
+/// ignores all accesses.
+pub trait TapeObserver<D> {
+    /// Whether this observer receives any events
+    ///
+    /// An [`ObservedTape`] whose observer sets this to `false` does not keep track of
+    /// the accesses at all, so that it is exactly as fast as the tape that it wraps.
+    const ACTIVE: bool = true;
+
+    /// Handle an access to a cell
+    fn observe(&mut self, event: TapeEvent<'_, D>);
+}
+
+impl<D> TapeObserver<D> for () {
+    const ACTIVE: bool = false;
+
+    fn observe(&mut self, _: TapeEvent<'_, D>) {}
+}
+
+impl<D, F> TapeObserver<D> for F
+where
+    F: FnMut(TapeEvent<'_, D>),
+{
+    fn observe(&mut self, event: TapeEvent<'_, D>) {
+        self(event)
+    }
+}
+
+/// An observer that counts the reads and writes of every cell, for example to draw a
+/// heatmap of the tape or to find the cells that a program uses most
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccessCounts {
+    reads: alloc::vec::Vec<u64>,
+    writes: alloc::vec::Vec<u64>,
+}
+
+#[cfg(feature = "alloc")]
+impl AccessCounts {
+    /// Create an observer that has not counted any accesses
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The amount of times that each cell was accessed, indexed by cell
+    ///
+    /// Cells past the end were never accessed.
+    pub fn reads(&self) -> &[u64] {
+        &self.reads
+    }
+
+    /// The amount of times that each cell was changed, indexed by cell
+    ///
+    /// Cells past the end were never changed.
+    pub fn writes(&self) -> &[u64] {
+        &self.writes
+    }
+
+    fn count(counts: &mut alloc::vec::Vec<u64>, index: usize) {
+        if counts.len() <= index {
+            counts.resize(index + 1, 0);
+        }
+        counts[index] += 1;
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<D> TapeObserver<D> for AccessCounts {
+    fn observe(&mut self, event: TapeEvent<'_, D>) {
+        match event {
+            TapeEvent::Read { index, .. } => Self::count(&mut self.reads, index),
+            TapeEvent::Write { index, .. } => Self::count(&mut self.writes, index),
+        }
+    }
+}
+
+/// A tape that reports accesses to the cells in a range to an observer, for example
+/// to implement watchpoints or to profile the memory accesses of a program
+///
+/// Every access to a cell is reported as a [`TapeEvent::Read`]. A tape cannot see what
+/// is done with a cell that it handed out mutably, so a change is reported as a
+/// [`TapeEvent::Write`] when the tape is accessed again, or when it is
+/// [flushed](Self::flush). While running a program, the interpreter keeps using the
+/// current cell until the data pointer moves, so a write usually covers all
+/// instructions that changed a cell in a row. [`BrainfuckProgram::step`] accesses the
+/// current cell at the start of every step, so stepping through a program reports a
+/// write for every step that changed a cell.
+///
+/// With `()` as its observer, see [`TapeObserver::ACTIVE`], the tape only forwards
+/// accesses to the tape that it wraps.
+///
+/// [`BrainfuckProgram::step`]: crate::BrainfuckProgram::step
+///
+/// ```
+/// # use brainfuck_interpreter::{BrainfuckProgram, ObservedTape, TapeEvent};
+/// let mut writes = Vec::new();
+/// let observer = |event: TapeEvent<'_, u8>| {
+///     if let TapeEvent::Write { index, new, .. } = event {
+///         writes.push((index, *new));
+///     }
+/// };
+///
+/// let tape = ObservedTape::with_range(vec![0u8; 4], 1..3, observer);
+/// let mut program = BrainfuckProgram::compile("+>++>+++>++++", tape).unwrap();
+/// while program.step(&mut |_| {}, &mut || 0) {}
+/// program.tape.flush();
+/// drop(program);
+///
+/// assert_eq!(writes, [(1, 1), (1, 2), (2, 1), (2, 2), (2, 3)]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ObservedTape<T, O>
+where
+    T: Tape,
+{
+    tape: T,
+    observer: O,
+    range: Range<usize>,
+    /// The index and value of the cell that was last accessed mutably, before it was
+    /// accessed
+    pending: Option<(usize, T::Data)>,
+}
+
+impl<T, O> ObservedTape<T, O>
+where
+    T: Tape,
+    O: TapeObserver<T::Data>,
+{
+    /// Report accesses to all cells of `tape` to `observer`
+    pub fn new(tape: T, observer: O) -> Self {
+        Self::with_range(tape, 0..usize::MAX, observer)
+    }
+
+    /// Report accesses to the cells of `tape` in `range` to `observer`
+    pub fn with_range(tape: T, range: Range<usize>, observer: O) -> Self {
+        Self {
+            tape,
+            observer,
+            range,
+            pending: None,
+        }
+    }
+
+    /// The observer
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    /// The observer, mutably
+    pub fn observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
+    /// The tape whose accesses are reported
+    pub fn get_ref(&self) -> &T {
+        &self.tape
+    }
+
+    /// Report the change to the cell that was last accessed mutably, if it changed
+    pub fn flush(&mut self) {
+        if !O::ACTIVE {
+            return;
+        }
+        if let Some((index, old)) = self.pending.take() {
+            if let Some(new) = self.tape.get_data_at(index) {
+                if *new != old {
+                    self.observer.observe(TapeEvent::Write {
+                        index,
+                        old: &old,
+                        new,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Stop reporting accesses, and return the tape and the observer
+    pub fn into_parts(mut self) -> (T, O) {
+        self.flush();
+        (self.tape, self.observer)
+    }
+
+    /// Report the access to the cell at `index`, if it is observed
+    fn read(&mut self, index: usize) -> Option<bool> {
+        self.flush();
+        if !self.range.contains(&index) {
+            return Some(false);
+        }
+        let value = self.tape.get_data_at(index)?;
+        self.observer.observe(TapeEvent::Read { index, value });
+        Some(true)
+    }
+}
+
+impl<T, O> Tape for ObservedTape<T, O>
+where
+    T: Tape,
+    O: TapeObserver<T::Data>,
+{
+    type Data = T::Data;
+
+    fn get_data_at(&mut self, index: usize) -> Option<&T::Data> {
+        if O::ACTIVE {
+            self.read(index)?;
+        }
+        self.tape.get_data_at(index)
+    }
+
+    fn get_data_at_mut(&mut self, index: usize) -> Option<&mut T::Data> {
+        if O::ACTIVE && self.read(index)? {
+            let old = self.tape.get_data_at(index)?.clone();
+            self.pending = Some((index, old));
+        }
+        self.tape.get_data_at_mut(index)
+    }
+
+    /// Reset the tape, without reporting the cells that it changes
+    fn reset(&mut self) {
+        self.pending = None;
+        self.tape.reset();
+    }
+
+    fn len_hint(&self) -> Option<usize> {
+        self.tape.len_hint()
+    }
+
+    fn row_len(&self) -> Option<usize> {
+        self.tape.row_len()
+    }
+
+    fn as_mut_slice(&mut self) -> Option<&mut [T::Data]> {
+        if O::ACTIVE {
+            None
+        } else {
+            self.tape.as_mut_slice()
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! A tape that can be shared between programs on different threads
+
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::Tape;
+
+/// A handle to a tape that is shared between programs, which may run on different
+/// threads, so that they can communicate through its cells
+///
+/// Every handle keeps a copy of the cell that its program is working on. At the start
+/// of every step, [`BrainfuckProgram::step`](crate::BrainfuckProgram::step) accesses
+/// the tape, which writes the copy back to the shared tape if the previous step changed
+/// it, and then reads the current cell from the shared tape. Writes therefore become
+/// visible to other handles at the start of the next step, or when the handle is
+/// [flushed](Self::flush) or dropped.
+///
+/// A step is not atomic: if two programs change the same cell in the same step, one
+/// of the changes is lost. Programs should agree on which of them writes which cells.
+/// Resetting a program that uses this tape resets the shared tape for all programs.
+///
+/// ```
+/// # use brainfuck_interpreter::{BrainfuckProgram, SharedTape};
+/// let tape = SharedTape::new(vec![0u8; 2]);
+/// let mut writer = BrainfuckProgram::compile(",", tape.clone()).unwrap();
+/// let mut reader = BrainfuckProgram::compile(".", tape).unwrap();
+///
+/// std::thread::spawn(move || writer.run(&mut |_| {}, &mut || b'!'))
+///     .join()
+///     .unwrap();
+///
+/// let mut output = Vec::new();
+/// reader.run(&mut |d| output.push(d), &mut || 0);
+/// assert_eq!(output, b"!");
+/// ```
+#[derive(Debug)]
+pub struct SharedTape<T>
+where
+    T: Tape,
+{
+    shared: Arc<Mutex<T>>,
+    /// The index, the data as it was read from the shared tape, and the data as it
+    /// was changed by the program, of the current cell
+    current: Option<(usize, T::Data, T::Data)>,
+}
+
+impl<T> SharedTape<T>
+where
+    T: Tape,
+{
+    /// Share `tape`
+    pub fn new(tape: T) -> Self {
+        Self::from_shared(Arc::new(Mutex::new(tape)))
+    }
+
+    /// Create a handle to a tape that is already shared
+    pub fn from_shared(shared: Arc<Mutex<T>>) -> Self {
+        Self {
+            shared,
+            current: None,
+        }
+    }
+
+    /// The shared tape
+    pub fn shared(&self) -> &Arc<Mutex<T>> {
+        &self.shared
+    }
+
+    /// Lock the shared tape, after writing back the current cell
+    ///
+    /// Programs that use the tape block until the lock is released.
+    pub fn lock(&mut self) -> MutexGuard<'_, T> {
+        self.flush();
+        Self::lock_shared(&self.shared)
+    }
+
+    /// Write the current cell back to the shared tape, if it was changed
+    pub fn flush(&mut self) {
+        if let Some((index, original, data)) = self.current.take() {
+            if original != data {
+                if let Some(cell) = Self::lock_shared(&self.shared).get_data_at_mut(index) {
+                    *cell = data;
+                }
+            }
+        }
+    }
+
+    /// A panic on another thread leaves the cells in a valid state, so a poisoned
+    /// lock is ignored
+    fn lock_shared(shared: &Mutex<T>) -> MutexGuard<'_, T> {
+        shared.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Make the cell at `index` the current cell
+    fn load(&mut self, index: usize) -> Option<&mut T::Data> {
+        self.flush();
+        let data = Self::lock_shared(&self.shared).get_data_at(index)?.clone();
+        let (_, _, data) = self.current.insert((index, data.clone(), data));
+        Some(data)
+    }
+}
+
+impl<T> Clone for SharedTape<T>
+where
+    T: Tape,
+{
+    /// Create another handle to the same shared tape
+    fn clone(&self) -> Self {
+        Self::from_shared(self.shared.clone())
+    }
+}
+
+impl<T> Drop for SharedTape<T>
+where
+    T: Tape,
+{
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl<T> Tape for SharedTape<T>
+where
+    T: Tape,
+{
+    type Data = T::Data;
+
+    fn get_data_at(&mut self, index: usize) -> Option<&T::Data> {
+        self.load(index).map(|data| &*data)
+    }
+
+    fn get_data_at_mut(&mut self, index: usize) -> Option<&mut T::Data> {
+        self.load(index)
+    }
+
+    fn reset(&mut self) {
+        self.current = None;
+        Self::lock_shared(&self.shared).reset();
+    }
+
+    fn len_hint(&self) -> Option<usize> {
+        Self::lock_shared(&self.shared).len_hint()
+    }
+
+    fn row_len(&self) -> Option<usize> {
+        Self::lock_shared(&self.shared).row_len()
+    }
+}
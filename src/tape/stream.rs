@@ -0,0 +1,284 @@
+//! A tape stored in a stream, such as a file or a device
+
+use std::{
+    io::{self, Read, Seek, SeekFrom, Write},
+    vec::Vec,
+};
+
+use crate::Tape;
+
+/// The amount of cells in a page of a [`StreamTape`]
+pub const STREAM_PAGE_SIZE: usize = 4096;
+
+/// The default amount of pages that a [`StreamTape`] keeps in memory
+pub const STREAM_CACHE_PAGES: usize = 16;
+
+/// The stream of a tape, which is only taken by [`StreamTape::into_inner`]
+fn stream_mut<S>(stream: &mut Option<S>) -> &mut S {
+    stream
+        .as_mut()
+        .expect("the stream is only taken by into_inner")
+}
+
+/// A part of the stream that is kept in memory
+struct Page {
+    /// The index of the page in the stream
+    index: usize,
+    cells: Vec<u8>,
+    /// Whether the cells may have changed since they were read
+    dirty: bool,
+    /// The value of the clock when the page was last used
+    used: u64,
+}
+
+/// A tape of byte-sized cells stored in a stream that can be read, written and seeked,
+/// such as a [`File`](std::fs::File), an in-memory [`Cursor`](std::io::Cursor) or a
+/// device
+///
+/// The stream is divided into pages of [`STREAM_PAGE_SIZE`] cells, and the most recently
+/// used pages are kept in memory, so that the stream is only accessed when the data
+/// pointer moves to a page that is not in memory. A page that has been accessed mutably
+/// is written back when it is evicted to make room for another page, when
+/// [`flush`](Self::flush) is called, and when the tape is dropped. Cells past the end of
+/// the stream are zero, and writing them back extends the stream.
+///
+/// The tape grows on access, like `Vec<u8>`. If the stream fails, the cell that was
+/// accessed is `None`, which the interpreter reports as a
+/// [`RuntimeError::TapeExhausted`](crate::RuntimeError::TapeExhausted), and the error
+/// can be taken with [`take_error`](Self::take_error).
+///
+/// ```
+/// # use brainfuck_interpreter::{BrainfuckProgram, StreamTape};
+/// # use std::io::Cursor;
+/// let tape = StreamTape::new(Cursor::new(Vec::new())).unwrap();
+/// let mut program = BrainfuckProgram::compile("++++++[>+++++++<-]", tape).unwrap();
+/// program.run(&mut |_| {}, &mut || 0);
+///
+/// let stream = program.tape.into_inner().unwrap().into_inner();
+/// assert_eq!(stream.len(), 4096);
+/// assert_eq!(stream[..2], [0, 42]);
+/// ```
+pub struct StreamTape<S>
+where
+    S: Read + Write + Seek,
+{
+    /// Only `None` after [`into_inner`](Self::into_inner)
+    stream: Option<S>,
+    pages: Vec<Page>,
+    capacity: usize,
+    /// The index in `pages` of the page that was used last
+    current: usize,
+    clock: u64,
+    /// The amount of cells that have been accessed, or that are in the stream
+    len: usize,
+    error: Option<io::Error>,
+}
+
+impl<S> core::fmt::Debug for StreamTape<S>
+where
+    S: Read + Write + Seek + core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("StreamTape")
+            .field("stream", &self.stream)
+            .field(
+                "pages",
+                &self.pages.iter().map(|page| page.index).collect::<Vec<_>>(),
+            )
+            .field("capacity", &self.capacity)
+            .field("len", &self.len)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl<S> StreamTape<S>
+where
+    S: Read + Write + Seek,
+{
+    /// Create a tape stored in `stream`, which keeps [`STREAM_CACHE_PAGES`] pages in
+    /// memory
+    pub fn new(stream: S) -> io::Result<Self> {
+        Self::with_cache(stream, STREAM_CACHE_PAGES)
+    }
+
+    /// Create a tape stored in `stream`, which keeps `pages` pages in memory, or one
+    /// page if `pages` is zero
+    pub fn with_cache(mut stream: S, pages: usize) -> io::Result<Self> {
+        let len = stream.seek(SeekFrom::End(0))? as usize;
+        Ok(Self {
+            stream: Some(stream),
+            pages: Vec::new(),
+            capacity: pages.max(1),
+            current: 0,
+            clock: 0,
+            len,
+            error: None,
+        })
+    }
+
+    /// The error that made the last access to a cell fail, if any, leaving `None` in
+    /// its place
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    /// Write `page` back to the stream, if it has changed
+    fn write_back(stream: &mut S, page: &mut Page) -> io::Result<()> {
+        if page.dirty {
+            stream.seek(SeekFrom::Start((page.index * STREAM_PAGE_SIZE) as u64))?;
+            stream.write_all(&page.cells)?;
+            page.dirty = false;
+        }
+        Ok(())
+    }
+
+    /// Read the page at `index` from the stream into `cells`
+    fn read_page(stream: &mut S, index: usize, cells: &mut [u8]) -> io::Result<()> {
+        stream.seek(SeekFrom::Start((index * STREAM_PAGE_SIZE) as u64))?;
+        let mut filled = 0;
+        while filled < cells.len() {
+            match stream.read(&mut cells[filled..]) {
+                Ok(0) => break,
+                Ok(read) => filled += read,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+        cells[filled..].fill(0);
+        Ok(())
+    }
+
+    /// Make the page at `index` the current page, reading it from the stream if it is
+    /// not in memory
+    fn load(&mut self, index: usize) -> io::Result<()> {
+        self.clock += 1;
+        if let Some(position) = self.pages.iter().position(|page| page.index == index) {
+            self.current = position;
+            self.pages[position].used = self.clock;
+            return Ok(());
+        }
+
+        let position = if self.pages.len() < self.capacity {
+            self.pages.push(Page {
+                index,
+                cells: std::vec![0; STREAM_PAGE_SIZE],
+                dirty: false,
+                used: 0,
+            });
+            self.pages.len() - 1
+        } else {
+            let (position, _) = self
+                .pages
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, page)| page.used)
+                .expect("the cache holds at least one page");
+            let stream = stream_mut(&mut self.stream);
+            Self::write_back(stream, &mut self.pages[position])?;
+            position
+        };
+
+        let stream = stream_mut(&mut self.stream);
+        let page = &mut self.pages[position];
+        // A page that cannot be read is not kept, so that reading it is tried again
+        page.index = usize::MAX;
+        Self::read_page(stream, index, &mut page.cells)?;
+        page.index = index;
+        page.used = self.clock;
+        self.current = position;
+        Ok(())
+    }
+
+    /// The page that holds the cell at `index`, and the offset of the cell in it
+    fn page(&mut self, index: usize) -> Option<(&mut Page, usize)> {
+        let (page, offset) = (index / STREAM_PAGE_SIZE, index % STREAM_PAGE_SIZE);
+        let hit = self
+            .pages
+            .get(self.current)
+            .is_some_and(|current| current.index == page);
+        if !hit {
+            if let Err(error) = self.load(page) {
+                self.error = Some(error);
+                return None;
+            }
+        }
+        self.len = self.len.max(index + 1);
+        Some((&mut self.pages[self.current], offset))
+    }
+
+    /// Write all pages that have changed to the stream, and flush it
+    pub fn flush(&mut self) -> io::Result<()> {
+        let stream = stream_mut(&mut self.stream);
+        for page in &mut self.pages {
+            Self::write_back(stream, page)?;
+        }
+        stream.flush()
+    }
+
+    /// Write all pages that have changed to the stream, and return the stream
+    pub fn into_inner(mut self) -> io::Result<S> {
+        self.flush()?;
+        Ok(self
+            .stream
+            .take()
+            .expect("the stream is only taken by into_inner"))
+    }
+}
+
+impl<S> Tape for StreamTape<S>
+where
+    S: Read + Write + Seek,
+{
+    type Data = u8;
+
+    fn get_data_at(&mut self, index: usize) -> Option<&u8> {
+        let (page, offset) = self.page(index)?;
+        Some(&page.cells[offset])
+    }
+
+    fn get_data_at_mut(&mut self, index: usize) -> Option<&mut u8> {
+        let (page, offset) = self.page(index)?;
+        page.dirty = true;
+        Some(&mut page.cells[offset])
+    }
+
+    /// Set every cell in the stream to zero
+    ///
+    /// If this fails, the error can be taken with [`take_error`](StreamTape::take_error).
+    fn reset(&mut self) {
+        self.pages.clear();
+        let len = self.len;
+        let result = (|| {
+            let stream = stream_mut(&mut self.stream);
+            stream.seek(SeekFrom::Start(0))?;
+            let zeroes = [0; STREAM_PAGE_SIZE];
+            let mut written = 0;
+            while written < len {
+                let chunk = (len - written).min(STREAM_PAGE_SIZE);
+                stream.write_all(&zeroes[..chunk])?;
+                written += chunk;
+            }
+            Ok(())
+        })();
+        if let Err(error) = result {
+            self.error = Some(error);
+        }
+    }
+
+    fn len_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<S> Drop for StreamTape<S>
+where
+    S: Read + Write + Seek,
+{
+    /// Write all pages that have changed to the stream, ignoring errors
+    fn drop(&mut self) {
+        if self.stream.is_some() {
+            self.flush().ok();
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! Helpers for testing Brainfuck programs against expected output
+//!
+//! Programs are run on a growing `Vec<u8>` tape. Reading input after all of it has
+//! been consumed returns `0`. A program that fails while running, or that executes
+//! more than [`MAX_INSTRUCTIONS`] instructions, fails the test instead of panicking or
+//! hanging it.
+
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{io::SliceInput, BrainfuckProgram, Error, RunConfig, RunOutcome, RuntimeError};
+
+/// The amount of instructions after which a program under test is considered to loop
+/// forever
+pub const MAX_INSTRUCTIONS: usize = 1 << 30;
+
+/// Why [`run_program`] did not produce output
+#[derive(Clone, Debug)]
+pub enum RunProgramError<'a> {
+    /// The program failed to compile
+    Compile(Error<'a>),
+    /// The program failed while running
+    Runtime(RuntimeError),
+    /// The program executed this many instructions without finishing
+    InstructionLimit(usize),
+}
+
+impl<'a> Display for RunProgramError<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunProgramError::Compile(error) => write!(f, "Program failed to compile: {}", error),
+            RunProgramError::Runtime(error) => write!(f, "Program failed: {}", error),
+            RunProgramError::InstructionLimit(limit) => {
+                write!(f, "Program did not finish within {} instructions", limit)
+            }
+        }
+    }
+}
+
+impl<'a> std::error::Error for RunProgramError<'a> {}
+
+/// Compile `source` and run it to completion with `input`, returning its output
+///
+/// The program may execute at most [`MAX_INSTRUCTIONS`] instructions.
+///
+/// ```
+/// # use brainfuck_interpreter::testing::{run_program, RunProgramError};
+/// assert_eq!(run_program(",[.,]", b"echo").unwrap(), b"echo");
+/// assert!(matches!(run_program("<+", b""), Err(RunProgramError::Runtime(_))));
+/// ```
+pub fn run_program<'a>(source: &'a str, input: &[u8]) -> Result<Vec<u8>, RunProgramError<'a>> {
+    run_program_with_limit(source, input, MAX_INSTRUCTIONS)
+}
+
+/// Compile `source` and run it to completion with `input`, executing at most
+/// `max_instructions` instructions, and return its output
+///
+/// ```
+/// # use brainfuck_interpreter::testing::{run_program_with_limit, RunProgramError};
+/// assert!(matches!(
+///     run_program_with_limit("+[]", b"", 1000),
+///     Err(RunProgramError::InstructionLimit(1000))
+/// ));
+/// ```
+pub fn run_program_with_limit<'a>(
+    source: &'a str,
+    input: &[u8],
+    max_instructions: usize,
+) -> Result<Vec<u8>, RunProgramError<'a>> {
+    let mut program = BrainfuckProgram::compile_with_source_map(source, Vec::new())
+        .map_err(RunProgramError::Compile)?;
+    let config = RunConfig {
+        max_instructions: Some(max_instructions),
+        ..RunConfig::default()
+    };
+    let mut output = Vec::new();
+    let result = program
+        .try_run_with_config(
+            &config,
+            &mut |data| output.push(data),
+            &mut SliceInput::new(input),
+        )
+        .map_err(RunProgramError::Runtime)?;
+    match result.outcome() {
+        RunOutcome::Halted => Ok(output),
+        _ => Err(RunProgramError::InstructionLimit(max_instructions)),
+    }
+}
+
+/// Assert that `source` compiles, and produces exactly `expected` when run with `input`
+///
+/// See [`assert_program_output!`](crate::assert_program_output).
+#[track_caller]
+pub fn assert_output(source: &str, input: &[u8], expected: &[u8]) {
+    match run_program(source, input) {
+        Ok(output) => {
+            if output != expected {
+                panic!(
+                    "Program output did not match\n  expected: {:?}\n    actual: {:?}",
+                    String::from_utf8_lossy(expected),
+                    String::from_utf8_lossy(&output),
+                );
+            }
+        }
+        Err(error) => panic!("{}", error),
+    }
+}
+
+/// Assert that a Brainfuck program produces the expected output
+///
+/// The program, input, and expected output may be anything that implements
+/// `AsRef<str>` (the program) or `AsRef<[u8]>` (input and output). The input
+/// can be omitted, in which case the program receives no input.
+///
+/// ```
+/// brainfuck_interpreter::assert_program_output!(",[.,]", "echo", "echo");
+/// brainfuck_interpreter::assert_program_output!("++++++++[>++++++++<-]>+.", "A");
+/// ```
+#[macro_export]
+macro_rules! assert_program_output {
+    ($source:expr, $expected:expr $(,)?) => {
+        $crate::testing::assert_output(
+            ::core::convert::AsRef::<str>::as_ref(&$source),
+            &[],
+            ::core::convert::AsRef::<[u8]>::as_ref(&$expected),
+        )
+    };
+    ($source:expr, $input:expr, $expected:expr $(,)?) => {
+        $crate::testing::assert_output(
+            ::core::convert::AsRef::<str>::as_ref(&$source),
+            ::core::convert::AsRef::<[u8]>::as_ref(&$input),
+            ::core::convert::AsRef::<[u8]>::as_ref(&$expected),
+        )
+    };
+}
+
+/// Why a fixture failed
+#[derive(Clone, Debug)]
+pub enum FixtureFailureKind {
+    /// The program or one of its accompanying files could not be read
+    Io(String),
+    /// The program failed to compile
+    Compile(String),
+    /// The program failed while running
+    Runtime(RuntimeError),
+    /// The program executed this many instructions without finishing
+    InstructionLimit(usize),
+    /// The program produced the wrong output
+    OutputMismatch {
+        /// The contents of the `.out` file
+        expected: Vec<u8>,
+        /// The output the program produced
+        actual: Vec<u8>,
+    },
+}
+
+/// A fixture that failed
+#[derive(Clone, Debug)]
+pub struct FixtureFailure {
+    /// The path of the program
+    pub path: PathBuf,
+    /// Why the fixture failed
+    pub kind: FixtureFailureKind,
+}
+
+impl Display for FixtureFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: ", self.path.display())?;
+        match &self.kind {
+            FixtureFailureKind::Io(error) => write!(f, "{}", error),
+            FixtureFailureKind::Compile(error) => write!(f, "{}", error),
+            FixtureFailureKind::Runtime(error) => write!(f, "{}", error),
+            FixtureFailureKind::InstructionLimit(limit) => {
+                write!(f, "did not finish within {} instructions", limit)
+            }
+            FixtureFailureKind::OutputMismatch { expected, actual } => write!(
+                f,
+                "output did not match\n  expected: {:?}\n    actual: {:?}",
+                String::from_utf8_lossy(expected),
+                String::from_utf8_lossy(actual),
+            ),
+        }
+    }
+}
+
+/// The result of running all fixtures in a directory
+#[derive(Clone, Debug, Default)]
+pub struct FixtureSummary {
+    /// The programs that produced the expected output
+    pub passed: Vec<PathBuf>,
+    /// The programs that did not
+    pub failed: Vec<FixtureFailure>,
+}
+
+fn run_fixture(program: &Path, expected: &Path) -> Result<(), FixtureFailureKind> {
+    let io = |error: std::io::Error| FixtureFailureKind::Io(error.to_string());
+
+    let source = fs::read_to_string(program).map_err(io)?;
+    let expected = fs::read(expected).map_err(io)?;
+    let input_path = program.with_extension("in");
+    let input = if input_path.exists() {
+        fs::read(input_path).map_err(io)?
+    } else {
+        Vec::new()
+    };
+
+    let actual = run_program(&source, &input).map_err(|error| match error {
+        RunProgramError::Compile(error) => FixtureFailureKind::Compile(error.to_string()),
+        RunProgramError::Runtime(error) => FixtureFailureKind::Runtime(error),
+        RunProgramError::InstructionLimit(limit) => FixtureFailureKind::InstructionLimit(limit),
+    })?;
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(FixtureFailureKind::OutputMismatch { expected, actual })
+    }
+}
+
+/// Run every fixture in `directory`
+///
+/// A fixture is a program with the extension `.b` or `.bf`, accompanied by a file with
+/// the same name and the extension `.out` containing its expected output. If a file with
+/// the extension `.in` exists, it is used as input for the program. Programs without
+/// an `.out` file are skipped.
+pub fn run_fixtures<P>(directory: P) -> std::io::Result<FixtureSummary>
+where
+    P: AsRef<Path>,
+{
+    let mut programs = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        let is_program = path
+            .extension()
+            .is_some_and(|extension| extension == "b" || extension == "bf");
+        if is_program {
+            programs.push(path);
+        }
+    }
+    programs.sort();
+
+    let mut summary = FixtureSummary::default();
+    for program in programs {
+        let expected = program.with_extension("out");
+        if !expected.exists() {
+            continue;
+        }
+        match run_fixture(&program, &expected) {
+            Ok(()) => summary.passed.push(program),
+            Err(kind) => summary.failed.push(FixtureFailure {
+                path: program,
+                kind,
+            }),
+        }
+    }
+    Ok(summary)
+}
+
+/// Run every fixture in `directory`, panicking with a description of all failures if any fail
+///
+/// See [`run_fixtures`] for the layout of the directory.
+#[track_caller]
+pub fn assert_fixtures<P>(directory: P)
+where
+    P: AsRef<Path>,
+{
+    let directory = directory.as_ref();
+    let summary = match run_fixtures(directory) {
+        Ok(summary) => summary,
+        Err(error) => panic!("Failed to read {}: {}", directory.display(), error),
+    };
+
+    if !summary.failed.is_empty() {
+        let mut message = String::new();
+        for failure in &summary.failed {
+            message.push_str(&failure.to_string());
+            message.push('\n');
+        }
+        panic!(
+            "{} of {} fixtures failed:\n{}",
+            summary.failed.len(),
+            summary.failed.len() + summary.passed.len(),
+            message
+        );
+    }
+}
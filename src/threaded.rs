@@ -0,0 +1,647 @@
+//! An interpreter that dispatches over fused operations instead of single instructions
+
+use alloc::vec::Vec;
+
+use crate::{
+    io::{Input, Output},
+    result::{Counted, RunStart},
+    BrainfuckInstruction, BrainfuckProgram, Executor, RunOutcome, RunResult, RuntimeError, Tape,
+};
+
+/// A single operation of [`ThreadedCode`]
+///
+/// Jumps contain the index of the operation to continue at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+    /// Add to the current cell
+    Add(u8),
+    /// Move the data pointer
+    Move(isize),
+    /// Add to the current cell, then move the data pointer
+    AddMove(u8, isize),
+    /// Move the data pointer, then add to the new current cell
+    MoveAdd(isize, u8),
+    /// `[-]` or `[+]`
+    Clear,
+    /// A loop that only moves the data pointer, like `[>]` or `[<<]`
+    Scan(isize),
+    Output,
+    Input,
+    JumpIfZero(usize),
+    JumpIfNonZero(usize),
+}
+
+/// A program translated into fused operations for
+/// [`BrainfuckProgram::try_run_threaded`]
+///
+/// Runs of `+`, `-`, `>` and `<` become a single operation, as do clearing loops like
+/// `[-]` and loops that only move the data pointer. A change of a cell followed by a
+/// move, or a move followed by a change, is fused into one operation as well. The
+/// operations are dispatched by a single `match` in a loop, which needs far fewer
+/// dispatches than [`BrainfuckProgram::try_step`] for the same program. The `backends`
+/// benchmark compares the two.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThreadedCode {
+    ops: Vec<Op>,
+    /// The index of the first instruction of every operation
+    origins: Vec<usize>,
+    /// The amount of instructions the code was translated from
+    instructions: usize,
+}
+
+impl ThreadedCode {
+    /// Translate `instructions`, or return `None` if they use extensions of Brainfuck, like
+    /// procedures
+    pub fn new(instructions: &[BrainfuckInstruction]) -> Option<Self> {
+        let mut code = Self {
+            ops: Vec::with_capacity(instructions.len()),
+            origins: Vec::with_capacity(instructions.len()),
+            instructions: instructions.len(),
+        };
+        let mut open_loops = Vec::new();
+
+        let mut index = 0;
+        while let Some(instruction) = instructions.get(index) {
+            let run = instructions[index..]
+                .iter()
+                .take_while(|next| *next == instruction)
+                .count();
+            let origin = index;
+            index += 1;
+
+            let op = match instruction {
+                BrainfuckInstruction::IncrementDataPointer => {
+                    index = origin + run;
+                    Op::Move(run as isize)
+                }
+                BrainfuckInstruction::DecrementDataPointer => {
+                    index = origin + run;
+                    Op::Move(-(run as isize))
+                }
+                BrainfuckInstruction::IncreaseData => {
+                    index = origin + run;
+                    Op::Add(run as u8)
+                }
+                BrainfuckInstruction::DecreaseData => {
+                    index = origin + run;
+                    Op::Add((run as u8).wrapping_neg())
+                }
+                BrainfuckInstruction::Output => Op::Output,
+                BrainfuckInstruction::Input => Op::Input,
+                BrainfuckInstruction::JumpForward(end) => {
+                    if let Some(op) = simple_loop(&instructions[origin + 1..*end]) {
+                        index = end + 1;
+                        op
+                    } else {
+                        open_loops.push(code.ops.len());
+                        // Patched once the matching `]` is translated
+                        Op::JumpIfZero(0)
+                    }
+                }
+                BrainfuckInstruction::JumpBackwards(_) => {
+                    let start = open_loops.pop()?;
+                    code.ops[start] = Op::JumpIfZero(code.ops.len() + 1);
+                    Op::JumpIfNonZero(start + 1)
+                }
+                BrainfuckInstruction::DefineProcedure(_)
+                | BrainfuckInstruction::EndProcedure
+                | BrainfuckInstruction::CallProcedure
+                | BrainfuckInstruction::HostCall
+                | BrainfuckInstruction::MoveUp
+                | BrainfuckInstruction::MoveDown
+                | BrainfuckInstruction::PreviousTape
+                | BrainfuckInstruction::NextTape
+                | BrainfuckInstruction::Push
+                | BrainfuckInstruction::Pop => return None,
+            };
+            code.push(op, origin);
+        }
+
+        Some(code)
+    }
+
+    /// Translate the loop whose `[` is at `start` in `instructions`, or return `None`
+    /// if it uses extensions of Brainfuck, like procedures
+    ///
+    /// The operations refer to the instructions of the loop relative to its `[`.
+    pub(crate) fn for_loop(instructions: &[BrainfuckInstruction], start: usize) -> Option<Self> {
+        let end = match instructions.get(start)? {
+            BrainfuckInstruction::JumpForward(end) => *end,
+            _ => return None,
+        };
+        let body: Vec<BrainfuckInstruction> = instructions[start..=end]
+            .iter()
+            .map(|instruction| match instruction {
+                BrainfuckInstruction::JumpForward(target) => {
+                    BrainfuckInstruction::JumpForward(target - start)
+                }
+                BrainfuckInstruction::JumpBackwards(target) => {
+                    BrainfuckInstruction::JumpBackwards(target - start)
+                }
+                other => *other,
+            })
+            .collect();
+        Self::new(&body)
+    }
+
+    /// Add `op`, which starts at the instruction `origin`, fusing it with the previous
+    /// operation if possible
+    ///
+    /// Operations that follow a jump are never fused, so jump targets stay intact.
+    fn push(&mut self, op: Op, origin: usize) {
+        let fused = match (self.ops.last(), op) {
+            (Some(Op::Add(amount)), Op::Move(offset)) => Some(Op::AddMove(*amount, offset)),
+            (Some(Op::Move(offset)), Op::Add(amount)) => Some(Op::MoveAdd(*offset, amount)),
+            _ => None,
+        };
+        match fused {
+            Some(fused) => *self.ops.last_mut().unwrap() = fused,
+            None => {
+                self.ops.push(op);
+                self.origins.push(origin);
+            }
+        }
+    }
+
+    /// The amount of operations
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Whether there are no operations
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
+
+/// The operation for a loop with `body`, if it only clears the cell or moves the data
+/// pointer
+fn simple_loop(body: &[BrainfuckInstruction]) -> Option<Op> {
+    let first = body.first()?;
+    if body.iter().any(|instruction| instruction != first) {
+        return None;
+    }
+    match first {
+        BrainfuckInstruction::IncreaseData | BrainfuckInstruction::DecreaseData
+            if body.len() == 1 =>
+        {
+            Some(Op::Clear)
+        }
+        BrainfuckInstruction::IncrementDataPointer => Some(Op::Scan(body.len() as isize)),
+        BrainfuckInstruction::DecrementDataPointer => Some(Op::Scan(-(body.len() as isize))),
+        _ => None,
+    }
+}
+
+#[allow(deprecated)]
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape<Data = u8>,
+{
+    /// Run the Brainfuck program to completion, dispatching over [`ThreadedCode`]
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`try_run_threaded`](Self::try_run_threaded) returns an error.
+    pub fn run_threaded<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> RunResult<u8>
+    where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        match self.try_run_threaded(output, input) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", self.explain(&error)),
+        }
+    }
+
+    /// Run the Brainfuck program to completion, or until it fails, translating it to
+    /// [`ThreadedCode`] first
+    ///
+    /// Threaded code always starts at the first instruction, so if the program has
+    /// already been stepped or if it uses extensions of Brainfuck, like procedures, this is
+    /// equivalent to [`try_run`](Self::try_run). When the data pointer would leave the
+    /// tape, execution continues in the interpreter from the start of the operation that
+    /// moved it, so errors, strict mode and growing tapes behave exactly as they do with
+    /// [`try_run`](Self::try_run).
+    ///
+    /// Instructions executed as threaded code are not counted in `execution_count`.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{io::VecOutput, programs, BrainfuckProgram};
+    /// let source = programs::HELLO_WORLD.source;
+    /// let mut program = BrainfuckProgram::compile(source, vec![0u8; 30_000]).unwrap();
+    /// let mut output = VecOutput::new();
+    /// program.try_run_threaded(&mut output, &mut || 0).unwrap();
+    /// assert_eq!(output.into_inner(), programs::HELLO_WORLD.output);
+    /// ```
+    pub fn try_run_threaded<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<RunResult<u8>, RuntimeError>
+    where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        if self.instruction_pointer != 0 {
+            return self.try_run(output, input);
+        }
+        let start = RunStart::new(self);
+        let code = match ThreadedCode::new(&self.instructions) {
+            Some(code) => code,
+            None => return self.try_run(output, input),
+        };
+        let (mut output, mut input) = (Counted::new(output), Counted::new(input));
+        self.run_code(&code, false, &mut output, &mut input)?;
+        Ok(start.finish(self, RunOutcome::Halted, &output, &input))
+    }
+
+    /// Run the Brainfuck program to completion like
+    /// [`run_threaded`](Self::run_threaded), using the cells of the tape directly if it
+    /// stores them contiguously
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`try_run_fast`](Self::try_run_fast) returns an error.
+    pub fn run_fast<FnOut, FnIn>(&mut self, output: &mut FnOut, input: &mut FnIn) -> RunResult<u8>
+    where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        match self.try_run_fast(output, input) {
+            Ok(result) => result,
+            Err(error) => panic!("{}", self.explain(&error)),
+        }
+    }
+
+    /// Run the Brainfuck program to completion, or until it fails, like
+    /// [`try_run_threaded`](Self::try_run_threaded), using the cells of the tape
+    /// directly if it stores them contiguously
+    ///
+    /// Threaded code operates on the slice returned by [`Tape::as_mut_slice`], so that
+    /// accessing a cell is a single index into it. When the data pointer moves past the
+    /// end of the slice, the tape is asked for the cell with
+    /// [`get_data_at_mut`](Tape::get_data_at_mut), so that growing tapes work as usual.
+    /// Tapes that do not store their cells contiguously are accessed through the [`Tape`]
+    /// trait, like [`try_run_threaded`](Self::try_run_threaded) does.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{io::VecOutput, programs, BrainfuckProgram};
+    /// let source = programs::HELLO_WORLD.source;
+    /// let mut program = BrainfuckProgram::compile(source, Vec::new()).unwrap();
+    /// let mut output = VecOutput::new();
+    /// program.try_run_fast(&mut output, &mut || 0).unwrap();
+    /// assert_eq!(output.into_inner(), programs::HELLO_WORLD.output);
+    /// ```
+    pub fn try_run_fast<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<RunResult<u8>, RuntimeError>
+    where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        if self.instruction_pointer != 0 {
+            return self.try_run(output, input);
+        }
+        let start = RunStart::new(self);
+        let code = match ThreadedCode::new(&self.instructions) {
+            Some(code) => code,
+            None => return self.try_run(output, input),
+        };
+        let (mut output, mut input) = (Counted::new(output), Counted::new(input));
+        self.run_code(&code, true, &mut output, &mut input)?;
+        Ok(start.finish(self, RunOutcome::Halted, &output, &input))
+    }
+
+    /// Run `code`, which must have been translated from the instructions of this
+    /// program, from its first operation, on the contiguous cells of the tape if `fast`
+    /// is set
+    fn run_code<FnOut, FnIn>(
+        &mut self,
+        code: &ThreadedCode,
+        fast: bool,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<(), RuntimeError>
+    where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        if self.tape.get_data_at_mut(self.data_pointer).is_some() {
+            let stopped = self.run_ops_on_tape(code, fast, output, input);
+            self.instruction_pointer = match stopped {
+                Some(op) => code.origins[op],
+                None => code.instructions,
+            };
+        }
+        self.execute(None, false, output, input).map(drop)
+    }
+
+    /// Run `code`, which must have been translated by [`ThreadedCode::for_loop`] from
+    /// the loop whose `[` is at the instruction pointer, and continue after the loop
+    ///
+    /// If an operation would move the data pointer off the tape, the instruction
+    /// pointer is left at the instruction that it was translated from instead, so that
+    /// the interpreter can take over from there.
+    pub(crate) fn run_threaded_loop<FnOut, FnIn>(
+        &mut self,
+        code: &ThreadedCode,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        if self.tape.get_data_at_mut(self.data_pointer).is_none() {
+            return;
+        }
+        let start = self.instruction_pointer;
+        let stopped = self.run_ops_on_tape(code, true, output, input);
+        self.instruction_pointer = start
+            + match stopped {
+                Some(op) => code.origins[op],
+                None => code.instructions,
+            };
+    }
+
+    /// Run `code` from its first operation, on the contiguous cells of the tape if
+    /// `fast` is set, with the data pointer at a cell of the tape
+    ///
+    /// Returns the operation that would have moved the data pointer off the tape, or
+    /// `None` if the end of the code was reached.
+    fn run_ops_on_tape<FnOut, FnIn>(
+        &mut self,
+        code: &ThreadedCode,
+        fast: bool,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Option<usize>
+    where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        let mut data_pointer = self.data_pointer;
+        let mut pc = 0;
+
+        let stopped = loop {
+            let slice = self
+                .tape
+                .as_mut_slice()
+                .filter(|cells| fast && data_pointer < cells.len());
+            let (stop, on_slice) = match slice {
+                Some(cells) => (
+                    run_ops(code, cells, &mut pc, &mut data_pointer, output, input),
+                    true,
+                ),
+                None => (
+                    run_ops(
+                        code,
+                        &mut TapeCells(&mut self.tape),
+                        &mut pc,
+                        &mut data_pointer,
+                        output,
+                        input,
+                    ),
+                    false,
+                ),
+            };
+            match stop {
+                Stop::Finished => break None,
+                Stop::Missing { op, target } => {
+                    // Continue on the slice if the tape has grown to contain the target
+                    let grown = on_slice
+                        && target.is_some_and(|target| {
+                            self.tape.get_data_at_mut(target).is_some()
+                                && self
+                                    .tape
+                                    .as_mut_slice()
+                                    .is_some_and(|cells| target < cells.len())
+                        });
+                    if !grown {
+                        break Some(op);
+                    }
+                    pc = op;
+                }
+            }
+        };
+
+        self.data_pointer = data_pointer;
+        stopped
+    }
+}
+
+/// The cells that threaded code runs on
+trait Cells {
+    /// The cell at `data_pointer`, which [`contains`](Self::contains) has accepted
+    fn cell(&mut self, data_pointer: usize) -> &mut u8;
+
+    /// Whether there is a cell at `data_pointer`
+    fn contains(&mut self, data_pointer: usize) -> bool;
+}
+
+impl Cells for [u8] {
+    fn cell(&mut self, data_pointer: usize) -> &mut u8 {
+        &mut self[data_pointer]
+    }
+
+    fn contains(&mut self, data_pointer: usize) -> bool {
+        data_pointer < self.len()
+    }
+}
+
+/// The cells of a tape, accessed through the [`Tape`] trait
+struct TapeCells<'a, T>(&'a mut T);
+
+impl<'a, T> Cells for TapeCells<'a, T>
+where
+    T: Tape<Data = u8>,
+{
+    fn cell(&mut self, data_pointer: usize) -> &mut u8 {
+        self.0
+            .get_data_at_mut(data_pointer)
+            .expect("threaded code only moves to cells on the tape")
+    }
+
+    fn contains(&mut self, data_pointer: usize) -> bool {
+        self.0.get_data_at_mut(data_pointer).is_some()
+    }
+}
+
+/// Why [`run_ops`] stopped
+enum Stop {
+    /// The end of the code was reached
+    Finished,
+    /// An operation would have moved the data pointer to a cell that is not in the
+    /// cells, and left it unchanged instead
+    Missing {
+        /// The index of the operation
+        op: usize,
+        /// The data pointer that has no cell, or `None` if it would be negative
+        target: Option<usize>,
+    },
+}
+
+/// Run `code` on `cells` from the operation at `pc`, with the data pointer at a cell in
+/// `cells`
+fn run_ops<C, FnOut, FnIn>(
+    code: &ThreadedCode,
+    cells: &mut C,
+    pc: &mut usize,
+    data_pointer: &mut usize,
+    output: &mut FnOut,
+    input: &mut FnIn,
+) -> Stop
+where
+    C: Cells + ?Sized,
+    FnOut: Output<u8>,
+    FnIn: Input<u8>,
+{
+    loop {
+        let op = match code.ops.get(*pc) {
+            Some(op) => *op,
+            None => return Stop::Finished,
+        };
+        let missing = |target| Stop::Missing { op: *pc, target };
+        match op {
+            Op::Add(amount) => {
+                let data = cells.cell(*data_pointer);
+                *data = data.wrapping_add(amount);
+            }
+            Op::Move(offset) => match moved(cells, *data_pointer, offset) {
+                Ok(target) => *data_pointer = target,
+                Err(target) => return missing(target),
+            },
+            Op::AddMove(amount, offset) => match moved(cells, *data_pointer, offset) {
+                Ok(target) => {
+                    let data = cells.cell(*data_pointer);
+                    *data = data.wrapping_add(amount);
+                    *data_pointer = target;
+                }
+                Err(target) => return missing(target),
+            },
+            Op::MoveAdd(offset, amount) => match moved(cells, *data_pointer, offset) {
+                Ok(target) => {
+                    *data_pointer = target;
+                    let data = cells.cell(*data_pointer);
+                    *data = data.wrapping_add(amount);
+                }
+                Err(target) => return missing(target),
+            },
+            Op::Clear => *cells.cell(*data_pointer) = 0,
+            Op::Scan(offset) => {
+                while *cells.cell(*data_pointer) != 0 {
+                    match moved(cells, *data_pointer, offset) {
+                        Ok(target) => *data_pointer = target,
+                        // The current cell is not zero, so running the loop again from
+                        // its `[` continues where it stopped
+                        Err(target) => return missing(target),
+                    }
+                }
+            }
+            Op::Output => output.output(*cells.cell(*data_pointer)),
+            Op::Input => *cells.cell(*data_pointer) = input.input(),
+            Op::JumpIfZero(target) => {
+                if *cells.cell(*data_pointer) == 0 {
+                    *pc = target;
+                    continue;
+                }
+            }
+            Op::JumpIfNonZero(target) => {
+                if *cells.cell(*data_pointer) != 0 {
+                    *pc = target;
+                    continue;
+                }
+            }
+        }
+        *pc += 1;
+    }
+}
+
+/// The data pointer moved by `offset`, or the data pointer that `cells` has no cell
+/// for, which is `None` if it would be negative
+fn moved<C>(cells: &mut C, data_pointer: usize, offset: isize) -> Result<usize, Option<usize>>
+where
+    C: Cells + ?Sized,
+{
+    let target = data_pointer.checked_add_signed(offset).ok_or(None)?;
+    if cells.contains(target) {
+        Ok(target)
+    } else {
+        Err(Some(target))
+    }
+}
+
+/// An [`Executor`] that runs programs as [`ThreadedCode`], using
+/// [`BrainfuckProgram::try_run_fast`]
+///
+/// The code is translated once, when the executor is created. Single steps are
+/// performed by the interpreter.
+#[derive(Clone, Debug)]
+pub struct ThreadedExecutor<T>
+where
+    T: Tape<Data = u8>,
+{
+    program: BrainfuckProgram<T>,
+    code: Option<ThreadedCode>,
+}
+
+#[allow(deprecated)]
+impl<T> ThreadedExecutor<T>
+where
+    T: Tape<Data = u8>,
+{
+    /// Create an executor that runs `program`
+    pub fn new(program: BrainfuckProgram<T>) -> Self {
+        let code = ThreadedCode::new(&program.instructions);
+        Self { program, code }
+    }
+
+    /// The program that is run
+    pub fn program(&self) -> &BrainfuckProgram<T> {
+        &self.program
+    }
+
+    /// Take the program that is run
+    pub fn into_inner(self) -> BrainfuckProgram<T> {
+        self.program
+    }
+}
+
+#[allow(deprecated)]
+impl<T> Executor for ThreadedExecutor<T>
+where
+    T: Tape<Data = u8>,
+{
+    type Data = u8;
+
+    fn step<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<bool, RuntimeError>
+    where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        self.program.try_step(output, input)
+    }
+
+    fn run<FnOut, FnIn>(&mut self, output: &mut FnOut, input: &mut FnIn) -> Result<(), RuntimeError>
+    where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        match &self.code {
+            Some(code) if self.program.instruction_pointer == 0 => {
+                self.program.run_code(code, true, output, input)
+            }
+            _ => self.program.try_run(output, input).map(drop),
+        }
+    }
+}
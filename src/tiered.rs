@@ -0,0 +1,178 @@
+//! Interpreting programs, and compiling the loops in which they spend their time
+
+use alloc::vec::Vec;
+
+#[cfg(all(feature = "native-jit", target_arch = "x86_64", unix))]
+use crate::backend::NativeLoop;
+use crate::{
+    io::{Input, Output},
+    BrainfuckInstruction, BrainfuckProgram, Executor, RuntimeError, Tape, ThreadedCode,
+};
+
+/// The default amount of times that a loop has to jump back to its start before it is
+/// compiled
+pub const HOT_THRESHOLD: u32 = 1000;
+
+/// The compiled version of a hot loop
+#[derive(Debug)]
+enum CompiledLoop {
+    /// Machine code, used if the tape stores its cells contiguously
+    #[cfg(all(feature = "native-jit", target_arch = "x86_64", unix))]
+    Native(NativeLoop, ThreadedCode),
+    Threaded(ThreadedCode),
+}
+
+/// An [`Executor`] that starts out interpreting a program, and compiles the loops that
+/// run often
+///
+/// Every time the `]` of a loop jumps back to its start, the loop gets hotter. Once it
+/// has done so [`HOT_THRESHOLD`] times, only that loop is translated to
+/// [`ThreadedCode`], or to machine code if the `native-jit` feature is enabled and the
+/// tape stores its cells contiguously. From then on, whenever the loop is reached, or
+/// when it jumps back to its start while it is being interpreted, the compiled loop
+/// runs instead, and execution continues in the interpreter after it.
+///
+/// Compiled code keeps track of the instruction that every part of it was translated
+/// from. When it would move the data pointer off the tape, the interpreter takes over
+/// at that instruction, so errors, strict mode and growing tapes behave exactly as
+/// they do when interpreting. Instructions executed by compiled code are not counted
+/// in `execution_count`.
+///
+/// ```
+/// # use brainfuck_interpreter::{io::VecOutput, programs, Executor, TieredExecutor};
+/// # use brainfuck_interpreter::BrainfuckProgram;
+/// let fixture = programs::SIERPINSKI;
+/// let program = BrainfuckProgram::compile(fixture.source, vec![0u8; 64]).unwrap();
+/// let mut executor = TieredExecutor::with_threshold(program, 10);
+///
+/// let mut output = VecOutput::new();
+/// executor.run(&mut output, &mut || 0).unwrap();
+/// assert_eq!(output.into_inner(), fixture.output);
+/// assert!(executor.compiled_loops().count() > 0);
+/// ```
+#[derive(Debug)]
+pub struct TieredExecutor<T>
+where
+    T: Tape<Data = u8>,
+{
+    program: BrainfuckProgram<T>,
+    /// How often the loop that starts at every instruction jumped back to its start
+    back_edges: Vec<u32>,
+    /// The compiled loop that starts at every instruction, if any
+    compiled: Vec<Option<CompiledLoop>>,
+    threshold: u32,
+}
+
+#[allow(deprecated)]
+impl<T> TieredExecutor<T>
+where
+    T: Tape<Data = u8>,
+{
+    /// Create an executor that runs `program`, compiling loops once they have jumped
+    /// back to their start [`HOT_THRESHOLD`] times
+    pub fn new(program: BrainfuckProgram<T>) -> Self {
+        Self::with_threshold(program, HOT_THRESHOLD)
+    }
+
+    /// Create an executor that runs `program`, compiling loops once they have jumped
+    /// back to their start `threshold` times
+    pub fn with_threshold(program: BrainfuckProgram<T>, threshold: u32) -> Self {
+        let len = program.instructions.len();
+        let mut compiled = Vec::with_capacity(len);
+        compiled.resize_with(len, || None);
+        Self {
+            program,
+            back_edges: alloc::vec![0; len],
+            compiled,
+            threshold,
+        }
+    }
+
+    /// The program that is run
+    pub fn program(&self) -> &BrainfuckProgram<T> {
+        &self.program
+    }
+
+    /// Take the program that is run
+    pub fn into_inner(self) -> BrainfuckProgram<T> {
+        self.program
+    }
+
+    /// The indices of the `[` of the loops that have been compiled
+    pub fn compiled_loops(&self) -> impl Iterator<Item = usize> + '_ {
+        self.compiled
+            .iter()
+            .enumerate()
+            .filter(|(_, compiled)| compiled.is_some())
+            .map(|(start, _)| start)
+    }
+
+    /// Compile the loop that starts at `start`, if it does not use extensions of
+    /// Brainfuck, like procedures
+    fn compile(&self, start: usize) -> Option<CompiledLoop> {
+        let code = ThreadedCode::for_loop(&self.program.instructions, start)?;
+        #[cfg(all(feature = "native-jit", target_arch = "x86_64", unix))]
+        if let Some(native) = NativeLoop::new(&self.program.instructions, start) {
+            return Some(CompiledLoop::Native(native, code));
+        }
+        Some(CompiledLoop::Threaded(code))
+    }
+}
+
+#[allow(deprecated)]
+impl<T> Executor for TieredExecutor<T>
+where
+    T: Tape<Data = u8>,
+{
+    type Data = u8;
+
+    /// Perform a single step in the interpreter, or run a compiled loop to completion
+    /// if the instruction pointer is at its `[`
+    fn step<FnOut, FnIn>(
+        &mut self,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<bool, RuntimeError>
+    where
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        let instruction_pointer = self.program.instruction_pointer;
+        if let Some(Some(compiled)) = self.compiled.get(instruction_pointer) {
+            match compiled {
+                #[cfg(all(feature = "native-jit", target_arch = "x86_64", unix))]
+                CompiledLoop::Native(native, code) => {
+                    if !self.program.run_native_loop(native, output, input) {
+                        self.program.run_threaded_loop(code, output, input);
+                    }
+                }
+                CompiledLoop::Threaded(code) => self.program.run_threaded_loop(code, output, input),
+            }
+            if self.program.instruction_pointer != instruction_pointer {
+                return Ok(true);
+            }
+            // The data pointer is not on the tape, which the interpreter reports
+        }
+
+        let running = self.program.try_step(output, input)?;
+
+        if let Some(BrainfuckInstruction::JumpBackwards(start)) =
+            self.program.instructions.get(instruction_pointer)
+        {
+            let start = *start;
+            if self.program.instruction_pointer == start + 1 {
+                let back_edges = &mut self.back_edges[start];
+                *back_edges = back_edges.saturating_add(1);
+                if *back_edges == self.threshold.max(1) {
+                    self.compiled[start] = self.compile(start);
+                }
+                // The current cell is not zero, so running the loop from its `[` continues
+                // where the interpreter is
+                if self.compiled[start].is_some() {
+                    self.program.instruction_pointer = start;
+                }
+            }
+        }
+        Ok(running)
+    }
+}
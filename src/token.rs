@@ -0,0 +1,145 @@
+//! Splitting source code into tokens for syntax highlighting
+
+use crate::{brainfuck_instruction, BrainfuckInstruction, SourcePos};
+
+/// What a [`Token`] is
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// A single command character. Jumps have a target of 0
+    Command(BrainfuckInstruction),
+    /// A run of text that is not a command, within a single line
+    Comment,
+    /// A run of whitespace that is not a command, within a single line
+    Whitespace,
+}
+
+/// A piece of source code, as produced by [`tokenize`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Token<'a> {
+    /// What this token is
+    pub kind: TokenKind,
+    /// The text of this token
+    pub text: &'a str,
+    /// The byte offset of this token in the source code
+    pub offset: usize,
+    /// The position of the first character of this token
+    pub position: SourcePos,
+    /// The amount of loops that this token is in. A `[` and its `]` are at the depth
+    /// of the code around the loop
+    pub depth: usize,
+}
+
+/// Split `source` into commands, comments and whitespace, keeping track of their loop
+/// depth
+///
+/// Together, the tokens cover all of `source` except for line breaks. Unlike
+/// [`parse`](crate::parse), this keeps the text that is not a command, which makes it
+/// suitable for syntax highlighting. This does not allocate.
+///
+/// ```
+/// # use brainfuck_interpreter::{tokenize, TokenKind};
+/// let tokens: Vec<_> = tokenize("+ [add one]").map(|t| (t.kind, t.text, t.depth)).collect();
+/// assert_eq!(tokens[1], (TokenKind::Whitespace, " ", 0));
+/// assert_eq!(tokens[3], (TokenKind::Comment, "add one", 1));
+/// assert_eq!(tokens[4].2, 0);
+/// ```
+pub fn tokenize(source: &str) -> impl Iterator<Item = Token<'_>> {
+    Tokens {
+        source,
+        offset: 0,
+        position: SourcePos {
+            line: 0,
+            character: 1,
+        },
+        depth: 0,
+    }
+}
+
+/// The iterator returned by [`tokenize`]
+struct Tokens<'a> {
+    source: &'a str,
+    offset: usize,
+    position: SourcePos,
+    depth: usize,
+}
+
+impl<'a> Tokens<'a> {
+    /// The length in bytes of the line break at the start of `rest`, if there is one
+    fn line_break(rest: &str) -> Option<usize> {
+        if rest.starts_with('\n') {
+            Some(1)
+        } else if rest.starts_with("\r\n") {
+            Some(2)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut rest = &self.source[self.offset..];
+        while let Some(len) = Self::line_break(rest) {
+            self.offset += len;
+            self.position = SourcePos {
+                line: self.position.line + 1,
+                character: 1,
+            };
+            rest = &rest[len..];
+        }
+
+        let first = rest.chars().next()?;
+        let start = self.offset;
+        let position = self.position;
+
+        let (kind, len, chars, depth) = match brainfuck_instruction(first) {
+            Some(instruction) => {
+                let depth = match instruction {
+                    BrainfuckInstruction::JumpForward(_) => {
+                        self.depth += 1;
+                        self.depth - 1
+                    }
+                    BrainfuckInstruction::JumpBackwards(_) => {
+                        self.depth = self.depth.saturating_sub(1);
+                        self.depth
+                    }
+                    _ => self.depth,
+                };
+                (TokenKind::Command(instruction), first.len_utf8(), 1, depth)
+            }
+            None => {
+                let mut len = 0;
+                let mut chars = 0;
+                let mut whitespace = true;
+                for (index, c) in rest.char_indices() {
+                    if brainfuck_instruction(c).is_some()
+                        || Self::line_break(&rest[index..]).is_some()
+                    {
+                        break;
+                    }
+                    len = index + c.len_utf8();
+                    chars += 1;
+                    whitespace &= c.is_whitespace();
+                }
+                let kind = if whitespace {
+                    TokenKind::Whitespace
+                } else {
+                    TokenKind::Comment
+                };
+                (kind, len, chars, self.depth)
+            }
+        };
+
+        self.offset += len;
+        self.position.character += chars;
+        Some(Token {
+            kind,
+            text: &self.source[start..start + len],
+            offset: start,
+            position,
+            depth,
+        })
+    }
+}
@@ -0,0 +1,417 @@
+//! Recording and replaying execution traces
+//!
+//! A trace consists of a header, followed by one entry per recorded step. All integers
+//! are little-endian, and variable-length integers are unsigned LEB128.
+//!
+//! | Size      | Contents                                                        |
+//! |-----------|-----------------------------------------------------------------|
+//! | 4         | The magic number, `BFTR`                                        |
+//! | 2         | The format version, currently `1`                               |
+//! | 4         | The interval at which steps were sampled, `1` for a full trace |
+//! | ...       | The entries                                                     |
+//!
+//! Every entry consists of the amount of steps since the previous entry (or since the
+//! start, for the first entry), the index of the instruction and the data pointer, all
+//! variable-length, followed by the cell at the data pointer before and after the step.
+
+use std::io::{self, Read, Write};
+
+use core::{convert::TryFrom, fmt::Display};
+
+use crate::{
+    io::{Input, Output},
+    BrainfuckProgram, Tape,
+};
+
+/// The magic number that every trace starts with
+pub const TRACE_MAGIC: [u8; 4] = *b"BFTR";
+
+/// The version of the trace format produced by [`TraceRecorder`]
+pub const TRACE_VERSION: u16 = 1;
+
+/// A single recorded step of a program
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TraceEntry {
+    /// The number of the step, starting at 0 for the first step after recording started
+    pub step: usize,
+    /// The index of the executed instruction
+    pub instruction: usize,
+    /// The data pointer before the instruction was executed
+    pub data_pointer: usize,
+    /// The cell at `data_pointer` before the instruction was executed
+    pub before: u8,
+    /// The cell at `data_pointer` after the instruction was executed
+    pub after: u8,
+}
+
+/// An error that can occur while reading a trace
+#[derive(Debug)]
+pub enum TraceError {
+    /// Reading the trace failed, or it ended in the middle of an entry
+    Io(io::Error),
+    /// The trace does not start with [`TRACE_MAGIC`]
+    BadMagic,
+    /// The trace has a version that is not supported
+    UnsupportedVersion(u16),
+}
+
+impl Display for TraceError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TraceError::Io(error) => write!(f, "Failed to read trace: {}", error),
+            TraceError::BadMagic => write!(f, "Data is not a Brainfuck trace"),
+            TraceError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported trace version {}", version)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TraceError {}
+
+impl From<io::Error> for TraceError {
+    fn from(error: io::Error) -> Self {
+        TraceError::Io(error)
+    }
+}
+
+fn write_varint<W: Write>(writer: &mut W, mut value: usize) -> io::Result<()> {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_byte<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut byte = [0];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_varint<R: Read>(reader: &mut R, first: u8) -> io::Result<usize> {
+    let mut value = 0usize;
+    let mut byte = first;
+    let mut shift = 0;
+    loop {
+        let bits = usize::from(byte & 0x7F);
+        if shift >= usize::BITS || (bits << shift) >> shift != bits {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Variable-length integer is too large",
+            ));
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+        byte = read_byte(reader)?;
+    }
+}
+
+/// Runs a program while writing a trace of its execution to a [`Write`] sink
+#[derive(Debug)]
+pub struct TraceRecorder<W>
+where
+    W: Write,
+{
+    writer: W,
+    sample_every: usize,
+    step: usize,
+    last_recorded: usize,
+}
+
+impl<W> TraceRecorder<W>
+where
+    W: Write,
+{
+    /// Create a recorder that records every step to `writer`
+    pub fn new(writer: W) -> io::Result<Self> {
+        Self::sampled(writer, 1)
+    }
+
+    /// Create a recorder that records every `sample_every`th step to `writer`,
+    /// starting with the first
+    ///
+    /// # Panics
+    /// If `sample_every` is zero, or does not fit in a `u32`
+    pub fn sampled(mut writer: W, sample_every: usize) -> io::Result<Self> {
+        assert!(sample_every > 0, "Sample interval must be at least 1");
+        let interval = u32::try_from(sample_every).expect("Sample interval must fit in a u32");
+
+        writer.write_all(&TRACE_MAGIC)?;
+        writer.write_all(&TRACE_VERSION.to_le_bytes())?;
+        writer.write_all(&interval.to_le_bytes())?;
+
+        Ok(Self {
+            writer,
+            sample_every,
+            step: 0,
+            last_recorded: 0,
+        })
+    }
+
+    /// Perform a step in `program`, recording it if it is sampled
+    pub fn step<T, FnOut, FnIn>(
+        &mut self,
+        program: &mut BrainfuckProgram<T>,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> io::Result<bool>
+    where
+        T: Tape<Data = u8>,
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        if !self.step.is_multiple_of(self.sample_every) {
+            self.step += 1;
+            return Ok(program.step(output, input));
+        }
+
+        let entry = match record_step(program, self.step, output, input) {
+            Some(entry) => entry,
+            None => return Ok(false),
+        };
+
+        write_varint(&mut self.writer, entry.step - self.last_recorded)?;
+        write_varint(&mut self.writer, entry.instruction)?;
+        write_varint(&mut self.writer, entry.data_pointer)?;
+        self.writer.write_all(&[entry.before, entry.after])?;
+
+        self.last_recorded = entry.step;
+        self.step += 1;
+        Ok(true)
+    }
+
+    /// Run `program` to completion, recording its execution
+    pub fn run<T, FnOut, FnIn>(
+        &mut self,
+        program: &mut BrainfuckProgram<T>,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> io::Result<()>
+    where
+        T: Tape<Data = u8>,
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        while self.step(program, output, input)? {}
+        Ok(())
+    }
+
+    /// Flush the trace, and return the underlying writer
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Perform a step in `program`, returning what happened, or `None` if it has finished
+#[allow(deprecated)]
+fn record_step<T, FnOut, FnIn>(
+    program: &mut BrainfuckProgram<T>,
+    step: usize,
+    output: &mut FnOut,
+    input: &mut FnIn,
+) -> Option<TraceEntry>
+where
+    T: Tape<Data = u8>,
+    FnOut: Output<u8>,
+    FnIn: Input<u8>,
+{
+    let instruction = program.instruction_pointer;
+    let data_pointer = program.data_pointer;
+    let before = program.tape.get_data_at(data_pointer).copied();
+
+    if !program.step(output, input) {
+        return None;
+    }
+
+    // The step would have panicked if the data pointer was out of bounds
+    let before = before.unwrap_or_default();
+    let after = program
+        .tape
+        .get_data_at(data_pointer)
+        .copied()
+        .unwrap_or_default();
+
+    Some(TraceEntry {
+        step,
+        instruction,
+        data_pointer,
+        before,
+        after,
+    })
+}
+
+/// The first difference between a recorded trace and a replayed execution
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TraceDivergence {
+    /// The recorded step, or `None` if the trace ended before the program did
+    pub expected: Option<TraceEntry>,
+    /// The replayed step, or `None` if the program finished before the trace ended
+    pub actual: Option<TraceEntry>,
+}
+
+impl Display for TraceDivergence {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match (self.expected, self.actual) {
+            (Some(expected), Some(actual)) => write!(
+                f,
+                "Step {}: expected instruction {} at data pointer {} ({} -> {}), \
+                 but executed instruction {} at data pointer {} ({} -> {})",
+                expected.step,
+                expected.instruction,
+                expected.data_pointer,
+                expected.before,
+                expected.after,
+                actual.instruction,
+                actual.data_pointer,
+                actual.before,
+                actual.after
+            ),
+            (Some(expected), None) => write!(
+                f,
+                "Program finished before step {} of the trace",
+                expected.step
+            ),
+            (None, Some(actual)) => {
+                write!(f, "Trace ended before step {} of the program", actual.step)
+            }
+            (None, None) => write!(f, "No divergence"),
+        }
+    }
+}
+
+/// Reads a trace written by a [`TraceRecorder`], and replays programs against it
+#[derive(Debug)]
+pub struct TraceReplayer<R>
+where
+    R: Read,
+{
+    reader: R,
+    sample_every: usize,
+    last_entry: usize,
+    started: bool,
+}
+
+impl<R> TraceReplayer<R>
+where
+    R: Read,
+{
+    /// Create a replayer, reading the header of the trace from `reader`
+    pub fn new(mut reader: R) -> Result<Self, TraceError> {
+        let mut header = [0; 10];
+        reader.read_exact(&mut header).map_err(|error| {
+            if error.kind() == io::ErrorKind::UnexpectedEof {
+                TraceError::BadMagic
+            } else {
+                TraceError::Io(error)
+            }
+        })?;
+
+        if header[..4] != TRACE_MAGIC {
+            return Err(TraceError::BadMagic);
+        }
+        let version = u16::from_le_bytes([header[4], header[5]]);
+        if version != TRACE_VERSION {
+            return Err(TraceError::UnsupportedVersion(version));
+        }
+        let sample_every = u32::from_le_bytes([header[6], header[7], header[8], header[9]]);
+
+        Ok(Self {
+            reader,
+            sample_every: (sample_every as usize).max(1),
+            last_entry: 0,
+            started: false,
+        })
+    }
+
+    /// The interval at which steps were sampled in the trace
+    pub fn sample_every(&self) -> usize {
+        self.sample_every
+    }
+
+    /// Read the next entry from the trace, or `None` if it has ended
+    pub fn next_entry(&mut self) -> Result<Option<TraceEntry>, TraceError> {
+        let mut first = [0];
+        if self.reader.read(&mut first)? == 0 {
+            return Ok(None);
+        }
+
+        let delta = read_varint(&mut self.reader, first[0])?;
+        let step = if self.started {
+            self.last_entry.checked_add(delta)
+        } else {
+            Some(delta)
+        }
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Step number overflowed"))?;
+
+        let first = read_byte(&mut self.reader)?;
+        let instruction = read_varint(&mut self.reader, first)?;
+        let first = read_byte(&mut self.reader)?;
+        let data_pointer = read_varint(&mut self.reader, first)?;
+        let before = read_byte(&mut self.reader)?;
+        let after = read_byte(&mut self.reader)?;
+
+        self.started = true;
+        self.last_entry = step;
+        Ok(Some(TraceEntry {
+            step,
+            instruction,
+            data_pointer,
+            before,
+            after,
+        }))
+    }
+
+    /// Re-execute `program`, comparing every sampled step against the trace
+    ///
+    /// Returns the first divergence, or `None` if the execution matches the trace.
+    /// Execution stops at the first divergence.
+    pub fn replay<T, FnOut, FnIn>(
+        &mut self,
+        program: &mut BrainfuckProgram<T>,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> Result<Option<TraceDivergence>, TraceError>
+    where
+        T: Tape<Data = u8>,
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        let mut step = 0usize;
+        loop {
+            if !step.is_multiple_of(self.sample_every) {
+                if !program.step(output, input) {
+                    break;
+                }
+                step += 1;
+                continue;
+            }
+
+            let actual = record_step(program, step, output, input);
+            let expected = self.next_entry()?;
+            if actual != expected {
+                return Ok(Some(TraceDivergence { expected, actual }));
+            }
+            if actual.is_none() {
+                return Ok(None);
+            }
+            step += 1;
+        }
+
+        // The program finished between two samples
+        match self.next_entry()? {
+            Some(expected) => Ok(Some(TraceDivergence {
+                expected: Some(expected),
+                actual: None,
+            })),
+            None => Ok(None),
+        }
+    }
+}
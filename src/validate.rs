@@ -0,0 +1,166 @@
+//! Checking hand-constructed instruction lists
+
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use crate::{BrainfuckInstruction, BrainfuckProgram, Tape};
+
+/// A problem with the instructions of a program, as found by
+/// [`BrainfuckProgram::validate`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValidationError {
+    /// The target of the instruction at `index` is not an index of an instruction
+    TargetOutOfRange {
+        /// The index of the instruction
+        index: usize,
+        /// Its target
+        target: usize,
+    },
+    /// The bracket at `index` does not have a matching bracket
+    Unmatched {
+        /// The index of the bracket
+        index: usize,
+    },
+    /// The bracket at `index` does not target its matching bracket
+    WrongTarget {
+        /// The index of the bracket
+        index: usize,
+        /// Its target
+        target: usize,
+        /// The index of its matching bracket
+        expected: usize,
+    },
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ValidationError::TargetOutOfRange { index, target } => write!(
+                f,
+                "Instruction {} targets instruction {}, which does not exist",
+                index, target
+            ),
+            ValidationError::Unmatched { index } => {
+                write!(f, "Instruction {} does not have a matching bracket", index)
+            }
+            ValidationError::WrongTarget {
+                index,
+                target,
+                expected,
+            } => write!(
+                f,
+                "Instruction {} targets instruction {}, but its matching bracket is instruction {}",
+                index, target, expected
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationError {}
+
+#[allow(deprecated)]
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Check that the instructions of this program are consistent, returning the
+    /// first problem, ordered by the index of the instruction
+    ///
+    /// Every `[` must target its matching `]` and the other way around, and every `(`
+    /// must target its matching `)`, as described in [`BrainfuckInstruction`]. Programs
+    /// that are compiled from source code always pass this check, but programs created
+    /// with [`from_instructions`](Self::from_instructions) or by changing
+    /// [`instructions`](Self::instructions) may not, and can panic or misbehave while
+    /// running.
+    ///
+    /// ```
+    /// # use brainfuck_interpreter::{BrainfuckInstruction::*, BrainfuckProgram, ValidationError};
+    /// let program = BrainfuckProgram::from_instructions(
+    ///     vec![IncreaseData, JumpForward(3), DecreaseData, JumpBackwards(1)],
+    ///     vec![0u8],
+    /// );
+    /// assert_eq!(program.validate(), Ok(()));
+    ///
+    /// let program = BrainfuckProgram::from_instructions(
+    ///     vec![JumpForward(0), DecreaseData, JumpBackwards(0)],
+    ///     vec![0u8],
+    /// );
+    /// assert_eq!(
+    ///     program.validate(),
+    ///     Err(ValidationError::WrongTarget { index: 0, target: 0, expected: 2 })
+    /// );
+    /// ```
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let len = self.instructions.len();
+        // The open brackets, as the index of every `[` or `(`
+        let mut open: Vec<usize> = Vec::new();
+        let mut errors = Vec::new();
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            if let Some(target) = instruction.target() {
+                if target >= len {
+                    errors.push(ValidationError::TargetOutOfRange { index, target });
+                }
+            }
+
+            match instruction {
+                BrainfuckInstruction::JumpForward(_) | BrainfuckInstruction::DefineProcedure(_) => {
+                    open.push(index)
+                }
+                BrainfuckInstruction::JumpBackwards(_) | BrainfuckInstruction::EndProcedure => {
+                    let start = match open.pop() {
+                        Some(start) => start,
+                        None => {
+                            errors.push(ValidationError::Unmatched { index });
+                            continue;
+                        }
+                    };
+                    let opening = self.instructions[start];
+                    let kinds_match = matches!(
+                        (opening, instruction),
+                        (
+                            BrainfuckInstruction::JumpForward(_),
+                            BrainfuckInstruction::JumpBackwards(_)
+                        ) | (
+                            BrainfuckInstruction::DefineProcedure(_),
+                            BrainfuckInstruction::EndProcedure
+                        )
+                    );
+                    if !kinds_match {
+                        errors.push(ValidationError::Unmatched { index: start });
+                        errors.push(ValidationError::Unmatched { index });
+                        continue;
+                    }
+                    if let Some(target) = opening.target().filter(|target| *target != index) {
+                        errors.push(ValidationError::WrongTarget {
+                            index: start,
+                            target,
+                            expected: index,
+                        });
+                    }
+                    if let Some(target) = instruction.target().filter(|target| *target != start) {
+                        errors.push(ValidationError::WrongTarget {
+                            index,
+                            target,
+                            expected: start,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        errors.extend(
+            open.into_iter()
+                .map(|index| ValidationError::Unmatched { index }),
+        );
+
+        match errors.into_iter().min_by_key(|error| match error {
+            ValidationError::TargetOutOfRange { index, .. }
+            | ValidationError::Unmatched { index }
+            | ValidationError::WrongTarget { index, .. } => *index,
+        }) {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
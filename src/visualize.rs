@@ -0,0 +1,224 @@
+//! Showing the state of a running program in a terminal
+//!
+//! The visualizer redraws the terminal using ANSI escape codes, so it works in any
+//! terminal that supports them, without further dependencies.
+
+use std::{
+    collections::VecDeque,
+    fmt::Write as _,
+    io::{self, Write},
+    time::Instant,
+};
+
+use crate::{
+    io::{Input, Output},
+    BrainfuckProgram, SourcePos, Span, Tape,
+};
+
+/// Clear the screen and move the cursor to the top left corner
+const CLEAR: &str = "\x1b[2J\x1b[H";
+/// Show the following text with its colors inverted
+const INVERT: &str = "\x1b[7m";
+/// Reset all text attributes
+const RESET: &str = "\x1b[0m";
+
+/// Configuration of [`BrainfuckProgram::run_visualized`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VisualizeConfig {
+    /// The amount of instructions executed between two redraws
+    pub interval: usize,
+    /// The amount of cells shown around the data pointer
+    pub cells: usize,
+    /// The maximum amount of characters of the current source line that is shown
+    pub line_width: usize,
+    /// The amount of most recent output bytes that is shown
+    pub output_len: usize,
+}
+
+impl Default for VisualizeConfig {
+    fn default() -> Self {
+        Self {
+            interval: 1 << 14,
+            cells: 16,
+            line_width: 72,
+            output_len: 72,
+        }
+    }
+}
+
+/// The part of the state that is kept between frames
+struct Frame<'a> {
+    source: &'a str,
+    config: &'a VisualizeConfig,
+    start: Instant,
+    steps: usize,
+    output: VecDeque<u8>,
+}
+
+#[allow(deprecated)]
+impl<'a> Frame<'a> {
+    /// Render the state of `program`
+    fn render<T>(&self, program: &mut BrainfuckProgram<T>) -> String
+    where
+        T: Tape<Data = u8>,
+    {
+        let mut frame = String::from(CLEAR);
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let speed = if elapsed > 0.0 {
+            self.steps as f64 / elapsed
+        } else {
+            0.0
+        };
+        // Writing to a `String` cannot fail
+        writeln!(
+            frame,
+            "ip {:<8} dp {:<8} steps {:<12} {:.0} instructions/s",
+            program.instruction_pointer, program.data_pointer, self.steps, speed
+        )
+        .ok();
+        frame.push('\n');
+
+        self.render_tape(program, &mut frame);
+        frame.push('\n');
+
+        if let Some(position) = program.source_of(program.instruction_pointer) {
+            self.render_line(program, position, &mut frame);
+        }
+        frame.push('\n');
+
+        frame.push_str("output: ");
+        for byte in &self.output {
+            match byte {
+                b' '..=b'~' => frame.push(*byte as char),
+                b'\n' => frame.push_str("\\n"),
+                _ => frame.push('.'),
+            }
+        }
+        frame.push('\n');
+        frame
+    }
+
+    /// Render a window of cells around the data pointer, with their indices above them
+    fn render_tape<T>(&self, program: &mut BrainfuckProgram<T>, frame: &mut String)
+    where
+        T: Tape<Data = u8>,
+    {
+        let data_pointer = program.data_pointer;
+        let mut first = data_pointer.saturating_sub(self.config.cells / 2);
+        if let Some(len) = program.tape.len_hint() {
+            first = first.min(len.saturating_sub(self.config.cells));
+        }
+        let end = match program.tape.len_hint() {
+            Some(len) => len.min(first + self.config.cells),
+            None => first + self.config.cells,
+        };
+
+        let mut indices = String::new();
+        let mut cells = String::new();
+        for index in first..end {
+            let value = match program.tape.get_data_at(index) {
+                Some(value) => *value,
+                None => break,
+            };
+            write!(indices, "{:>5}", index).ok();
+            if index == data_pointer {
+                write!(cells, " {}{:>4}{}", INVERT, value, RESET).ok();
+            } else {
+                write!(cells, "{:>5}", value).ok();
+            }
+        }
+        writeln!(frame, "{}\n{}", indices, cells).ok();
+    }
+
+    /// Render the source line of the current instruction, with an arrow below the
+    /// instruction
+    fn render_line<T>(&self, program: &BrainfuckProgram<T>, position: SourcePos, frame: &mut String)
+    where
+        T: Tape,
+    {
+        let line = match self.source.lines().nth(position.line) {
+            Some(line) => line,
+            None => return,
+        };
+        // Only show part of long lines, so that the arrow stays on screen
+        let skip = (position.character - 1).saturating_sub(self.config.line_width / 2);
+        let start = line.char_indices().nth(skip).map_or(line.len(), |(i, _)| i);
+        let window = &line[start..];
+        let end = window
+            .char_indices()
+            .nth(self.config.line_width)
+            .map_or(window.len(), |(i, _)| i);
+
+        let span = Span {
+            // The source map only has positions for existing instructions
+            instruction: program.instructions[program.instruction_pointer],
+            text: &window[..end],
+            line: 0,
+            character: position.character - skip,
+        };
+        writeln!(frame, "line {}:\n{}", position.line + 1, span).ok();
+    }
+}
+
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape<Data = u8>,
+{
+    /// Run the Brainfuck program to completion, while showing its state on `terminal`
+    ///
+    /// Every [`VisualizeConfig::interval`] instructions, and once the program has
+    /// finished, the screen is redrawn with the instruction pointer, the data pointer,
+    /// the speed of execution, a window of cells around the data pointer, and the most
+    /// recent output. If the program was compiled with a source map, for instance with
+    /// [`compile_with_source_map`](Self::compile_with_source_map), the current line of
+    /// `source` is shown as well, with an arrow below the current instruction.
+    ///
+    /// Output is passed on to `output` as well as shown on screen.
+    pub fn run_visualized<W, FnOut, FnIn>(
+        &mut self,
+        source: &str,
+        config: &VisualizeConfig,
+        mut terminal: W,
+        output: &mut FnOut,
+        input: &mut FnIn,
+    ) -> io::Result<()>
+    where
+        W: Write,
+        FnOut: Output<u8>,
+        FnIn: Input<u8>,
+    {
+        let mut frame = Frame {
+            source,
+            config,
+            start: Instant::now(),
+            steps: 0,
+            output: VecDeque::with_capacity(config.output_len),
+        };
+        let interval = config.interval.max(1);
+
+        loop {
+            let running = self.step(
+                &mut |data| {
+                    if frame.output.len() == config.output_len {
+                        frame.output.pop_front();
+                    }
+                    if config.output_len > 0 {
+                        frame.output.push_back(data);
+                    }
+                    output.output(data);
+                },
+                input,
+            );
+            if running {
+                frame.steps += 1;
+            }
+            if !running || frame.steps.is_multiple_of(interval) {
+                terminal.write_all(frame.render(self).as_bytes())?;
+                terminal.flush()?;
+            }
+            if !running {
+                return Ok(());
+            }
+        }
+    }
+}
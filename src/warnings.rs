@@ -0,0 +1,148 @@
+//! Warnings about programs that compile, but are likely to be wrong
+
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use crate::{
+    analyze_loops, BrainfuckInstruction, BrainfuckLexer, BrainfuckProgram, Error, SourcePos, Span,
+    Tape,
+};
+
+/// The kind of a [`CompileWarning`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompileWarningKind {
+    /// A loop that never changes the data pointer or any cell, so it never
+    /// finishes once it is entered. For example `[]` or `[.]`
+    InfiniteLoop,
+    /// A loop that moves the data pointer to the left in every iteration, and is
+    /// entered so close to the first cell that its first iteration already moves the
+    /// data pointer left of it. For example `>[<<]`
+    LeftwardDrift,
+}
+
+/// A warning about a program that compiled successfully
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompileWarning {
+    /// What the warning is about
+    pub kind: CompileWarningKind,
+    /// Where the code that the warning is about starts
+    pub start: SourcePos,
+    /// Where the code that the warning is about ends
+    pub end: SourcePos,
+}
+
+impl Display for CompileWarning {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.kind {
+            CompileWarningKind::InfiniteLoop => write!(
+                f,
+                "Loop from {} to {} never changes the data pointer or any cell, and never finishes once entered",
+                self.start, self.end
+            ),
+            CompileWarningKind::LeftwardDrift => write!(
+                f,
+                "Loop from {} to {} moves the data pointer to the left, and moves it left of the first cell in its first iteration",
+                self.start, self.end
+            ),
+        }
+    }
+}
+
+/// Find loops in `spans` that can never finish
+fn infinite_loops(spans: &[Span], warnings: &mut Vec<CompileWarning>) {
+    // For every open loop: its start, and whether it changes anything
+    let mut open: Vec<(usize, bool)> = Vec::new();
+    for (index, span) in spans.iter().enumerate() {
+        match span.get_instruction() {
+            BrainfuckInstruction::JumpForward(_) => open.push((index, false)),
+            BrainfuckInstruction::JumpBackwards(_) => {
+                if let Some((start, changes)) = open.pop() {
+                    if !changes {
+                        warnings.push(CompileWarning {
+                            kind: CompileWarningKind::InfiniteLoop,
+                            start: spans[start].get_position(),
+                            end: span.get_position(),
+                        });
+                    }
+                    if let Some((_, parent_changes)) = open.last_mut() {
+                        *parent_changes |= changes;
+                    }
+                }
+            }
+            BrainfuckInstruction::Output => {}
+            _ => {
+                if let Some((_, changes)) = open.last_mut() {
+                    *changes = true;
+                }
+            }
+        }
+    }
+}
+
+/// Find loops in `spans` that drift to the left, and move left of the first cell as
+/// soon as they are entered
+fn leftward_drift(spans: &[Span], warnings: &mut Vec<CompileWarning>) {
+    let instructions: Vec<_> = spans.iter().map(|span| span.get_instruction()).collect();
+    let mut loops = analyze_loops(&instructions).into_iter();
+    let mut open = Vec::new();
+
+    // The offset of the data pointer from the first cell, while it is known. Within a
+    // loop, this is the offset during its first iteration
+    let mut offset = Some(0isize);
+    for instruction in &instructions {
+        match instruction {
+            BrainfuckInstruction::IncrementDataPointer => offset = offset.map(|o| o + 1),
+            BrainfuckInstruction::DecrementDataPointer => offset = offset.map(|o| o - 1),
+            BrainfuckInstruction::MoveUp
+            | BrainfuckInstruction::MoveDown
+            | BrainfuckInstruction::PreviousTape
+            | BrainfuckInstruction::NextTape => offset = None,
+            BrainfuckInstruction::JumpForward(_) => {
+                let info = match loops.next() {
+                    Some(info) => info,
+                    None => continue,
+                };
+                if let (Some(offset), Some(delta), Some((min, _))) =
+                    (offset, info.delta, info.offsets)
+                {
+                    if delta < 0 && offset + min < 0 {
+                        warnings.push(CompileWarning {
+                            kind: CompileWarningKind::LeftwardDrift,
+                            start: spans[info.start].get_position(),
+                            end: spans[info.end].get_position(),
+                        });
+                    }
+                }
+                open.push(info);
+            }
+            // After a loop that moves the data pointer, it could be anywhere
+            BrainfuckInstruction::JumpBackwards(_)
+                if !open.pop().is_some_and(|info| info.is_balanced()) =>
+            {
+                offset = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<T> BrainfuckProgram<T>
+where
+    T: Tape,
+{
+    /// Compile a Brainfuck program, given by `input`, and report likely mistakes in it.
+    /// All non-valid characters are ignored
+    ///
+    /// The warnings are ordered by the position at which the code they are about ends.
+    pub fn compile_with_warnings(
+        input: &str,
+        tape: T,
+    ) -> Result<(Self, Vec<CompileWarning>), Error<'_>> {
+        let spans = Self::compile_spans(input, &BrainfuckLexer)?;
+        let mut warnings = Vec::new();
+        infinite_loops(&spans, &mut warnings);
+        leftward_drift(&spans, &mut warnings);
+        warnings.sort_by_key(|warning| warning.end);
+        Ok((Self::from_spans(&spans, tape), warnings))
+    }
+}
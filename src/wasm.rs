@@ -0,0 +1,115 @@
+//! [`wasm-bindgen`] bindings, for running Brainfuck programs in the browser
+//!
+//! [`wasm-bindgen`]: https://docs.rs/wasm-bindgen
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::{BrainfuckProgram, BufferedProgram, BufferedStatus, RuntimeError};
+
+/// The state of a [`Playground`] after stepping it
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaygroundStatus {
+    /// The program can continue running
+    Running,
+    /// The program is waiting for input
+    WaitingForInput,
+    /// The program has finished
+    Halted,
+    /// The program stopped because of an error, see [`Playground::error`]
+    Failed,
+}
+
+impl From<BufferedStatus> for PlaygroundStatus {
+    fn from(status: BufferedStatus) -> Self {
+        match status {
+            BufferedStatus::Running => Self::Running,
+            BufferedStatus::WaitingForInput => Self::WaitingForInput,
+            BufferedStatus::Halted => Self::Halted,
+            BufferedStatus::Failed(_) => Self::Failed,
+        }
+    }
+}
+
+/// A compiled Brainfuck program with a growing byte tape, driven from JavaScript
+#[wasm_bindgen]
+pub struct Playground {
+    program: BufferedProgram<Vec<u8>>,
+    error: Option<RuntimeError>,
+}
+
+#[wasm_bindgen]
+#[allow(deprecated)]
+impl Playground {
+    /// Compile `source`, throwing a description of the error if it is not a valid program
+    pub fn compile(source: &str) -> Result<Playground, JsValue> {
+        match BrainfuckProgram::compile(source, Vec::new()) {
+            Ok(program) => Ok(Self {
+                program: BufferedProgram::new(program),
+                error: None,
+            }),
+            Err(error) => Err(JsValue::from_str(&error.to_string())),
+        }
+    }
+
+    /// Perform at most `steps` steps of the program
+    pub fn step(&mut self, steps: u32) -> PlaygroundStatus {
+        let status = self.program.step(steps as usize);
+        if let BufferedStatus::Failed(error) = status {
+            self.error = Some(error);
+        }
+        status.into()
+    }
+
+    /// A description of the error that stopped the program, if it has failed
+    pub fn error(&self) -> Option<String> {
+        self.error
+            .as_ref()
+            .map(|error| self.program.program().explain(error).to_string())
+    }
+
+    /// Take all output produced since the last call to this function
+    pub fn read_output(&mut self) -> Vec<u8> {
+        self.program.read_output()
+    }
+
+    /// Queue `bytes` as input for the program
+    pub fn write_input(&mut self, bytes: &[u8]) {
+        self.program.write_input(bytes);
+    }
+
+    /// Reset the program, its tape, and both I/O buffers
+    pub fn reset(&mut self) {
+        self.program.reset();
+        self.error = None;
+    }
+
+    /// The current data pointer
+    pub fn data_pointer(&self) -> usize {
+        self.program.program().data_pointer
+    }
+
+    /// The current instruction pointer
+    pub fn instruction_pointer(&self) -> usize {
+        self.program.program().instruction_pointer
+    }
+
+    /// The amount of instructions that have been executed
+    pub fn execution_count(&self) -> usize {
+        self.program.program().execution_count
+    }
+
+    /// Read `len` cells of the tape, starting at `start`
+    ///
+    /// Cells that the program has not reached yet read as zero, without growing the tape.
+    pub fn tape(&self, start: usize, len: usize) -> Vec<u8> {
+        let tape = &self.program.program().tape;
+        (start..start.saturating_add(len))
+            .map(|index| tape.get(index).copied().unwrap_or(0))
+            .collect()
+    }
+}
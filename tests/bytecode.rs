@@ -0,0 +1,77 @@
+#![cfg(feature = "alloc")]
+
+use brainfuck_interpreter::{
+    BrainfuckInstruction, BrainfuckProgram, Bytecode, BytecodeEncodeError,
+};
+
+/// One of every instruction, with jumps that target their matching brackets
+fn every_instruction() -> Vec<BrainfuckInstruction> {
+    use BrainfuckInstruction::*;
+    vec![
+        IncrementDataPointer,
+        DecrementDataPointer,
+        IncreaseData,
+        DecreaseData,
+        Output,
+        Input,
+        JumpForward(8),
+        HostCall,
+        JumpBackwards(6),
+        DefineProcedure(11),
+        CallProcedure,
+        EndProcedure,
+        MoveUp,
+        MoveDown,
+        PreviousTape,
+        NextTape,
+        Push,
+        Pop,
+    ]
+}
+
+#[test]
+fn words_round_trip_every_instruction() {
+    let instructions = every_instruction();
+    let bytecode = Bytecode::from_instructions(&instructions).unwrap();
+    assert_eq!(bytecode.to_instructions(), instructions);
+}
+
+#[test]
+fn serialized_bytecode_round_trips_every_instruction() {
+    let instructions = every_instruction();
+    let program = BrainfuckProgram::from_instructions(instructions.clone(), vec![0u8]);
+    let loaded =
+        BrainfuckProgram::from_bytecode(&program.to_bytecode().unwrap(), vec![0u8]).unwrap();
+    assert_eq!(loaded.instructions(), &instructions[..]);
+}
+
+#[test]
+fn words_reject_targets_that_do_not_fit() {
+    let instructions = [BrainfuckInstruction::JumpForward(Bytecode::MAX_TARGET + 1)];
+    assert!(Bytecode::from_instructions(&instructions).is_none());
+    let instructions = [BrainfuckInstruction::JumpForward(Bytecode::MAX_TARGET)];
+    assert!(Bytecode::from_instructions(&instructions).is_some());
+}
+
+#[test]
+fn serializing_rejects_targets_that_do_not_fit() {
+    let target = u32::MAX as usize + 1;
+    let instructions = vec![
+        BrainfuckInstruction::IncreaseData,
+        BrainfuckInstruction::JumpForward(target),
+    ];
+    let program = BrainfuckProgram::from_instructions(instructions, vec![0u8]);
+    assert_eq!(
+        program.to_bytecode(),
+        Err(BytecodeEncodeError::TargetTooLarge { index: 1 })
+    );
+}
+
+#[test]
+fn serializing_rejects_folded_programs() {
+    let mut program = BrainfuckProgram::compile("+++[>++<-]>.", vec![0u8]).unwrap();
+    assert!(program.to_bytecode().is_ok());
+    program.fold_prefix(1_000);
+    assert!(program.initial_tape.is_some());
+    assert_eq!(program.to_bytecode(), Err(BytecodeEncodeError::InitialTape));
+}
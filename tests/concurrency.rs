@@ -0,0 +1,52 @@
+#![cfg(feature = "std")]
+
+use std::thread;
+
+use brainfuck_interpreter::{BoundedVecTape, BrainfuckProgram, SharedTape, Tape};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn programs_are_send_and_sync() {
+    assert_send_sync::<BrainfuckProgram<Vec<u8>>>();
+    assert_send_sync::<BrainfuckProgram<[u8; 16]>>();
+    assert_send_sync::<BrainfuckProgram<&mut [u8]>>();
+    assert_send_sync::<BrainfuckProgram<BoundedVecTape<u8>>>();
+    assert_send_sync::<BrainfuckProgram<SharedTape<Vec<u8>>>>();
+}
+
+#[test]
+fn program_runs_on_worker_thread() {
+    let mut program = BrainfuckProgram::compile("++++++++[>++++<-]>+.", vec![0u8]).unwrap();
+    let output = thread::spawn(move || {
+        let mut output = Vec::new();
+        program.run(&mut |d| output.push(d), &mut || 0);
+        output
+    })
+    .join()
+    .unwrap();
+    assert_eq!(output, b"!");
+}
+
+#[test]
+fn programs_communicate_through_shared_tape() {
+    let tape = SharedTape::new(vec![0u8; 2]);
+
+    // Waits until cell 0 is set, prints it, and clears it
+    let mut reader = BrainfuckProgram::compile(">+[<[.>-<[-]]>]", tape.clone()).unwrap();
+    // Reads a single byte into cell 0
+    let mut writer = BrainfuckProgram::compile(",", tape.clone()).unwrap();
+
+    let reader = thread::spawn(move || {
+        let mut output = Vec::new();
+        reader.run(&mut |d| output.push(d), &mut || 0);
+        output
+    });
+    thread::spawn(move || writer.run(&mut |_| {}, &mut || b'x'))
+        .join()
+        .unwrap();
+
+    assert_eq!(reader.join().unwrap(), b"x");
+    let mut tape = tape;
+    assert_eq!(tape.lock().get_data_at(0), Some(&0));
+}
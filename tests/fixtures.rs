@@ -0,0 +1,36 @@
+#![cfg(feature = "std")]
+
+use std::fs;
+
+use brainfuck_interpreter::testing::{run_fixtures, FixtureFailureKind};
+
+#[test]
+fn failing_fixtures_are_reported() {
+    let directory = std::env::temp_dir().join(format!("bf-fixtures-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let fixtures: [(&str, &str, &[u8]); 3] = [
+        ("echo", ",[.,]", b"abc"),
+        ("underflow", "<+.", b""),
+        ("wrong", "+.", b"\x02"),
+    ];
+    for (name, source, output) in fixtures.iter() {
+        fs::write(directory.join(format!("{}.b", name)), source).unwrap();
+        fs::write(directory.join(format!("{}.out", name)), output).unwrap();
+    }
+    fs::write(directory.join("echo.in"), "abc").unwrap();
+
+    let summary = run_fixtures(&directory).unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!(summary.passed, [directory.join("echo.b")]);
+    assert_eq!(summary.failed.len(), 2);
+    assert_eq!(summary.failed[0].path, directory.join("underflow.b"));
+    assert!(matches!(
+        summary.failed[0].kind,
+        FixtureFailureKind::Runtime(_)
+    ));
+    assert!(matches!(
+        summary.failed[1].kind,
+        FixtureFailureKind::OutputMismatch { .. }
+    ));
+}
@@ -0,0 +1,35 @@
+#![cfg(feature = "alloc")]
+
+use brainfuck_interpreter::{format_source, BrainfuckProgram, FormatOptions};
+
+const PROGRAMS: [&str; 3] = [
+    "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.>++.",
+    "This program echoes its input [ until it reads a zero ],[.,]",
+    "[ a comment loop, containing commas, and full stops. ]\n\n+++[>+++[>+<-]<-]>>.",
+];
+
+fn assert_round_trip(source: &str, options: &FormatOptions) {
+    let formatted = format_source(source, options).unwrap();
+    let original = BrainfuckProgram::compile(source, [0u8; 0]).unwrap();
+    let reformatted = BrainfuckProgram::compile(&formatted, [0u8; 0]).unwrap();
+    assert_eq!(original.instructions(), reformatted.instructions());
+    assert_eq!(format_source(&formatted, options).unwrap(), formatted);
+}
+
+#[test]
+fn formatting_preserves_instructions() {
+    let narrow = FormatOptions {
+        indent: 2,
+        max_width: 10,
+        ..FormatOptions::default()
+    };
+    let stripped = FormatOptions {
+        strip_comments: true,
+        ..FormatOptions::default()
+    };
+    for program in PROGRAMS.iter() {
+        assert_round_trip(program, &FormatOptions::default());
+        assert_round_trip(program, &narrow);
+        assert_round_trip(program, &stripped);
+    }
+}
@@ -0,0 +1,175 @@
+#![cfg(feature = "alloc")]
+
+use brainfuck_interpreter::{
+    compile_into, programs, BrainfuckInstruction, BrainfuckProgram, Bytecode, CompileIntoError,
+    EofBehavior, Error, Host, Interpreter, Machine, MachineConfig, RunOutcome, RunResult, RunStats,
+    RuntimeError,
+};
+
+fn buffer() -> Vec<BrainfuckInstruction> {
+    vec![BrainfuckInstruction::IncreaseData; 4096]
+}
+
+#[test]
+fn compiles_like_brainfuck_program() {
+    for fixture in programs::ALL {
+        let mut buffer = buffer();
+        let instructions = compile_into(fixture.source, &mut buffer).unwrap();
+        let program = BrainfuckProgram::compile(fixture.source, vec![0u8]).unwrap();
+        assert_eq!(instructions, program.instructions(), "{}", fixture.name);
+    }
+}
+
+#[test]
+fn runs_fixtures_on_an_array_tape() {
+    // Reading a zero is the only end of input that an `Input` can express
+    for fixture in programs::ALL.iter().filter(|f| f.eof == EofBehavior::Zero) {
+        let mut buffer = buffer();
+        let instructions = compile_into(fixture.source, &mut buffer).unwrap();
+        let mut interpreter = Interpreter::new(instructions, [0u8; 1024]);
+
+        let mut input = fixture.input.iter().copied();
+        let mut output = Vec::new();
+        interpreter
+            .try_run(&mut |data| output.push(data), &mut || {
+                input.next().unwrap_or(0)
+            })
+            .unwrap();
+        assert_eq!(output, fixture.run(), "{}", fixture.name);
+    }
+}
+
+#[test]
+fn reports_the_same_bracket_errors() {
+    for source in ["+[[-]", "]", "[]]", "[\n[\n]"] {
+        let mut buffer = buffer();
+        let expected = match BrainfuckProgram::compile(source, vec![0u8]).unwrap_err() {
+            Error::MissingClosingBrace(span) => (true, span.get_position()),
+            Error::MissingOpeningBrace(span) => (false, span.get_position()),
+        };
+        let found = match compile_into(source, &mut buffer).unwrap_err() {
+            CompileIntoError::Brackets(Error::MissingClosingBrace(span)) => {
+                (true, span.get_position())
+            }
+            CompileIntoError::Brackets(Error::MissingOpeningBrace(span)) => {
+                (false, span.get_position())
+            }
+            error => panic!("{}", error),
+        };
+        assert_eq!(found, expected, "{}", source);
+    }
+}
+
+#[test]
+fn stops_at_the_end_of_the_tape() {
+    let mut buffer = buffer();
+    let instructions = compile_into("+>+>+", &mut buffer).unwrap();
+    let mut cells = [0u8; 2];
+    let mut interpreter = Interpreter::new(instructions, &mut cells[..]);
+    let error = interpreter.try_run(&mut |_| {}, &mut || 0).unwrap_err();
+    assert_eq!(
+        error,
+        RuntimeError::TapeExhausted {
+            data_pointer: 2,
+            position: None,
+            excerpt: None,
+        }
+    );
+    assert_eq!(interpreter.instruction_pointer(), 4);
+    assert_eq!(cells, [1, 1]);
+}
+
+#[test]
+fn rejects_instructions_that_need_memory() {
+    let instructions = [
+        BrainfuckInstruction::IncreaseData,
+        BrainfuckInstruction::Push,
+    ];
+    let mut interpreter = Interpreter::new(&instructions, [0u8; 1]);
+    let error = interpreter.try_run(&mut |_| {}, &mut || 0).unwrap_err();
+    assert_eq!(
+        error,
+        RuntimeError::Unsupported {
+            instruction_pointer: 1,
+            instruction: BrainfuckInstruction::Push,
+        }
+    );
+}
+
+#[test]
+fn large_programs_compile() {
+    for fixture in programs::LARGE {
+        let program = BrainfuckProgram::compile(fixture.source, vec![0u8]).unwrap();
+        assert!(!program.instructions().is_empty(), "{}", fixture.name);
+    }
+    // Mandelbrot takes too long to run without optimizations
+    assert_eq!(programs::HANOI.run(), programs::HANOI.output);
+}
+
+#[test]
+fn entry_points_return_the_same_result() {
+    let source = "+++>,[.,]<";
+    let compile = || BrainfuckProgram::compile(source, vec![0u8; 2]).unwrap();
+    let input = || {
+        let mut input = b"ab".iter().copied();
+        move || input.next().unwrap_or(0)
+    };
+    let expected = compile().run(&mut |_| {}, &mut input());
+    assert_eq!(expected.stats().outputs, 2);
+    assert_eq!(expected.stats().inputs, 3);
+    let expected = (
+        expected.stats(),
+        expected.final_data_pointer(),
+        expected.exit_code(),
+    );
+
+    let summary = |result: RunResult<u8>| {
+        assert_eq!(result.outcome(), RunOutcome::Halted);
+        (
+            result.stats(),
+            result.final_data_pointer(),
+            result.exit_code(),
+        )
+    };
+    // The interpreter does not count the step that finds the end of the program
+    let without_end = |(stats, data_pointer, exit_code): (RunStats, usize, Option<i32>)| {
+        let instructions = stats.instructions - 1;
+        (
+            RunStats {
+                instructions,
+                ..stats
+            },
+            data_pointer,
+            exit_code,
+        )
+    };
+    let result = compile().run_observed(|_| {}, &mut |_| {}, &mut input());
+    assert_eq!(summary(result), expected);
+
+    let mut program = compile();
+    let bytecode = Bytecode::from_instructions(program.instructions()).unwrap();
+    let result = program.try_run_bytecode(&bytecode, &mut |_| {}, &mut input());
+    assert_eq!(summary(result.unwrap()), expected);
+
+    let result = Host::new().run(&mut compile(), &mut |_| {}, &mut input());
+    assert_eq!(summary(result.unwrap()), expected);
+
+    let program = compile();
+    let mut interpreter = Interpreter::new(program.instructions(), [0u8; 2]);
+    let result = interpreter.try_run(&mut |_| {}, &mut input());
+    assert_eq!(summary(result.unwrap()), without_end(expected));
+
+    let mut machine = Machine::new(MachineConfig::default(), source).unwrap();
+    let mut machine_input = b"ab".iter().map(|byte| *byte as u32);
+    let result = machine
+        .run(&mut |_| {}, &mut || machine_input.next())
+        .unwrap();
+    assert_eq!(
+        (
+            result.stats(),
+            result.final_data_pointer(),
+            result.exit_code()
+        ),
+        expected
+    );
+}
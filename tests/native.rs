@@ -0,0 +1,88 @@
+#![cfg(all(target_arch = "x86_64", unix, feature = "native-jit"))]
+
+use brainfuck_interpreter::{programs, BrainfuckProgram, EofBehavior, RuntimeError, Tape};
+
+/// Run `program` natively and with the interpreter, and check that both produce the
+/// same output and leave the same tape and data pointer behind
+fn assert_same<T>(source: &str, input: &[u8], tape: T) -> Vec<u8>
+where
+    T: Tape<Data = u8> + AsMut<[u8]> + Clone + PartialEq + core::fmt::Debug,
+{
+    let mut native = BrainfuckProgram::compile(source, tape.clone()).unwrap();
+    let mut native_input = input.iter().copied();
+    let mut native_output = Vec::new();
+    native
+        .try_run_native(&mut |data| native_output.push(data), &mut || {
+            native_input.next().unwrap_or(0)
+        })
+        .unwrap();
+
+    let mut interpreted = BrainfuckProgram::compile(source, tape).unwrap();
+    let mut input = input.iter().copied();
+    let mut output = Vec::new();
+    interpreted
+        .try_run(&mut |data| output.push(data), &mut || {
+            input.next().unwrap_or(0)
+        })
+        .unwrap();
+
+    assert_eq!(native_output, output, "{}", source);
+    assert_eq!(native.tape, interpreted.tape, "{}", source);
+    assert_eq!(
+        native.data_pointer(),
+        interpreted.data_pointer(),
+        "{}",
+        source
+    );
+    assert_eq!(
+        native.instruction_pointer(),
+        interpreted.instruction_pointer()
+    );
+    output
+}
+
+fn zero_eof_fixtures() -> impl Iterator<Item = &'static programs::Fixture> {
+    programs::ALL
+        .iter()
+        .filter(|fixture| fixture.eof == EofBehavior::Zero)
+}
+
+#[test]
+fn fixed_size_tapes_match_the_interpreter() {
+    for fixture in zero_eof_fixtures() {
+        let output = assert_same(fixture.source, fixture.input, vec![0u8; 30_000]);
+        assert_eq!(output, fixture.output, "{}", fixture.name);
+        assert_same(fixture.source, fixture.input, [0u8; 30_000]);
+    }
+}
+
+#[test]
+fn growing_tapes_continue_in_the_interpreter() {
+    for fixture in zero_eof_fixtures() {
+        let output = assert_same(fixture.source, fixture.input, vec![0u8; 2]);
+        assert_eq!(output, fixture.output, "{}", fixture.name);
+        assert_same(fixture.source, fixture.input, Vec::new());
+    }
+}
+
+#[test]
+fn moves_off_a_fixed_tape_fail_in_the_interpreter() {
+    let mut program = BrainfuckProgram::compile_with_source_map("+>+>+.", [0u8; 2]).unwrap();
+    let mut output = Vec::new();
+    let error = program
+        .try_run_native(&mut |data| output.push(data), &mut || 0)
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        RuntimeError::TapeExhausted {
+            data_pointer: 2,
+            ..
+        }
+    ));
+    assert_eq!(program.tape, [1, 1]);
+    assert!(output.is_empty());
+
+    let mut program = BrainfuckProgram::compile("+<+", [0u8; 2]).unwrap();
+    assert!(program.try_run_native(&mut |_| {}, &mut || 0).is_err());
+    assert_eq!(program.tape, [1, 0]);
+}
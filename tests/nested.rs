@@ -0,0 +1,31 @@
+#![cfg(feature = "alloc")]
+
+use brainfuck_interpreter::{programs, run_nested, EofBehavior};
+
+const DBFI: &str = programs::DBFI.source;
+
+const HELLO_WORLD: &str = "Prints Hello World! ++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.>++.";
+
+/// Echoes its input, and halts at EOF both if it reads a zero and if the cell is unchanged
+const ECHO: &str = ",[.[-],]";
+
+#[test]
+fn dbfi_runs_programs() {
+    for eof in [EofBehavior::Zero, EofBehavior::Unchanged] {
+        assert_eq!(
+            run_nested(DBFI, HELLO_WORLD, b"", eof).unwrap(),
+            b"Hello World!\n"
+        );
+        assert_eq!(run_nested(DBFI, ECHO, b"nested!", eof).unwrap(), b"nested!");
+    }
+}
+
+#[test]
+fn dbfi_runs_itself() {
+    let mut input = b",.".to_vec();
+    input.extend_from_slice(b"!x");
+    assert_eq!(
+        run_nested(DBFI, DBFI, &input, EofBehavior::Zero).unwrap(),
+        b"x"
+    );
+}
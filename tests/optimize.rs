@@ -0,0 +1,94 @@
+#![cfg(feature = "alloc")]
+
+use brainfuck_interpreter::{
+    Backend, BrainfuckProgram, CompileOptions, Machine, MachineConfig, OptimizationLevel,
+    RuntimeError, Saturating, Trapping, WrapPolicy,
+};
+
+const LEVELS: [OptimizationLevel; 4] = [
+    OptimizationLevel::None,
+    OptimizationLevel::Basic,
+    OptimizationLevel::Aggressive,
+    OptimizationLevel::Peeling,
+];
+
+fn options(optimization: OptimizationLevel) -> CompileOptions {
+    CompileOptions {
+        optimization,
+        ..CompileOptions::default()
+    }
+}
+
+#[test]
+fn saturating_cells_keep_cancelling_pairs() {
+    for level in LEVELS {
+        let tape = vec![Saturating(0u8); 1];
+        let mut program =
+            BrainfuckProgram::compile_with_options("-+.", tape, &options(level)).unwrap();
+        let mut output = Vec::new();
+        program.run(&mut |value| output.push(value), &mut || Saturating(0));
+        assert_eq!(output, [Saturating(1)], "{:?}", level);
+    }
+}
+
+#[test]
+fn trapping_cells_keep_cancelling_pairs() {
+    for level in LEVELS {
+        let tape = vec![Trapping(0u8); 1];
+        let mut program =
+            BrainfuckProgram::compile_with_options("-+.", tape, &options(level)).unwrap();
+        let error = program
+            .try_run(&mut |_| {}, &mut || Trapping(0))
+            .unwrap_err();
+        assert!(
+            matches!(
+                error,
+                RuntimeError::CellOverflow {
+                    instruction_pointer: 0,
+                    ..
+                }
+            ),
+            "{:?}",
+            level
+        );
+    }
+}
+
+#[test]
+fn wrapping_cells_drop_cancelling_pairs() {
+    let program = BrainfuckProgram::compile_with_options(
+        "-+.",
+        vec![0u8; 1],
+        &options(OptimizationLevel::Basic),
+    )
+    .unwrap();
+    assert_eq!(program.instructions().len(), 1);
+}
+
+#[test]
+fn optimizing_machines_keep_non_wrapping_behaviour() {
+    for backend in [Backend::Optimized, Backend::Threaded, Backend::Jit] {
+        let config = MachineConfig {
+            cell_wrap: WrapPolicy::Saturate,
+            backend,
+            ..MachineConfig::default()
+        };
+        let mut machine = Machine::new(config, "-+.").unwrap();
+        assert_eq!(machine.run_bytes(b"").unwrap(), [1], "{:?}", backend);
+
+        let config = MachineConfig {
+            cell_wrap: WrapPolicy::Trap,
+            backend,
+            ..MachineConfig::default()
+        };
+        let mut machine = Machine::new(config, "-+.").unwrap();
+        assert!(
+            matches!(
+                machine.run_bytes(b""),
+                Err(RuntimeError::CellOverflow { .. })
+            ),
+            "{:?}",
+            backend
+        );
+    }
+}
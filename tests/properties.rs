@@ -0,0 +1,83 @@
+#![cfg(feature = "alloc")]
+
+use brainfuck_interpreter::{ast, minify, BrainfuckInstruction, BrainfuckProgram};
+use proptest::prelude::*;
+
+/// Source code that mostly consists of commands, with some comments in between
+fn source() -> impl Strategy<Value = String> {
+    proptest::string::string_regex(r"([<>+\-.,\[\]]|[a-z \n]){0,64}").unwrap()
+}
+
+/// Source code in which every bracket is matched
+fn balanced_source() -> impl Strategy<Value = String> {
+    let leaf = proptest::string::string_regex(r"[<>+\-.,a ]{0,4}").unwrap();
+    leaf.prop_recursive(4, 64, 4, |inner| {
+        proptest::collection::vec(
+            prop_oneof![inner.clone(), inner.prop_map(|body| format!("[{}]", body))],
+            0..4,
+        )
+        .prop_map(|parts| parts.concat())
+    })
+}
+
+fn is_balanced(source: &str) -> bool {
+    let mut depth = 0usize;
+    for character in source.chars() {
+        match character {
+            '[' => depth += 1,
+            ']' => match depth.checked_sub(1) {
+                Some(new_depth) => depth = new_depth,
+                None => return false,
+            },
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+proptest! {
+    #[test]
+    fn compile_unchecked_never_panics(input in proptest::collection::vec(any::<u8>(), 0..256)) {
+        let _ = BrainfuckProgram::compile_unchecked(&input, [0u8; 0]);
+    }
+
+    #[test]
+    fn compiles_exactly_when_balanced(source in source()) {
+        let compiled = BrainfuckProgram::compile(&source, [0u8; 0]);
+        prop_assert_eq!(compiled.is_ok(), is_balanced(&source));
+    }
+
+    #[test]
+    fn jumps_target_their_matching_bracket(source in balanced_source()) {
+        let program = BrainfuckProgram::compile(&source, [0u8; 0]).unwrap();
+        let mut open = Vec::new();
+        for (index, instruction) in program.instructions().iter().enumerate() {
+            match *instruction {
+                BrainfuckInstruction::JumpForward(target) => {
+                    prop_assert_eq!(
+                        program.instructions().get(target),
+                        Some(&BrainfuckInstruction::JumpBackwards(index))
+                    );
+                    open.push(index);
+                }
+                BrainfuckInstruction::JumpBackwards(target) => {
+                    prop_assert_eq!(open.pop(), Some(target));
+                }
+                _ => {}
+            }
+        }
+        prop_assert!(open.is_empty());
+    }
+
+    #[test]
+    fn printing_preserves_instructions(source in balanced_source()) {
+        let program = BrainfuckProgram::compile(&source, [0u8; 0]).unwrap();
+
+        let printed = ast::to_source(&program.to_ast());
+        let reparsed = BrainfuckProgram::compile(&printed, [0u8; 0]).unwrap();
+        prop_assert_eq!(reparsed.instructions(), program.instructions());
+
+        let minified = BrainfuckProgram::compile(&minify(&source), [0u8; 0]).unwrap();
+        prop_assert_eq!(minified.instructions(), program.instructions());
+    }
+}
@@ -0,0 +1,100 @@
+#![cfg(feature = "alloc")]
+
+use brainfuck_interpreter::{programs, BrainfuckProgram};
+
+/// Read an unsigned LEB128 number from the start of `bytes`, returning it and its length
+fn read_unsigned(bytes: &[u8]) -> (u32, usize) {
+    let mut value = 0;
+    for (index, byte) in bytes.iter().enumerate() {
+        value |= u32::from(byte & 0x7F) << (7 * index);
+        if byte & 0x80 == 0 {
+            return (value, index + 1);
+        }
+    }
+    panic!("LEB128 number is not terminated");
+}
+
+/// The ids of the sections of `module`, checking that their lengths add up
+fn section_ids(module: &[u8]) -> Vec<u8> {
+    let mut ids = Vec::new();
+    let mut rest = &module[8..];
+    while let Some((&id, after_id)) = rest.split_first() {
+        let (len, len_len) = read_unsigned(after_id);
+        let contents = len_len + len as usize;
+        assert!(contents <= after_id.len(), "section {} is truncated", id);
+        ids.push(id);
+        rest = &after_id[contents..];
+    }
+    ids
+}
+
+#[test]
+fn module_has_header_and_ordered_sections() {
+    for fixture in programs::ALL {
+        let program = BrainfuckProgram::compile(fixture.source, vec![0u8]).unwrap();
+        let module = program.to_wasm_module();
+
+        assert_eq!(&module[..4], b"\0asm", "{}", fixture.name);
+        assert_eq!(&module[4..8], &1u32.to_le_bytes(), "{}", fixture.name);
+        // Type, import, function, memory, export and code
+        assert_eq!(
+            section_ids(&module),
+            [1, 2, 3, 5, 7, 10],
+            "{}",
+            fixture.name
+        );
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod execution {
+    use brainfuck_interpreter::{programs, BrainfuckProgram};
+    use wasmi::{Caller, Engine, Linker, Module, Store};
+
+    /// The input that is left, and the output produced so far
+    type Io = (Vec<u8>, Vec<u8>);
+
+    fn run_module(source: &str, input: &[u8]) -> Vec<u8> {
+        let program = BrainfuckProgram::compile(source, vec![0u8]).unwrap();
+        let engine = Engine::default();
+        let module = Module::new(&engine, &program.to_wasm_module()[..]).unwrap();
+
+        let mut input = input.to_vec();
+        input.reverse();
+        let mut store = Store::new(&engine, (input, Vec::new()));
+        let mut linker = <Linker<Io>>::new(&engine);
+        linker
+            .func_wrap("env", "output", |mut caller: Caller<'_, Io>, data: i32| {
+                caller.data_mut().1.push(data as u8)
+            })
+            .unwrap();
+        linker
+            .func_wrap("env", "input", |mut caller: Caller<'_, Io>| -> i32 {
+                i32::from(caller.data_mut().0.pop().unwrap_or(0))
+            })
+            .unwrap();
+
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .unwrap()
+            .start(&mut store)
+            .unwrap();
+        let run = instance.get_typed_func::<(), ()>(&store, "run").unwrap();
+        run.call(&mut store, ()).unwrap();
+        store.into_data().1
+    }
+
+    #[test]
+    fn module_runs_like_the_interpreter() {
+        let fixtures = [programs::HELLO_WORLD, programs::SIERPINSKI, programs::DBFI];
+        for fixture in fixtures.iter() {
+            assert_eq!(
+                run_module(fixture.source, fixture.input),
+                fixture.output,
+                "{}",
+                fixture.name
+            );
+        }
+        assert_eq!(run_module(",[.,]", b"echo"), b"echo");
+    }
+}